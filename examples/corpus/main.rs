@@ -0,0 +1,20 @@
+//! Parses the corpus of problematic csv files (see: examples/corpus/data) and prints what the parser makes of them.
+//!
+//! ```text
+//! cargo run --example corpus                  # all cases
+//! cargo run --example corpus -- mixed_quotes  # only the given cases
+//! ```
+
+mod render;
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let cases = match args.is_empty() {
+        true => render::case_names(),
+        false => args,
+    };
+    for case in cases {
+        println!("== {} ==", case);
+        print!("{}", render::render_case(&case));
+    }
+}
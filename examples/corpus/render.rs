@@ -0,0 +1,85 @@
+//! Renders the parse results of the corpus cases as text. Shared by the `corpus` example and the integration tests
+//! (see: tests/corpus.rs), which compare it to the `<case>.expected` files.
+//!
+//! Every case is a data file `<case>.csv` and the (json) config `<case>.json` to parse it with.
+
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use patti_csv::prelude::*;
+
+/// In case a (broken) parser would never stop yielding errors.
+const MAX_ROWS: usize = 100;
+
+pub fn corpus_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/corpus/data")
+}
+
+/// The names of all cases, sorted.
+pub fn case_names() -> Vec<String> {
+    let mut names = fs::read_dir(corpus_dir())
+        .expect("corpus dir")
+        .filter_map(|e| {
+            let path = e.expect("corpus entry").path();
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("csv") => path.file_stem().map(|s| s.to_string_lossy().into_owned()),
+                _ => None,
+            }
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+/// Sniffs and parses the data of the case, one line per (header or data) row or error.
+pub fn render_case(case: &str) -> String {
+    let dir = corpus_dir();
+    let data = fs::read(dir.join(format!("{}.csv", case))).expect("corpus data");
+    let cfg_str = fs::read_to_string(dir.join(format!("{}.json", case))).expect("corpus config");
+    let cfg: ConfigRoot = serde_json::from_str(&cfg_str).expect("corpus config json");
+
+    let mut out = match PattiCsvParser::sniff(&mut Cursor::new(&data), 4096) {
+        Ok(s) => format!(
+            "sniffed: encoding: {:?}; bom: {}; line terminator: {:?}; separator: {:?}; enclosure: {:?}\n",
+            s.encoding, s.bom, s.line_terminator, s.separator_char, s.enclosure_char
+        ),
+        Err(e) => format!("sniffed: {}\n", render_err(&e)),
+    };
+    let parser = match PattiCsvParser::try_from(cfg) {
+        Ok(p) => p,
+        Err(e) => return format!("{}config: {}\n", out, render_err(&e)),
+    };
+    let mut data = Cursor::new(data);
+    for (i, row) in parser.parse_iter(&mut data).take(MAX_ROWS).enumerate() {
+        let line = match row {
+            Ok(row) => row
+                .0
+                .iter()
+                .map(|c| format!("{}={:?}", c.get_name().escape_debug(), c.get_data()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            Err(e) => format!("error: {}", render_err(&e).escape_debug()),
+        };
+        out.push_str(&format!("{}: {}\n", i, line));
+    }
+    out
+}
+
+/// The errors of the converters (i.e. of venum) are not ours to pin down, only their context is.
+fn render_err(e: &PattiCsvError) -> String {
+    match e {
+        PattiCsvError::Generic { msg }
+            if !msg.starts_with("[ERROR_ON_") && !msg.starts_with("error reading") =>
+        {
+            match msg.find("; line: ") {
+                Some(pos) => format!("conversion failed{}", &msg[pos..]),
+                None => String::from("conversion failed"),
+            }
+        }
+        PattiCsvError::Generic { msg } | PattiCsvError::ConfigError { msg } => msg.clone(),
+        other => format!("{:?}", other),
+    }
+}
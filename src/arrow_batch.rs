@@ -0,0 +1,421 @@
+use std::sync::Arc;
+
+use arrow::{
+    array::{
+        ArrayRef, BooleanArray, Date32Array, Decimal128Array, Float32Array, Float64Array,
+        Int16Array, Int32Array, Int64Array, Int8Array, StringArray, TimestampMicrosecondArray,
+        UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+    },
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    record_batch::RecordBatch,
+};
+use chrono::NaiveDate;
+use venum::{value::Value, value_type::ValueType};
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::errors::{PattiCsvError, Result};
+
+/// The scale of the `Decimal128` columns, unless set otherwise (see: `ArrowBatchBuilder::with_decimal_scale`).
+pub const DEFAULT_DECIMAL_SCALE: i8 = 10;
+
+/// The precision of the `Decimal128` columns, i.e. the max. Mind, that an `Int128` beyond it can't be stored.
+const DECIMAL_PRECISION: u8 = 38;
+
+/// The arrow datatype of the column type. Chars are strings, an `Int128` is a `Decimal128` with scale 0, and an
+/// `UInt128` (which arrow has nothing for) is its string form. Dates and times are `Date32` and microsecond
+/// timestamps, the ones with an offset in UTC.
+pub fn arrow_data_type(dtype: &ValueType, decimal_scale: i8) -> DataType {
+    match dtype {
+        ValueType::Char | ValueType::String | ValueType::UInt128 => DataType::Utf8,
+        ValueType::Int8 => DataType::Int8,
+        ValueType::Int16 => DataType::Int16,
+        ValueType::Int32 => DataType::Int32,
+        ValueType::Int64 => DataType::Int64,
+        ValueType::Int128 => DataType::Decimal128(DECIMAL_PRECISION, 0),
+        ValueType::UInt8 => DataType::UInt8,
+        ValueType::UInt16 => DataType::UInt16,
+        ValueType::UInt32 => DataType::UInt32,
+        ValueType::UInt64 => DataType::UInt64,
+        ValueType::Float32 => DataType::Float32,
+        ValueType::Float64 => DataType::Float64,
+        ValueType::Bool => DataType::Boolean,
+        ValueType::Decimal => DataType::Decimal128(DECIMAL_PRECISION, decimal_scale),
+        ValueType::NaiveDate => DataType::Date32,
+        ValueType::NaiveDateTime => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ValueType::DateTime => DataType::Timestamp(TimeUnit::Microsecond, Some(Arc::from("UTC"))),
+    }
+}
+
+/// The arrow schema of the (typed) row, with a nullable field per cell, named like the cell.
+pub fn arrow_schema(row: &DataCellRow, decimal_scale: i8) -> Schema {
+    Schema::new(
+        row.0
+            .iter()
+            .map(|cell| {
+                Field::new(
+                    cell.get_name(),
+                    arrow_data_type(&cell.dtype, decimal_scale),
+                    true,
+                )
+            })
+            .collect::<Vec<Field>>(),
+    )
+}
+
+/// Helper method.
+fn value_err(column: usize, header: &str, expected: &ValueType, value: &Value) -> PattiCsvError {
+    PattiCsvError::Generic {
+        msg: format!(
+            "[ERROR_ON_ARROW] Value doesn't match the column type; column: {}, header: {}, type: {:?}, value: {:?}",
+            column, header, expected, value
+        ),
+    }
+}
+
+/// Helper macro. Builds a primitive array from the values of the `Value` variant (or `None`), errors on others.
+macro_rules! primitive_array {
+    ($array:ty, $variant:ident, $values:expr, $column:expr, $header:expr, $dtype:expr) => {{
+        let values = $values
+            .iter()
+            .map(|v| match v {
+                Value::None => Ok(None),
+                Value::$variant(x) => Ok(Some(*x)),
+                v => Err(value_err($column, $header, $dtype, v)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Arc::new(<$array>::from(values)) as ArrayRef
+    }};
+}
+
+/// Helper method. The array of one column.
+fn build_array(
+    column: usize,
+    field: &Field,
+    dtype: &ValueType,
+    values: &[Value],
+) -> Result<ArrayRef> {
+    let header = field.name().as_str();
+    let array = match dtype {
+        ValueType::Int8 => primitive_array!(Int8Array, Int8, values, column, header, dtype),
+        ValueType::Int16 => primitive_array!(Int16Array, Int16, values, column, header, dtype),
+        ValueType::Int32 => primitive_array!(Int32Array, Int32, values, column, header, dtype),
+        ValueType::Int64 => primitive_array!(Int64Array, Int64, values, column, header, dtype),
+        ValueType::UInt8 => primitive_array!(UInt8Array, UInt8, values, column, header, dtype),
+        ValueType::UInt16 => primitive_array!(UInt16Array, UInt16, values, column, header, dtype),
+        ValueType::UInt32 => primitive_array!(UInt32Array, UInt32, values, column, header, dtype),
+        ValueType::UInt64 => primitive_array!(UInt64Array, UInt64, values, column, header, dtype),
+        ValueType::Float32 => {
+            primitive_array!(Float32Array, Float32, values, column, header, dtype)
+        }
+        ValueType::Float64 => {
+            primitive_array!(Float64Array, Float64, values, column, header, dtype)
+        }
+        ValueType::Bool => primitive_array!(BooleanArray, Bool, values, column, header, dtype),
+        ValueType::Char | ValueType::String | ValueType::UInt128 => {
+            let values = values
+                .iter()
+                .map(|v| match v {
+                    Value::None => Ok(None),
+                    Value::String(s) => Ok(Some(s.clone())),
+                    Value::Char(c) => Ok(Some(c.to_string())),
+                    Value::UInt128(u) => Ok(Some(u.to_string())),
+                    v => Err(value_err(column, header, dtype, v)),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Arc::new(StringArray::from(values)) as ArrayRef
+        }
+        ValueType::Int128 | ValueType::Decimal => {
+            let (precision, scale) = match field.data_type() {
+                DataType::Decimal128(p, s) => (*p, *s),
+                _ => (DECIMAL_PRECISION, 0),
+            };
+            let values = values
+                .iter()
+                .map(|v| match v {
+                    Value::None => Ok(None),
+                    Value::Int128(i) => Ok(Some(*i)),
+                    Value::Decimal(d) => {
+                        let mut d = *d;
+                        d.rescale(scale as u32);
+                        Ok(Some(d.mantissa()))
+                    }
+                    v => Err(value_err(column, header, dtype, v)),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let array = Decimal128Array::from(values)
+                .with_precision_and_scale(precision, scale)
+                .map_err(|e| PattiCsvError::Generic {
+                    msg: format!(
+                        "[ERROR_ON_ARROW] {}; column: {}, header: {}",
+                        e, column, header
+                    ),
+                })?;
+            array
+                .validate_decimal_precision(precision)
+                .map_err(|e| PattiCsvError::Generic {
+                    msg: format!(
+                        "[ERROR_ON_ARROW] {}; column: {}, header: {}",
+                        e, column, header
+                    ),
+                })?;
+            Arc::new(array) as ArrayRef
+        }
+        ValueType::NaiveDate => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+            let values = values
+                .iter()
+                .map(|v| match v {
+                    Value::None => Ok(None),
+                    Value::NaiveDate(d) => {
+                        Ok(Some(d.signed_duration_since(epoch).num_days() as i32))
+                    }
+                    v => Err(value_err(column, header, dtype, v)),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Arc::new(Date32Array::from(values)) as ArrayRef
+        }
+        ValueType::NaiveDateTime | ValueType::DateTime => {
+            let values = values
+                .iter()
+                .map(|v| match v {
+                    Value::None => Ok(None),
+                    Value::NaiveDateTime(d) => Ok(Some(d.and_utc().timestamp_micros())),
+                    Value::DateTime(d) => Ok(Some(d.timestamp_micros())),
+                    v => Err(value_err(column, header, dtype, v)),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let array = TimestampMicrosecondArray::from(values);
+            match dtype {
+                ValueType::DateTime => Arc::new(array.with_timezone("UTC")) as ArrayRef,
+                _ => Arc::new(array) as ArrayRef,
+            }
+        }
+    };
+    Ok(array)
+}
+
+/// Accumulates (parsed) rows into arrow `RecordBatch`es of (up to) the batch size, e.g. to feed DataFusion or to
+/// write Parquet. The schema is taken from the first row (see: `arrow_schema`), so all rows need the same columns.
+/// There is no header, so if the rows come from a parser, that yields the header row, skip it (or see:
+/// `PattiCsvParserBuilder::emit_header_row`).
+#[derive(Debug)]
+pub struct ArrowBatchBuilder {
+    batch_size: usize,
+    decimal_scale: i8,
+    schema: Option<SchemaRef>,
+    dtypes: Vec<ValueType>,
+    columns: Vec<Vec<Value>>,
+    num_rows: usize,
+}
+
+impl ArrowBatchBuilder {
+    /// A batch size of 0 is taken as 1.
+    pub fn new(batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            decimal_scale: DEFAULT_DECIMAL_SCALE,
+            schema: None,
+            dtypes: Vec::new(),
+            columns: Vec::new(),
+            num_rows: 0,
+        }
+    }
+
+    /// The scale of the `Decimal` columns, i.e. the decimals are rounded to it. It's kept within 0 and 28, the max.
+    /// scale of a `Decimal`. Default: `DEFAULT_DECIMAL_SCALE`
+    pub fn with_decimal_scale(mut self, decimal_scale: i8) -> Self {
+        self.decimal_scale = decimal_scale.clamp(0, 28);
+        self
+    }
+
+    /// The schema, once the first row is pushed.
+    pub fn schema(&self) -> Option<SchemaRef> {
+        self.schema.clone()
+    }
+
+    /// The number of rows pushed, but not yet in a batch.
+    pub fn num_buffered_rows(&self) -> usize {
+        self.columns.first().map_or(0, Vec::len)
+    }
+
+    /// The number of rows pushed so far.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Adds the row, and returns the batch, once there are batch size rows.
+    pub fn push_row(&mut self, row: &DataCellRow) -> Result<Option<RecordBatch>> {
+        if self.schema.is_none() {
+            self.schema = Some(Arc::new(arrow_schema(row, self.decimal_scale)));
+            self.dtypes = row.0.iter().map(|cell| cell.dtype.clone()).collect();
+            self.columns = vec![Vec::with_capacity(self.batch_size); row.0.len()];
+        }
+        if row.0.len() != self.dtypes.len() {
+            return Err(PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_ARROW] Row has {} cells, but the schema has {} columns; row: {}",
+                    row.0.len(),
+                    self.dtypes.len(),
+                    self.num_rows + 1
+                ),
+            });
+        }
+        for (column, cell) in self.columns.iter_mut().zip(row.0.iter()) {
+            column.push(cell.get_data().clone());
+        }
+        self.num_rows += 1;
+        if self.num_buffered_rows() >= self.batch_size {
+            return self.finish();
+        }
+        Ok(None)
+    }
+
+    /// The batch of the rows, that are not yet in a batch (if any), e.g. after the last row.
+    pub fn finish(&mut self) -> Result<Option<RecordBatch>> {
+        let schema = match &self.schema {
+            Some(schema) if self.num_buffered_rows() > 0 => schema.clone(),
+            _ => return Ok(None),
+        };
+        let arrays = schema
+            .fields()
+            .iter()
+            .zip(self.dtypes.iter())
+            .zip(self.columns.iter_mut())
+            .enumerate()
+            .map(|(i, ((field, dtype), values))| {
+                let array = build_array(i, field, dtype, values);
+                values.clear();
+                array
+            })
+            .collect::<Result<Vec<ArrayRef>>>()?;
+        RecordBatch::try_new(schema, arrays)
+            .map(Some)
+            .map_err(|e| PattiCsvError::Generic {
+                msg: format!("[ERROR_ON_ARROW] Could not build record batch: {}", e),
+            })
+    }
+}
+
+/// Accumulates the (parsed) rows into record batches of (up to) the batch size (see: `ArrowBatchBuilder`). Stops at
+/// the first error, i.e. a row, that failed to parse, is returned as such.
+///
+/// ```
+/// use patti_csv::{arrow_batch::to_record_batches, prelude::*};
+///
+/// let parser = PattiCsvParserBuilder::csv()
+///     .stringly_type_columns(2)
+///     .emit_header_row(false)
+///     .build()
+///     .unwrap();
+/// let mut data = std::io::Cursor::new("id,name\n1,foo\n2,bar\n3,baz");
+/// let batches = to_record_batches(parser.parse_iter(&mut data), 2).unwrap();
+/// assert_eq!(vec![2, 1], batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>());
+/// ```
+pub fn to_record_batches<I>(rows: I, batch_size: usize) -> Result<Vec<RecordBatch>>
+where
+    I: IntoIterator<Item = Result<DataCellRow>>,
+{
+    let mut builder = ArrowBatchBuilder::new(batch_size);
+    let mut batches = Vec::new();
+    for row in rows {
+        if let Some(batch) = builder.push_row(&row?)? {
+            batches.push(batch);
+        }
+    }
+    if let Some(batch) = builder.finish()? {
+        batches.push(batch);
+    }
+    Ok(batches)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::Array;
+
+    use crate::{iterating_parser::PattiCsvParserBuilder, parser_config::TypeColumnEntry};
+
+    use super::*;
+
+    #[test]
+    fn parse_into_record_batches() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Decimal),
+                TypeColumnEntry::new(None, ValueType::Bool),
+                TypeColumnEntry::new(None, ValueType::NaiveDate),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .emit_header_row(false)
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(
+            "id,amount,paid,due,note\n1,12.5,true,1970-01-03,a\n2,,false,,\n3,-0.125,true,1969-12-31,c",
+        );
+        let mut builder = ArrowBatchBuilder::new(2).with_decimal_scale(2);
+        let mut batches = Vec::new();
+        for row in parser.parse_iter(&mut test_data_cursor) {
+            if let Some(batch) = builder.push_row(&row.unwrap()).unwrap() {
+                batches.push(batch);
+            }
+        }
+        assert_eq!(1, builder.num_buffered_rows());
+        batches.push(builder.finish().unwrap().unwrap());
+        assert_eq!(None, builder.finish().unwrap());
+        assert_eq!(3, builder.num_rows());
+
+        let schema = builder.schema().unwrap();
+        assert_eq!(
+            vec![
+                &DataType::Int32,
+                &DataType::Decimal128(38, 2),
+                &DataType::Boolean,
+                &DataType::Date32,
+                &DataType::Utf8
+            ],
+            schema
+                .fields()
+                .iter()
+                .map(|f| f.data_type())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("amount", schema.field(1).name());
+
+        let first = &batches[0];
+        assert_eq!(2, first.num_rows());
+        let ids = first
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!((1, 2), (ids.value(0), ids.value(1)));
+        let amounts = first
+            .column(1)
+            .as_any()
+            .downcast_ref::<Decimal128Array>()
+            .unwrap();
+        assert_eq!(1250, amounts.value(0));
+        assert!(amounts.is_null(1));
+        let due = first
+            .column(3)
+            .as_any()
+            .downcast_ref::<Date32Array>()
+            .unwrap();
+        assert_eq!(2, due.value(0));
+
+        let last = &batches[1];
+        assert_eq!(1, last.num_rows());
+        let amounts = last
+            .column(1)
+            .as_any()
+            .downcast_ref::<Decimal128Array>()
+            .unwrap();
+        assert_eq!(-13, amounts.value(0)); // rounded to the scale
+        let due = last
+            .column(3)
+            .as_any()
+            .downcast_ref::<Date32Array>()
+            .unwrap();
+        assert_eq!(-1, due.value(0));
+    }
+}
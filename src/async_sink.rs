@@ -0,0 +1,133 @@
+use std::{io::Read, sync::Arc};
+
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{errors::Result, iterating_parser::PattiCsvParser, report::ParseReport};
+
+/// The sending half of a bounded channel, that is fed from a (synchronous) parse run. Sending blocks while the channel
+/// is full, i.e. a slow consumer slows down the parsing (backpressure), instead of piling up rows in memory.
+pub trait RowSender {
+    /// Blocks until the item is sent. `false`, if the receiving half is gone.
+    fn send_blocking(&self, item: Result<DataCellRow>) -> bool;
+}
+
+#[cfg(feature = "tokio")]
+impl RowSender for tokio::sync::mpsc::Sender<Result<DataCellRow>> {
+    /// NOTE: Panics, if called from within an async context. Run it on a blocking thread (see: `spawn_parse_tokio`).
+    fn send_blocking(&self, item: Result<DataCellRow>) -> bool {
+        self.blocking_send(item).is_ok()
+    }
+}
+
+#[cfg(feature = "async_channel")]
+impl RowSender for async_channel::Sender<Result<DataCellRow>> {
+    fn send_blocking(&self, item: Result<DataCellRow>) -> bool {
+        async_channel::Sender::send_blocking(self, item).is_ok()
+    }
+}
+
+/// Parses everything into the sender, incl. the header row (if it's emitted) and the errors. This blocks, so it must
+/// run on a thread where that's ok. If the receiving half is dropped, this stops early, i.e. the report only covers
+/// the rows read so far.
+pub fn pump_rows<R: Read, S: RowSender>(
+    parser: &PattiCsvParser,
+    reader: &mut R,
+    sender: &S,
+) -> ParseReport {
+    let mut iter = parser.parse_iter(reader);
+    for res in iter.by_ref() {
+        if !sender.send_blocking(res) {
+            break;
+        }
+    }
+    iter.finish_report()
+}
+
+/// Parses on tokio's blocking thread pool into a bounded `tokio::sync::mpsc` channel with the given (non-zero) capacity.
+/// The channel is closed when the run is done, and the join handle gives the report.
+///
+/// NOTE: Must be called from within a tokio runtime.
+#[cfg(feature = "tokio")]
+pub fn spawn_parse_tokio<R: Read + Send + 'static>(
+    parser: Arc<PattiCsvParser>,
+    mut reader: R,
+    capacity: usize,
+) -> (
+    tokio::sync::mpsc::Receiver<Result<DataCellRow>>,
+    tokio::task::JoinHandle<ParseReport>,
+) {
+    let (tx, rx) = tokio::sync::mpsc::channel(capacity);
+    let handle = tokio::task::spawn_blocking(move || pump_rows(&parser, &mut reader, &tx));
+    (rx, handle)
+}
+
+/// Parses on a new thread into a bounded `async_channel` with the given (non-zero) capacity. The channel is closed when
+/// the run is done, and the join handle gives the report. Since `async_channel` works with any executor, this
+/// doesn't need a runtime.
+#[cfg(feature = "async_channel")]
+pub fn spawn_parse_async_channel<R: Read + Send + 'static>(
+    parser: Arc<PattiCsvParser>,
+    mut reader: R,
+    capacity: usize,
+) -> (
+    async_channel::Receiver<Result<DataCellRow>>,
+    std::thread::JoinHandle<ParseReport>,
+) {
+    let (tx, rx) = async_channel::bounded(capacity);
+    let handle = std::thread::spawn(move || pump_rows(&parser, &mut reader, &tx));
+    (rx, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::iterating_parser::PattiCsvParserBuilder;
+
+    fn parser() -> Arc<PattiCsvParser> {
+        Arc::new(
+            PattiCsvParserBuilder::csv()
+                .stringly_type_columns(2)
+                .build()
+                .unwrap(),
+        )
+    }
+
+    fn data(num_rows: usize) -> std::io::Cursor<String> {
+        std::io::Cursor::new(
+            std::iter::once(String::from("id,name\n"))
+                .chain((0..num_rows).map(|i| format!("{},foo\n", i)))
+                .collect(),
+        )
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn pump_into_tokio_channel() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let (num_rows, report) = rt.block_on(async {
+            let (mut rx, handle) = spawn_parse_tokio(parser(), data(100), 2);
+            let mut num_rows = 0;
+            while let Some(row) = rx.recv().await {
+                assert!(row.is_ok());
+                num_rows += 1;
+            }
+            (num_rows, handle.await.unwrap())
+        });
+        assert_eq!(101, num_rows); // incl. header
+        assert_eq!(100, report.num_rows_ok);
+    }
+
+    #[cfg(feature = "async_channel")]
+    #[test]
+    fn pump_into_async_channel_stops_when_receiver_is_dropped() {
+        let (rx, handle) = spawn_parse_async_channel(parser(), data(100), 1);
+        assert!(rx.recv_blocking().unwrap().is_ok()); // header
+        assert!(rx.recv_blocking().unwrap().is_ok());
+        drop(rx);
+        let report = handle.join().unwrap();
+        assert!(report.num_rows_ok < 100);
+    }
+}
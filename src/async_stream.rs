@@ -0,0 +1,110 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use tokio::io::AsyncRead;
+use tokio_util::io::SyncIoBridge;
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{
+    async_sink::spawn_parse_tokio,
+    errors::{PattiCsvError, Result},
+    iterating_parser::PattiCsvParser,
+    report::ParseReport,
+};
+
+/// The number of rows buffered between the parse run and the consumer of a `RowStream`, by default.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// The rows of an async reader (e.g. a socket, or a download from an object store), as a `Stream`. The parsing itself
+/// is synchronous, so it runs on tokio's blocking thread pool, reading through a bridge, and never blocks a worker
+/// thread. The rows are handed over by a bounded channel, i.e. a slow consumer slows down the parsing (backpressure).
+/// See: `PattiCsvParser::parse_stream`
+#[derive(Debug)]
+pub struct RowStream {
+    rx: tokio::sync::mpsc::Receiver<Result<DataCellRow>>,
+    handle: tokio::task::JoinHandle<ParseReport>,
+}
+
+impl RowStream {
+    /// Starts the parse run, with the given (non-zero) number of buffered rows.
+    ///
+    /// NOTE: Must be called from within a tokio runtime.
+    pub fn new<R>(parser: Arc<PattiCsvParser>, reader: R, capacity: usize) -> Self
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let (rx, handle) = spawn_parse_tokio(parser, SyncIoBridge::new(reader), capacity);
+        Self { rx, handle }
+    }
+
+    /// The report of the parse run. If the stream isn't exhausted yet, the run is stopped, i.e. the report only covers
+    /// the rows read so far.
+    pub async fn finish_report(self) -> Result<ParseReport> {
+        let Self { rx, handle } = self;
+        drop(rx);
+        handle.await.map_err(|e| PattiCsvError::Generic {
+            msg: format!("[ERROR_ON_STREAM] The parse run failed: {}", e),
+        })
+    }
+}
+
+impl Stream for RowStream {
+    type Item = Result<DataCellRow>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+
+    use venum::value::Value;
+
+    use super::*;
+
+    use crate::iterating_parser::PattiCsvParserBuilder;
+
+    async fn next_row(stream: &mut RowStream) -> Option<Result<DataCellRow>> {
+        poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+    }
+
+    #[test]
+    fn parse_async_reader_as_stream() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let parser = Arc::new(
+            PattiCsvParserBuilder::csv()
+                .stringly_type_columns(2)
+                .build()
+                .unwrap(),
+        );
+        let (rows, report) = rt.block_on(async {
+            let (client, mut server) = tokio::io::duplex(16);
+            tokio::spawn(async move {
+                use tokio::io::AsyncWriteExt;
+                for line in ["id,name\n", "1,foo\n", "2,bar\n"] {
+                    server.write_all(line.as_bytes()).await.unwrap();
+                }
+            });
+            let mut stream = parser.parse_stream(client);
+            let mut rows = Vec::new();
+            while let Some(row) = next_row(&mut stream).await {
+                rows.push(row.unwrap());
+            }
+            (rows, stream.finish_report().await.unwrap())
+        });
+        assert_eq!(3, rows.len()); // incl. header
+        assert_eq!(
+            &Value::from(String::from("bar")),
+            rows[2].get_by_name("name").unwrap().get_data()
+        );
+        assert_eq!(2, report.num_rows_ok);
+    }
+}
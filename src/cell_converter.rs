@@ -0,0 +1,117 @@
+use std::fmt::Debug;
+
+use venum::{value::Value, value_type::ValueType};
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    parser_config::TypeColumnEntry,
+    report::ComponentInfo,
+};
+
+/// Converts a (sanitized) token into a typed `Value`, according to the typing of its column.
+///
+/// NOTE: Tokens that become `Value::None` according to the `NullPolicy` of the column never reach the converter.
+pub trait CellConverter: Debug {
+    fn convert(&self, token: String, column_typing: &TypeColumnEntry) -> Result<Value>;
+    fn get_self_info(&self) -> String {
+        String::from("n/a")
+    }
+    /// The stable id of this kind of converter. See: `ComponentInfo`
+    fn kind(&self) -> &'static str {
+        "custom"
+    }
+    /// The kind and the parameters, e.g. for reports.
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind())
+    }
+}
+
+/// The default converter. Uses venum (i.e. `Value::from_str_and_type_...`) for all conversions.
+#[derive(Debug, Default)]
+pub struct VenumCellConverter;
+impl VenumCellConverter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+impl CellConverter for VenumCellConverter {
+    fn convert(&self, token: String, column_typing: &TypeColumnEntry) -> Result<Value> {
+        // Special short-cut case for String -> String "conversion". I.e. we don't have to do anything.
+        if column_typing.target_type == ValueType::String {
+            return Ok(Value::String(token));
+        }
+        Value::from_str_and_type_with_chrono_pattern_with_none_map(
+            &token,
+            &column_typing.target_type,
+            column_typing.chrono_pattern.as_deref(),
+            None, // already handled by the NullPolicy
+        )
+        .map_err(|e| PattiCsvError::Generic {
+            msg: format!("{:?}", e),
+        })
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "venum"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct YesNoToBool;
+    impl CellConverter for YesNoToBool {
+        fn convert(&self, token: String, _column_typing: &TypeColumnEntry) -> Result<Value> {
+            match token.as_str() {
+                "yes" => Ok(Value::Bool(true)),
+                "no" => Ok(Value::Bool(false)),
+                _ => Err(PattiCsvError::Generic {
+                    msg: format!("not yes/no: {}", token),
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn venum_cell_converter() {
+        let conv = VenumCellConverter::new();
+        assert_eq!(
+            Value::Int32(42),
+            conv.convert(
+                String::from("42"),
+                &TypeColumnEntry::new(None, ValueType::Int32)
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            Value::String(String::from("foo")),
+            conv.convert(
+                String::from("foo"),
+                &TypeColumnEntry::new(None, ValueType::String)
+            )
+            .unwrap()
+        );
+        assert!(conv
+            .convert(
+                String::from("foo"),
+                &TypeColumnEntry::new(None, ValueType::Int32)
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn custom_cell_converter() {
+        let conv = YesNoToBool;
+        let tce = TypeColumnEntry::new(None, ValueType::Bool);
+        assert_eq!(
+            Value::Bool(true),
+            conv.convert(String::from("yes"), &tce).unwrap()
+        );
+        assert!(conv.convert(String::from("true"), &tce).is_err());
+        assert_eq!("n/a", conv.get_self_info());
+    }
+}
@@ -0,0 +1,189 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use venum::{value::Value, value_type::ValueType};
+use venum_tds::data_cell_row::DataCellRow;
+
+/// Statistics of one column, gathered while parsing (see: `PattiCsvParserBuilder::column_stats`), e.g. for data
+/// profiling. Only the data rows, that are yielded as `Ok`, count, except for the type errors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    /// The position of the column in the rows, i.e. after a column selection (if any).
+    pub idx: usize,
+    pub name: String,
+    pub dtype: ValueType,
+    pub num_non_null: usize,
+    pub num_null: usize,
+    /// Cells that can't be typed (incl. missing ones). Mind, that a row fails on its first bad cell, unless all cell
+    /// errors are collected (or the `ErrorPolicy` is not `Fail`), i.e. the other bad cells of the row don't count then.
+    pub num_type_errors: usize,
+    /// The smallest and the largest value, for all types with an order (i.e. not e.g. a `NaN`).
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+    distinct: HashSet<String>,
+    max_distinct: usize,
+}
+
+impl ColumnStats {
+    fn new(idx: usize, name: String, dtype: ValueType, max_distinct: usize) -> Self {
+        Self {
+            idx,
+            name,
+            dtype,
+            num_non_null: 0,
+            num_null: 0,
+            num_type_errors: 0,
+            min: None,
+            max: None,
+            distinct: HashSet::new(),
+            max_distinct,
+        }
+    }
+
+    /// The number of distinct (non-null) values, up to the cap. See: `is_distinct_capped`
+    pub fn num_distinct(&self) -> usize {
+        self.distinct.len()
+    }
+
+    /// Whether the cap of distinct values was reached, i.e. there are at least `num_distinct` distinct values.
+    pub fn is_distinct_capped(&self) -> bool {
+        self.distinct.len() >= self.max_distinct
+    }
+
+    fn add_value(&mut self, value: &Value) {
+        if *value == Value::None {
+            self.num_null += 1;
+            return;
+        }
+        self.num_non_null += 1;
+        if !self.is_distinct_capped() {
+            let key = match value {
+                Value::String(s) => s.clone(),
+                v => format!("{:?}", v),
+            };
+            self.distinct.insert(key);
+        }
+        if value.partial_cmp(value) != Some(Ordering::Equal) {
+            return; // unordered, e.g. NaN
+        }
+        match &self.min {
+            Some(min) if value.partial_cmp(min) != Some(Ordering::Less) => {}
+            _ => self.min = Some(value.clone()),
+        }
+        match &self.max {
+            Some(max) if value.partial_cmp(max) != Some(Ordering::Greater) => {}
+            _ => self.max = Some(value.clone()),
+        }
+    }
+}
+
+/// Helper struct. Gathers the `ColumnStats` of the (selected) columns for the parser iterator.
+#[derive(Debug, Clone)]
+pub(crate) struct ColumnStatsCollector {
+    max_distinct: usize,
+    template_idxs: Vec<usize>, // by column of the rows, the column of the template
+    columns: Vec<ColumnStats>,
+}
+
+impl ColumnStatsCollector {
+    pub(crate) fn new(max_distinct: usize) -> Self {
+        Self {
+            max_distinct,
+            template_idxs: Vec::new(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Sets up the columns from the (complete) column layout template and the selected columns (if any), once they
+    /// are known.
+    pub(crate) fn set_columns(&mut self, template: &DataCellRow, selected: Option<&[usize]>) {
+        self.template_idxs = match selected {
+            Some(selected) => selected.to_vec(),
+            None => (0..template.0.len()).collect(),
+        };
+        self.columns = self
+            .template_idxs
+            .iter()
+            .enumerate()
+            .map(|(idx, &i)| {
+                let cell = &template.0[i];
+                ColumnStats::new(
+                    idx,
+                    cell.name.clone(),
+                    cell.dtype.clone(),
+                    self.max_distinct,
+                )
+            })
+            .collect();
+    }
+
+    pub(crate) fn add_row(&mut self, row: &DataCellRow) {
+        for (stats, cell) in self.columns.iter_mut().zip(row.0.iter()) {
+            stats.add_value(cell.get_data());
+        }
+    }
+
+    /// By the columns of the template, i.e. the ones that aren't selected are ignored.
+    pub(crate) fn add_type_errors(&mut self, template_idxs: &[usize]) {
+        for i in template_idxs {
+            if let Some(pos) = self.template_idxs.iter().position(|t| t == i) {
+                self.columns[pos].num_type_errors += 1;
+            }
+        }
+    }
+
+    pub(crate) fn columns(&self) -> &[ColumnStats] {
+        &self.columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use venum_tds::data_cell::DataCell;
+
+    use super::*;
+
+    #[test]
+    fn gather_column_stats() {
+        let mut template = DataCellRow::new();
+        template.push(DataCell::new_without_data(
+            ValueType::Float64,
+            String::from("x"),
+            0,
+        ));
+        template.push(DataCell::new_without_data(
+            ValueType::String,
+            String::from("s"),
+            1,
+        ));
+        let mut collector = ColumnStatsCollector::new(2);
+        collector.set_columns(&template, None);
+
+        for (x, s) in [
+            (Value::Float64(2.5), Value::from(String::from("b"))),
+            (Value::Float64(f64::NAN), Value::from(String::from("a"))),
+            (Value::None, Value::from(String::from("c"))),
+            (Value::Float64(-1.0), Value::None),
+        ] {
+            let mut row = template.clone();
+            row.0[0].data = x;
+            row.0[1].data = s;
+            collector.add_row(&row);
+        }
+        collector.add_type_errors(&[0, 0]);
+
+        let x = &collector.columns()[0];
+        assert_eq!((3, 1, 2), (x.num_non_null, x.num_null, x.num_type_errors));
+        assert_eq!(Some(Value::Float64(-1.0)), x.min);
+        assert_eq!(Some(Value::Float64(2.5)), x.max);
+        let s = &collector.columns()[1];
+        assert_eq!(Some(Value::from(String::from("a"))), s.min);
+        assert_eq!(Some(Value::from(String::from("c"))), s.max);
+        assert_eq!(2, s.num_distinct());
+        assert!(s.is_distinct_capped());
+
+        collector.set_columns(&template, Some(&[1]));
+        collector.add_type_errors(&[0]);
+        assert_eq!(1, collector.columns().len());
+        assert_eq!(0, collector.columns()[0].num_type_errors);
+    }
+}
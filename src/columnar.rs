@@ -0,0 +1,521 @@
+use std::io::Read;
+
+use venum::{value::Value, value_type::ValueType};
+use venum_tds::{data_cell::DataCell, data_cell_row::DataCellRow};
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    iterating_parser::{PattiCsvParser, PattiCsvParserIterator},
+};
+
+/// One typed column. `Value::None`s are allowed, every other value is of `dtype`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub dtype: ValueType,
+    pub values: Vec<Value>,
+}
+
+/// Helper method.
+fn convert_err(column: &Column, dtype: &ValueType, row_idx: usize, value: &Value) -> PattiCsvError {
+    columnar_err(format!(
+        "Can't convert the value of column {} to {:?}; row: {}, value: {:?}",
+        column.name, dtype, row_idx, value
+    ))
+}
+
+/// Helper macro. Parses the strings (of a `String` column) into the `Value` variant, keeps the `Value::None`s.
+macro_rules! bulk_parse {
+    ($values:expr, $variant:ident, $column:expr, $dtype:expr) => {{
+        let mut converted = Vec::with_capacity($values.len());
+        for (row_idx, v) in $values.iter().enumerate() {
+            converted.push(match v {
+                Value::String(s) => Value::$variant(
+                    s.parse()
+                        .map_err(|_| convert_err($column, &$dtype, row_idx, v))?,
+                ),
+                _ => Value::None,
+            });
+        }
+        converted
+    }};
+}
+
+impl Column {
+    pub fn new<S: Into<String>>(name: S, dtype: ValueType, values: Vec<Value>) -> Self {
+        Self {
+            name: name.into(),
+            dtype,
+            values,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Converts the values of a `String` column to `dtype`, all at once, with one parse loop per type for the
+    /// integer, float and bool types (and `Value::from_str_and_type` for the others), instead of going through the
+    /// cell converter cell by cell. `Value::None`s stay as they are. Either all values are converted, or (on the first
+    /// one, that can't be) none.
+    pub fn convert_strings(&mut self, dtype: ValueType) -> Result<()> {
+        if self.dtype == dtype {
+            return Ok(());
+        }
+        if self.dtype != ValueType::String {
+            return Err(columnar_err(format!(
+                "Column {} is of type {:?}, not String",
+                self.name, self.dtype
+            )));
+        }
+        let values = &self.values;
+        let converted = match dtype {
+            ValueType::Int8 => bulk_parse!(values, Int8, self, dtype),
+            ValueType::Int16 => bulk_parse!(values, Int16, self, dtype),
+            ValueType::Int32 => bulk_parse!(values, Int32, self, dtype),
+            ValueType::Int64 => bulk_parse!(values, Int64, self, dtype),
+            ValueType::UInt8 => bulk_parse!(values, UInt8, self, dtype),
+            ValueType::UInt16 => bulk_parse!(values, UInt16, self, dtype),
+            ValueType::UInt32 => bulk_parse!(values, UInt32, self, dtype),
+            ValueType::UInt64 => bulk_parse!(values, UInt64, self, dtype),
+            ValueType::Float32 => bulk_parse!(values, Float32, self, dtype),
+            ValueType::Float64 => bulk_parse!(values, Float64, self, dtype),
+            ValueType::Bool => bulk_parse!(values, Bool, self, dtype),
+            _ => {
+                let mut converted = Vec::with_capacity(values.len());
+                for (row_idx, v) in values.iter().enumerate() {
+                    converted.push(match v {
+                        Value::String(s) => Value::from_str_and_type(s, &dtype)
+                            .map_err(|_| convert_err(self, &dtype, row_idx, v))?,
+                        _ => Value::None,
+                    });
+                }
+                converted
+            }
+        };
+        self.values = converted;
+        self.dtype = dtype;
+        Ok(())
+    }
+}
+
+/// Column oriented, in-memory data, for consumers that work on whole columns instead of rows. It's built row by row,
+/// e.g. from the parser (see: `from_parser`), where the first row pushed defines the columns.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ColumnarData {
+    columns: Vec<Column>,
+    num_rows: usize,
+}
+
+fn columnar_err(msg: String) -> PattiCsvError {
+    PattiCsvError::Generic {
+        msg: format!("[ERROR_ON_COLUMNAR] {}", msg),
+    }
+}
+
+impl ColumnarData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses everything into columns, i.e. all data rows. The header row (if any) only names the columns. Fails on
+    /// the first error.
+    pub fn from_parser<R: Read>(parser: &PattiCsvParser, reader: &mut R) -> Result<Self> {
+        let mut data = Self::new();
+        let mut iter = parser.parse_iter(reader);
+        while let Some(res) = iter.next() {
+            let row = res?;
+            if !iter.is_at_header_row() {
+                data.push_row(row)?;
+            }
+        }
+        Ok(data)
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    pub fn column_at(&self, idx: usize) -> Option<&Column> {
+        self.columns.get(idx)
+    }
+
+    /// Parses the data in chunks of (up to) `chunk_size` rows, each as `ColumnarData` (see: `ColumnarChunks`). Like
+    /// `from_parser`, the header row (if any) only names the columns.
+    pub fn chunks<'pars, 'rd, R: Read>(
+        parser: &'pars PattiCsvParser,
+        reader: &'rd mut R,
+        chunk_size: usize,
+    ) -> ColumnarChunks<'pars, 'rd, R> {
+        ColumnarChunks {
+            iter: parser.parse_iter(reader),
+            chunk_size: chunk_size.max(1),
+            conversions: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Converts the `String` column to `dtype` (see: `Column::convert_strings`).
+    pub fn convert_column(&mut self, name: &str, dtype: ValueType) -> Result<()> {
+        let idx = self.column_idx(name)?;
+        self.columns[idx].convert_strings(dtype)
+    }
+
+    fn column_idx(&self, name: &str) -> Result<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.name == name)
+            .ok_or_else(|| columnar_err(format!("No column: {}", name)))
+    }
+
+    /// Appends a row. The first row defines the columns (names and types), every following row must have the same
+    /// number of cells.
+    pub fn push_row(&mut self, row: DataCellRow) -> Result<()> {
+        if self.columns.is_empty() && self.num_rows == 0 {
+            self.columns = row
+                .0
+                .iter()
+                .map(|cell| Column::new(cell.name.clone(), cell.dtype.clone(), Vec::new()))
+                .collect();
+        }
+        if row.0.len() != self.columns.len() {
+            return Err(columnar_err(format!(
+                "Row has {} cells, but there are {} columns",
+                row.0.len(),
+                self.columns.len()
+            )));
+        }
+        for (column, cell) in self.columns.iter_mut().zip(row) {
+            column.values.push(cell.data);
+        }
+        self.num_rows += 1;
+        Ok(())
+    }
+
+    /// The row with the 0-based index `n`. None if out of bounds.
+    pub fn row(&self, n: usize) -> Option<Result<DataCellRow>> {
+        if n >= self.num_rows {
+            return None;
+        }
+        let mut row = DataCellRow::with_capacity(self.columns.len());
+        for (idx, column) in self.columns.iter().enumerate() {
+            let cell = DataCell::new_with_type_info(
+                column.dtype.clone(),
+                column.name.clone(),
+                idx,
+                column.values[n].clone(),
+            )
+            .map_err(|e| columnar_err(format!("{:?}; column: {}", e, column.name)));
+            match cell {
+                Ok(cell) => row.push(cell),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        Some(Ok(row))
+    }
+
+    /// Appends a column. It must have a value for every row, and a name that isn't used yet.
+    pub fn add_column(&mut self, column: Column) -> Result<()> {
+        self.insert_column(self.columns.len(), column)
+    }
+
+    fn insert_column(&mut self, idx: usize, column: Column) -> Result<()> {
+        if column.len() != self.num_rows {
+            return Err(columnar_err(format!(
+                "Column {} has {} values, but there are {} rows",
+                column.name,
+                column.len(),
+                self.num_rows
+            )));
+        }
+        if self.column(&column.name).is_some() {
+            return Err(columnar_err(format!(
+                "Column exists already: {}",
+                column.name
+            )));
+        }
+        self.columns.insert(idx, column);
+        Ok(())
+    }
+
+    pub fn delete_column(&mut self, name: &str) -> Option<Column> {
+        let idx = self.column_idx(name).ok()?;
+        Some(self.columns.remove(idx))
+    }
+
+    /// Splits the values of a column into new columns, which are added right after it. The column itself is kept.
+    /// `split` gets every value of the column, and must return one value per new column.
+    pub fn split_column_add<F>(
+        &mut self,
+        name: &str,
+        new_columns: Vec<(String, ValueType)>,
+        split: F,
+    ) -> Result<()>
+    where
+        F: Fn(&Value) -> Result<Vec<Value>>,
+    {
+        let idx = self.column_idx(name)?;
+        // Check the names first, so that we either add all new columns, or none.
+        for (i, (new_name, _)) in new_columns.iter().enumerate() {
+            if self.column(new_name).is_some()
+                || new_columns[..i].iter().any(|(n, _)| n == new_name)
+            {
+                return Err(columnar_err(format!("Column exists already: {}", new_name)));
+            }
+        }
+        let mut new_values = vec![Vec::with_capacity(self.num_rows); new_columns.len()];
+        for (row_idx, value) in self.columns[idx].values.iter().enumerate() {
+            let split_values = split(value)?;
+            if split_values.len() != new_columns.len() {
+                return Err(columnar_err(format!(
+                    "Split of column {} gave {} values for {} new columns; row: {}",
+                    name,
+                    split_values.len(),
+                    new_columns.len(),
+                    row_idx
+                )));
+            }
+            for (values, v) in new_values.iter_mut().zip(split_values) {
+                values.push(v);
+            }
+        }
+        for (offset, ((new_name, dtype), values)) in
+            new_columns.into_iter().zip(new_values).enumerate()
+        {
+            self.insert_column(idx + 1 + offset, Column::new(new_name, dtype, values))?;
+        }
+        Ok(())
+    }
+}
+
+/// The chunked API: parses the data in chunks of `ColumnarData` (see: `ColumnarData::chunks`), e.g. to keep the
+/// memory bounded. Columns registered with `convert_column` have to be typed as `String` by the parser. They are
+/// converted per chunk, with one parse loop per column (see: `Column::convert_strings`), instead of cell by cell. Mind,
+/// that the cell converter, and everything the parser does with the typed value (e.g. the value transformers or the
+/// constraints), is done with the string then. After the first error, there are no more chunks.
+pub struct ColumnarChunks<'pars, 'rd, R: Read> {
+    iter: PattiCsvParserIterator<'pars, 'rd, R>,
+    chunk_size: usize,
+    conversions: Vec<(String, ValueType)>,
+    done: bool,
+}
+
+impl<R: Read> ColumnarChunks<'_, '_, R> {
+    /// Converts the (`String`) column with the name to `dtype`, in every chunk.
+    pub fn convert_column<S: Into<String>>(mut self, name: S, dtype: ValueType) -> Self {
+        self.conversions.push((name.into(), dtype));
+        self
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<ColumnarData>> {
+        let mut data = ColumnarData::new();
+        while data.num_rows() < self.chunk_size {
+            match self.iter.next() {
+                None => {
+                    self.done = true;
+                    break;
+                }
+                Some(res) => {
+                    let row = res?;
+                    if !self.iter.is_at_header_row() {
+                        data.push_row(row)?;
+                    }
+                }
+            }
+        }
+        if data.num_rows() == 0 {
+            return Ok(None);
+        }
+        for (name, dtype) in &self.conversions {
+            data.convert_column(name, dtype.clone())?;
+        }
+        Ok(Some(data))
+    }
+}
+
+impl<R: Read> Iterator for ColumnarChunks<'_, '_, R> {
+    type Item = Result<ColumnarData>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.next_chunk();
+        if res.is_err() {
+            self.done = true;
+        }
+        res.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{iterating_parser::PattiCsvParserBuilder, parser_config::TypeColumnEntry};
+
+    fn columnar_data() -> ColumnarData {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("id,name\n1,Ada Lovelace\n2,Alan Turing");
+        ColumnarData::from_parser(&parser, &mut test_data_cursor).unwrap()
+    }
+
+    #[test]
+    fn columnar_from_parser() {
+        let data = columnar_data();
+        assert_eq!((2, 2), (data.num_rows(), data.num_columns()));
+        assert_eq!(
+            &Column::new(
+                "id",
+                ValueType::Int32,
+                vec![Value::Int32(1), Value::Int32(2)]
+            ),
+            data.column("id").unwrap()
+        );
+        let row = data.row(1).unwrap().unwrap();
+        assert_eq!(
+            &Value::String(String::from("Alan Turing")),
+            row.get_by_name("name").unwrap().get_data()
+        );
+        assert!(data.row(2).is_none());
+    }
+
+    #[test]
+    fn columnar_add_delete_split_columns() {
+        let mut data = columnar_data();
+        data.split_column_add(
+            "name",
+            vec![
+                (String::from("first"), ValueType::String),
+                (String::from("last"), ValueType::String),
+            ],
+            |v| match v {
+                Value::String(s) => Ok(s
+                    .splitn(2, ' ')
+                    .map(|p| Value::String(String::from(p)))
+                    .collect()),
+                _ => Ok(vec![Value::None, Value::None]),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["id", "name", "first", "last"],
+            data.columns()
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Value::String(String::from("Turing")),
+            data.column("last").unwrap().values[1]
+        );
+
+        assert!(data.delete_column("name").is_some());
+        assert!(data.delete_column("name").is_none());
+        assert!(data
+            .add_column(Column::new("x", ValueType::Bool, vec![Value::Bool(true)]))
+            .is_err());
+        assert!(data
+            .add_column(Column::new("id", ValueType::Int32, vec![Value::None; 2]))
+            .is_err());
+        data.add_column(Column::new("x", ValueType::Bool, vec![Value::None; 2]))
+            .unwrap();
+        assert_eq!(4, data.num_columns());
+    }
+
+    #[test]
+    fn columnar_convert_strings() {
+        let mut column = Column::new(
+            "n",
+            ValueType::String,
+            vec![Value::from("1"), Value::None, Value::from("-3")],
+        );
+        column.convert_strings(ValueType::Int16).unwrap();
+        assert_eq!(
+            Column::new(
+                "n",
+                ValueType::Int16,
+                vec![Value::Int16(1), Value::None, Value::Int16(-3)]
+            ),
+            column
+        );
+        // Not a String column (anymore)
+        assert!(column.convert_strings(ValueType::Int32).is_err());
+
+        let mut column = Column::new(
+            "f",
+            ValueType::String,
+            vec![Value::from("1.5"), Value::from("x")],
+        );
+        assert!(column.convert_strings(ValueType::Float64).is_err());
+        // Nothing converted
+        assert_eq!(ValueType::String, column.dtype);
+        assert_eq!(Value::from("1.5"), column.values[0]);
+    }
+
+    #[test]
+    fn columnar_chunks_with_conversions() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap();
+        let mut test_data_cursor =
+            std::io::Cursor::new("id,price,ok\n1,1.5,true\n2,,false\n3,7,true");
+        let chunks = ColumnarData::chunks(&parser, &mut test_data_cursor, 2)
+            .convert_column("id", ValueType::UInt32)
+            .convert_column("price", ValueType::Float64)
+            .convert_column("ok", ValueType::Bool)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            vec![2, 1],
+            chunks.iter().map(|c| c.num_rows()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            &Column::new(
+                "price",
+                ValueType::Float64,
+                vec![Value::Float64(1.5), Value::None]
+            ),
+            chunks[0].column("price").unwrap()
+        );
+        assert_eq!(
+            &Column::new("ok", ValueType::Bool, vec![Value::Bool(true)]),
+            chunks[1].column("ok").unwrap()
+        );
+
+        let mut test_data_cursor =
+            std::io::Cursor::new("id,price,ok\n1,1.5,true\nx,2,true\n3,7,true");
+        let mut chunks = ColumnarData::chunks(&parser, &mut test_data_cursor, 1)
+            .convert_column("id", ValueType::UInt32);
+        assert!(chunks.next().unwrap().is_ok());
+        assert!(chunks.next().unwrap().is_err());
+        assert!(chunks.next().is_none());
+    }
+}
@@ -0,0 +1,306 @@
+use std::{
+    fs::File,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use crate::errors::{PattiCsvError, Result};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const ZIP_EMPTY_MAGIC: &[u8] = b"PK\x05\x06"; // an archive without entries
+const MAGIC_LEN: usize = 4;
+// The read buffer of a file is sized to the file, within these bounds
+const MIN_FILE_BUF_CAPACITY: usize = 8 * 1024;
+const MAX_FILE_BUF_CAPACITY: usize = 256 * 1024;
+
+/// The compression of the input data. It's detected by the magic bytes at the start of the data, not by the file
+/// extension. Decoding needs the feature of the same name (`gzip`, `zstd` or `zip`), detecting doesn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum CompressionKind {
+    Plain,
+    Gzip,
+    Zstd,
+    Zip,
+}
+
+impl CompressionKind {
+    /// Detects the compression by the first bytes of the data. Everything that's neither gzip, zstd nor zip is plain.
+    pub fn detect(first_bytes: &[u8]) -> Self {
+        if first_bytes.starts_with(GZIP_MAGIC) {
+            CompressionKind::Gzip
+        } else if first_bytes.starts_with(ZSTD_MAGIC) {
+            CompressionKind::Zstd
+        } else if first_bytes.starts_with(ZIP_MAGIC) || first_bytes.starts_with(ZIP_EMPTY_MAGIC) {
+            CompressionKind::Zip
+        } else {
+            CompressionKind::Plain
+        }
+    }
+}
+
+/// Reads the decompressed data, e.g. as input for the parser:
+///
+/// ```
+/// use patti_csv::compression::{CompressionKind, DecompressedReader};
+///
+/// let mut reader = DecompressedReader::new(std::io::Cursor::new("id,name\n1,foo"), None).unwrap();
+/// assert_eq!(CompressionKind::Plain, reader.kind());
+/// ```
+///
+/// The kind is detected by the magic bytes, unless it's given explicitly (e.g. for plain data that happens to start
+/// like a gzip stream). A zip archive must contain exactly one file, which is decompressed into memory (the archive
+/// needs random access, which a reader doesn't give us). Gzip and zstd are decompressed while reading, e.g. a
+/// `.csv.gz` file can be parsed as it is (see: `open`).
+pub struct DecompressedReader {
+    kind: CompressionKind,
+    inner: Box<dyn Read + Send>,
+    buf_capacity: Option<usize>, // only for files, see: open
+}
+
+impl std::fmt::Debug for DecompressedReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DecompressedReader")
+            .field("kind", &self.kind)
+            .finish_non_exhaustive()
+    }
+}
+
+fn compression_err(msg: String) -> PattiCsvError {
+    PattiCsvError::ConfigError {
+        msg: format!("[ERROR_ON_DECOMPRESS] {}", msg),
+    }
+}
+
+impl DecompressedReader {
+    pub fn new<R: Read + Send + 'static>(
+        mut reader: R,
+        kind: Option<CompressionKind>,
+    ) -> Result<Self> {
+        // Peek at the magic bytes, and put them back in front afterwards
+        let mut head = Vec::with_capacity(MAGIC_LEN);
+        (&mut reader)
+            .take(MAGIC_LEN as u64)
+            .read_to_end(&mut head)?;
+        let kind = kind.unwrap_or_else(|| CompressionKind::detect(&head));
+        let reader = Cursor::new(head).chain(reader);
+        let inner: Box<dyn Read + Send> = match kind {
+            CompressionKind::Plain => Box::new(reader),
+            CompressionKind::Gzip => gzip_decoder(reader)?,
+            CompressionKind::Zstd => zstd_decoder(reader)?,
+            CompressionKind::Zip => zip_decoder(reader)?,
+        };
+        Ok(Self {
+            kind,
+            inner,
+            buf_capacity: None,
+        })
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P, kind: Option<CompressionKind>) -> Result<Self> {
+        let file = File::open(path.as_ref()).map_err(|e| PattiCsvError::Generic {
+            msg: format!(
+                "[ERROR_ON_DECOMPRESS] Cannot open file {}. Error: {}",
+                path.as_ref().display(),
+                e
+            ),
+        })?;
+        let capacity = file.metadata().map_or(MIN_FILE_BUF_CAPACITY, |m| {
+            (m.len().min(MAX_FILE_BUF_CAPACITY as u64) as usize).max(MIN_FILE_BUF_CAPACITY)
+        });
+        let mut reader = Self::new(file, kind)?;
+        reader.buf_capacity = Some(capacity);
+        Ok(reader)
+    }
+
+    /// The compression of the data, either detected or given.
+    pub fn kind(&self) -> CompressionKind {
+        self.kind
+    }
+
+    /// The size of the read buffer for the data, i.e. sized to the file (within bounds), if it was opened from one.
+    /// The reader doesn't buffer itself, whoever reads it does (e.g. the tokenizer, see: `PattiCsvParser::parse_file`).
+    pub fn buf_capacity(&self) -> Option<usize> {
+        self.buf_capacity
+    }
+}
+
+impl Read for DecompressedReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_decoder<R: Read + Send + 'static>(reader: R) -> Result<Box<dyn Read + Send>> {
+    // Concatenated gzip members are one stream, like `gunzip` does it
+    Ok(Box::new(flate2::read::MultiGzDecoder::new(reader)))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_decoder<R: Read + Send + 'static>(_reader: R) -> Result<Box<dyn Read + Send>> {
+    Err(compression_err(String::from(
+        "Data is gzip compressed, but the feature 'gzip' is not enabled",
+    )))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decoder<R: Read + Send + 'static>(reader: R) -> Result<Box<dyn Read + Send>> {
+    // Concatenated frames are one stream as well
+    Ok(Box::new(zstd::stream::read::Decoder::new(reader)?))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decoder<R: Read + Send + 'static>(_reader: R) -> Result<Box<dyn Read + Send>> {
+    Err(compression_err(String::from(
+        "Data is zstd compressed, but the feature 'zstd' is not enabled",
+    )))
+}
+
+#[cfg(feature = "zip")]
+fn zip_decoder<R: Read + Send + 'static>(mut reader: R) -> Result<Box<dyn Read + Send>> {
+    let mut archive_bytes = Vec::new();
+    reader.read_to_end(&mut archive_bytes)?;
+    let mut archive = zip::ZipArchive::new(Cursor::new(archive_bytes))
+        .map_err(|e| compression_err(format!("Cannot read zip archive. Error: {}", e)))?;
+    let files = archive
+        .file_names()
+        .filter(|n| !n.ends_with('/'))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    if files.len() != 1 {
+        return Err(compression_err(format!(
+            "Zip archive must contain exactly one file, but there are: {:?}",
+            files
+        )));
+    }
+    let mut file = archive.by_name(&files[0]).map_err(|e| {
+        compression_err(format!(
+            "Cannot read {} from zip archive. Error: {}",
+            files[0], e
+        ))
+    })?;
+    let mut data = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut data)?;
+    Ok(Box::new(Cursor::new(data)))
+}
+
+#[cfg(not(feature = "zip"))]
+fn zip_decoder<R: Read + Send + 'static>(_reader: R) -> Result<Box<dyn Read + Send>> {
+    Err(compression_err(String::from(
+        "Data is a zip archive, but the feature 'zip' is not enabled",
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::iterating_parser::PattiCsvParserBuilder;
+
+    #[test]
+    fn detect_by_magic_bytes() {
+        assert_eq!(
+            CompressionKind::Gzip,
+            CompressionKind::detect(&[0x1f, 0x8b, 0x08])
+        );
+        assert_eq!(
+            CompressionKind::Zip,
+            CompressionKind::detect(b"PK\x03\x04...")
+        );
+        assert_eq!(
+            CompressionKind::Zstd,
+            CompressionKind::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0x00])
+        );
+        assert_eq!(CompressionKind::Zip, CompressionKind::detect(b"PK\x05\x06"));
+        assert_eq!(CompressionKind::Plain, CompressionKind::detect(b"PK,id"));
+        assert_eq!(CompressionKind::Plain, CompressionKind::detect(b""));
+    }
+
+    #[test]
+    fn plain_passes_through() {
+        let mut reader = DecompressedReader::new(Cursor::new("id,name\n1,foo"), None).unwrap();
+        assert_eq!(CompressionKind::Plain, reader.kind());
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let mut iter = parser.parse_iter(&mut reader);
+        assert_eq!(2, iter.by_ref().filter(|r| r.is_ok()).count());
+        let report = iter.finish_report().with_compression(reader.kind());
+        assert_eq!(Some(CompressionKind::Plain), report.compression);
+
+        // Overriding the detection
+        let mut reader = DecompressedReader::new(
+            Cursor::new(vec![0x1f, 0x8b, b'x']),
+            Some(CompressionKind::Plain),
+        )
+        .unwrap();
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(vec![0x1f, 0x8b, b'x'], data);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_is_decompressed() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"id,name\n1,foo").unwrap();
+        let gz = encoder.finish().unwrap();
+
+        let mut reader = DecompressedReader::new(Cursor::new(gz), None).unwrap();
+        assert_eq!(CompressionKind::Gzip, reader.kind());
+        let mut data = String::new();
+        reader.read_to_string(&mut data).unwrap();
+        assert_eq!("id,name\n1,foo", data);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_is_decompressed() {
+        let mut zst = zstd::stream::encode_all(&b"id,name\n"[..], 0).unwrap();
+        zst.extend(zstd::stream::encode_all(&b"1,foo"[..], 0).unwrap());
+
+        let mut reader = DecompressedReader::new(Cursor::new(zst), None).unwrap();
+        assert_eq!(CompressionKind::Zstd, reader.kind());
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let rows = parser
+            .parse_iter(&mut reader)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(2, rows.len());
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn zip_is_decompressed() {
+        use std::io::Write;
+
+        let zipped = |names: &[&str]| {
+            let mut w = zip::ZipWriter::new(Cursor::new(Vec::new()));
+            for name in names {
+                w.start_file(*name, zip::write::SimpleFileOptions::default())
+                    .unwrap();
+                w.write_all(b"id,name\n1,foo").unwrap();
+            }
+            w.finish().unwrap().into_inner()
+        };
+
+        let mut reader = DecompressedReader::new(Cursor::new(zipped(&["data.csv"])), None).unwrap();
+        assert_eq!(CompressionKind::Zip, reader.kind());
+        let mut data = String::new();
+        reader.read_to_string(&mut data).unwrap();
+        assert_eq!("id,name\n1,foo", data);
+
+        assert!(DecompressedReader::new(Cursor::new(zipped(&["a.csv", "b.csv"])), None).is_err());
+        assert!(DecompressedReader::new(Cursor::new(zipped(&[])), None).is_err());
+    }
+}
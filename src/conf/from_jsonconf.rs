@@ -1,14 +1,38 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
+
+use rust_decimal::Decimal;
+use venum::value::Value;
 
 use crate::{
     conf::jsonconf::{self, *},
+    constraints::ColumnConstraints,
+    derived_columns::{DatePart, DerivedColumn, DerivedSplit},
     errors::{PattiCsvError, Result},
+    fixed_width::FixedWidthLineTokenizer,
     iterating_parser::{PattiCsvParser, PattiCsvParserBuilder},
-    parser_config::{TypeColumnEntry, VecOfTokenTransitizers},
+    output::{OutputDestination, OutputDialect, OutputFormat, OutputTarget},
+    parser_config::{
+        ColumnCountMismatchPolicy, DuplicateHeaderPolicy, DuplicateRowComparison,
+        DuplicateRowPolicy, HeaderMatchMode, MemoryBudget, MoneyColumns, NullPolicy, NumberFormat,
+        Preamble, QuotedTokenPolicy, SanitizerOrder, TolerantNumeric, TypeColumnEntry,
+        VecOfTokenTransitizers,
+    },
     skip_take_lines::*,
+    split_columns::{
+        BoxedSplitToken, SplitByRegex, SplitBySeparator, SplitColumn, SplitValueAndUnit,
+    },
     transform_sanitize_token::*,
+    value_transformer::{BoxedValueTransformer, MapValues, Round, Scale, ShiftDate},
 };
 
+/// Helper method.
+#[cfg(not(feature = "unicode"))]
+fn unicode_feature_err(what: &str) -> PattiCsvError {
+    PattiCsvError::ConfigError {
+        msg: format!("{} needs the feature 'unicode', which is not enabled", what),
+    }
+}
+
 fn resolve_sanitize_column_opts_entry(
     entry: &SanitizeColumnOpts,
 ) -> Result<VecOfTokenTransitizers> {
@@ -24,17 +48,21 @@ fn resolve_sanitize_column_opts_entry(
             CasingOpts::ToUpper => Ok(vec![Box::new(ToUppercase)]),
         },
 
-        jsonconf::SanitizeColumnOpts::Eradicate { spec } => Ok(spec
+        jsonconf::SanitizeColumnOpts::Eradicate { spec, opts } => Ok(spec
             .iter()
             .map(|er| -> Box<dyn TransformSanitizeToken + Send + Sync> {
-                Box::new(Eradicate::new(er))
+                Box::new(Eradicate::new_with_match(er, LiteralMatch::from(opts)))
             })
             .collect::<VecOfTokenTransitizers>()),
 
-        jsonconf::SanitizeColumnOpts::Replace { spec } => Ok(spec
+        jsonconf::SanitizeColumnOpts::Replace { spec, opts } => Ok(spec
             .iter()
             .map(|re| -> Box<dyn TransformSanitizeToken + Send + Sync> {
-                Box::new(ReplaceWith::new(&re.from, &re.to))
+                Box::new(ReplaceWith::new_with_match(
+                    &re.from,
+                    &re.to,
+                    LiteralMatch::from(opts),
+                ))
             })
             .collect::<VecOfTokenTransitizers>()),
 
@@ -42,38 +70,70 @@ fn resolve_sanitize_column_opts_entry(
             let re = RegexTake::new(spec)?; // <--- this is why we do all this...
             Ok(vec![Box::new(re)])
         }
+
+        jsonconf::SanitizeColumnOpts::Substring { spec } => {
+            let substring = Substring::new(spec.start, spec.len);
+            match spec.graphemes {
+                #[cfg(feature = "unicode")]
+                Some(true) => Ok(vec![Box::new(substring.graphemes())]),
+                #[cfg(not(feature = "unicode"))]
+                Some(true) => Err(unicode_feature_err("substring by graphemes")),
+                _ => Ok(vec![Box::new(substring)]),
+            }
+        }
+
+        jsonconf::SanitizeColumnOpts::DropRow { spec } => {
+            Ok(vec![Box::new(DropRowOn::new(spec.iter().collect()))])
+        }
+
+        #[cfg(feature = "unicode")]
+        jsonconf::SanitizeColumnOpts::NormalizeUnicode { spec } => match spec {
+            NormalFormOpts::Nfc => Ok(vec![Box::new(NormalizeUnicode::Nfc)]),
+            NormalFormOpts::Nfkc => Ok(vec![Box::new(NormalizeUnicode::Nfkc)]),
+        },
+        #[cfg(not(feature = "unicode"))]
+        jsonconf::SanitizeColumnOpts::NormalizeUnicode { spec: _ } => {
+            Err(unicode_feature_err("normalizeUnicode"))
+        }
+        jsonconf::SanitizeColumnOpts::StripControlChars => {
+            Ok(vec![Box::new(StripControlChars::new())])
+        }
+        jsonconf::SanitizeColumnOpts::When { match_regex, then } => {
+            let then = resolve_sanitize_column_opts_entry(then)?;
+            Ok(vec![Box::new(ConditionalTransitizer::when_regex(
+                match_regex,
+                then,
+            )?)])
+        }
     }
 }
 
+/// Helper method. The transitizers of all the sanitizers of the entry, in order.
+fn mk_token_transitizers_for(entry: &SanitizeColumnsEntry) -> Result<VecOfTokenTransitizers> {
+    let tmp_accum: Result<VecOfTokenTransitizers> = Ok(Vec::with_capacity(entry.sanitizers.len())); // This wont be the correct length, but more of a lower bound
+
+    return entry
+        .sanitizers
+        .iter()
+        .map(|san| -> Result<VecOfTokenTransitizers> { resolve_sanitize_column_opts_entry(san) })
+        // I really didn't get how I needed to use flatten + collect in this context, so I did it manually, in the end.
+        // Essentially we want this: [Result<TransformSanitizeTokens>, Result<TransformSanitizeTokens>, ...] -> Result<TransformSanitizeTokens>
+        // However, this means the first error will always end up in the Err part of the Result.
+        .fold(tmp_accum, |acc, mut curr| match acc {
+            Ok(mut acc) => match curr {
+                Ok(ref mut curr) => {
+                    acc.append(curr);
+                    Ok(acc)
+                }
+                Err(err) => Err(err), // if we have an error, pass it through...
+            },
+            Err(err) => Err(err), // if we had an error before, pass it through...
+        });
+}
+
 fn resolve_sanitize_columns_entry(
     entry: &SanitizeColumnsEntry,
 ) -> Result<Vec<(Option<usize>, VecOfTokenTransitizers)>> {
-    // inner resolve helper
-    fn mk_token_transitizers_for(entry: &SanitizeColumnsEntry) -> Result<VecOfTokenTransitizers> {
-        let tmp_accum: Result<VecOfTokenTransitizers> =
-            Ok(Vec::with_capacity(entry.sanitizers.len())); // This wont be the correct length, but more of a lower bound
-
-        return entry
-            .sanitizers
-            .iter()
-            .map(|san| -> Result<VecOfTokenTransitizers> {
-                resolve_sanitize_column_opts_entry(san)
-            })
-            // I really didn't get how I needed to use flatten + collect in this context, so I did it manually, in the end.
-            // Essentially we want this: [Result<TransformSanitizeTokens>, Result<TransformSanitizeTokens>, ...] -> Result<TransformSanitizeTokens>
-            // However, this means the first error will always end up in the Err part of the Result.
-            .fold(tmp_accum, |acc, mut curr| match acc {
-                Ok(mut acc) => match curr {
-                    Ok(ref mut curr) => {
-                        acc.append(curr);
-                        Ok(acc)
-                    }
-                    Err(err) => Err(err), // if we have an error, pass it through...
-                },
-                Err(err) => Err(err), // if we had an error before, pass it through...
-            });
-    }
-
     if let Some(idxs) = &entry.idxs {
         let mut res: Vec<(Option<usize>, VecOfTokenTransitizers)> =
             Vec::with_capacity(idxs.len() * entry.sanitizers.len()); // again, capacity is more of a lower bound
@@ -91,32 +151,249 @@ fn resolve_sanitize_columns_entry(
     }
 }
 
+fn resolve_transform_column_opts(entry: &TransformColumnOpts) -> Result<BoxedValueTransformer> {
+    match entry {
+        TransformColumnOpts::Scale { spec } => {
+            let factor = Decimal::from_str(spec).map_err(|e| PattiCsvError::ConfigError {
+                msg: format!("scale needs a decimal factor, but is: '{}'. {}", spec, e),
+            })?;
+            Ok(Box::new(Scale::new(factor)))
+        }
+        TransformColumnOpts::Round { spec } => Ok(Box::new(Round::new(*spec))),
+        TransformColumnOpts::ShiftDate { spec } => Ok(Box::new(ShiftDate::new(*spec))),
+        TransformColumnOpts::MapValues { spec, strict } => {
+            let map_values = MapValues::new(spec.clone());
+            Ok(Box::new(if *strict == Some(true) {
+                map_values.strict()
+            } else {
+                map_values
+            }))
+        }
+    }
+}
+
+fn resolve_skip_expr(expr: &SkipExprOpts) -> Result<Box<dyn SkipTakeLines + Send + Sync>> {
+    let resolve_all =
+        |exprs: &[SkipExprOpts]| -> Result<Vec<Box<dyn SkipTakeLines + Send + Sync>>> {
+            exprs.iter().map(resolve_skip_expr).collect()
+        };
+    Ok(match expr {
+        SkipExprOpts::StartsWith(s) => Box::new(SkipLinesStartingWith::new(s.as_str())),
+        SkipExprOpts::Regex(re) => Box::new(SkipLinesByRegex::new(re)?),
+        SkipExprOpts::FromStart(n) => Box::new(SkipLinesFromStart::new(*n)),
+        SkipExprOpts::EmptyLine => Box::new(SkipEmptyLines::new()),
+        SkipExprOpts::And(exprs) => Box::new(SkipIfAll::new(resolve_all(exprs)?)),
+        SkipExprOpts::Or(exprs) => Box::new(SkipIfAny::new(resolve_all(exprs)?)),
+        SkipExprOpts::Not(expr) => Box::new(SkipIfNot::new(resolve_skip_expr(expr)?)),
+    })
+}
+
+impl ConfigRoot {
+    /// The column typings of this configuration (if any), e.g. to diff them against a new file. Without the
+    /// constraints, which are only resolved, when the parser is built. See: `schema_evolution`.
+    pub fn column_typings(&self) -> Vec<TypeColumnEntry> {
+        self.type_columns
+            .iter()
+            .flatten()
+            .map(TypeColumnEntry::from)
+            .collect()
+    }
+
+    /// The output target of this configuration (if any). See: `output`.
+    pub fn output_target(&self) -> Result<Option<OutputTarget>> {
+        self.output.as_ref().map(OutputTarget::try_from).transpose()
+    }
+
+    /// The parser and the output target, i.e. everything a read-transform-write job needs.
+    pub fn into_parser_and_output(self) -> Result<(PattiCsvParser, Option<OutputTarget>)> {
+        let output_target = self.output_target()?;
+        Ok((PattiCsvParser::try_from(self)?, output_target))
+    }
+}
+
+impl TryFrom<&OutputOpts> for OutputTarget {
+    type Error = PattiCsvError;
+
+    fn try_from(opts: &OutputOpts) -> Result<Self> {
+        let format = match (&opts.format, &opts.dialect) {
+            (OutputFormatOpts::Csv, dialect_opts) => {
+                let mut dialect = OutputDialect::default();
+                if let Some(d) = dialect_opts {
+                    if let Some(c) = d.separator_char {
+                        dialect.separator_char = c;
+                    }
+                    if d.enclosure_char.is_some() {
+                        dialect.enclosure_char = d.enclosure_char;
+                    }
+                    if let Some(b) = d.write_header {
+                        dialect.write_header = b;
+                    }
+                }
+                OutputFormat::Csv(dialect)
+            }
+            (format, Some(_)) => {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!(
+                        "output dialect is only for csv, but format is: {:?}",
+                        format
+                    ),
+                })
+            }
+            (OutputFormatOpts::Ndjson, None) => OutputFormat::NdJson,
+            (OutputFormatOpts::Arrow, None) => OutputFormat::Arrow,
+        };
+        if opts.path.is_empty() {
+            return Err(PattiCsvError::ConfigError {
+                msg: String::from(
+                    "output path must not be empty (use \"stdout\" for the standard output)",
+                ),
+            });
+        }
+        Ok(OutputTarget {
+            format,
+            destination: OutputDestination::from(opts.path.as_str()),
+        })
+    }
+}
+
+impl From<&Option<LiteralMatchOpts>> for LiteralMatch {
+    fn from(opts: &Option<LiteralMatchOpts>) -> Self {
+        match opts {
+            None => LiteralMatch::default(),
+            Some(opts) => LiteralMatch {
+                ignore_case: opts.ignore_case.unwrap_or(false),
+                whole_token: opts.whole_token.unwrap_or(false),
+                max_count: opts.max_count,
+            },
+        }
+    }
+}
+
+impl From<&HeaderMatchOpts> for HeaderMatchMode {
+    fn from(opts: &HeaderMatchOpts) -> Self {
+        match opts {
+            HeaderMatchOpts::Exact => HeaderMatchMode::Exact,
+            HeaderMatchOpts::IgnoreCaseAndWhitespace => HeaderMatchMode::IgnoreCaseAndWhitespace,
+        }
+    }
+}
+
 impl From<&TypeColumnsEntry> for TypeColumnEntry {
     fn from(entry: &TypeColumnsEntry) -> Self {
-        match (&entry.src_pattern, &entry.map_to_none) {
-            (None, None) => TypeColumnEntry::new(entry.header.clone(), entry.target_type.clone()),
-            (None, Some(map_to_none)) => TypeColumnEntry::new_with_map_to_none(
-                entry.header.clone(),
-                entry.target_type.clone(),
-                map_to_none.clone(),
-            ),
-            (Some(src_pattern), None) => TypeColumnEntry::new_with_chrono_pattern(
+        let tce = match &entry.src_pattern {
+            None => TypeColumnEntry::new(entry.header.clone(), entry.target_type.clone()),
+            Some(src_pattern) => TypeColumnEntry::new_with_chrono_pattern(
                 entry.header.clone(),
                 entry.target_type.clone(),
                 src_pattern.clone(),
             ),
-            (Some(src_pattern), Some(map_to_none)) => {
-                TypeColumnEntry::new_with_chrono_pattern_with_map_to_none(
-                    entry.header.clone(),
-                    entry.target_type.clone(),
-                    src_pattern.clone(),
-                    map_to_none.clone(),
-                )
+        };
+        let mut null_policy = NullPolicy::default();
+        if let Some(map_to_none) = &entry.map_to_none {
+            null_policy = null_policy.with_map_list(map_to_none.clone());
+        }
+        if let Some(b) = entry.empty_is_none {
+            null_policy.empty_is_none = b;
+        }
+        if let Some(b) = entry.missing_is_none {
+            null_policy.missing_is_none = b;
+        }
+        if let Some(b) = entry.quoted_empty_is_empty_string {
+            null_policy.quoted_empty_is_empty_string = b;
+        }
+        let tce = tce
+            .with_null_policy(null_policy)
+            .with_preserve_leading_zeros(entry.preserve_leading_zeros.unwrap_or(false));
+        let tce = match &entry.tolerant_numeric {
+            Some(opts) => {
+                let mut tolerant_numeric = TolerantNumeric::new();
+                if let Some(c) = opts.decimal_separator {
+                    tolerant_numeric = tolerant_numeric.with_decimal_separator(c);
+                }
+                if let Some(b) = opts.record_stripped {
+                    tolerant_numeric = tolerant_numeric.with_record_stripped(b);
+                }
+                tce.with_tolerant_numeric(tolerant_numeric)
+            }
+            None => tce,
+        };
+        let tce = match &entry.quoted_policy {
+            Some(QuotedTokenPolicyOpts::Convert) | None => tce,
+            Some(QuotedTokenPolicyOpts::Literal) => {
+                tce.with_quoted_policy(QuotedTokenPolicy::Literal)
+            }
+            Some(QuotedTokenPolicyOpts::Reject) => {
+                tce.with_quoted_policy(QuotedTokenPolicy::Reject)
             }
+        };
+        let tce = match &entry.number_format {
+            Some(opts) => tce.with_number_format(NumberFormat::new(
+                opts.decimal_separator,
+                opts.thousands_separator,
+            )),
+            None => tce,
+        };
+        let tce = match (&entry.map_to_true, &entry.map_to_false) {
+            (None, None) => tce,
+            (map_to_true, map_to_false) => tce.with_bool_mapping(
+                map_to_true.clone().unwrap_or_default(),
+                map_to_false.clone().unwrap_or_default(),
+            ),
+        };
+        let tce = match &entry.default_value {
+            Some(default_value) => tce.with_default_value(default_value.clone()),
+            None => tce,
+        };
+        match &entry.header_aliases {
+            Some(aliases) => tce.with_header_aliases(aliases.clone()),
+            None => tce,
         }
     }
 }
 
+/// Helper method. The typing of the entry, incl. its constraints (which `From` leaves out, since resolving them can
+/// fail).
+fn resolve_type_columns_entry(entry: &TypeColumnsEntry) -> Result<TypeColumnEntry> {
+    let tce = TypeColumnEntry::from(entry);
+    let opts = match &entry.constraints {
+        Some(opts) => opts,
+        None => return Ok(tce),
+    };
+    let to_value = |token: &String| {
+        Value::from_str_and_type_with_chrono_pattern_with_none_map(
+            token,
+            &tce.target_type,
+            tce.chrono_pattern.as_deref(),
+            None,
+        )
+        .map_err(|e| PattiCsvError::ConfigError {
+            msg: format!(
+                "constraint value {:?} is no {:?} (header: {:?}): {:?}",
+                token, tce.target_type, tce.header, e
+            ),
+        })
+    };
+    let mut constraints = ColumnConstraints::new().with_non_null(opts.non_null.unwrap_or(false));
+    if let Some(min) = &opts.min {
+        constraints = constraints.with_min(to_value(min)?);
+    }
+    if let Some(max) = &opts.max {
+        constraints = constraints.with_max(to_value(max)?);
+    }
+    if let Some(regex) = &opts.regex {
+        constraints = constraints.with_regex(regex)?;
+    }
+    if let Some(allowed_values) = &opts.allowed_values {
+        constraints = constraints.with_allowed_values(
+            allowed_values
+                .iter()
+                .map(to_value)
+                .collect::<Result<Vec<_>>>()?,
+        );
+    }
+    Ok(tce.with_constraints(constraints))
+}
+
 /// Helper method. Fills a given transitizer map with VecOfTokenTransitizers for the given entry.
 fn add_transitizers_from(
     entry: &SanitizeColumnsEntry,
@@ -155,6 +432,99 @@ fn add_transitizers_from(
     Ok(())
 }
 
+/// The splitter of a split (or derived split) of the column, by separator, regex or value and unit.
+fn resolve_splitter(
+    idx: usize,
+    separator: &Option<String>,
+    regex: &Option<String>,
+    value_and_unit: Option<bool>,
+) -> Result<BoxedSplitToken> {
+    let value_and_unit = value_and_unit.unwrap_or(false);
+    Ok(match (separator, regex, value_and_unit) {
+        (Some(sep), None, false) if !sep.is_empty() => Box::new(SplitBySeparator::new(sep.as_str())),
+        (None, Some(re), false) => Box::new(SplitByRegex::new(re)?),
+        (None, None, true) => Box::new(SplitValueAndUnit::new()),
+        (None, Some(re), true) => Box::new(SplitValueAndUnit::with_regex(re)?),
+        _ => {
+            return Err(PattiCsvError::ConfigError {
+                msg: format!(
+                    "split of column {} needs either a (non-empty) separator, a regex or valueAndUnit, but has: {:?} / {:?} / {}",
+                    idx, separator, regex, value_and_unit
+                ),
+            })
+        }
+    })
+}
+
+impl TryFrom<&SplitColumnsEntry> for SplitColumn {
+    type Error = PattiCsvError;
+
+    fn try_from(entry: &SplitColumnsEntry) -> Result<Self> {
+        let splitter = resolve_splitter(
+            entry.idx,
+            &entry.separator,
+            &entry.regex,
+            entry.value_and_unit,
+        )?;
+        Ok(SplitColumn::new(
+            entry.idx,
+            splitter,
+            entry
+                .destinations
+                .iter()
+                .map(resolve_type_columns_entry)
+                .collect::<Result<Vec<_>>>()?,
+        ))
+    }
+}
+
+impl From<&DatePartOpts> for DatePart {
+    fn from(opts: &DatePartOpts) -> Self {
+        match opts {
+            DatePartOpts::Year => DatePart::Year,
+            DatePartOpts::Month => DatePart::Month,
+            DatePartOpts::Day => DatePart::Day,
+            DatePartOpts::Quarter => DatePart::Quarter,
+            DatePartOpts::IsoWeek => DatePart::IsoWeek,
+            DatePartOpts::IsoYear => DatePart::IsoYear,
+            DatePartOpts::DayOfWeek => DatePart::DayOfWeek,
+        }
+    }
+}
+
+/// A derived columns entry is either a date part (with a header), or a split (with destinations).
+fn add_derived_columns_entry(
+    builder: PattiCsvParserBuilder,
+    entry: &DerivedColumnsEntry,
+) -> Result<PattiCsvParserBuilder> {
+    let is_split =
+        entry.separator.is_some() || entry.regex.is_some() || entry.value_and_unit.is_some();
+    match (&entry.part, &entry.header, &entry.destinations) {
+        (Some(part), Some(header), None) if !is_split => Ok(builder.derived_column(
+            DerivedColumn::new(entry.idx, DatePart::from(part), header.as_str()),
+        )),
+        (None, None, Some(destinations)) => Ok(builder.derived_split(DerivedSplit::new(
+            entry.idx,
+            resolve_splitter(
+                entry.idx,
+                &entry.separator,
+                &entry.regex,
+                entry.value_and_unit,
+            )?,
+            destinations
+                .iter()
+                .map(resolve_type_columns_entry)
+                .collect::<Result<Vec<_>>>()?,
+        ))),
+        _ => Err(PattiCsvError::ConfigError {
+            msg: format!(
+                "derived column of column {} needs either a part and a header, or a split with destinations",
+                entry.idx
+            ),
+        }),
+    }
+}
+
 /// A ref to ConfigRoot would actually be sufficient, but we want the ConfigRoot to be dropped.
 impl TryFrom<ConfigRoot> for PattiCsvParser {
     type Error = PattiCsvError;
@@ -165,17 +535,150 @@ impl TryFrom<ConfigRoot> for PattiCsvParser {
             .separator_char(cfg.parser_opts.separator_char)
             .first_data_line_is_header(cfg.parser_opts.first_line_is_header);
 
+        if let Some(b) = cfg.parser_opts.trim_trailing_whitespace {
+            builder = builder.trim_trailing_whitespace(b);
+        }
+        if let Some(b) = cfg.parser_opts.detect_header_enclosure {
+            builder = builder.detect_header_enclosure(b);
+        }
+        if let Some(opts) = &cfg.parser_opts.resolve_typings_by_header {
+            builder = builder.resolve_typings_by_header(opts.into());
+        }
+        if let Some(columns) = &cfg.parser_opts.fixed_width_columns {
+            let columns = columns.iter().map(|&[start, end]| start..end).collect();
+            builder = builder.line_tokenizer(Box::new(FixedWidthLineTokenizer::new(columns)?));
+        }
+        if let Some(c) = cfg.parser_opts.record_separator_char {
+            builder = builder.record_separator(c);
+        }
+        if let Some(c) = cfg.parser_opts.comment_char {
+            builder = builder.comment_char(c);
+        }
+        if let Some(label) = &cfg.parser_opts.encoding {
+            builder = builder.encoding(label);
+        }
+        if let Some(b) = cfg.parser_opts.strip_bom {
+            builder = builder.strip_bom(b);
+        }
+        if let Some(b) = cfg.parser_opts.smart_sanitizer_defaults {
+            builder = builder.smart_sanitizer_defaults(b);
+        }
+        if let Some(b) = cfg.parser_opts.collect_all_cell_errors {
+            builder = builder.collect_all_cell_errors(b);
+        }
+        if let Some(opts) = &cfg.parser_opts.preamble {
+            let mut preamble = match opts.lines {
+                Some(n) => Preamble::lines(n),
+                None => Preamble::while_key_value(),
+            };
+            if let Some(sep) = &opts.kv_separator {
+                preamble = preamble.with_kv_separator(sep.as_str());
+            }
+            builder = builder.preamble(preamble);
+        }
+        if let Some(opts) = &cfg.parser_opts.memory_budget {
+            builder = builder.memory_budget(MemoryBudget {
+                max_skipped_lines: opts.max_skipped_lines,
+                max_saved_skipped_lines_bytes: opts.max_saved_skipped_lines_bytes,
+                max_dropped_rows: opts.max_dropped_rows,
+                max_stripped_tokens: opts.max_stripped_tokens,
+            });
+        }
+        for opts in cfg.parser_opts.money_columns.iter().flatten() {
+            let mut money_columns = MoneyColumns::new(opts.amount_column, opts.currency_column);
+            if opts.combine == Some(true) {
+                money_columns = money_columns.combined();
+            }
+            builder = builder.money_columns(money_columns);
+        }
+        if let Some(opts) = &cfg.parser_opts.duplicate_rows {
+            let policy = match opts.policy {
+                DuplicateRowPolicyOpts::Drop => DuplicateRowPolicy::Drop,
+                DuplicateRowPolicyOpts::Flag => DuplicateRowPolicy::Flag,
+            };
+            let compare_by = match opts.compare_by {
+                Some(DuplicateRowComparisonOpts::RawTokens) => DuplicateRowComparison::RawTokens,
+                Some(DuplicateRowComparisonOpts::TypedRow) | None => {
+                    DuplicateRowComparison::TypedRow
+                }
+            };
+            builder = builder.duplicate_row_policy(policy, compare_by);
+        }
+        if let Some(opts) = &cfg.parser_opts.duplicate_headers {
+            builder = builder.on_duplicate_headers(match opts {
+                DuplicateHeaderPolicyOpts::Error => DuplicateHeaderPolicy::Error,
+                DuplicateHeaderPolicyOpts::RenameWithSuffix => {
+                    DuplicateHeaderPolicy::RenameWithSuffix
+                }
+                DuplicateHeaderPolicyOpts::KeepFirst => DuplicateHeaderPolicy::KeepFirst,
+            });
+        }
+        if let Some(opts) = &cfg.parser_opts.column_count_mismatch {
+            builder = builder.on_column_count_mismatch(match opts {
+                ColumnCountMismatchOpts::Error => ColumnCountMismatchPolicy::Error,
+                ColumnCountMismatchOpts::PadWithNone => ColumnCountMismatchPolicy::PadWithNone,
+                ColumnCountMismatchOpts::TruncateExtra => ColumnCountMismatchPolicy::TruncateExtra,
+            });
+        }
+        if let Some(map_to_none) = &cfg.parser_opts.global_map_to_none {
+            builder = builder.global_map_to_none(map_to_none.clone());
+        }
+        if let Some(unique_columns) = &cfg.parser_opts.unique_columns {
+            builder = builder.unique_columns(unique_columns.clone());
+        }
+
         if let Some(vec_san_col_entry) = &cfg.sanitize_columns {
             let mut transitizers: HashMap<Option<usize>, VecOfTokenTransitizers> =
                 HashMap::with_capacity(vec_san_col_entry.len()); // only correct for idx(1)<-->sanitizer(1) relationships
 
-            vec_san_col_entry.iter().try_for_each(|san_col_entry| {
-                add_transitizers_from(san_col_entry, &mut transitizers)
-            })?;
+            let mut transitizers_by_name: HashMap<String, VecOfTokenTransitizers> = HashMap::new();
+
+            for san_col_entry in vec_san_col_entry {
+                match (&san_col_entry.names, &san_col_entry.idxs) {
+                    (None, _) => add_transitizers_from(san_col_entry, &mut transitizers)?,
+                    (Some(_), Some(_)) => {
+                        return Err(PattiCsvError::ConfigError {
+                            msg: format!(
+                                "sanitizers are either for idxs or for names, not both: {:?}",
+                                san_col_entry
+                            ),
+                        })
+                    }
+                    (Some(names), None) => {
+                        for name in names {
+                            let mut new_transitizers = mk_token_transitizers_for(san_col_entry)?;
+                            transitizers_by_name
+                                .entry(name.clone())
+                                .or_default()
+                                .append(&mut new_transitizers);
+                        }
+                    }
+                }
+            }
 
             if !transitizers.is_empty() {
                 builder = builder.column_transitizers(transitizers);
             }
+            if !transitizers_by_name.is_empty() {
+                builder = builder.column_transitizers_by_name(transitizers_by_name);
+            }
+
+            for san_col_entry in vec_san_col_entry {
+                let order = match &san_col_entry.order {
+                    None => continue,
+                    Some(SanitizerOrderOpts::BeforeGlobal) => SanitizerOrder::LocalFirst,
+                    Some(SanitizerOrderOpts::AfterGlobal) => SanitizerOrder::GlobalFirst,
+                };
+                let idxs = san_col_entry.idxs.as_ref().ok_or_else(|| PattiCsvError::ConfigError {
+                    msg: format!(
+                        "the order of sanitizers is only for column specific sanitizers (i.e. with idxs): {:?}",
+                        san_col_entry
+                    ),
+                })?;
+                for &i in idxs {
+                    builder = builder.sanitizer_order(i, order);
+                }
+            }
         }
 
         if let Some(skip_take_lines_cfg) = &cfg.parser_opts.lines {
@@ -187,6 +690,9 @@ impl TryFrom<ConfigRoot> for PattiCsvParser {
             if let Some(v) = skip_take_lines_cfg.skip_lines_from_start {
                 skip_take_lines.push(Box::new(SkipLinesFromStart::new(v)));
             }
+            if let Some(v) = skip_take_lines_cfg.skip_lines_from_end {
+                builder = builder.skip_lines_from_end(v);
+            }
             if let Some(v) = &skip_take_lines_cfg.skip_lines_by_startswith {
                 v.iter()
                     .for_each(|e| skip_take_lines.push(Box::new(SkipLinesStartingWith::new(e))));
@@ -197,15 +703,66 @@ impl TryFrom<ConfigRoot> for PattiCsvParser {
                     skip_take_lines.push(Box::new(tmp))
                 }
             }
+            for expr in skip_take_lines_cfg.skip_lines_by_expr.iter().flatten() {
+                skip_take_lines.push(resolve_skip_expr(expr)?);
+            }
+            // A line is taken, if any of the take filters takes it
+            let mut take_lines: Vec<Box<dyn SkipTakeLines + Send + Sync>> = Vec::new();
+            for s in skip_take_lines_cfg
+                .take_lines_by_startswith
+                .iter()
+                .flatten()
+            {
+                take_lines.push(Box::new(TakeLinesStartingWith::new(s)));
+            }
+            for re in skip_take_lines_cfg.take_lines_by_regex.iter().flatten() {
+                take_lines.push(Box::new(TakeLinesByRegex::new(re)?));
+            }
+            match take_lines.len() {
+                0 => (),
+                1 => skip_take_lines.append(&mut take_lines),
+                _ => skip_take_lines.push(Box::new(SkipIfAll::new(take_lines))),
+            }
+            if let Some([from, to]) = skip_take_lines_cfg.only_within_lines {
+                if from == 0 || from > to {
+                    return Err(PattiCsvError::ConfigError {
+                        msg: format!("onlyWithinLines must be [from, to] with 1 <= from <= to, but is: [{}, {}]", from, to),
+                    });
+                }
+                skip_take_lines = skip_take_lines
+                    .into_iter()
+                    .map(|f| -> Box<dyn SkipTakeLines + Send + Sync> {
+                        Box::new(OnlyWithinLines::new(from..=to, f))
+                    })
+                    .collect();
+            }
 
             if !skip_take_lines.is_empty() {
                 builder = builder.skip_take_lines_fns(skip_take_lines);
             }
         }
 
-        if let Some(col_typings_cfg) = &cfg.type_columns {
-            let col_typings = col_typings_cfg.iter().map(TypeColumnEntry::from).collect();
-            builder = builder.column_typings(col_typings);
+        if cfg.type_columns.is_some() {
+            builder = builder.column_typings(
+                cfg.type_columns
+                    .iter()
+                    .flatten()
+                    .map(resolve_type_columns_entry)
+                    .collect::<Result<Vec<_>>>()?,
+            );
+        }
+        for entry in cfg.split_columns.iter().flatten() {
+            builder = builder.split_column(SplitColumn::try_from(entry)?);
+        }
+        for entry in cfg.derived_columns.iter().flatten() {
+            builder = add_derived_columns_entry(builder, entry)?;
+        }
+        for entry in cfg.transform_columns.iter().flatten() {
+            for &i in &entry.idxs {
+                for opts in &entry.transformers {
+                    builder = builder.value_transformer(i, resolve_transform_column_opts(opts)?);
+                }
+            }
         }
 
         builder.build()
@@ -219,6 +776,7 @@ mod tests {
     use venum_tds::{data_cell::DataCell, data_cell_row::DataCellRow};
 
     use super::*;
+    use crate::report::ComponentInfo;
 
     pub mod resolve_sanitize_column_opts_entry {
         use super::*;
@@ -298,10 +856,79 @@ mod tests {
             Ok(())
         }
 
+        #[cfg(feature = "unicode")]
+        #[test]
+        fn resolve_substring() -> Result<()> {
+            let test_setup_val = SanitizeColumnOpts::Substring {
+                spec: SubstringOpts {
+                    start: 1,
+                    len: Some(2),
+                    graphemes: Some(true),
+                },
+            };
+            let exp = Substring::new(1, Some(2)).graphemes();
+            let test_val = resolve_sanitize_column_opts_entry(&test_setup_val)?;
+
+            assert_eq!(exp.get_self_info(), test_val[0].get_self_info());
+            Ok(())
+        }
+
+        #[test]
+        fn resolve_when() -> Result<()> {
+            let test_setup_val: SanitizeColumnOpts = serde_json::from_str(
+                r#"{ "type": "when", "match": "^[a-z]{2}$", "then": { "type": "casing", "spec": "toUpper" } }"#,
+            )
+            .unwrap();
+            let test_val = resolve_sanitize_column_opts_entry(&test_setup_val)?;
+
+            assert_eq!(1, test_val.len());
+            assert_eq!("when", test_val[0].kind());
+            assert_eq!(Ok(String::from("CH")), test_val[0].transitize("ch"));
+            assert_eq!(Ok(String::from("chf")), test_val[0].transitize("chf"));
+
+            let test_setup_val = SanitizeColumnOpts::When {
+                match_regex: String::from("("),
+                then: Box::new(SanitizeColumnOpts::Trim {
+                    spec: TrimOpts::All,
+                }),
+            };
+            assert!(resolve_sanitize_column_opts_entry(&test_setup_val).is_err());
+            Ok(())
+        }
+
+        #[cfg(feature = "unicode")]
+        #[test]
+        fn resolve_normalize_unicode_and_strip_control_chars() -> Result<()> {
+            let test_setup_val: Vec<SanitizeColumnOpts> = serde_json::from_str(
+                r#"[ { "type": "stripControlChars" }, { "type": "normalizeUnicode", "spec": "nfkc" } ]"#,
+            )
+            .unwrap();
+            let test_val = test_setup_val
+                .iter()
+                .map(resolve_sanitize_column_opts_entry)
+                .collect::<Result<Vec<_>>>()?;
+
+            assert_eq!("stripControlChars", test_val[0][0].kind());
+            assert_eq!(
+                Ok(String::from("1000")),
+                test_val[0][0].transitize("\u{FEFF}10\u{200B}00")
+            );
+            assert_eq!(
+                NormalizeUnicode::Nfkc.get_self_info(),
+                test_val[1][0].get_self_info()
+            );
+            assert_eq!(
+                Ok(String::from("fi")),
+                test_val[1][0].transitize("\u{FB01}")
+            );
+            Ok(())
+        }
+
         #[test]
         fn resolve_eradicate() -> Result<()> {
             let test_setup_val = SanitizeColumnOpts::Eradicate {
                 spec: vec![String::from("foo"), String::from("bar")],
+                opts: None,
             };
             let exp = vec![
                 Box::new(Eradicate::new("foo")),
@@ -333,6 +960,7 @@ mod tests {
                         to: String::from("ddd"),
                     },
                 ],
+                opts: None,
             };
             let exp = vec![
                 Box::new(ReplaceWith::new("aaa", "bbb")),
@@ -357,6 +985,8 @@ mod tests {
         let sce = SanitizeColumnsEntry {
             comment: None,
             idxs: Some(vec![0_usize, 1]),
+            names: None,
+            order: None,
             sanitizers: vec![
                 SanitizeColumnOpts::Trim {
                     spec: TrimOpts::All,
@@ -386,6 +1016,8 @@ mod tests {
         let sce = SanitizeColumnsEntry {
             comment: None,
             idxs: None,
+            names: None,
+            order: None,
             sanitizers: vec![SanitizeColumnOpts::Trim {
                 spec: TrimOpts::All,
             }],
@@ -409,11 +1041,14 @@ mod tests {
         let sce = SanitizeColumnsEntry {
             comment: None,
             idxs: Some(vec![1_usize]),
+            names: None,
+            order: None,
             sanitizers: vec![SanitizeColumnOpts::Replace {
                 spec: vec![ReplaceColumnSanitizerEntry {
                     from: String::from("foo"),
                     to: String::from("bar"),
                 }],
+                opts: None,
             }],
         };
 
@@ -450,6 +1085,28 @@ mod tests {
         assert_eq!(exp, res);
     }
 
+    #[test]
+    fn from_type_columns_entry_for_type_column_entry_null_policy() {
+        let exp = TypeColumnEntry::new(Some(String::from("header-1")), ValueType::String)
+            .with_null_policy(
+                NullPolicy::empty_is_empty_string()
+                    .with_map_list(vec![String::from("null")])
+                    .with_missing_is_none(true)
+                    .with_quoted_empty_is_empty_string(true),
+            )
+            .with_quoted_policy(QuotedTokenPolicy::Literal);
+        let test = TypeColumnsEntry::builder()
+            .with_header("header-1")
+            .with_map_to_none(vec![String::from("null")])
+            .with_empty_is_none(false)
+            .with_missing_is_none(true)
+            .with_quoted_empty_is_empty_string(true)
+            .with_quoted_policy(QuotedTokenPolicyOpts::Literal)
+            .build_with_target_type(ValueType::String);
+        let res = TypeColumnEntry::from(&test);
+        assert_eq!(exp, res);
+    }
+
     #[test]
     fn try_from_data_cfg_root_tuple_for_patti_csv_parser_1() {
         let cfg = ConfigRoot {
@@ -461,17 +1118,42 @@ mod tests {
                 lines: Some(ParserOptLines {
                     comment: None,
                     skip_lines_from_start: Some(1_usize),
+                    skip_lines_from_end: None,
                     skip_empty_lines: Some(true),
                     skip_lines_by_startswith: Some(vec![String::from("#"), String::from("-")]),
                     skip_lines_by_regex: None,
+                    skip_lines_by_expr: None,
+                    only_within_lines: None,
+                    take_lines_by_startswith: None,
+                    take_lines_by_regex: None,
                 }),
                 first_line_is_header: true,
                 save_skipped_lines: false,
+                trim_trailing_whitespace: None,
+                detect_header_enclosure: None,
+                resolve_typings_by_header: None,
+                duplicate_rows: None,
+                smart_sanitizer_defaults: None,
+                record_separator_char: None,
+                money_columns: None,
+                collect_all_cell_errors: None,
+                preamble: None,
+                memory_budget: None,
+                fixed_width_columns: None,
+                comment_char: None,
+                encoding: None,
+                strip_bom: None,
+                duplicate_headers: None,
+                column_count_mismatch: None,
+                global_map_to_none: None,
+                unique_columns: None,
             },
             sanitize_columns: Some(vec![
                 SanitizeColumnsEntry {
                     comment: None,
                     idxs: None,
+                    names: None,
+                    order: None,
                     sanitizers: vec![SanitizeColumnOpts::Trim {
                         spec: TrimOpts::All,
                     }],
@@ -479,6 +1161,8 @@ mod tests {
                 SanitizeColumnsEntry {
                     comment: None,
                     idxs: Some(vec![0_usize]),
+                    names: None,
+                    order: None,
                     sanitizers: vec![SanitizeColumnOpts::Casing {
                         spec: CasingOpts::ToLower,
                     }],
@@ -503,6 +1187,10 @@ mod tests {
                     .with_datetype_src_pattern("%F")
                     .build_with_target_type(ValueType::NaiveDate),
             ]),
+            split_columns: None,
+            derived_columns: None,
+            transform_columns: None,
+            output: None,
         };
 
         let data_str =
@@ -584,4 +1272,763 @@ mod tests {
             res_line01
         );
     }
+
+    #[test]
+    fn config_root_with_sanitizer_order() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false
+            },
+            "sanitizeColumns": [
+                { "sanitizers": [{ "type": "trim", "spec": "all" }] },
+                {
+                    "idxs": [0],
+                    "order": "beforeGlobal",
+                    "sanitizers": [{ "type": "regexTake", "spec": "^(.*)\\(" }]
+                }
+            ],
+            "typeColumns": [{ "targetType": "String" }]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        assert_eq!(
+            vec![
+                format!(
+                    "local: {}",
+                    RegexTake::new("^(.*)\\(").unwrap().get_self_info()
+                ),
+                format!("global: {}", TrimAll.get_self_info())
+            ],
+            parser.sanitizer_chain(0)
+        );
+        assert_eq!(
+            vec![
+                (
+                    "local",
+                    ComponentInfo::new("regexTake").with_param("regex", "^(.*)\\(")
+                ),
+                ("global", ComponentInfo::new("trimAll"))
+            ],
+            parser.describe_sanitizer_chain(0)
+        );
+        let mut test_data_cursor = std::io::Cursor::new("amount\n10.50 (CHF)");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            &Value::String(String::from("10.50")),
+            res[1].get_by_name("amount").unwrap().get_data()
+        );
+    }
+
+    #[test]
+    fn config_root_with_sanitizers_by_name() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false,
+                "resolveTypingsByHeader": "exact"
+            },
+            "sanitizeColumns": [
+                { "sanitizers": [{ "type": "trim", "spec": "all" }] },
+                { "names": ["amount"], "sanitizers": [{ "type": "replace", "spec": [{ "from": "_", "to": "." }] }] }
+            ],
+            "typeColumns": [
+                { "header": "id", "targetType": "Int32" },
+                { "header": "amount", "targetType": "Float64" }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        for data in ["id,amount\n1, 1_5", "amount,id\n1_5 ,1"] {
+            let mut test_data_cursor = std::io::Cursor::new(data);
+            let res = parser
+                .parse_iter(&mut test_data_cursor)
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(
+                &Value::Float64(1.5),
+                res[1].get_by_name("amount").unwrap().get_data()
+            );
+        }
+
+        let cfg_str = r#"
+        {
+            "parserOpts": { "separatorChar": ",", "firstLineIsHeader": true, "saveSkippedLines": false },
+            "sanitizeColumns": [
+                { "idxs": [0], "names": ["amount"], "sanitizers": [{ "type": "trim", "spec": "all" }] }
+            ],
+            "typeColumns": [{ "targetType": "String" }]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        assert!(matches!(
+            PattiCsvParser::try_from(cfg),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn config_root_with_fixed_width_columns() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": false,
+                "saveSkippedLines": false,
+                "fixedWidthColumns": [[0, 4], [6, 10]]
+            },
+            "sanitizeColumns": [{ "sanitizers": [{ "type": "trim", "spec": "all" }] }],
+            "typeColumns": [{ "targetType": "Int32" }, { "targetType": "String" }]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("  42,,ab,c\n0007XXd");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(&Value::Int32(42), res[0].0[0].get_data());
+        assert_eq!(&Value::from(String::from("ab,c")), res[0].0[1].get_data());
+        assert_eq!(&Value::from(String::from("d")), res[1].0[1].get_data());
+
+        let cfg_str = cfg_str.replace("[6, 10]", "[6, 6]");
+        let cfg: ConfigRoot = serde_json::from_str(&cfg_str).unwrap();
+        assert!(matches!(
+            PattiCsvParser::try_from(cfg),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn config_root_with_bool_mapping_and_number_format() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ";",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false
+            },
+            "typeColumns": [
+                { "targetType": "Bool", "mapToTrue": ["Y"], "mapToFalse": ["N"] },
+                { "targetType": "Float64", "numberFormat": { "decimalSeparator": ",", "thousandsSeparator": "." } }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("active;amount\nY;1.000,5\nN;1\ntrue;0");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(&Value::Float64(1000.5), res[1].0[1].get_data());
+        assert_eq!(
+            vec![Value::Bool(true), Value::Bool(false), Value::Bool(true)],
+            res[1..]
+                .iter()
+                .map(|r| r.0[0].get_data().clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn config_root_with_comment_char() {
+        let cfg_str = r##"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false,
+                "commentChar": "#"
+            },
+            "typeColumns": [{ "targetType": "String" }, { "targetType": "Int32" }]
+        }
+        "##;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("# export\nc1,c2\na,1\n# note\nb,2");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let res = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(3, res.len()); // incl. header
+        assert_eq!(&Value::Int32(2), res[2].0[1].get_data());
+        assert_eq!(2, iter.get_stats().num_comment_lines);
+
+        let cfg_str = cfg_str.replace("\"commentChar\": \"#\"", "\"commentChar\": \",\"");
+        let cfg: ConfigRoot = serde_json::from_str(&cfg_str).unwrap();
+        assert!(matches!(
+            PattiCsvParser::try_from(cfg),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn config_root_with_duplicate_headers() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false,
+                "duplicateHeaders": "renameWithSuffix"
+            },
+            "typeColumns": [{ "targetType": "Int32" }, { "targetType": "Int32" }]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("id,id\n1,2");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            &Value::Int32(2),
+            res[1].get_by_name("id_2").unwrap().get_data()
+        );
+
+        let cfg_str = cfg_str.replace("renameWithSuffix", "error");
+        let cfg: ConfigRoot = serde_json::from_str(&cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("id,id\n1,2");
+        assert!(matches!(
+            parser.parse_iter(&mut test_data_cursor).next(),
+            Some(Err(PattiCsvError::ConfigError { .. }))
+        ));
+    }
+
+    #[test]
+    fn config_root_with_column_count_mismatch() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false,
+                "columnCountMismatch": "padWithNone"
+            },
+            "typeColumns": [{ "targetType": "Int32" }, { "targetType": "Int32" }]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("a,b\n1\n1,2,3");
+        let res = parser.parse_iter(&mut test_data_cursor).collect::<Vec<_>>();
+        assert_eq!(&Value::None, res[1].as_ref().unwrap().0[1].get_data());
+        assert!(matches!(
+            res[2],
+            Err(PattiCsvError::ColumnCountMismatch { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn config_root_with_global_map_to_none() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false,
+                "globalMapToNone": ["NULL", "N/A"]
+            },
+            "typeColumns": [
+                { "targetType": "Int32" },
+                { "targetType": "String", "mapToNone": ["-"] }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("a,b\nN/A,-\nNULL,NULL");
+        let rows = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(&Value::None, rows[1].0[0].get_data());
+        assert_eq!(&Value::None, rows[1].0[1].get_data());
+        assert_eq!(&Value::None, rows[2].0[0].get_data());
+        assert_eq!(
+            &Value::String(String::from("NULL")),
+            rows[2].0[1].get_data()
+        );
+    }
+
+    #[test]
+    fn config_root_with_unique_columns() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false,
+                "uniqueColumns": [[0, 1]]
+            },
+            "typeColumns": [{ "targetType": "String" }, { "targetType": "Int32" }]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("a,b\nx,1\nx,2\nx,1");
+        let res = parser.parse_iter(&mut test_data_cursor).collect::<Vec<_>>();
+        assert!(res[2].is_ok());
+        assert!(matches!(
+            res[3],
+            Err(PattiCsvError::DuplicateKey {
+                line: 4,
+                first_line: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn config_root_with_constraints() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false
+            },
+            "typeColumns": [
+                { "targetType": "NaiveDate", "constraints": { "nonNull": true, "min": "2000-01-01" } },
+                { "targetType": "String", "constraints": { "allowedValues": ["CHF", "EUR"] } }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg.clone()).unwrap();
+        let mut test_data_cursor =
+            std::io::Cursor::new("a,b\n2021-01-03,CHF\n1999-12-31,EUR\n2021-01-03,USD");
+        let res = parser.parse_iter(&mut test_data_cursor).collect::<Vec<_>>();
+        assert!(res[1].is_ok());
+        assert!(matches!(
+            res[2],
+            Err(PattiCsvError::Validation {
+                line: 3,
+                column: 0,
+                ..
+            })
+        ));
+        assert!(matches!(
+            res[3],
+            Err(PattiCsvError::Validation {
+                line: 4,
+                column: 1,
+                ..
+            })
+        ));
+
+        let mut cfg = cfg;
+        cfg.type_columns.as_mut().unwrap()[0]
+            .constraints
+            .as_mut()
+            .unwrap()
+            .max = Some(String::from("tomorrow"));
+        assert!(matches!(
+            PattiCsvParser::try_from(cfg),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn config_root_with_default_values() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false,
+                "globalMapToNone": ["NULL"]
+            },
+            "typeColumns": [
+                { "targetType": "Int32", "defaultValue": "0" },
+                { "targetType": "Bool", "defaultValue": "false" }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg.clone()).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("a,b\nNULL,\n1,true");
+        let rows = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(&Value::Int32(0), rows[1].0[0].get_data());
+        assert_eq!(&Value::Bool(false), rows[1].0[1].get_data());
+        assert_eq!(&Value::Int32(1), rows[2].0[0].get_data());
+
+        let mut cfg = cfg;
+        cfg.type_columns.as_mut().unwrap()[0].default_value = Some(String::from("zero"));
+        assert!(matches!(
+            PattiCsvParser::try_from(cfg),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn config_root_with_split_columns() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false
+            },
+            "typeColumns": [{ "targetType": "String" }],
+            "splitColumns": [
+                {
+                    "idx": 0,
+                    "regex": "^(\\w+)-(\\d+)$",
+                    "destinations": [
+                        { "header": "name", "targetType": "String" },
+                        { "header": "year", "targetType": "Int16" }
+                    ]
+                }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("person\nada-1815");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            &Value::Int16(1815),
+            res[1].get_by_name("year").unwrap().get_data()
+        );
+
+        let entry = SplitColumnsEntry {
+            comment: None,
+            idx: 0,
+            separator: Some(String::from(";")),
+            regex: Some(String::from("(.*)")),
+            value_and_unit: None,
+            destinations: vec![],
+        };
+        assert!(SplitColumn::try_from(&entry).is_err());
+        let entry = SplitColumnsEntry {
+            comment: None,
+            idx: 0,
+            separator: Some(String::from(" ")),
+            regex: None,
+            value_and_unit: Some(true),
+            destinations: vec![],
+        };
+        assert!(SplitColumn::try_from(&entry).is_err());
+
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ";",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false
+            },
+            "typeColumns": [{ "targetType": "String" }],
+            "splitColumns": [
+                {
+                    "idx": 0,
+                    "valueAndUnit": true,
+                    "destinations": [
+                        { "header": "weight", "targetType": "Float64" },
+                        { "header": "unit", "targetType": "String" }
+                    ]
+                }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("weight\n42.5 kg");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            &Value::Float64(42.5),
+            res[1].get_by_name("weight").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::String(String::from("kg")),
+            res[1].get_by_name("unit").unwrap().get_data()
+        );
+    }
+
+    #[test]
+    fn config_root_with_derived_columns() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false
+            },
+            "typeColumns": [{ "targetType": "String" }, { "targetType": "NaiveDate" }],
+            "derivedColumns": [
+                { "idx": 1, "part": "isoYear", "header": "year" },
+                { "idx": 1, "part": "isoWeek", "header": "week" }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(
+            "id,date
+a,2021-01-03
+b,",
+        );
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            vec!["id", "date", "year", "week"],
+            res[0].0.iter().map(|c| c.get_name()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            &Value::Int32(2020),
+            res[1].get_by_name("year").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::UInt32(53),
+            res[1].get_by_name("week").unwrap().get_data()
+        );
+        assert_eq!(&Value::None, res[2].get_by_name("week").unwrap().get_data());
+    }
+
+    #[test]
+    fn config_root_with_derived_splits() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false
+            },
+            "typeColumns": [{ "targetType": "String" }, { "targetType": "String" }],
+            "deriveColumns": [
+                {
+                    "idx": 1,
+                    "separator": "-",
+                    "destinations": [
+                        { "header": "year", "targetType": "Int16" },
+                        { "header": "seq", "targetType": "UInt32" }
+                    ]
+                }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("id,ref\na,2021-42");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            vec!["id", "ref", "year", "seq"],
+            res[0].0.iter().map(|c| c.get_name()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            &Value::String(String::from("2021-42")),
+            res[1].get_by_name("ref").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Int16(2021),
+            res[1].get_by_name("year").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::UInt32(42),
+            res[1].get_by_name("seq").unwrap().get_data()
+        );
+
+        // either a date part or a split
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false
+            },
+            "typeColumns": [{ "targetType": "NaiveDate" }],
+            "derivedColumns": [
+                { "idx": 0, "part": "year", "header": "year", "separator": "-" }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        assert!(matches!(
+            PattiCsvParser::try_from(cfg),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn config_root_with_take_lines() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false,
+                "lines": {
+                    "skipLinesFromEnd": 1,
+                    "skipLinesByStartswith": ["DATA,x"],
+                    "takeLinesByStartswith": ["kind,"],
+                    "takeLinesByRegex": ["^DATA,"]
+                }
+            },
+            "typeColumns": [{ "targetType": "String" }, { "targetType": "Int32" }]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(
+            "Report,2024
+kind,value
+DATA,1
+SUM,1
+DATA,x
+DATA,2
+DATA,3",
+        );
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(3, res.len());
+        assert_eq!(
+            &Value::Int32(2),
+            res[2].get_by_name("value").unwrap().get_data()
+        );
+    }
+
+    #[test]
+    fn config_root_with_transform_columns() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false
+            },
+            "typeColumns": [
+                { "targetType": "Decimal" },
+                { "targetType": "NaiveDate" },
+                { "targetType": "String" }
+            ],
+            "transformColumns": [
+                {
+                    "idxs": [0],
+                    "transformers": [{ "type": "scale", "spec": "0.001" }, { "type": "round", "spec": 2 }]
+                },
+                { "idxs": [1], "transformers": [{ "type": "shiftDate", "spec": 1 }] },
+                {
+                    "idxs": [2],
+                    "transformers": [{ "type": "mapValues", "spec": { "O": "open" }, "strict": true }]
+                }
+            ]
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let parser = PattiCsvParser::try_from(cfg).unwrap();
+        let mut test_data_cursor =
+            std::io::Cursor::new("amount,day,status\n12345,2024-02-28,O\n1,,X");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .skip(1) // header
+            .collect::<Vec<_>>();
+        let row = res[0].as_ref().unwrap();
+        assert_eq!(
+            &Value::Decimal(Decimal::from_str("12.35").unwrap()),
+            row.get_by_name("amount").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::NaiveDate(chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+            row.get_by_name("day").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::from(String::from("open")),
+            row.get_by_name("status").unwrap().get_data()
+        );
+        assert!(res[1]
+            .as_ref()
+            .is_err_and(|e| format!("{:?}", e).contains("[ERROR_ON_VALUE_TRANSFORM]")));
+
+        let cfg_str = cfg_str.replace("0.001", "a lot");
+        let cfg: ConfigRoot = serde_json::from_str(&cfg_str).unwrap();
+        assert!(matches!(
+            PattiCsvParser::try_from(cfg),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn config_root_into_parser_and_output() {
+        let cfg_str = r#"
+        {
+            "parserOpts": {
+                "separatorChar": ",",
+                "firstLineIsHeader": true,
+                "saveSkippedLines": false
+            },
+            "typeColumns": [{ "targetType": "String" }],
+            "output": {
+                "format": "csv",
+                "dialect": { "separatorChar": ";", "writeHeader": false },
+                "path": "out/result.csv"
+            }
+        }
+        "#;
+        let cfg: ConfigRoot = serde_json::from_str(cfg_str).unwrap();
+        let (_, output) = cfg.into_parser_and_output().unwrap();
+        assert_eq!(
+            Some(OutputTarget {
+                format: OutputFormat::Csv(OutputDialect {
+                    separator_char: ';',
+                    enclosure_char: Some('"'),
+                    write_header: false,
+                }),
+                destination: OutputDestination::Path(std::path::PathBuf::from("out/result.csv")),
+            }),
+            output
+        );
+
+        let opts = OutputOpts {
+            comment: None,
+            format: OutputFormatOpts::Ndjson,
+            dialect: None,
+            path: String::from("stdout"),
+        };
+        assert_eq!(
+            Ok(OutputTarget {
+                format: OutputFormat::NdJson,
+                destination: OutputDestination::Stdout,
+            }),
+            OutputTarget::try_from(&opts)
+        );
+        let opts = OutputOpts {
+            dialect: Some(OutputDialectOpts {
+                separator_char: None,
+                enclosure_char: None,
+                write_header: None,
+            }),
+            ..opts
+        };
+        assert!(matches!(
+            OutputTarget::try_from(&opts),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
 }
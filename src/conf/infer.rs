@@ -0,0 +1,197 @@
+//! Bootstrapping a `ConfigRoot` from a sample of the data, instead of writing it by hand.
+
+use std::io::Read;
+
+use crate::{
+    conf::jsonconf::{ConfigRoot, ParserOpts, TypeColumnsEntry},
+    errors::{PattiCsvError, Result},
+    inference::{infer_types, infer_types_lenient},
+    line_tokenizer::DelimitedLineTokenizer,
+    probe::{probe, sniff, SniffedEncoding},
+};
+
+/// The options of `infer_config_from_sample`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferConfigOpts {
+    pub max_bytes: usize,
+    pub max_lines: usize, // incl. the header line
+    /// Treat the suggested null markers (e.g. "NULL") as empty values, and map them to `None` in the typings (see:
+    /// `PattiCsvParser::infer_types_lenient`).
+    pub lenient: bool,
+}
+
+impl Default for InferConfigOpts {
+    fn default() -> Self {
+        Self {
+            max_bytes: 64 * 1024,
+            max_lines: 1000,
+            lenient: false,
+        }
+    }
+}
+
+impl InferConfigOpts {
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines;
+        self
+    }
+
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+}
+
+/// Reads (at most) `max_bytes` bytes of the data, sniffs the separator and the enclosure char (see: `probe::sniff`),
+/// checks whether the first line looks like a header, and infers the column types from (at most) `max_lines` lines
+/// (see: `inference::infer_types`). The result is a plain config, i.e. no sanitizers, transformers etc., meant to be
+/// reviewed and extended (e.g. serialized to json). Only for UTF-8 (or ASCII) data.
+pub fn infer_config_from_sample<R: Read>(
+    data: &mut R,
+    opts: &InferConfigOpts,
+) -> Result<ConfigRoot> {
+    let mut bytes = Vec::with_capacity(opts.max_bytes.min(64 * 1024));
+    data.take(opts.max_bytes as u64).read_to_end(&mut bytes)?;
+    let sniffed = sniff(&mut bytes.as_slice(), opts.max_bytes)?;
+    if !matches!(
+        sniffed.encoding,
+        SniffedEncoding::Ascii | SniffedEncoding::Utf8
+    ) {
+        return Err(PattiCsvError::ConfigError {
+            msg: format!(
+                "can't infer a config from {:?} encoded data, only from UTF-8",
+                sniffed.encoding
+            ),
+        });
+    }
+    // The last line is probably cut off (unless we've read everything), so we don't use it.
+    let mut sample_bytes = bytes.as_slice();
+    if bytes.len() == opts.max_bytes {
+        if let Some(pos) = bytes.iter().rposition(|b| *b == b'\n') {
+            sample_bytes = &bytes[..=pos];
+        }
+    }
+
+    let separator_char = sniffed.separator_char.unwrap_or(',');
+    let dlt = DelimitedLineTokenizer::new(separator_char, sniffed.enclosure_char, None, false);
+    let probed = probe(&dlt, &mut sample_bytes, opts.max_lines)?;
+    let first_line_is_header = probed.candidate_header.is_some();
+    let header = probed.candidate_header.as_deref();
+    let rows = match first_line_is_header {
+        true => &probed.sample[1..],
+        false => &probed.sample[..],
+    };
+    let report = match opts.lenient {
+        true => infer_types_lenient(header, rows),
+        false => infer_types(header, rows),
+    };
+
+    let type_columns = report
+        .columns
+        .iter()
+        .map(|c| {
+            let mut builder = TypeColumnsEntry::builder();
+            if first_line_is_header {
+                builder.with_header(&c.name);
+            }
+            if c.preserve_leading_zeros {
+                builder.with_preserve_leading_zeros(true);
+            }
+            let map_to_none = report.null_markers_of_column(c.idx);
+            if report.null_markers_applied && !map_to_none.is_empty() {
+                builder.with_map_to_none(map_to_none);
+            }
+            builder.build_with_target_type(c.recommended_type.clone())
+        })
+        .collect();
+
+    Ok(ConfigRoot {
+        comment: Some(format!(
+            "Inferred from a sample of {} rows",
+            report.num_rows_sampled
+        )),
+        parser_opts: ParserOpts {
+            enclosure_char: sniffed.enclosure_char,
+            first_line_is_header,
+            ..ParserOpts::new(separator_char)
+        },
+        sanitize_columns: None,
+        split_columns: None,
+        derived_columns: None,
+        type_columns: Some(type_columns),
+        transform_columns: None,
+        output: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::value_type::ValueType;
+
+    use crate::iterating_parser::PattiCsvParser;
+
+    use super::*;
+
+    #[test]
+    fn infer_config_and_parse_with_it() {
+        let data =
+            "id;name;amount;since\n007;\"Doe; John\";12.50;2021-01-03\n042;Jane;NULL;2021-02-04\n";
+        let config = infer_config_from_sample(
+            &mut data.as_bytes(),
+            &InferConfigOpts::default().with_lenient(true),
+        )
+        .unwrap();
+        assert_eq!(';', config.parser_opts.separator_char);
+        assert_eq!(Some('"'), config.parser_opts.enclosure_char);
+        assert!(config.parser_opts.first_line_is_header);
+
+        let type_columns = config.type_columns.as_ref().unwrap();
+        assert_eq!(
+            vec![
+                &ValueType::String,
+                &ValueType::String,
+                &ValueType::Float64,
+                &ValueType::NaiveDate
+            ],
+            type_columns
+                .iter()
+                .map(|t| &t.target_type)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(String::from("amount")), type_columns[2].header);
+        assert_eq!(
+            Some(vec![String::from("NULL")]),
+            type_columns[2].map_to_none
+        );
+        assert_eq!(Some(true), type_columns[0].preserve_leading_zeros);
+
+        // round trip through json, then parse the data with it
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(!json.contains("null"));
+        let config = ConfigRoot::from_json_str(&json).unwrap();
+        let parser = PattiCsvParser::try_from(config).unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let rows = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(3, rows.len());
+        assert_eq!(
+            &venum::value::Value::from(String::from("Doe; John")),
+            rows[1].get_by_name("name").unwrap().get_data()
+        );
+
+        assert!(matches!(
+            infer_config_from_sample(
+                &mut [0xFFu8, 0xFE, b'a', 0].as_slice(),
+                &InferConfigOpts::default()
+            ),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+}
@@ -1,27 +1,83 @@
-use serde::Deserialize;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 use venum::value_type::ValueType;
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigRoot {
     pub comment: Option<String>,
     pub parser_opts: ParserOpts,
     pub sanitize_columns: Option<Vec<SanitizeColumnsEntry>>,
+    pub split_columns: Option<Vec<SplitColumnsEntry>>,
+    #[serde(alias = "deriveColumns")]
+    pub derived_columns: Option<Vec<DerivedColumnsEntry>>,
     pub type_columns: Option<Vec<TypeColumnsEntry>>,
+    pub transform_columns: Option<Vec<TransformColumnsEntry>>,
+    pub output: Option<OutputOpts>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputOpts {
+    pub comment: Option<String>,
+    pub format: OutputFormatOpts,
+    pub dialect: Option<OutputDialectOpts>, // only for csv
+    pub path: String,                       // "stdout" (or "-") for the standard output
 }
 
-/// If skip and take options are present, the take filter overrules the skip filter.
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputFormatOpts {
+    Csv,
+    Ndjson,
+    Arrow,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputDialectOpts {
+    pub separator_char: Option<char>,
+    pub enclosure_char: Option<char>,
+    pub write_header: Option<bool>,
+}
+
+/// With take options, only the lines any of them takes are kept (the skip options still apply to them).
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "Lines", rename_all = "camelCase")]
 pub struct ParserOptLines {
     pub comment: Option<String>,
     pub skip_lines_from_start: Option<usize>,
+    pub skip_lines_from_end: Option<usize>,
     pub skip_lines_by_startswith: Option<Vec<String>>,
     pub skip_lines_by_regex: Option<Vec<String>>,
     pub skip_empty_lines: Option<bool>,
+    pub skip_lines_by_expr: Option<Vec<SkipExprOpts>>,
+    pub only_within_lines: Option<[usize; 2]>, // from, to (1-indexed, inclusive)
+    pub take_lines_by_startswith: Option<Vec<String>>,
+    pub take_lines_by_regex: Option<Vec<String>>,
+}
+
+/// A skip filter as (boolean) expression, e.g. `{ "and": [{ "startsWith": "#" }, { "not": { "fromStart": 1 } }] }`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipExprOpts {
+    StartsWith(String),
+    Regex(String),
+    FromStart(usize),
+    EmptyLine,
+    And(Vec<SkipExprOpts>),
+    Or(Vec<SkipExprOpts>),
+    Not(Box<SkipExprOpts>),
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "ParserOpts", rename_all = "camelCase")]
 pub struct ParserOpts {
     pub comment: Option<String>,
@@ -30,9 +86,99 @@ pub struct ParserOpts {
     pub lines: Option<ParserOptLines>,
     pub first_line_is_header: bool,
     pub save_skipped_lines: bool,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub detect_header_enclosure: Option<bool>,
+    pub resolve_typings_by_header: Option<HeaderMatchOpts>,
+    pub duplicate_rows: Option<DuplicateRowsOpts>,
+    pub smart_sanitizer_defaults: Option<bool>,
+    pub record_separator_char: Option<char>,
+    pub money_columns: Option<Vec<MoneyColumnsOpts>>,
+    pub collect_all_cell_errors: Option<bool>,
+    pub preamble: Option<PreambleOpts>,
+    pub memory_budget: Option<MemoryBudgetOpts>,
+    pub fixed_width_columns: Option<Vec<[usize; 2]>>, // byte ranges, [start, end)
+    pub comment_char: Option<char>,
+    pub encoding: Option<String>, // a label, e.g. "windows-1252"
+    pub strip_bom: Option<bool>,
+    pub duplicate_headers: Option<DuplicateHeaderPolicyOpts>,
+    pub column_count_mismatch: Option<ColumnCountMismatchOpts>,
+    pub global_map_to_none: Option<Vec<String>>,
+    pub unique_columns: Option<Vec<Vec<usize>>>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryBudgetOpts {
+    pub max_skipped_lines: Option<usize>,
+    pub max_saved_skipped_lines_bytes: Option<usize>,
+    pub max_dropped_rows: Option<usize>,
+    pub max_stripped_tokens: Option<usize>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreambleOpts {
+    pub lines: Option<usize>, // None means: while the lines are key-value pairs
+    pub kv_separator: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MoneyColumnsOpts {
+    pub amount_column: usize,
+    pub currency_column: usize,
+    pub combine: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateRowsOpts {
+    pub policy: DuplicateRowPolicyOpts,
+    pub compare_by: Option<DuplicateRowComparisonOpts>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateRowPolicyOpts {
+    Drop,
+    Flag,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateHeaderPolicyOpts {
+    Error,
+    RenameWithSuffix,
+    KeepFirst,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ColumnCountMismatchOpts {
+    Error,
+    PadWithNone,
+    TruncateExtra,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateRowComparisonOpts {
+    TypedRow,
+    RawTokens,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HeaderMatchOpts {
+    Exact,
+    IgnoreCaseAndWhitespace,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum TrimOpts {
     All,
@@ -40,21 +186,48 @@ pub enum TrimOpts {
     Trailing,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum CasingOpts {
     ToLower,
     ToUpper,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum NormalFormOpts {
+    Nfc,
+    Nfkc,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LiteralMatchOpts {
+    pub ignore_case: Option<bool>,
+    pub whole_token: Option<bool>,
+    pub max_count: Option<usize>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SubstringOpts {
+    pub start: usize,
+    pub len: Option<usize>,
+    pub graphemes: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct ReplaceColumnSanitizerEntry {
     pub from: String,
     pub to: String,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum SanitizeColumnOpts {
     Trim {
@@ -65,24 +238,121 @@ pub enum SanitizeColumnOpts {
     },
     Eradicate {
         spec: Vec<String>,
+        opts: Option<LiteralMatchOpts>,
     },
     Replace {
         spec: Vec<ReplaceColumnSanitizerEntry>,
+        opts: Option<LiteralMatchOpts>,
     },
     RegexTake {
         spec: String,
     },
+    Substring {
+        spec: SubstringOpts,
+    },
+    DropRow {
+        spec: Vec<String>,
+    },
+    NormalizeUnicode {
+        spec: NormalFormOpts,
+    },
+    StripControlChars,
+    /// Only applies the sanitizer to tokens, that the regex matches.
+    When {
+        #[serde(rename = "match")]
+        match_regex: String,
+        then: Box<SanitizeColumnOpts>,
+    },
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct SanitizeColumnsEntry {
     pub comment: Option<String>,
     pub idxs: Option<Vec<usize>>,
+    pub names: Option<Vec<String>>, // instead of idxs, the (final) column names
+    pub order: Option<SanitizerOrderOpts>, // only with idxs
     pub sanitizers: Vec<SanitizeColumnOpts>,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SanitizerOrderOpts {
+    BeforeGlobal,
+    AfterGlobal,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TransformColumnOpts {
+    Scale {
+        spec: String,
+    }, // a decimal, e.g. "0.01"
+    Round {
+        spec: u32,
+    },
+    ShiftDate {
+        spec: i64,
+    }, // days
+    MapValues {
+        spec: HashMap<String, String>,
+        strict: Option<bool>,
+    },
+}
+
+/// Transformers of the typed values, i.e. they run after typing. Unlike sanitizers, they are always column specific.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformColumnsEntry {
+    pub comment: Option<String>,
+    pub idxs: Vec<usize>,
+    pub transformers: Vec<TransformColumnOpts>,
+}
+
+/// Exactly one of `separator` and `regex` must be set, or `value_and_unit` (optionally with a `regex`).
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitColumnsEntry {
+    pub comment: Option<String>,
+    pub idx: usize,
+    pub separator: Option<String>,
+    pub regex: Option<String>,
+    pub value_and_unit: Option<bool>,
+    pub destinations: Vec<TypeColumnsEntry>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivedColumnsEntry {
+    pub comment: Option<String>,
+    pub idx: usize,
+    pub part: Option<DatePartOpts>, // either a date part (with a header)...
+    pub header: Option<String>,
+    pub separator: Option<String>, // ...or a split (like in SplitColumnsEntry) with destinations
+    pub regex: Option<String>,
+    pub value_and_unit: Option<bool>,
+    pub destinations: Option<Vec<TypeColumnsEntry>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DatePartOpts {
+    Year,
+    Month,
+    Day,
+    Quarter,
+    IsoWeek,
+    IsoYear,
+    DayOfWeek,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct TypeColumnsEntry {
     pub header: Option<String>,
@@ -90,6 +360,54 @@ pub struct TypeColumnsEntry {
     pub target_type: ValueType,
     pub src_pattern: Option<String>,
     pub map_to_none: Option<Vec<String>>,
+    pub empty_is_none: Option<bool>,
+    pub missing_is_none: Option<bool>,
+    pub quoted_empty_is_empty_string: Option<bool>,
+    pub header_aliases: Option<Vec<String>>,
+    pub preserve_leading_zeros: Option<bool>,
+    pub tolerant_numeric: Option<TolerantNumericOpts>,
+    pub quoted_policy: Option<QuotedTokenPolicyOpts>,
+    pub map_to_true: Option<Vec<String>>,
+    pub map_to_false: Option<Vec<String>>,
+    pub number_format: Option<NumberFormatOpts>,
+    pub default_value: Option<String>,
+    pub constraints: Option<ConstraintsOpts>,
+}
+
+/// The bounds and allowed values are typed like the tokens of the column.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstraintsOpts {
+    pub non_null: Option<bool>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub regex: Option<String>,
+    pub allowed_values: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum QuotedTokenPolicyOpts {
+    Convert,
+    Literal,
+    Reject,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TolerantNumericOpts {
+    pub decimal_separator: Option<char>,
+    pub record_stripped: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberFormatOpts {
+    pub decimal_separator: char,
+    pub thousands_separator: Option<char>,
 }
 
 impl TypeColumnsEntry {
@@ -100,6 +418,18 @@ impl TypeColumnsEntry {
             target_type,
             src_pattern: None,
             map_to_none: None,
+            empty_is_none: None,
+            missing_is_none: None,
+            quoted_empty_is_empty_string: None,
+            header_aliases: None,
+            preserve_leading_zeros: None,
+            tolerant_numeric: None,
+            quoted_policy: None,
+            map_to_true: None,
+            map_to_false: None,
+            number_format: None,
+            default_value: None,
+            constraints: None,
         }
     }
     pub fn builder() -> TypeColumnsEntryBuilder {
@@ -113,6 +443,18 @@ pub struct TypeColumnsEntryBuilder {
     pub target_type: Option<ValueType>, // mandatory!
     pub src_pattern: Option<String>,
     pub map_to_none: Option<Vec<String>>,
+    pub empty_is_none: Option<bool>,
+    pub missing_is_none: Option<bool>,
+    pub quoted_empty_is_empty_string: Option<bool>,
+    pub header_aliases: Option<Vec<String>>,
+    pub preserve_leading_zeros: Option<bool>,
+    pub tolerant_numeric: Option<TolerantNumericOpts>,
+    pub quoted_policy: Option<QuotedTokenPolicyOpts>,
+    pub map_to_true: Option<Vec<String>>,
+    pub map_to_false: Option<Vec<String>>,
+    pub number_format: Option<NumberFormatOpts>,
+    pub default_value: Option<String>,
+    pub constraints: Option<ConstraintsOpts>,
 }
 impl TypeColumnsEntryBuilder {
     pub fn new() -> Self {
@@ -122,6 +464,18 @@ impl TypeColumnsEntryBuilder {
             target_type: None,
             src_pattern: None,
             map_to_none: None,
+            empty_is_none: None,
+            missing_is_none: None,
+            quoted_empty_is_empty_string: None,
+            header_aliases: None,
+            preserve_leading_zeros: None,
+            tolerant_numeric: None,
+            quoted_policy: None,
+            map_to_true: None,
+            map_to_false: None,
+            number_format: None,
+            default_value: None,
+            constraints: None,
         }
     }
     pub fn with_header(&mut self, header: &str) -> &mut Self {
@@ -140,6 +494,58 @@ impl TypeColumnsEntryBuilder {
         self.map_to_none = Some(map_to_none);
         self
     }
+    pub fn with_empty_is_none(&mut self, empty_is_none: bool) -> &mut Self {
+        self.empty_is_none = Some(empty_is_none);
+        self
+    }
+    pub fn with_missing_is_none(&mut self, missing_is_none: bool) -> &mut Self {
+        self.missing_is_none = Some(missing_is_none);
+        self
+    }
+    pub fn with_quoted_empty_is_empty_string(
+        &mut self,
+        quoted_empty_is_empty_string: bool,
+    ) -> &mut Self {
+        self.quoted_empty_is_empty_string = Some(quoted_empty_is_empty_string);
+        self
+    }
+    pub fn with_header_aliases(&mut self, header_aliases: Vec<String>) -> &mut Self {
+        self.header_aliases = Some(header_aliases);
+        self
+    }
+    pub fn with_preserve_leading_zeros(&mut self, preserve_leading_zeros: bool) -> &mut Self {
+        self.preserve_leading_zeros = Some(preserve_leading_zeros);
+        self
+    }
+    pub fn with_tolerant_numeric(&mut self, tolerant_numeric: TolerantNumericOpts) -> &mut Self {
+        self.tolerant_numeric = Some(tolerant_numeric);
+        self
+    }
+    pub fn with_quoted_policy(&mut self, quoted_policy: QuotedTokenPolicyOpts) -> &mut Self {
+        self.quoted_policy = Some(quoted_policy);
+        self
+    }
+    pub fn with_bool_mapping(
+        &mut self,
+        map_to_true: Vec<String>,
+        map_to_false: Vec<String>,
+    ) -> &mut Self {
+        self.map_to_true = Some(map_to_true);
+        self.map_to_false = Some(map_to_false);
+        self
+    }
+    pub fn with_number_format(&mut self, number_format: NumberFormatOpts) -> &mut Self {
+        self.number_format = Some(number_format);
+        self
+    }
+    pub fn with_default_value(&mut self, default_value: &str) -> &mut Self {
+        self.default_value = Some(String::from(default_value));
+        self
+    }
+    pub fn with_constraints(&mut self, constraints: ConstraintsOpts) -> &mut Self {
+        self.constraints = Some(constraints);
+        self
+    }
     pub fn build_with_target_type(&mut self, target_type: ValueType) -> TypeColumnsEntry {
         TypeColumnsEntry {
             header: std::mem::take(&mut self.header),
@@ -147,6 +553,18 @@ impl TypeColumnsEntryBuilder {
             target_type,
             src_pattern: std::mem::take(&mut self.src_pattern),
             map_to_none: std::mem::take(&mut self.map_to_none),
+            empty_is_none: std::mem::take(&mut self.empty_is_none),
+            missing_is_none: std::mem::take(&mut self.missing_is_none),
+            quoted_empty_is_empty_string: std::mem::take(&mut self.quoted_empty_is_empty_string),
+            header_aliases: std::mem::take(&mut self.header_aliases),
+            preserve_leading_zeros: std::mem::take(&mut self.preserve_leading_zeros),
+            tolerant_numeric: std::mem::take(&mut self.tolerant_numeric),
+            quoted_policy: std::mem::take(&mut self.quoted_policy),
+            map_to_true: std::mem::take(&mut self.map_to_true),
+            map_to_false: std::mem::take(&mut self.map_to_false),
+            number_format: std::mem::take(&mut self.number_format),
+            default_value: std::mem::take(&mut self.default_value),
+            constraints: std::mem::take(&mut self.constraints),
         }
     }
 }
@@ -157,34 +575,437 @@ impl Default for TypeColumnsEntryBuilder {
     }
 }
 
+impl ConfigRoot {
+    pub fn builder(parser_opts: ParserOpts) -> ConfigRootBuilder {
+        ConfigRootBuilder::new(parser_opts)
+    }
+}
+
+/// Builds a `ConfigRoot` programmatically, e.g. to persist it afterwards (see: `ConfigRoot::to_json_string`). The
+/// `add_*` methods append to the respective list.
+pub struct ConfigRootBuilder {
+    config: ConfigRoot,
+}
+impl ConfigRootBuilder {
+    pub fn new(parser_opts: ParserOpts) -> Self {
+        Self {
+            config: ConfigRoot {
+                comment: None,
+                parser_opts,
+                sanitize_columns: None,
+                split_columns: None,
+                derived_columns: None,
+                type_columns: None,
+                transform_columns: None,
+                output: None,
+            },
+        }
+    }
+    pub fn with_comment(&mut self, comment: &str) -> &mut Self {
+        self.config.comment = Some(String::from(comment));
+        self
+    }
+    pub fn add_sanitize_columns(&mut self, entry: SanitizeColumnsEntry) -> &mut Self {
+        self.config
+            .sanitize_columns
+            .get_or_insert_with(Vec::new)
+            .push(entry);
+        self
+    }
+    pub fn add_split_columns(&mut self, entry: SplitColumnsEntry) -> &mut Self {
+        self.config
+            .split_columns
+            .get_or_insert_with(Vec::new)
+            .push(entry);
+        self
+    }
+    pub fn add_derived_columns(&mut self, entry: DerivedColumnsEntry) -> &mut Self {
+        self.config
+            .derived_columns
+            .get_or_insert_with(Vec::new)
+            .push(entry);
+        self
+    }
+    pub fn add_type_columns(&mut self, entry: TypeColumnsEntry) -> &mut Self {
+        self.config
+            .type_columns
+            .get_or_insert_with(Vec::new)
+            .push(entry);
+        self
+    }
+    pub fn add_transform_columns(&mut self, entry: TransformColumnsEntry) -> &mut Self {
+        self.config
+            .transform_columns
+            .get_or_insert_with(Vec::new)
+            .push(entry);
+        self
+    }
+    pub fn with_output(&mut self, output: OutputOpts) -> &mut Self {
+        self.config.output = Some(output);
+        self
+    }
+    pub fn build(&mut self) -> ConfigRoot {
+        self.config.clone()
+    }
+}
+
+impl ParserOpts {
+    /// With the separator char only, i.e. no enclosure char, no header line, and the defaults of the parser for
+    /// everything else.
+    pub fn new(separator_char: char) -> Self {
+        Self {
+            comment: None,
+            separator_char,
+            enclosure_char: None,
+            lines: None,
+            first_line_is_header: false,
+            save_skipped_lines: false,
+            trim_trailing_whitespace: None,
+            detect_header_enclosure: None,
+            resolve_typings_by_header: None,
+            duplicate_rows: None,
+            smart_sanitizer_defaults: None,
+            record_separator_char: None,
+            money_columns: None,
+            collect_all_cell_errors: None,
+            preamble: None,
+            memory_budget: None,
+            fixed_width_columns: None,
+            comment_char: None,
+            encoding: None,
+            strip_bom: None,
+            duplicate_headers: None,
+            column_count_mismatch: None,
+            global_map_to_none: None,
+            unique_columns: None,
+        }
+    }
+    pub fn builder(separator_char: char) -> ParserOptsBuilder {
+        ParserOptsBuilder::new(separator_char)
+    }
+}
+
+/// See: `ParserOpts::new`
+pub struct ParserOptsBuilder {
+    opts: ParserOpts,
+}
+impl ParserOptsBuilder {
+    pub fn new(separator_char: char) -> Self {
+        Self {
+            opts: ParserOpts::new(separator_char),
+        }
+    }
+    pub fn with_comment(&mut self, comment: &str) -> &mut Self {
+        self.opts.comment = Some(String::from(comment));
+        self
+    }
+    pub fn with_enclosure_char(&mut self, enclosure_char: char) -> &mut Self {
+        self.opts.enclosure_char = Some(enclosure_char);
+        self
+    }
+    pub fn with_lines(&mut self, lines: ParserOptLines) -> &mut Self {
+        self.opts.lines = Some(lines);
+        self
+    }
+    pub fn with_first_line_is_header(&mut self, first_line_is_header: bool) -> &mut Self {
+        self.opts.first_line_is_header = first_line_is_header;
+        self
+    }
+    pub fn with_save_skipped_lines(&mut self, save_skipped_lines: bool) -> &mut Self {
+        self.opts.save_skipped_lines = save_skipped_lines;
+        self
+    }
+    pub fn with_trim_trailing_whitespace(&mut self, trim_trailing_whitespace: bool) -> &mut Self {
+        self.opts.trim_trailing_whitespace = Some(trim_trailing_whitespace);
+        self
+    }
+    pub fn with_detect_header_enclosure(&mut self, detect_header_enclosure: bool) -> &mut Self {
+        self.opts.detect_header_enclosure = Some(detect_header_enclosure);
+        self
+    }
+    pub fn with_resolve_typings_by_header(&mut self, mode: HeaderMatchOpts) -> &mut Self {
+        self.opts.resolve_typings_by_header = Some(mode);
+        self
+    }
+    pub fn with_duplicate_rows(&mut self, duplicate_rows: DuplicateRowsOpts) -> &mut Self {
+        self.opts.duplicate_rows = Some(duplicate_rows);
+        self
+    }
+    pub fn with_smart_sanitizer_defaults(&mut self, smart_sanitizer_defaults: bool) -> &mut Self {
+        self.opts.smart_sanitizer_defaults = Some(smart_sanitizer_defaults);
+        self
+    }
+    pub fn with_record_separator_char(&mut self, record_separator_char: char) -> &mut Self {
+        self.opts.record_separator_char = Some(record_separator_char);
+        self
+    }
+    pub fn add_money_columns(&mut self, money_columns: MoneyColumnsOpts) -> &mut Self {
+        self.opts
+            .money_columns
+            .get_or_insert_with(Vec::new)
+            .push(money_columns);
+        self
+    }
+    pub fn with_collect_all_cell_errors(&mut self, collect_all_cell_errors: bool) -> &mut Self {
+        self.opts.collect_all_cell_errors = Some(collect_all_cell_errors);
+        self
+    }
+    pub fn with_preamble(&mut self, preamble: PreambleOpts) -> &mut Self {
+        self.opts.preamble = Some(preamble);
+        self
+    }
+    pub fn with_memory_budget(&mut self, memory_budget: MemoryBudgetOpts) -> &mut Self {
+        self.opts.memory_budget = Some(memory_budget);
+        self
+    }
+    pub fn with_fixed_width_columns(&mut self, fixed_width_columns: Vec<[usize; 2]>) -> &mut Self {
+        self.opts.fixed_width_columns = Some(fixed_width_columns);
+        self
+    }
+    pub fn with_comment_char(&mut self, comment_char: char) -> &mut Self {
+        self.opts.comment_char = Some(comment_char);
+        self
+    }
+    pub fn with_encoding(&mut self, encoding: &str) -> &mut Self {
+        self.opts.encoding = Some(String::from(encoding));
+        self
+    }
+    pub fn with_strip_bom(&mut self, strip_bom: bool) -> &mut Self {
+        self.opts.strip_bom = Some(strip_bom);
+        self
+    }
+    pub fn with_duplicate_headers(&mut self, policy: DuplicateHeaderPolicyOpts) -> &mut Self {
+        self.opts.duplicate_headers = Some(policy);
+        self
+    }
+    pub fn with_column_count_mismatch(&mut self, policy: ColumnCountMismatchOpts) -> &mut Self {
+        self.opts.column_count_mismatch = Some(policy);
+        self
+    }
+    pub fn with_global_map_to_none(&mut self, map_to_none: Vec<String>) -> &mut Self {
+        self.opts.global_map_to_none = Some(map_to_none);
+        self
+    }
+    pub fn with_unique_columns(&mut self, unique_columns: Vec<Vec<usize>>) -> &mut Self {
+        self.opts.unique_columns = Some(unique_columns);
+        self
+    }
+    pub fn build(&mut self) -> ParserOpts {
+        self.opts.clone()
+    }
+}
+
+impl SanitizeColumnsEntry {
+    pub fn builder() -> SanitizeColumnsEntryBuilder {
+        SanitizeColumnsEntryBuilder::new()
+    }
+}
+
+/// Without idxs and names, the sanitizers are global, i.e. for all columns.
+pub struct SanitizeColumnsEntryBuilder {
+    entry: SanitizeColumnsEntry,
+}
+impl SanitizeColumnsEntryBuilder {
+    pub fn new() -> Self {
+        Self {
+            entry: SanitizeColumnsEntry {
+                comment: None,
+                idxs: None,
+                names: None,
+                order: None,
+                sanitizers: Vec::new(),
+            },
+        }
+    }
+    pub fn with_comment(&mut self, comment: &str) -> &mut Self {
+        self.entry.comment = Some(String::from(comment));
+        self
+    }
+    pub fn with_idxs(&mut self, idxs: Vec<usize>) -> &mut Self {
+        self.entry.idxs = Some(idxs);
+        self
+    }
+    pub fn with_names(&mut self, names: Vec<String>) -> &mut Self {
+        self.entry.names = Some(names);
+        self
+    }
+    pub fn with_order(&mut self, order: SanitizerOrderOpts) -> &mut Self {
+        self.entry.order = Some(order);
+        self
+    }
+    pub fn add_sanitizer(&mut self, sanitizer: SanitizeColumnOpts) -> &mut Self {
+        self.entry.sanitizers.push(sanitizer);
+        self
+    }
+    pub fn build(&mut self) -> SanitizeColumnsEntry {
+        self.entry.clone()
+    }
+}
+
+impl Default for SanitizeColumnsEntryBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn deser_parser_opt_lines() {
-        let data = r#"
+        let data = r##"
         {
             "comment": "We do this, because...",
             "skipLinesFromStart": 1,
             "skipLinesFromEnd": 1,
             "skipLinesByStartswith": ["foo", "-"],
             "skipLinesByRegex": ["bar.*"],
-            "skipEmptyLines": true
+            "skipEmptyLines": true,
+            "skipLinesByExpr": [
+                { "and": [{ "startsWith": "#" }, { "not": { "fromStart": 1 } }] },
+                "emptyLine"
+            ],
+            "onlyWithinLines": [1, 20],
+            "takeLinesByStartswith": ["id,", "DATA"],
+            "takeLinesByRegex": ["^\\d"]
         }
-        "#;
+        "##;
         assert_eq!(
             ParserOptLines {
                 comment: Some("We do this, because...".to_string()),
                 skip_lines_from_start: Some(1),
+                skip_lines_from_end: Some(1),
                 skip_lines_by_startswith: Some(vec!["foo".to_string(), "-".to_string()]),
                 skip_lines_by_regex: Some(vec!["bar.*".to_string()]),
                 skip_empty_lines: Some(true),
+                skip_lines_by_expr: Some(vec![
+                    SkipExprOpts::And(vec![
+                        SkipExprOpts::StartsWith(String::from("#")),
+                        SkipExprOpts::Not(Box::new(SkipExprOpts::FromStart(1)))
+                    ]),
+                    SkipExprOpts::EmptyLine
+                ]),
+                only_within_lines: Some([1, 20]),
+                take_lines_by_startswith: Some(vec!["id,".to_string(), "DATA".to_string()]),
+                take_lines_by_regex: Some(vec![r"^\d".to_string()]),
             },
             serde_json::from_str(data).expect("could not deserialize ")
         )
     }
 
+    #[test]
+    fn deser_type_columns_entry_with_header_aliases() {
+        let data = r#"
+        {
+            "header": "Qty",
+            "targetType": "Int32",
+            "headerAliases": ["Quantity", "Amount"],
+            "preserveLeadingZeros": false
+        }
+        "#;
+        assert_eq!(
+            TypeColumnsEntry::builder()
+                .with_header("Qty")
+                .with_header_aliases(vec!["Quantity".to_string(), "Amount".to_string()])
+                .with_preserve_leading_zeros(false)
+                .build_with_target_type(ValueType::Int32),
+            serde_json::from_str(data).expect("could not deserialize ")
+        );
+        assert_eq!(
+            HeaderMatchOpts::IgnoreCaseAndWhitespace,
+            serde_json::from_str(r#""ignoreCaseAndWhitespace""#).expect("could not deserialize ")
+        );
+    }
+
+    #[test]
+    fn deser_duplicate_rows_opts() {
+        let data = r#"
+        {
+            "policy": "drop",
+            "compareBy": "rawTokens"
+        }
+        "#;
+        assert_eq!(
+            DuplicateRowsOpts {
+                policy: DuplicateRowPolicyOpts::Drop,
+                compare_by: Some(DuplicateRowComparisonOpts::RawTokens)
+            },
+            serde_json::from_str(data).expect("could not deserialize ")
+        );
+    }
+
+    #[test]
+    fn deser_money_columns_opts() {
+        let data = r#"
+        {
+            "amountColumn": 3,
+            "currencyColumn": 4,
+            "combine": true
+        }
+        "#;
+        assert_eq!(
+            MoneyColumnsOpts {
+                amount_column: 3,
+                currency_column: 4,
+                combine: Some(true)
+            },
+            serde_json::from_str(data).expect("could not deserialize ")
+        );
+    }
+
+    #[test]
+    fn deser_col_sanitize_config_substring() {
+        let data = r#"
+        {
+            "type": "substring",
+            "spec": { "start": 0, "len": 3 }
+        }
+        "#;
+        assert_eq!(
+            SanitizeColumnOpts::Substring {
+                spec: SubstringOpts {
+                    start: 0,
+                    len: Some(3),
+                    graphemes: None
+                }
+            },
+            serde_json::from_str(data).expect("could not deserialize ")
+        );
+    }
+
+    #[test]
+    fn deser_col_sanitize_config_drop_row() {
+        let data = r#"
+        {
+            "type": "dropRow",
+            "spec": ["VOID"]
+        }
+        "#;
+        assert_eq!(
+            SanitizeColumnOpts::DropRow {
+                spec: vec![String::from("VOID")]
+            },
+            serde_json::from_str(data).expect("could not deserialize ")
+        );
+    }
+
+    #[test]
+    fn deser_preamble_opts() {
+        let data = r#"
+        {
+            "kvSeparator": "="
+        }
+        "#;
+        assert_eq!(
+            PreambleOpts {
+                lines: None,
+                kv_separator: Some(String::from("="))
+            },
+            serde_json::from_str(data).expect("could not deserialize ")
+        );
+    }
+
     #[test]
     fn deser_col_sanitize_config_trim() {
         // Trailing
@@ -266,12 +1087,18 @@ mod tests {
         let data = r#"
         {
             "type": "eradicate",
-            "spec": ["."]
+            "spec": ["."],
+            "opts": { "ignoreCase": true, "maxCount": 1 }
         }
         "#;
         assert_eq!(
             SanitizeColumnOpts::Eradicate {
-                spec: vec![".".to_string()]
+                spec: vec![".".to_string()],
+                opts: Some(LiteralMatchOpts {
+                    ignore_case: Some(true),
+                    whole_token: None,
+                    max_count: Some(1)
+                })
             },
             serde_json::from_str(data).expect("could not deserialize ")
         );
@@ -309,7 +1136,8 @@ mod tests {
                 spec: vec![ReplaceColumnSanitizerEntry {
                     from: "foo".to_string(),
                     to: "bar".to_string()
-                }]
+                }],
+                opts: None
             },
             serde_json::from_str(data).expect("could not deserialize ")
         );
@@ -428,12 +1256,35 @@ mod tests {
                 lines: Some(ParserOptLines {
                     comment: Some(String::from("Some optional explanation")),
                     skip_lines_from_start: Some(1 as usize),
+                    skip_lines_from_end: None,
                     skip_empty_lines: Some(true),
                     skip_lines_by_startswith: Some(vec![String::from("#"), String::from("-")]),
                     skip_lines_by_regex: None,
+                    skip_lines_by_expr: None,
+                    only_within_lines: None,
+                    take_lines_by_startswith: None,
+                    take_lines_by_regex: None,
                 }),
                 first_line_is_header: true,
                 save_skipped_lines: false,
+                trim_trailing_whitespace: None,
+                detect_header_enclosure: None,
+                resolve_typings_by_header: None,
+                duplicate_rows: None,
+                smart_sanitizer_defaults: None,
+                record_separator_char: None,
+                money_columns: None,
+                collect_all_cell_errors: None,
+                preamble: None,
+                memory_budget: None,
+                fixed_width_columns: None,
+                comment_char: None,
+                encoding: None,
+                strip_bom: None,
+                duplicate_headers: None,
+                column_count_mismatch: None,
+                global_map_to_none: None,
+                unique_columns: None,
             },
             sanitize_columns: Some(vec![
                 SanitizeColumnsEntry {
@@ -441,6 +1292,8 @@ mod tests {
                         "(GLOBAL, all columns) Some optional explanation",
                     )),
                     idxs: None,
+                    names: None,
+                    order: None,
                     sanitizers: vec![SanitizeColumnOpts::Trim {
                         spec: TrimOpts::All,
                     }],
@@ -448,6 +1301,8 @@ mod tests {
                 SanitizeColumnsEntry {
                     comment: Some(String::from("Some optional explanation")),
                     idxs: Some(vec![0_usize]),
+                    names: None,
+                    order: None,
                     sanitizers: vec![SanitizeColumnOpts::Casing {
                         spec: CasingOpts::ToLower,
                     }],
@@ -455,6 +1310,8 @@ mod tests {
                 SanitizeColumnsEntry {
                     comment: Some(String::from("Some optional explanation")),
                     idxs: Some(vec![1_usize]),
+                    names: None,
+                    order: None,
                     sanitizers: vec![SanitizeColumnOpts::Casing {
                         spec: CasingOpts::ToUpper,
                     }],
@@ -479,6 +1336,10 @@ mod tests {
                     .with_datetype_src_pattern("%FT%T%:z")
                     .build_with_target_type(ValueType::DateTime),
             ]),
+            split_columns: None,
+            derived_columns: None,
+            transform_columns: None,
+            output: None,
         };
 
         assert_eq!(
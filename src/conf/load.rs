@@ -0,0 +1,261 @@
+//! Loading (and saving) a `ConfigRoot` from json, yaml (feature `yamlconf`) or toml (feature `tomlconf`). The structure
+//! is the same in every format (see: resources/JSONCONF.md), only toml has no `null`, i.e. optional values are left
+//! out. When saving, they are left out in every format.
+
+use std::{fs, path::Path};
+
+use crate::{
+    conf::jsonconf::ConfigRoot,
+    errors::{PattiCsvError, Result},
+};
+
+fn config_err(format: &str, e: impl std::fmt::Display) -> PattiCsvError {
+    PattiCsvError::ConfigError {
+        msg: format!("[ERROR_ON_CONFIG] Invalid {} configuration: {}", format, e),
+    }
+}
+
+fn serialize_err(format: &str, e: impl std::fmt::Display) -> PattiCsvError {
+    PattiCsvError::Generic {
+        msg: format!(
+            "[ERROR_ON_CONFIG] Could not serialize {} configuration: {}",
+            format, e
+        ),
+    }
+}
+
+impl ConfigRoot {
+    pub fn from_json_str(s: &str) -> Result<Self> {
+        serde_json::from_str(s).map_err(|e| config_err("json", e))
+    }
+
+    #[cfg(feature = "yamlconf")]
+    pub fn from_yaml_str(s: &str) -> Result<Self> {
+        serde_yaml::from_str(s).map_err(|e| config_err("yaml", e))
+    }
+
+    #[cfg(feature = "tomlconf")]
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        toml::from_str(s).map_err(|e| config_err("toml", e))
+    }
+
+    /// Loads the configuration by the extension of the file: `.json`, `.yaml`/`.yml` or `.toml` (case-insensitive).
+    /// Formats, whose feature is not enabled, are an error.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let ext = config_format(path)?;
+        let s = fs::read_to_string(path)?;
+        match ext.as_str() {
+            #[cfg(feature = "yamlconf")]
+            "yaml" | "yml" => Self::from_yaml_str(&s),
+            #[cfg(feature = "tomlconf")]
+            "toml" => Self::from_toml_str(&s),
+            _ => Self::from_json_str(&s),
+        }
+    }
+
+    /// Pretty-printed.
+    pub fn to_json_string(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| serialize_err("json", e))
+    }
+
+    #[cfg(feature = "yamlconf")]
+    pub fn to_yaml_string(&self) -> Result<String> {
+        serde_yaml::to_string(self).map_err(|e| serialize_err("yaml", e))
+    }
+
+    /// Pretty-printed.
+    #[cfg(feature = "tomlconf")]
+    pub fn to_toml_string(&self) -> Result<String> {
+        toml::to_string_pretty(self).map_err(|e| serialize_err("toml", e))
+    }
+
+    /// Saves the configuration by the extension of the file, like `from_path` loads it. An existing file is
+    /// overwritten.
+    pub fn to_path<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let s = match config_format(path)?.as_str() {
+            #[cfg(feature = "yamlconf")]
+            "yaml" | "yml" => self.to_yaml_string()?,
+            #[cfg(feature = "tomlconf")]
+            "toml" => self.to_toml_string()?,
+            _ => self.to_json_string()?,
+        };
+        fs::write(path, s)?;
+        Ok(())
+    }
+}
+
+/// Helper method. The (lowercase) extension of the file, if its format is enabled.
+fn config_format(path: &Path) -> Result<String> {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match enabled_formats().contains(&ext.as_str()) {
+        true => Ok(ext),
+        false => Err(PattiCsvError::ConfigError {
+            msg: format!(
+                "[ERROR_ON_CONFIG] Unsupported configuration format (extension: {:?}, enabled: {}); path: {}",
+                ext,
+                enabled_formats().join(", "),
+                path.display()
+            ),
+        }),
+    }
+}
+
+/// Helper method. The file extensions of the formats, whose feature is enabled.
+fn enabled_formats() -> Vec<&'static str> {
+    let mut formats = vec!["json"];
+    if cfg!(feature = "yamlconf") {
+        formats.extend(["yaml", "yml"]);
+    }
+    if cfg!(feature = "tomlconf") {
+        formats.push("toml");
+    }
+    formats
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::value_type::ValueType;
+
+    use crate::conf::jsonconf::{
+        ParserOpts, SanitizeColumnOpts, SanitizeColumnsEntry, TrimOpts, TypeColumnsEntry,
+    };
+
+    use super::*;
+
+    const JSON: &str = r##"{
+        "parserOpts": {
+            "separatorChar": ";",
+            "enclosureChar": "'",
+            "firstLineIsHeader": true,
+            "saveSkippedLines": false,
+            "lines": { "skipLinesByStartswith": ["#"] }
+        },
+        "sanitizeColumns": [{ "sanitizers": [{ "type": "trim", "spec": "all" }] }],
+        "typeColumns": [
+            { "header": "id", "targetType": "Int32" },
+            { "targetType": "NaiveDate", "srcPattern": "%d.%m.%Y" }
+        ]
+    }"##;
+
+    #[test]
+    fn load_json_and_unsupported() {
+        let cfg = ConfigRoot::from_json_str(JSON).unwrap();
+        assert_eq!(';', cfg.parser_opts.separator_char);
+        assert_eq!(
+            ValueType::NaiveDate,
+            cfg.type_columns.unwrap()[1].target_type
+        );
+        assert!(matches!(
+            ConfigRoot::from_json_str("{}"),
+            Err(PattiCsvError::ConfigError { msg }) if msg.starts_with("[ERROR_ON_CONFIG]")
+        ));
+        assert!(ConfigRoot::from_path("patti.ini").is_err());
+        assert!(ConfigRoot::from_path("missing.json").is_err());
+    }
+
+    #[test]
+    fn build_and_save_json() {
+        let cfg = ConfigRoot::builder(
+            ParserOpts::builder(';')
+                .with_enclosure_char('\'')
+                .with_first_line_is_header(true)
+                .build(),
+        )
+        .add_sanitize_columns(
+            SanitizeColumnsEntry::builder()
+                .add_sanitizer(SanitizeColumnOpts::Trim {
+                    spec: TrimOpts::All,
+                })
+                .build(),
+        )
+        .add_type_columns(
+            TypeColumnsEntry::builder()
+                .with_header("id")
+                .build_with_target_type(ValueType::Int32),
+        )
+        .add_type_columns(
+            TypeColumnsEntry::builder()
+                .with_datetype_src_pattern("%d.%m.%Y")
+                .build_with_target_type(ValueType::NaiveDate),
+        )
+        .build();
+        let mut exp = ConfigRoot::from_json_str(JSON).unwrap();
+        exp.parser_opts.lines = None;
+        assert_eq!(exp, cfg);
+
+        let path = std::env::temp_dir().join(format!("patti_csv_save_{}.json", std::process::id()));
+        cfg.to_path(&path).unwrap();
+        let res = ConfigRoot::from_path(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(cfg, res.unwrap());
+        assert!(!cfg.to_json_string().unwrap().contains("null"));
+        assert!(cfg.to_path("patti.ini").is_err());
+    }
+
+    #[cfg(all(feature = "yamlconf", feature = "tomlconf"))]
+    #[test]
+    fn load_yaml_and_toml_like_json() {
+        let yaml = r##"
+parserOpts:
+  separatorChar: ";"
+  enclosureChar: "'"
+  firstLineIsHeader: true
+  saveSkippedLines: false
+  lines:
+    skipLinesByStartswith: ["#"]
+sanitizeColumns:
+  - sanitizers:
+      - type: trim
+        spec: all
+typeColumns:
+  - header: id
+    targetType: Int32
+  - targetType: NaiveDate
+    srcPattern: "%d.%m.%Y"
+"##;
+        let toml = r##"
+[parserOpts]
+separatorChar = ";"
+enclosureChar = "'"
+firstLineIsHeader = true
+saveSkippedLines = false
+lines = { skipLinesByStartswith = ["#"] }
+
+[[sanitizeColumns]]
+sanitizers = [{ type = "trim", spec = "all" }]
+
+[[typeColumns]]
+header = "id"
+targetType = "Int32"
+
+[[typeColumns]]
+targetType = "NaiveDate"
+srcPattern = "%d.%m.%Y"
+"##;
+        let json = ConfigRoot::from_json_str(JSON).unwrap();
+        assert_eq!(json, ConfigRoot::from_yaml_str(yaml).unwrap());
+        assert_eq!(json, ConfigRoot::from_toml_str(toml).unwrap());
+
+        // and back again
+        assert_eq!(
+            json,
+            ConfigRoot::from_yaml_str(&json.to_yaml_string().unwrap()).unwrap()
+        );
+        assert_eq!(
+            json,
+            ConfigRoot::from_toml_str(&json.to_toml_string().unwrap()).unwrap()
+        );
+
+        let path = std::env::temp_dir().join(format!("patti_csv_load_{}.YML", std::process::id()));
+        fs::write(&path, yaml).unwrap();
+        let res = ConfigRoot::from_path(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(json, res.unwrap());
+    }
+}
@@ -1,4 +1,8 @@
 #[cfg(feature = "jsonconf")]
 pub mod from_jsonconf;
 #[cfg(feature = "jsonconf")]
+pub mod infer;
+#[cfg(feature = "jsonconf")]
 pub mod jsonconf;
+#[cfg(feature = "jsonconf")]
+pub mod load;
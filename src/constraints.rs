@@ -0,0 +1,169 @@
+use std::{cmp::Ordering, mem::discriminant};
+
+use regex::Regex;
+use venum::value::Value;
+
+use crate::errors::{PattiCsvError, Result};
+
+/// Constraints of the typed values of a column (see: `TypeColumnEntry::with_constraints`), i.e. a data-quality gate.
+/// They are checked after typing (and after the value transformers, and the default value). A violation is a
+/// `PattiCsvError::Validation` with the line and column, or a cell error, if the parser collects them (see:
+/// `ErrorPolicy`). The bounds and allowed values must be of the type of the column, since they are compared as typed
+/// values.
+///
+/// NOTE: Except for `non_null`, `Value::None`s are never checked. The padded cells of a short line (see:
+/// `ColumnCountMismatchPolicy::PadWithNone`) are checked like any other.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnConstraints {
+    pub non_null: bool,
+    pub min: Option<Value>, // inclusive
+    pub max: Option<Value>, // inclusive
+    pub regex: Option<Regex>,
+    pub allowed_values: Option<Vec<Value>>,
+}
+
+impl ColumnConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_non_null(mut self, non_null: bool) -> Self {
+        self.non_null = non_null;
+        self
+    }
+
+    /// E.g. for numbers and dates.
+    pub fn with_min(mut self, min: Value) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// E.g. for numbers and dates.
+    pub fn with_max(mut self, max: Value) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Only for `String` columns. It matches anywhere in the value, so anchor it (`^...$`) to match the whole value.
+    pub fn with_regex(mut self, pattern: &str) -> Result<Self> {
+        self.regex = Some(Regex::new(pattern).map_err(|e| PattiCsvError::ConfigError {
+            msg: format!("invalid constraint regex {:?}: {}", pattern, e),
+        })?);
+        Ok(self)
+    }
+
+    pub fn with_allowed_values(mut self, allowed_values: Vec<Value>) -> Self {
+        self.allowed_values = Some(allowed_values);
+        self
+    }
+
+    /// Checks the (typed) value, and returns the violated constraint, if any.
+    pub fn check(&self, value: &Value) -> std::result::Result<(), String> {
+        if *value == Value::None {
+            return match self.non_null {
+                true => Err(String::from(
+                    "[ERROR_ON_CONSTRAINT] Value is null (nonNull)",
+                )),
+                false => Ok(()),
+            };
+        }
+        if let Some(min) = &self.min {
+            if compare(value, min)? == Ordering::Less {
+                return Err(format!(
+                    "[ERROR_ON_CONSTRAINT] Value {:?} is less than the min {:?}",
+                    value, min
+                ));
+            }
+        }
+        if let Some(max) = &self.max {
+            if compare(value, max)? == Ordering::Greater {
+                return Err(format!(
+                    "[ERROR_ON_CONSTRAINT] Value {:?} is greater than the max {:?}",
+                    value, max
+                ));
+            }
+        }
+        if let Some(regex) = &self.regex {
+            match value {
+                Value::String(s) if regex.is_match(s) => {}
+                _ => {
+                    return Err(format!(
+                        "[ERROR_ON_CONSTRAINT] Value {:?} doesn't match the regex {:?}",
+                        value,
+                        regex.as_str()
+                    ))
+                }
+            }
+        }
+        if let Some(allowed_values) = &self.allowed_values {
+            if !allowed_values.contains(value) {
+                return Err(format!(
+                    "[ERROR_ON_CONSTRAINT] Value {:?} is not one of the allowed values {:?}",
+                    value, allowed_values
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Equal, if the regexes have the same pattern.
+impl PartialEq for ColumnConstraints {
+    fn eq(&self, other: &Self) -> bool {
+        self.non_null == other.non_null
+            && self.min == other.min
+            && self.max == other.max
+            && self.regex.as_ref().map(Regex::as_str) == other.regex.as_ref().map(Regex::as_str)
+            && self.allowed_values == other.allowed_values
+    }
+}
+
+// The bounds are typed values of one column, i.e. never NaN, unless configured so.
+impl Eq for ColumnConstraints {}
+
+/// Helper method. Values of different types (e.g. an `Int64` bound of an `Int32` column) are not comparable, since the
+/// derived ordering of `Value` would go by the variant.
+fn compare(value: &Value, bound: &Value) -> std::result::Result<Ordering, String> {
+    match discriminant(value) == discriminant(bound) {
+        true => value.partial_cmp(bound),
+        false => None,
+    }
+    .ok_or_else(|| {
+        format!(
+            "[ERROR_ON_CONSTRAINT] Value {:?} is not comparable with the bound {:?}",
+            value, bound
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_constraints() {
+        let c = ColumnConstraints::new()
+            .with_non_null(true)
+            .with_min(Value::Int32(1))
+            .with_max(Value::Int32(10));
+        assert_eq!(Ok(()), c.check(&Value::Int32(1)));
+        assert_eq!(Ok(()), c.check(&Value::Int32(10)));
+        assert!(c.check(&Value::Int32(0)).is_err());
+        assert!(c.check(&Value::Int32(11)).is_err());
+        assert!(c.check(&Value::None).is_err());
+        assert!(c.check(&Value::Int64(5)).is_err()); // not comparable
+        assert_eq!(Ok(()), ColumnConstraints::new().check(&Value::None));
+
+        let c = ColumnConstraints::new()
+            .with_regex("^[A-Z]{3}$")
+            .unwrap()
+            .with_allowed_values(vec![Value::from("CHF"), Value::from("EUR")]);
+        assert_eq!(Ok(()), c.check(&Value::from("CHF")));
+        assert!(c
+            .check(&Value::from("USD"))
+            .unwrap_err()
+            .contains("allowed values"));
+        assert!(c.check(&Value::from("chf")).unwrap_err().contains("regex"));
+        assert!(ColumnConstraints::new().with_regex("(").is_err());
+    }
+}
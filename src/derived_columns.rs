@@ -0,0 +1,496 @@
+use chrono::{Datelike, NaiveDate};
+use venum::{value::Value, value_type::ValueType};
+use venum_tds::{data_cell::DataCell, data_cell_row::DataCellRow};
+
+use crate::{
+    cell_converter::VenumCellConverter,
+    errors::{PattiCsvError, Result},
+    line_tokenizer::TokenMeta,
+    parser_common::type_cell,
+    parser_config::TypeColumnEntry,
+    split_columns::BoxedSplitToken,
+};
+
+/// A part of a date, e.g. to get partitioning keys like year and month. The year parts are `Value::Int32`, all others
+/// `Value::UInt32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatePart {
+    Year,
+    Month,   // 1 - 12
+    Day,     // 1 - 31
+    Quarter, // 1 - 4
+    /// The ISO 8601 week (1 - 53). The first days of January may belong to the last week of the year before (and the
+    /// last days of December to the first week of the next year), so use it together with `IsoYear`.
+    IsoWeek,
+    /// The year the ISO 8601 week belongs to.
+    IsoYear,
+    DayOfWeek, // ISO 8601, i.e. 1 (Monday) - 7 (Sunday)
+}
+
+impl DatePart {
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            DatePart::Year | DatePart::IsoYear => ValueType::Int32,
+            _ => ValueType::UInt32,
+        }
+    }
+
+    pub fn of(&self, date: NaiveDate) -> Value {
+        match self {
+            DatePart::Year => Value::Int32(date.year()),
+            DatePart::Month => Value::UInt32(date.month()),
+            DatePart::Day => Value::UInt32(date.day()),
+            DatePart::Quarter => Value::UInt32(date.month0() / 3 + 1),
+            DatePart::IsoWeek => Value::UInt32(date.iso_week().week()),
+            DatePart::IsoYear => Value::Int32(date.iso_week().year()),
+            DatePart::DayOfWeek => Value::UInt32(date.weekday().number_from_monday()),
+        }
+    }
+}
+
+/// A new column with a part of a date column (see: `DatePart`), appended after all other columns (in the order they
+/// were added). The date column must be typed as `NaiveDate`, `NaiveDateTime` or `DateTime` (where the date is taken
+/// as is, i.e. in its own offset). If it's `Value::None`, so is the derived cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivedColumn {
+    pub column: usize, // after splitting, i.e. like the other typed columns
+    pub part: DatePart,
+    pub header: String,
+}
+
+impl DerivedColumn {
+    pub fn new<T>(column: usize, part: DatePart, header: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            column,
+            part,
+            header: header.into(),
+        }
+    }
+}
+
+/// New columns with the parts of a (typed) `String` column, e.g. a `"10.00 CHF"` column split into an amount and a
+/// currency, appended after the derived date parts (in the order they were added). Unlike a `SplitColumn` (which
+/// replaces the raw token, before typing), the column itself is kept. The value is split like a `SplitColumn` splits
+/// (see: `SplitToken`), and every part is typed with the typing of its destination, which must have a header. If the
+/// value is `Value::None`, so are the derived cells.
+#[derive(Debug)]
+pub struct DerivedSplit {
+    pub column: usize, // after splitting, i.e. like the other typed columns
+    pub splitter: BoxedSplitToken,
+    pub destinations: Vec<TypeColumnEntry>,
+}
+
+impl DerivedSplit {
+    pub fn new(
+        column: usize,
+        splitter: BoxedSplitToken,
+        destinations: Vec<TypeColumnEntry>,
+    ) -> Self {
+        Self {
+            column,
+            splitter,
+            destinations,
+        }
+    }
+}
+
+/// Helper method. The number of derived cells, i.e. of the date parts and of the destinations of the splits.
+pub(crate) fn num_derived_cells(
+    derived_columns: &[DerivedColumn],
+    derived_splits: &[DerivedSplit],
+) -> usize {
+    derived_columns.len()
+        + derived_splits
+            .iter()
+            .map(|ds| ds.destinations.len())
+            .sum::<usize>()
+}
+
+fn derive_err(msg: String) -> PattiCsvError {
+    PattiCsvError::ConfigError {
+        msg: format!("[ERROR_ON_DERIVE] {}", msg),
+    }
+}
+
+fn is_date_type(vt: &ValueType) -> bool {
+    matches!(
+        vt,
+        ValueType::NaiveDate | ValueType::NaiveDateTime | ValueType::DateTime
+    )
+}
+
+/// Helper method. Checks the derived columns (and splits) against the (final) column typings. If the typings are
+/// resolved by header, the columns are only known per file, so only the headers (and the splits themselves) are checked.
+pub(crate) fn check_derived_columns(
+    column_typings: &[TypeColumnEntry],
+    derived_columns: &[DerivedColumn],
+    derived_splits: &[DerivedSplit],
+    by_header: bool,
+) -> Result<()> {
+    let headers = derived_columns
+        .iter()
+        .map(|dc| dc.header.as_str())
+        .chain(derived_splits.iter().flat_map(|ds| {
+            ds.destinations
+                .iter()
+                .map(|d| d.header.as_deref().unwrap_or_default())
+        }))
+        .collect::<Vec<_>>();
+    for (i, header) in headers.iter().enumerate() {
+        if header.is_empty() {
+            return Err(derive_err(format!(
+                "Derived column {} (after the other columns) needs a header",
+                i
+            )));
+        }
+        if headers[..i].contains(header)
+            || column_typings
+                .iter()
+                .any(|tce| tce.header.as_deref() == Some(*header))
+        {
+            return Err(derive_err(format!(
+                "Header of derived column is not unique: {}",
+                header
+            )));
+        }
+    }
+    for ds in derived_splits {
+        if ds.destinations.is_empty() {
+            return Err(derive_err(format!(
+                "Column {} must be split into at least one destination",
+                ds.column
+            )));
+        }
+        if let Some(n) = ds.splitter.num_parts() {
+            if n != ds.destinations.len() {
+                return Err(derive_err(format!(
+                    "Splitter of column {} gives {} parts, but there are {} destinations",
+                    ds.column,
+                    n,
+                    ds.destinations.len()
+                )));
+            }
+        }
+        if by_header {
+            continue;
+        }
+        match column_typings.get(ds.column) {
+            None => {
+                return Err(derive_err(format!(
+                    "Column {} can't be split, there are only {} column typings",
+                    ds.column,
+                    column_typings.len()
+                )))
+            }
+            Some(tce) if tce.target_type != ValueType::String => {
+                return Err(derive_err(format!(
+                    "Column {} must be typed as String to split it, but is: {:?}",
+                    ds.column, tce.target_type
+                )))
+            }
+            Some(_) => (),
+        }
+    }
+    for dc in derived_columns {
+        if by_header {
+            continue;
+        }
+        match column_typings.get(dc.column) {
+            None => {
+                return Err(derive_err(format!(
+                    "Column {} can't be derived from, there are only {} column typings",
+                    dc.column,
+                    column_typings.len()
+                )))
+            }
+            Some(tce) if !is_date_type(&tce.target_type) => {
+                return Err(derive_err(format!(
+                    "Column {} must be typed as a date to derive {:?} from, but is: {:?}",
+                    dc.column, dc.part, tce.target_type
+                )))
+            }
+            Some(_) => (),
+        }
+    }
+    Ok(())
+}
+
+/// Helper method. Appends the (empty) derived cells to the column layout template.
+pub(crate) fn append_derived_template_cells(
+    template: &mut DataCellRow,
+    derived_columns: &[DerivedColumn],
+    derived_splits: &[DerivedSplit],
+) {
+    for dc in derived_columns {
+        let idx = template.0.len();
+        template.push(DataCell::new_without_data(
+            dc.part.value_type(),
+            dc.header.clone(),
+            idx,
+        ));
+    }
+    for d in derived_splits.iter().flat_map(|ds| &ds.destinations) {
+        let idx = template.0.len();
+        template.push(DataCell::new_without_data(
+            d.target_type.clone(),
+            d.header.clone().unwrap_or_default(),
+            idx,
+        ));
+    }
+}
+
+/// Helper method. Fills the derived cells (i.e. the last ones) of a typed row.
+pub(crate) fn derive_columns(
+    row: &mut DataCellRow,
+    derived_columns: &[DerivedColumn],
+    derived_splits: &[DerivedSplit],
+    line_num: usize,
+) -> Result<()> {
+    let num_typed = row
+        .0
+        .len()
+        .checked_sub(num_derived_cells(derived_columns, derived_splits))
+        .ok_or_else(|| PattiCsvError::Generic {
+            msg: format!(
+                "[ERROR_ON_DERIVE] No derived cells in row; line: {}",
+                line_num
+            ),
+        })?;
+    for (i, dc) in derived_columns.iter().enumerate() {
+        let err = |msg: String| PattiCsvError::Generic {
+            msg: format!(
+                "[ERROR_ON_DERIVE] {}; line: {}; column: {}; derived: {}",
+                msg, line_num, dc.column, dc.header
+            ),
+        };
+        let date = match row.0[..num_typed].get(dc.column).map(DataCell::get_data) {
+            None => return Err(err(String::from("No such column"))),
+            Some(Value::None) => None,
+            Some(Value::NaiveDate(d)) => Some(*d),
+            Some(Value::NaiveDateTime(dt)) => Some(dt.date()),
+            Some(Value::DateTime(dt)) => Some(dt.date_naive()),
+            Some(other) => return Err(err(format!("Not a date: {:?}", other))),
+        };
+        let idx = num_typed + i;
+        row.0[idx] = DataCell::new_with_type_info(
+            dc.part.value_type(),
+            dc.header.clone(),
+            idx,
+            date.map_or(Value::None, |d| dc.part.of(d)),
+        )
+        .map_err(|e| err(format!("{:?}", e)))?;
+    }
+    let mut idx = num_typed + derived_columns.len();
+    for ds in derived_splits {
+        let err = |msg: String| PattiCsvError::Generic {
+            msg: format!(
+                "[ERROR_ON_DERIVE] {}; line: {}; column: {}",
+                msg, line_num, ds.column
+            ),
+        };
+        let parts = match row.0[..num_typed].get(ds.column).map(DataCell::get_data) {
+            None => return Err(err(String::from("No such column"))),
+            Some(Value::None) => None,
+            Some(Value::String(s)) => {
+                let parts = ds.splitter.split(s).map_err(|e| match e {
+                    PattiCsvError::Generic { msg } => err(msg),
+                    e => e,
+                })?;
+                if parts.len() != ds.destinations.len() {
+                    return Err(err(format!(
+                        "Split gave {} parts, but there are {} destinations; value: {}",
+                        parts.len(),
+                        ds.destinations.len(),
+                        s
+                    )));
+                }
+                Some(parts)
+            }
+            Some(other) => return Err(err(format!("Not a string: {:?}", other))),
+        };
+        let mut parts = parts.map(Vec::into_iter);
+        for d in &ds.destinations {
+            let header = d.header.clone().unwrap_or_default();
+            let value = match parts.as_mut().and_then(Iterator::next) {
+                Some(part) => type_cell(Some(part), TokenMeta::default(), d, &VenumCellConverter)
+                    .map_err(|e| match e {
+                    PattiCsvError::Generic { msg } => err(format!("{}; derived: {}", msg, header)),
+                    e => e,
+                })?,
+                None => Value::None,
+            };
+            row.0[idx] = DataCell::new_with_type_info(d.target_type.clone(), header, idx, value)
+                .map_err(|e| err(format!("{:?}", e)))?;
+            idx += 1;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::split_columns::SplitValueAndUnit;
+
+    use super::*;
+
+    #[test]
+    fn date_parts() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let parts = |d: NaiveDate| {
+            [
+                DatePart::Year,
+                DatePart::Month,
+                DatePart::Day,
+                DatePart::Quarter,
+                DatePart::IsoWeek,
+                DatePart::IsoYear,
+                DatePart::DayOfWeek,
+            ]
+            .map(|p| p.of(d))
+        };
+        assert_eq!(
+            [
+                Value::Int32(2021),
+                Value::UInt32(1),
+                Value::UInt32(3),
+                Value::UInt32(1),
+                Value::UInt32(53),
+                Value::Int32(2020),
+                Value::UInt32(7),
+            ],
+            parts(date(2021, 1, 3))
+        );
+        assert_eq!(
+            [
+                Value::Int32(2024),
+                Value::UInt32(12),
+                Value::UInt32(30),
+                Value::UInt32(4),
+                Value::UInt32(1),
+                Value::Int32(2025),
+                Value::UInt32(1),
+            ],
+            parts(date(2024, 12, 30))
+        );
+    }
+
+    #[test]
+    fn check_and_derive() {
+        let typings = vec![
+            TypeColumnEntry::new(Some(String::from("id")), ValueType::Int32),
+            TypeColumnEntry::new(None, ValueType::NaiveDate),
+        ];
+        let check =
+            |dc: DerivedColumn, by_header| check_derived_columns(&typings, &[dc], &[], by_header);
+        assert!(check(DerivedColumn::new(1, DatePart::Quarter, "q"), false).is_ok());
+        assert!(check(DerivedColumn::new(0, DatePart::Quarter, "q"), false).is_err());
+        assert!(check(DerivedColumn::new(2, DatePart::Quarter, "q"), false).is_err());
+        assert!(check(DerivedColumn::new(2, DatePart::Quarter, "q"), true).is_ok());
+        assert!(check(DerivedColumn::new(1, DatePart::Quarter, "id"), false).is_err());
+        assert!(check(DerivedColumn::new(1, DatePart::Quarter, ""), false).is_err());
+
+        let derived = [
+            DerivedColumn::new(1, DatePart::Year, "year"),
+            DerivedColumn::new(0, DatePart::Month, "month"),
+        ];
+        let row = |date: Value, derived: &[DerivedColumn]| {
+            let mut row = DataCellRow::new();
+            row.push(DataCell::new(String::from("id"), 0, Value::Int32(1)).unwrap());
+            row.push(
+                DataCell::new_with_type_info(ValueType::NaiveDate, String::from("1"), 1, date)
+                    .unwrap(),
+            );
+            append_derived_template_cells(&mut row, derived, &[]);
+            row
+        };
+        let mut r = row(Value::None, &derived[..1]);
+        derive_columns(&mut r, &derived[..1], &[], 2).unwrap();
+        assert_eq!(&Value::None, r.get_by_name("year").unwrap().get_data());
+
+        let date = Value::NaiveDate(NaiveDate::from_ymd_opt(2021, 1, 3).unwrap());
+        let mut r = row(date.clone(), &derived[..1]);
+        derive_columns(&mut r, &derived[..1], &[], 2).unwrap();
+        assert_eq!(
+            &Value::Int32(2021),
+            r.get_by_name("year").unwrap().get_data()
+        );
+
+        let mut r = row(date, &derived);
+        assert!(derive_columns(&mut r, &derived, &[], 2).is_err()); // the month of an Int32
+    }
+
+    #[test]
+    fn check_and_derive_splits() {
+        let typings = vec![
+            TypeColumnEntry::new(Some(String::from("id")), ValueType::Int32),
+            TypeColumnEntry::new(None, ValueType::String),
+        ];
+        let dests = || {
+            vec![
+                TypeColumnEntry::new(Some(String::from("amount")), ValueType::Float64),
+                TypeColumnEntry::new(Some(String::from("unit")), ValueType::String),
+            ]
+        };
+        let split = |column: usize, destinations: Vec<TypeColumnEntry>| {
+            DerivedSplit::new(column, Box::new(SplitValueAndUnit::new()), destinations)
+        };
+        let check =
+            |ds: DerivedSplit, by_header| check_derived_columns(&typings, &[], &[ds], by_header);
+        assert!(check(split(1, dests()), false).is_ok());
+        assert!(check(split(0, dests()), false).is_err()); // not a String
+        assert!(check(split(2, dests()), false).is_err());
+        assert!(check(split(2, dests()), true).is_ok());
+        assert!(check(split(1, dests()[..1].to_vec()), true).is_err()); // two parts
+        let mut no_header = dests();
+        no_header[1].header = None;
+        assert!(check(split(1, no_header), true).is_err());
+        let mut not_unique = dests();
+        not_unique[1].header = Some(String::from("id"));
+        assert!(check(split(1, not_unique), true).is_err());
+        assert!(check_derived_columns(
+            &typings,
+            &[DerivedColumn::new(1, DatePart::Year, "unit")],
+            &[split(1, dests())],
+            true
+        )
+        .is_err());
+
+        let splits = [split(1, dests())];
+        let row = |value: Value| {
+            let mut row = DataCellRow::new();
+            row.push(DataCell::new(String::from("id"), 0, Value::Int32(1)).unwrap());
+            row.push(
+                DataCell::new_with_type_info(ValueType::String, String::from("1"), 1, value)
+                    .unwrap(),
+            );
+            append_derived_template_cells(&mut row, &[], &splits);
+            row
+        };
+        let mut r = row(Value::String(String::from("10.5 kg")));
+        derive_columns(&mut r, &[], &splits, 2).unwrap();
+        assert_eq!(4, r.0.len());
+        assert_eq!(
+            &Value::String(String::from("10.5 kg")),
+            r.get_by_name("1").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Float64(10.5),
+            r.get_by_name("amount").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::String(String::from("kg")),
+            r.get_by_name("unit").unwrap().get_data()
+        );
+        assert_eq!("unit", r.0[3].get_name());
+
+        let mut r = row(Value::None);
+        derive_columns(&mut r, &[], &splits, 2).unwrap();
+        assert_eq!(&Value::None, r.get_by_name("amount").unwrap().get_data());
+
+        let mut r = row(Value::String(String::from("kg")));
+        assert!(derive_columns(&mut r, &[], &splits, 2).is_err()); // no match
+    }
+}
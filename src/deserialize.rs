@@ -0,0 +1,325 @@
+use std::fmt;
+
+use serde::de::{
+    self, value::StrDeserializer, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess,
+    SeqAccess, Visitor,
+};
+use venum::{value::Value, value_type::ValueType};
+use venum_tds::{data_cell::DataCell, data_cell_row::DataCellRow};
+
+use crate::errors::{PattiCsvError, Result};
+
+/// Deserializes a row into a user type, e.g. a struct (whose fields are matched by the column names, i.e. the final
+/// headers) or a tuple (by position). `Value::None` deserializes into `None` (of an `Option`), values without a serde
+/// counterpart (dates, times and decimals) as their string form, so that e.g. chrono's `NaiveDate` or a `String` can
+/// take them.
+pub fn from_row<T: DeserializeOwned>(row: &DataCellRow) -> Result<T> {
+    T::deserialize(RowDeserializer { row }).map_err(|e| PattiCsvError::Generic {
+        msg: format!("[ERROR_ON_DESERIALIZE] {}", e.0),
+    })
+}
+
+/// Wraps a row iterator (e.g. a `PattiCsvParserIterator`), and deserializes every row into a `T`. See: `from_row`.
+pub struct DeserializeIter<I, T> {
+    inner: I,
+    _marker: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<I: Iterator<Item = Result<DataCellRow>>, T: DeserializeOwned> DeserializeIter<I, T> {
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Gives back the wrapped iterator, e.g. to get its stats or report.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I: Iterator<Item = Result<DataCellRow>>, T: DeserializeOwned> Iterator
+    for DeserializeIter<I, T>
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|res| res.and_then(|row| from_row(&row)))
+    }
+}
+
+#[derive(Debug)]
+struct DeError(String);
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<M: fmt::Display>(msg: M) -> Self {
+        DeError(msg.to_string())
+    }
+}
+
+struct RowDeserializer<'a> {
+    row: &'a DataCellRow,
+}
+
+impl<'de> de::Deserializer<'de> for RowDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        visitor.visit_map(CellsAccess {
+            cells: self.row.0.iter(),
+            curr: None,
+        })
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        visitor.visit_seq(ValuesAccess {
+            cells: self.row.0.iter(),
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct map struct enum identifier ignored_any
+    }
+}
+
+/// The cells of a row as map, by name.
+struct CellsAccess<'a, I> {
+    cells: I,
+    curr: Option<&'a DataCell>,
+}
+
+impl<'de, 'a, I: Iterator<Item = &'a DataCell>> MapAccess<'de> for CellsAccess<'a, I> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> std::result::Result<Option<K::Value>, DeError> {
+        self.curr = self.cells.next();
+        match self.curr {
+            None => Ok(None),
+            Some(cell) => {
+                let name: StrDeserializer<'_, DeError> = cell.get_name().into_deserializer();
+                seed.deserialize(name).map(Some)
+            }
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        let cell = self
+            .curr
+            .ok_or_else(|| DeError(String::from("Value without a key")))?;
+        seed.deserialize(ValueDeserializer(cell.get_data()))
+            .map_err(|e| DeError(format!("{}; header: {}", e, cell.get_name())))
+    }
+}
+
+/// The values of a row as sequence, by position.
+struct ValuesAccess<I> {
+    cells: I,
+}
+
+impl<'de, 'a, I: Iterator<Item = &'a DataCell>> SeqAccess<'de> for ValuesAccess<I> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> std::result::Result<Option<T::Value>, DeError> {
+        match self.cells.next() {
+            None => Ok(None),
+            Some(cell) => seed
+                .deserialize(ValueDeserializer(cell.get_data()))
+                .map(Some)
+                .map_err(|e| DeError(format!("{}; header: {}", e, cell.get_name()))),
+        }
+    }
+}
+
+struct ValueDeserializer<'a>(&'a Value);
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        match self.0 {
+            Value::None => visitor.visit_none(),
+            Value::Char(c) => visitor.visit_char(*c),
+            Value::String(s) => visitor.visit_str(s),
+            Value::Int8(v) => visitor.visit_i8(*v),
+            Value::Int16(v) => visitor.visit_i16(*v),
+            Value::Int32(v) => visitor.visit_i32(*v),
+            Value::Int64(v) => visitor.visit_i64(*v),
+            Value::Int128(v) => visitor.visit_i128(*v),
+            Value::UInt8(v) => visitor.visit_u8(*v),
+            Value::UInt16(v) => visitor.visit_u16(*v),
+            Value::UInt32(v) => visitor.visit_u32(*v),
+            Value::UInt64(v) => visitor.visit_u64(*v),
+            Value::UInt128(v) => visitor.visit_u128(*v),
+            Value::Float32(v) => visitor.visit_f32(*v),
+            Value::Float64(v) => visitor.visit_f64(*v),
+            Value::Bool(b) => visitor.visit_bool(*b),
+            v => {
+                let s = v
+                    .try_convert_to(&ValueType::String)
+                    .and_then(String::try_from)
+                    .map_err(|e| DeError(format!("{:?}", e)))?;
+                visitor.visit_string(s)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        match self.0 {
+            Value::None => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Unit variants by name, e.g. a status column into an enum.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        match self.0 {
+            Value::String(s) => visitor.visit_enum(s.as_str().into_deserializer()),
+            v => Err(DeError(format!("Not a variant name: {:?}", v))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit unit_struct seq
+        tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::{iterating_parser::PattiCsvParserBuilder, parser_config::TypeColumnEntry};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Status {
+        Open,
+        Closed,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Ticket {
+        id: i64, // an Int32 column fits as well
+        title: String,
+        due: Option<String>,
+        status: Status,
+    }
+
+    #[test]
+    fn deserialize_rows() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::NaiveDate),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(
+            "id,title,due,status\n1,foo,2021-01-03,open\n2,bar,,closed\n3,baz,,unknown",
+        );
+        let res = parser
+            .parse_iter_deserialize::<Ticket, _>(&mut test_data_cursor)
+            .collect::<Vec<_>>();
+        assert_eq!(3, res.len()); // no header row
+        assert_eq!(
+            &Ticket {
+                id: 1,
+                title: String::from("foo"),
+                due: Some(String::from("2021-01-03")),
+                status: Status::Open,
+            },
+            res[0].as_ref().unwrap()
+        );
+        assert_eq!(None, res[1].as_ref().unwrap().due);
+        assert!(res[2].is_err());
+
+        let mut test_data_cursor = std::io::Cursor::new("id,title,due,status\n1,foo,,open");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .skip(1) // header
+            .map(|r| from_row::<(u8, String, Option<String>)>(&r.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(Ok((1, String::from("foo"), None)), res[0]);
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Narrow {
+            id: u8,
+            missing: String,
+        }
+        let mut test_data_cursor = std::io::Cursor::new("id,title,due,status\n1000,foo,,open");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .skip(1) // header
+            .map(|r| from_row::<Narrow>(&r.unwrap()))
+            .collect::<Vec<_>>();
+        assert!(res[0].is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::{self, Read};
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    line_tokenizer::DelimitedLineTokenizer,
+};
+
+/// The encoding for a label (e.g. `"windows-1252"`, `"latin1"` or `"utf-16le"`), as defined by the WHATWG Encoding
+/// Standard. Labels are case-insensitive.
+#[cfg(feature = "encoding")]
+pub fn encoding_for_label(label: &str) -> Result<&'static encoding_rs::Encoding> {
+    encoding_rs::Encoding::for_label(label.as_bytes()).ok_or_else(|| PattiCsvError::ConfigError {
+        msg: format!("unknown encoding: {:?}", label),
+    })
+}
+
+#[cfg(not(feature = "encoding"))]
+pub(crate) fn check_encoding_label(label: &str) -> Result<()> {
+    Err(PattiCsvError::ConfigError {
+        msg: format!(
+            "decoding {:?} needs the feature 'encoding', which is not enabled",
+            label
+        ),
+    })
+}
+
+/// The data, either borrowed from the caller (see: `PattiCsvParser::parse_iter`), or owned by the tokenizer (see:
+/// `PattiCsvParser::parse_file`).
+pub(crate) enum Source<'rd, R: Read> {
+    Borrowed(&'rd mut R),
+    #[cfg(feature = "io")]
+    Owned(R),
+}
+
+impl<R: Read> Read for Source<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Source::Borrowed(data) => data.read(buf),
+            #[cfg(feature = "io")]
+            Source::Owned(data) => data.read(buf),
+        }
+    }
+}
+
+/// The data as read by the tokenizer: as is (i.e. UTF-8), or transcoded into UTF-8 while reading, if an encoding is
+/// configured (see: `PattiCsvParserBuilder::encoding`). A BOM always wins over the configured encoding, e.g. a
+/// UTF-16BE BOM with `"utf-16le"`.
+pub(crate) enum RawData<'rd, R: Read> {
+    Utf8(Source<'rd, R>),
+    #[cfg(feature = "encoding")]
+    Decoded(encoding_rs_io::DecodeReaderBytes<Source<'rd, R>, Vec<u8>>),
+}
+
+impl<'rd, R: Read> RawData<'rd, R> {
+    #[cfg_attr(not(feature = "encoding"), allow(unused_variables))]
+    pub(crate) fn new(dlt: &DelimitedLineTokenizer, data: Source<'rd, R>) -> Self {
+        #[cfg(feature = "encoding")]
+        if let Some(encoding) = dlt.encoding {
+            return RawData::Decoded(
+                encoding_rs_io::DecodeReaderBytesBuilder::new()
+                    .encoding(Some(encoding))
+                    .utf8_passthru(true)
+                    .bom_override(true)
+                    .strip_bom(dlt.strip_bom)
+                    .build(data),
+            );
+        }
+        RawData::Utf8(data)
+    }
+}
+
+impl<R: Read> Read for RawData<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            RawData::Utf8(data) => data.read(buf),
+            #[cfg(feature = "encoding")]
+            RawData::Decoded(data) => data.read(buf),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "encoding"))]
+mod tests {
+    use venum::value::Value;
+
+    use crate::iterating_parser::PattiCsvParserBuilder;
+
+    use super::*;
+
+    fn utf16le_with_bom(s: &str) -> Vec<u8> {
+        [0xFF, 0xFE]
+            .into_iter()
+            .chain(s.encode_utf16().flat_map(|u| u.to_le_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_encoded_data() {
+        let parser = PattiCsvParserBuilder::csv()
+            .encoding("windows-1252")
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        // "Müller,€5" in windows-1252
+        let mut test_data_cursor = io::Cursor::new(b"name,amount\nM\xFCller,\x805".to_vec());
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            &Value::from(String::from("Müller")),
+            res[1].get_by_name("name").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::from(String::from("€5")),
+            res[1].get_by_name("amount").unwrap().get_data()
+        );
+
+        // the BOM wins, and is stripped
+        let mut test_data_cursor = io::Cursor::new(utf16le_with_bom("name,amount\nfoo,1"));
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!("name", res[1].0[0].get_name());
+        assert_eq!(
+            &Value::from(String::from("foo")),
+            res[1].get_by_name("name").unwrap().get_data()
+        );
+
+        let parser = PattiCsvParserBuilder::csv()
+            .strip_bom(false)
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let mut test_data_cursor = io::Cursor::new("\u{feff}name,amount\nfoo,1");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!("\u{feff}name", res[1].0[0].get_name());
+
+        assert!(matches!(
+            PattiCsvParserBuilder::csv()
+                .encoding("no-such-encoding")
+                .stringly_type_columns(2)
+                .build(),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+}
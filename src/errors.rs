@@ -10,11 +10,43 @@ pub enum WrappedErrors {
 
 #[derive(Debug, Display, PartialEq)]
 pub enum PattiCsvError {
-    Generic { msg: String },
-    ConfigError { msg: String },
+    Generic {
+        msg: String,
+    },
+    ConfigError {
+        msg: String,
+    },
     Wrapped(WrappedErrors),
     Tokenize(TokenizerError),
     Sanitize(SanitizeError),
+    Row(RowError),
+    /// A data line with more or less tokens (after splitting columns) than there are columns. Only returned, if the
+    /// parser checks it (see: `ColumnCountMismatchPolicy`).
+    ColumnCountMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A typed value, that violates a constraint of its column (see: `ColumnConstraints`).
+    Validation {
+        line: usize,
+        column: usize,
+        header: String,
+        msg: String,
+    },
+    /// A row with the same key as an earlier one (see: `PattiCsvParserBuilder::unique_columns`), with both lines. The
+    /// key is given in its string form.
+    DuplicateKey {
+        line: usize,
+        first_line: usize,
+        columns: Vec<usize>,
+        key: Vec<String>,
+    },
+    /// Not an error as such, but the signal (e.g. from a transitizer, see: `DropRowOn`) to drop the current row. The
+    /// parser iterator doesn't yield it, but counts the row as dropped (see: `dropped_rows` in the stats).
+    DropRow {
+        reason: String,
+    },
 }
 
 #[derive(Error, Debug, PartialEq, Eq)]
@@ -25,6 +57,24 @@ pub enum TokenizerError {
     UnescapedEnclChar { line: usize, token_num: usize },
 }
 
+/// All cell errors of one row. Only returned, if the parser collects all cell errors of a row, instead of failing on
+/// the first one. Collected by the parser iterator, if the row is skipped or typed anyway (see: `ErrorPolicy`).
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("{} bad cell(s) in line: {line:?}, cells: {cells:?}", .cells.len())]
+pub struct RowError {
+    pub line: usize,
+    pub cells: Vec<CellError>,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("column: {column:?}, header: {header:?}, msg: {msg:?}")]
+pub struct CellError {
+    pub column: usize,
+    pub header: String,
+    pub msg: String,
+    pub token: Option<String>, // the (sanitized) token, that failed, None if it's missing
+}
+
 #[derive(Error, Debug, PartialEq, Eq)]
 #[error("line: {line:?}, column: {column:?}, from_token: {from_token:?}, msg: {msg:?}")]
 pub struct SanitizeError {
@@ -0,0 +1,171 @@
+use std::{collections::VecDeque, ops::Range};
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    line_tokenizer::LineTokenizer,
+    report::ComponentInfo,
+};
+
+/// Cuts lines into tokens by fixed byte ranges, e.g. for mainframe exports (see: `PattiCsvParserBuilder::fixed_width`).
+/// The padding is kept, so e.g. a `TrimAll` sanitizer is usually wanted. A line, that is too short for a range (e.g.
+/// because trailing spaces were stripped), yields what there is, i.e. an empty token for a range beyond its end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedWidthLineTokenizer {
+    columns: Vec<Range<usize>>,
+}
+
+impl FixedWidthLineTokenizer {
+    /// The byte ranges (end exclusive) of the columns, in the order of the tokens. There may be gaps in between, e.g.
+    /// for filler.
+    pub fn new(columns: Vec<Range<usize>>) -> Result<Self> {
+        if columns.is_empty() {
+            return Err(PattiCsvError::ConfigError {
+                msg: String::from("fixed-width data needs at least one column"),
+            });
+        }
+        if let Some((idx, range)) = columns.iter().enumerate().find(|(_, r)| r.is_empty()) {
+            return Err(PattiCsvError::ConfigError {
+                msg: format!(
+                    "the range of fixed-width column {} is empty: {:?}",
+                    idx, range
+                ),
+            });
+        }
+        Ok(Self { columns })
+    }
+
+    /// Contiguous columns of the given widths (in bytes), from the start of the line.
+    pub fn from_widths(widths: &[usize]) -> Result<Self> {
+        let columns = widths
+            .iter()
+            .scan(0, |start, &width| {
+                let range = *start..*start + width;
+                *start += width;
+                Some(range)
+            })
+            .collect();
+        Self::new(columns)
+    }
+
+    pub fn columns(&self) -> &[Range<usize>] {
+        &self.columns
+    }
+}
+
+impl LineTokenizer for FixedWidthLineTokenizer {
+    fn tokenize_line(&self, line_num: usize, line: &str) -> Result<VecDeque<String>> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(idx, range)| {
+                let (start, end) = (range.start.min(line.len()), range.end.min(line.len()));
+                line.get(start..end)
+                    .map(String::from)
+                    .ok_or_else(|| PattiCsvError::Generic {
+                        msg: format!(
+                            "[ERROR_ON_TOKENIZE] Column range {:?} cuts a char in half; line: {}; column: {}",
+                            range, line_num, idx
+                        ),
+                    })
+            })
+            .collect()
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "fixedWidth"
+    }
+    fn describe(&self) -> ComponentInfo {
+        let columns = self
+            .columns
+            .iter()
+            .map(|r| format!("{}..{}", r.start, r.end))
+            .collect::<Vec<_>>()
+            .join(",");
+        ComponentInfo::new(self.kind()).with_param("columns", columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::{value::Value, value_type::ValueType};
+
+    use crate::{
+        iterating_parser::PattiCsvParserBuilder, parser_config::TypeColumnEntry,
+        transform_sanitize_token::TrimAll,
+    };
+
+    use super::*;
+
+    #[test]
+    fn tokenize_fixed_width_lines() {
+        let fwt = FixedWidthLineTokenizer::new(vec![0..3, 5..8, 8..12]).unwrap();
+        assert_eq!(
+            VecDeque::from([
+                String::from("001"),
+                String::from("abc"),
+                String::from(" 1.5")
+            ]),
+            fwt.tokenize_line(1, "001XXabc 1.5").unwrap()
+        );
+        // too short
+        assert_eq!(
+            VecDeque::from([String::from("001"), String::from("a"), String::new()]),
+            fwt.tokenize_line(1, "001XXa").unwrap()
+        );
+        assert!(fwt.tokenize_line(1, "001XXabé1.5").is_err());
+
+        assert_eq!(
+            &[0..3, 3..5],
+            FixedWidthLineTokenizer::from_widths(&[3, 2])
+                .unwrap()
+                .columns()
+        );
+        assert!(FixedWidthLineTokenizer::new(Vec::new()).is_err());
+        assert!(FixedWidthLineTokenizer::from_widths(&[3, 0]).is_err());
+    }
+
+    #[test]
+    fn parse_fixed_width_data() {
+        let parser = PattiCsvParserBuilder::fixed_width(
+            FixedWidthLineTokenizer::from_widths(&[4, 10, 6]).unwrap(),
+        )
+        .column_transitizers(std::collections::HashMap::from([(
+            None,
+            vec![Box::new(TrimAll::new()) as _],
+        )]))
+        .column_typings(vec![
+            TypeColumnEntry::new(Some(String::from("id")), ValueType::Int32),
+            TypeColumnEntry::new(Some(String::from("name")), ValueType::String),
+            TypeColumnEntry::new(Some(String::from("amount")), ValueType::Float64),
+        ])
+        .build()
+        .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(
+            "0001Ada Lovel   12.5\n0002Charles    100.0\n0003\"quoted\"     1.0",
+        );
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(3, res.len()); // no header
+        assert_eq!(
+            vec![
+                Value::Int32(2),
+                Value::from(String::from("Charles")),
+                Value::Float64(100.0)
+            ],
+            res[1]
+                .0
+                .iter()
+                .map(|c| c.get_data().clone())
+                .collect::<Vec<_>>()
+        );
+        // enclosures mean nothing
+        assert_eq!(
+            &Value::from(String::from("\"quoted\"")),
+            res[2].get_by_name("name").unwrap().get_data()
+        );
+    }
+}
@@ -0,0 +1,699 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    ops::Range,
+    path::Path,
+};
+
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{
+    derived_columns::append_derived_template_cells,
+    errors::{PattiCsvError, Result},
+    iterating_parser::PattiCsvParser,
+    line_tokenizer::{DelimitedLineTokenizer, TokenMeta, UTF8BOM},
+    money::{check_money_columns_typings, set_combined_money_template_cells},
+    parser_common::{
+        apply_global_map_to_none, build_header_row, build_layout_template, check_column_count,
+        dedup_header_tokens, resolve_column_typings_by_header, resolve_named_transitizers,
+        type_line_tokens_timed, ResolvedColumns,
+    },
+    parser_config::TypeColumnEntry,
+    preamble::PreambleState,
+    split_columns::{split_token_meta, split_tokens},
+};
+
+const INDEX_FILE_MAGIC: &str = "patti_csv_row_index v1";
+
+/// Position of a record (i.e. a line that was not skipped) in the raw data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordPos {
+    pub offset: u64,     // byte offset of the start of the record
+    pub line_num: usize, // 1-based, physical line number. Needed for the skip-filters and error context
+}
+
+/// Byte-offset index of all records of some data. The header (if any) is kept separately, meaning `records[0]`
+/// is always the first data row.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RowIndex {
+    pub header: Option<RecordPos>,
+    pub records: Vec<RecordPos>,
+    pub data_len: u64, // total number of bytes of the indexed data. Used to detect stale, persisted indexes.
+}
+
+impl RowIndex {
+    /// Builds the index in one pass over the data, honoring the skip-filters of the tokenizer.
+    pub fn build<R: Read>(
+        dlt: &DelimitedLineTokenizer,
+        first_data_line_is_header: bool,
+        data: &mut R,
+    ) -> Result<Self> {
+        #[cfg(feature = "encoding")]
+        if let Some(encoding) = dlt.encoding {
+            return Err(PattiCsvError::ConfigError {
+                msg: format!(
+                    "a row index needs the offsets of the raw data, i.e. UTF-8, but the data is: {}",
+                    encoding.name()
+                ),
+            });
+        }
+        let mut buf_raw_data = BufReader::new(data);
+        let mut index = RowIndex::default();
+        let mut line = String::new();
+        let mut offset: u64 = 0;
+        let mut line_num: usize = 0;
+        let mut preamble = PreambleState::new(dlt.preamble.as_ref());
+
+        loop {
+            line.clear();
+            line_num += 1;
+
+            let bytes_read = dlt
+                .read_record(&mut buf_raw_data, &mut line, line_num == 1)
+                .map_err(|e| PattiCsvError::Generic {
+                    msg: format!("error reading line {}. {}", line_num, e),
+                })?;
+            if bytes_read == 0 {
+                break;
+            }
+
+            let line_start = offset;
+            offset += bytes_read as u64;
+
+            if dlt.strip_bom && line_num == 1 && line.as_bytes().starts_with(&UTF8BOM) {
+                line.remove(0); // we remove the char(!) that consists of these 3 bytes, not the bytes!
+            }
+
+            if preamble.consume(dlt.strip_record_terminator(&line))
+                || dlt.is_comment_line(&line)
+                || dlt.skip_line_by_skiptake_sanitizer(line_num, &line)
+            {
+                continue;
+            }
+
+            let pos = RecordPos {
+                offset: line_start,
+                line_num,
+            };
+            if first_data_line_is_header && index.header.is_none() && index.records.is_empty() {
+                index.header = Some(pos);
+            } else {
+                index.records.push(pos);
+            }
+        }
+
+        // the last records are only known at the end. line_num is one past the last record by now
+        if dlt.skip_lines_from_end > 0 {
+            let last_kept = (line_num - 1).saturating_sub(dlt.skip_lines_from_end);
+            index.records.retain(|pos| pos.line_num <= last_kept);
+            index.header = index.header.filter(|pos| pos.line_num <= last_kept);
+        }
+
+        index.data_len = offset;
+        Ok(index)
+    }
+
+    /// Persists the index in a simple, line based text format. The fingerprint (see `PattiCsvParser::fingerprint`)
+    /// identifies the dialect and schema the index was built with.
+    pub fn write_to<W: Write>(&self, fingerprint: u64, w: &mut W) -> Result<()> {
+        writeln!(w, "{}", INDEX_FILE_MAGIC)?;
+        writeln!(w, "fingerprint {:016x}", fingerprint)?;
+        writeln!(w, "data_len {}", self.data_len)?;
+        match self.header {
+            Some(pos) => writeln!(w, "header {} {}", pos.offset, pos.line_num)?,
+            None => writeln!(w, "header -")?,
+        }
+        for pos in &self.records {
+            writeln!(w, "{} {}", pos.offset, pos.line_num)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a persisted index. Fails, if the index was built with a different fingerprint.
+    pub fn read_from<R: BufRead>(r: R, expected_fingerprint: u64) -> Result<Self> {
+        let mut lines = r.lines();
+        let mut next_line = |what: &str| -> Result<String> {
+            match lines.next() {
+                Some(l) => Ok(l?),
+                None => Err(PattiCsvError::Generic {
+                    msg: format!(
+                        "[ERROR_ON_INDEX_READ] Index file truncated, missing {}",
+                        what
+                    ),
+                }),
+            }
+        };
+
+        if next_line("magic")? != INDEX_FILE_MAGIC {
+            return Err(PattiCsvError::Generic {
+                msg: String::from(
+                    "[ERROR_ON_INDEX_READ] Not a patti_csv row index file (or unsupported version)",
+                ),
+            });
+        }
+
+        let fingerprint_line = next_line("fingerprint")?;
+        let fingerprint = fingerprint_line
+            .strip_prefix("fingerprint ")
+            .and_then(|f| u64::from_str_radix(f, 16).ok())
+            .ok_or_else(|| malformed(&fingerprint_line))?;
+        if fingerprint != expected_fingerprint {
+            return Err(PattiCsvError::ConfigError {
+                msg: format!("Row index was built with a different dialect and/or schema (fingerprint {:016x}, expected {:016x})", fingerprint, expected_fingerprint),
+            });
+        }
+
+        let data_len_line = next_line("data_len")?;
+        let data_len = data_len_line
+            .strip_prefix("data_len ")
+            .and_then(|l| l.parse::<u64>().ok())
+            .ok_or_else(|| malformed(&data_len_line))?;
+
+        let header_line = next_line("header")?;
+        let header = match header_line.strip_prefix("header ") {
+            Some("-") => None,
+            Some(pos) => Some(parse_record_pos(pos)?),
+            None => return Err(malformed(&header_line)),
+        };
+
+        let records = lines
+            .map(|l| parse_record_pos(&l?))
+            .collect::<Result<Vec<RecordPos>>>()?;
+
+        Ok(RowIndex {
+            header,
+            records,
+            data_len,
+        })
+    }
+
+    /// Number of data rows, i.e. without the header.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+fn malformed(line: &str) -> PattiCsvError {
+    PattiCsvError::Generic {
+        msg: format!(
+            "[ERROR_ON_INDEX_READ] Malformed line in index file: {}",
+            line
+        ),
+    }
+}
+
+fn parse_record_pos(s: &str) -> Result<RecordPos> {
+    let (offset, line_num) = s.split_once(' ').ok_or_else(|| malformed(s))?;
+    Ok(RecordPos {
+        offset: offset.parse().map_err(|_| malformed(s))?,
+        line_num: line_num.parse().map_err(|_| malformed(s))?,
+    })
+}
+
+/// Random access to the (typed) rows of seekable data, through a `RowIndex` that is built upfront.
+pub struct IndexedReader<'pars, R: Read + Seek> {
+    parser: &'pars PattiCsvParser,
+    data: R,
+    index: RowIndex,
+    column_layout_template: DataCellRow,
+    column_typings: Vec<TypeColumnEntry>,
+    resolved_columns: ResolvedColumns<'pars>,
+    header_row: Option<DataCellRow>,
+}
+
+impl<'pars, R: Read + Seek> IndexedReader<'pars, R> {
+    pub fn new(parser: &'pars PattiCsvParser, mut data: R) -> Result<Self> {
+        data.seek(SeekFrom::Start(0))?;
+        let index = RowIndex::build(&parser.dlt, parser.first_data_line_is_header, &mut data)?;
+        Self::with_index(parser, data, index)
+    }
+
+    /// Uses an already existing index. The index must have been built from the very same data!
+    pub fn with_index(parser: &'pars PattiCsvParser, data: R, index: RowIndex) -> Result<Self> {
+        let mut reader = Self {
+            parser,
+            data,
+            index,
+            column_layout_template: DataCellRow::default(),
+            column_typings: parser.column_typings.clone(),
+            resolved_columns: ResolvedColumns::default(),
+            header_row: None,
+        };
+
+        let header_tokens = match reader.index.header {
+            Some(pos) => {
+                let mut header_tokens = reader.read_tokens_at(pos, true)?.0;
+                dedup_header_tokens(&mut header_tokens, reader.parser.duplicate_headers)?;
+                Some(header_tokens)
+            }
+            None => None,
+        };
+
+        if let (Some(ht), Some(mode)) = (&header_tokens, &reader.parser.typings_by_header) {
+            reader.column_typings =
+                resolve_column_typings_by_header(ht, &reader.parser.column_typings, mode)?;
+            check_money_columns_typings(
+                &reader.column_typings,
+                reader.parser.money_columns.as_deref().unwrap_or_default(),
+                false,
+            )?;
+        }
+        if !reader.parser.global_map_to_none.is_empty() {
+            reader.column_typings =
+                apply_global_map_to_none(&reader.column_typings, &reader.parser.global_map_to_none);
+        }
+
+        // Same sanity check as in the iterator, on the first line we have.
+        let first_tokens_len = match (&header_tokens, reader.index.records.first()) {
+            (Some(ht), _) => Some(ht.len()),
+            (None, Some(&pos)) => Some(reader.read_tokens_at(pos, false)?.0.len()),
+            (None, None) => None,
+        };
+        if let Some(len_data) = first_tokens_len {
+            let len_typings = reader.column_typings.len();
+            if len_typings != len_data {
+                return Err(PattiCsvError::ConfigError { msg: format!("Column typings provided, but length {} differs from actual length of data with num columns {}", len_typings, len_data) });
+            }
+        }
+
+        reader.column_layout_template =
+            build_layout_template(header_tokens.as_ref(), &reader.column_typings)?;
+        set_combined_money_template_cells(
+            &mut reader.column_layout_template,
+            reader.parser.money_columns.as_ref(),
+        );
+        append_derived_template_cells(
+            &mut reader.column_layout_template,
+            &reader.parser.derived_columns,
+            &reader.parser.derived_splits,
+        );
+        reader.resolved_columns.named_transitizers = resolve_named_transitizers(
+            parser,
+            &reader.column_layout_template,
+            &reader.column_typings,
+        )?;
+        if header_tokens.is_some() {
+            reader.header_row = Some(build_header_row(&reader.column_layout_template)?);
+        }
+
+        Ok(reader)
+    }
+
+    /// Uses a persisted index (see `save_index`). Fails, if the index file was written for a different dialect/schema,
+    /// or if the data changed in size since then.
+    pub fn with_index_file<P: AsRef<Path>>(
+        parser: &'pars PattiCsvParser,
+        mut data: R,
+        path: P,
+    ) -> Result<Self> {
+        let index = RowIndex::read_from(BufReader::new(File::open(path)?), parser.fingerprint())?;
+
+        let data_len = data.seek(SeekFrom::End(0))?;
+        if data_len != index.data_len {
+            return Err(PattiCsvError::ConfigError {
+                msg: format!("Row index is stale. It was built for {} bytes of data, but the data has {} bytes", index.data_len, data_len),
+            });
+        }
+
+        Self::with_index(parser, data, index)
+    }
+
+    /// Persists the index to a (sidecar) file, so that other processes can skip the indexing pass.
+    pub fn save_index<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.index.write_to(self.parser.fingerprint(), &mut w)?;
+        w.flush()?;
+        Ok(())
+    }
+
+    pub fn get_index(&self) -> &RowIndex {
+        &self.index
+    }
+
+    pub fn header_row(&self) -> Option<&DataCellRow> {
+        self.header_row.as_ref()
+    }
+
+    /// Number of data rows, i.e. without the header.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Parses the data row with the 0-based index `n` (the header does not count as a row). None if out of bounds.
+    /// A row dropped by a transitizer is returned as `PattiCsvError::DropRow` here, since the index has no stats.
+    pub fn get_row(&mut self, n: usize) -> Option<Result<DataCellRow>> {
+        let pos = *self.index.records.get(n)?;
+        Some(self.read_row_at(pos))
+    }
+
+    /// Parses all data rows in the given range. The range is cut off at the end of the data.
+    pub fn rows_range(&mut self, range: Range<usize>) -> Result<Vec<DataCellRow>> {
+        let end = range.end.min(self.index.len());
+        let start = range.start.min(end);
+
+        (start..end)
+            .map(|n| self.read_row_at(self.index.records[n]))
+            .collect()
+    }
+
+    fn read_row_at(&mut self, pos: RecordPos) -> Result<DataCellRow> {
+        let (mut tokens, mut token_meta) = self.read_tokens_at(pos, false)?;
+        check_column_count(
+            pos.line_num,
+            &mut tokens,
+            Some(&mut token_meta),
+            self.column_typings.len(),
+            self.parser.column_count_mismatch,
+        )?;
+        type_line_tokens_timed(
+            pos.line_num,
+            tokens,
+            Some(&token_meta),
+            &self.column_layout_template,
+            &self.column_typings,
+            self.parser,
+            None,
+            None,
+            None,
+            Some(&self.resolved_columns),
+            None,
+        )
+    }
+
+    fn read_tokens_at(
+        &mut self,
+        pos: RecordPos,
+        is_header: bool,
+    ) -> Result<(VecDeque<String>, Vec<TokenMeta>)> {
+        self.data.seek(SeekFrom::Start(pos.offset))?;
+
+        let mut line = String::new();
+        self.parser.dlt.read_record(
+            &mut BufReader::new(&mut self.data),
+            &mut line,
+            pos.offset == 0,
+        )?;
+
+        if pos.offset == 0 && self.parser.dlt.strip_bom && line.as_bytes().starts_with(&UTF8BOM) {
+            line.remove(0);
+        }
+
+        let dlt = &self.parser.dlt;
+        let (tokens, mut token_meta) = if is_header {
+            let mut token_meta = Vec::new();
+            let tokens = dlt.tokenize_header(
+                &mut Vec::new(),
+                &mut token_meta,
+                pos.line_num,
+                dlt.strip_record_terminator(&line),
+            )?;
+            (tokens, token_meta)
+        } else {
+            dlt.tokenize_with_meta(pos.line_num, dlt.strip_record_terminator(&line))?
+        };
+        split_token_meta(&mut token_meta, &self.parser.split_columns);
+        let tokens = split_tokens(pos.line_num, tokens, &self.parser.split_columns, is_header)?;
+        Ok((tokens, token_meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::{value::Value, value_type::ValueType};
+
+    use super::*;
+    use crate::skip_take_lines::*;
+
+    fn test_parser() -> PattiCsvParser {
+        PattiCsvParser::builder()
+            .separator_char(',')
+            .skip_take_lines_fns(vec![Box::new(SkipLinesStartingWith::new("#"))])
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(Some(String::from("num")), ValueType::Int32),
+            ])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn build_row_index() {
+        let mut data = std::io::Cursor::new("# comment\nc1,c2\na,1\n# comment\nb,2\nc,3\n");
+        let parser = test_parser();
+        let index = RowIndex::build(&parser.dlt, true, &mut data).unwrap();
+
+        assert_eq!(
+            Some(RecordPos {
+                offset: 10,
+                line_num: 2
+            }),
+            index.header
+        );
+        assert_eq!(
+            vec![
+                RecordPos {
+                    offset: 16,
+                    line_num: 3
+                },
+                RecordPos {
+                    offset: 30,
+                    line_num: 5
+                },
+                RecordPos {
+                    offset: 34,
+                    line_num: 6
+                },
+            ],
+            index.records
+        );
+    }
+
+    #[test]
+    fn build_row_index_with_enclosed_newlines() {
+        let data = std::io::Cursor::new("c1,c2\n\"a\nb\",1\n\"c\r\n\",2\nd,3\n");
+        let parser = PattiCsvParser::builder()
+            .separator_char(',')
+            .enclosure_char(Some('"'))
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(Some(String::from("num")), ValueType::Int32),
+            ])
+            .build()
+            .unwrap();
+        let mut reader = IndexedReader::new(&parser, data).unwrap();
+
+        assert_eq!(
+            vec![(6, 2), (14, 3), (22, 4)], // the line numbers count records
+            reader
+                .get_index()
+                .records
+                .iter()
+                .map(|pos| (pos.offset, pos.line_num))
+                .collect::<Vec<_>>()
+        );
+        let rows = reader.rows_range(0..2).unwrap();
+        assert_eq!(
+            &Value::String(String::from("a\nb")),
+            rows[0].get_by_name("c1").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Int32(2),
+            rows[1].get_by_name("num").unwrap().get_data()
+        );
+        let row = reader.get_row(2).unwrap().unwrap();
+        assert_eq!(&Value::Int32(3), row.get_by_name("num").unwrap().get_data());
+    }
+
+    #[test]
+    fn build_row_index_skipping_lines_from_end() {
+        let mut data = std::io::Cursor::new("c1,c2\na,1\nb,2\nsum,3\n");
+        let parser = PattiCsvParser::builder()
+            .separator_char(',')
+            .skip_lines_from_end(1)
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let index = RowIndex::build(&parser.dlt, true, &mut data).unwrap();
+
+        assert_eq!(
+            vec![2, 3],
+            index
+                .records
+                .iter()
+                .map(|pos| pos.line_num)
+                .collect::<Vec<_>>()
+        );
+        assert_ne!(test_parser().fingerprint(), parser.fingerprint());
+    }
+
+    #[test]
+    fn get_row_random_access() {
+        let data = std::io::Cursor::new("# comment\nc1,c2\na,1\n# comment\nb,2\nc,3");
+        let parser = test_parser();
+        let mut reader = IndexedReader::new(&parser, data).unwrap();
+
+        assert_eq!(3, reader.len());
+
+        let header = reader.header_row().unwrap();
+        assert_eq!(
+            &Value::String(String::from("c1")),
+            header.get_by_name("c1").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::String(String::from("num")),
+            header.get_by_name("num").unwrap().get_data()
+        );
+
+        let row = reader.get_row(2).unwrap().unwrap();
+        assert_eq!(
+            &Value::String(String::from("c")),
+            row.get_by_name("c1").unwrap().get_data()
+        );
+        assert_eq!(&Value::Int32(3), row.get_by_name("num").unwrap().get_data());
+
+        let row = reader.get_row(0).unwrap().unwrap();
+        assert_eq!(&Value::Int32(1), row.get_by_name("num").unwrap().get_data());
+
+        assert!(reader.get_row(3).is_none());
+    }
+
+    #[test]
+    fn get_row_with_global_map_to_none() {
+        let data = std::io::Cursor::new(
+            "c1,c2
+a,NULL
+NULL,2",
+        );
+        let parser = PattiCsvParser::builder()
+            .separator_char(',')
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(Some(String::from("num")), ValueType::Int32),
+            ])
+            .global_map_to_none(vec![String::from("NULL")])
+            .build()
+            .unwrap();
+        let mut reader = IndexedReader::new(&parser, data).unwrap();
+
+        let row = reader.get_row(0).unwrap().unwrap();
+        assert_eq!(&Value::None, row.get_by_name("num").unwrap().get_data());
+        let row = reader.get_row(1).unwrap().unwrap();
+        assert_eq!(&Value::None, row.get_by_name("c1").unwrap().get_data());
+        assert_eq!(&Value::Int32(2), row.get_by_name("num").unwrap().get_data());
+    }
+
+    #[test]
+    fn row_index_write_read_roundtrip() {
+        let mut data = std::io::Cursor::new("# comment\nc1,c2\na,1\n# comment\nb,2\nc,3\n");
+        let parser = test_parser();
+        let index = RowIndex::build(&parser.dlt, true, &mut data).unwrap();
+
+        let mut persisted: Vec<u8> = Vec::new();
+        index
+            .write_to(parser.fingerprint(), &mut persisted)
+            .unwrap();
+
+        let read_index =
+            RowIndex::read_from(std::io::Cursor::new(&persisted), parser.fingerprint()).unwrap();
+        assert_eq!(index, read_index);
+        assert_eq!(38, read_index.data_len);
+    }
+
+    #[test]
+    fn row_index_read_err_on_different_fingerprint() {
+        let mut data = std::io::Cursor::new("c1,c2\na,1\n");
+        let parser = test_parser();
+        let index = RowIndex::build(&parser.dlt, true, &mut data).unwrap();
+
+        let mut persisted: Vec<u8> = Vec::new();
+        index
+            .write_to(parser.fingerprint(), &mut persisted)
+            .unwrap();
+
+        let other_parser = PattiCsvParser::builder()
+            .separator_char(';')
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(Some(String::from("num")), ValueType::Int32),
+            ])
+            .build()
+            .unwrap();
+
+        let res = RowIndex::read_from(std::io::Cursor::new(&persisted), other_parser.fingerprint());
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn fingerprint_by_stable_component_ids() {
+        assert_eq!(test_parser().fingerprint(), test_parser().fingerprint());
+
+        let other_parser = PattiCsvParser::builder()
+            .separator_char(',')
+            .skip_take_lines_fns(vec![Box::new(SkipLinesStartingWith::new("//"))])
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(Some(String::from("num")), ValueType::Int32),
+            ])
+            .build()
+            .unwrap();
+        assert_ne!(test_parser().fingerprint(), other_parser.fingerprint());
+    }
+
+    #[test]
+    fn indexed_reader_from_index_file() {
+        let index_path = std::env::temp_dir().join("patti_csv_indexed_reader_from_index_file.idx");
+        let parser = test_parser();
+
+        let reader =
+            IndexedReader::new(&parser, std::io::Cursor::new("c1,c2\na,1\nb,2\n")).unwrap();
+        reader.save_index(&index_path).unwrap();
+
+        let mut reader = IndexedReader::with_index_file(
+            &parser,
+            std::io::Cursor::new("c1,c2\na,1\nb,2\n"),
+            &index_path,
+        )
+        .unwrap();
+        let row = reader.get_row(1).unwrap().unwrap();
+        assert_eq!(&Value::Int32(2), row.get_by_name("num").unwrap().get_data());
+
+        // data changed in the meantime
+        let res = IndexedReader::with_index_file(
+            &parser,
+            std::io::Cursor::new("c1,c2\na,1\nb,2\nc,3\n"),
+            &index_path,
+        );
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+
+        std::fs::remove_file(&index_path).unwrap();
+    }
+
+    #[test]
+    fn rows_range() {
+        let data = std::io::Cursor::new("c1,c2\na,1\nb,2\nc,3\n");
+        let parser = test_parser();
+        let mut reader = IndexedReader::new(&parser, data).unwrap();
+
+        let rows = reader.rows_range(1..10).unwrap();
+        assert_eq!(2, rows.len());
+        assert_eq!(
+            &Value::Int32(2),
+            rows[0].get_by_name("num").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Int32(3),
+            rows[1].get_by_name("num").unwrap().get_data()
+        );
+
+        assert!(reader.rows_range(5..7).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,613 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use venum::value_type::ValueType;
+
+use crate::parser_config::{NullPolicy, TypeColumnEntry};
+
+/// rust_decimal can hold (at least) 28 significant digits.
+const MAX_DECIMAL_PRECISION: usize = 28;
+
+/// Tokens that (case-insensitively) are commonly used for "no value". Tokens made of punctuation only (e.g. "-" or
+/// "?") are candidates as well.
+const KNOWN_NULL_MARKERS: [&str; 8] = [
+    "null", "n/a", "na", "#n/a", "none", "nil", "missing", "unknown",
+];
+
+/// Per-column result of the type inference, with the numbers the recommendation is based on. See:
+/// `PattiCsvParser::infer_types`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "jsonconf", derive(serde::Serialize))]
+#[cfg_attr(feature = "jsonconf", serde(rename_all = "camelCase"))]
+pub struct ColumnInference {
+    pub idx: usize,
+    pub name: String,
+    /// Non-empty (trimmed) values.
+    pub num_values: usize,
+    pub num_empty: usize,
+    pub num_ints: usize,
+    /// Numbers with a decimal point, e.g. "12.50".
+    pub num_decimals: usize,
+    /// Numbers in scientific notation, as well as "NaN" and "inf".
+    pub num_floats: usize,
+    pub num_non_numeric: usize,
+    /// Non-numeric values, that are "true" or "false".
+    pub num_bools: usize,
+    /// Non-numeric values, that are ISO 8601 dates, e.g. "2021-01-03".
+    pub num_dates: usize,
+    /// Max. number of digits after the decimal point.
+    pub max_scale: usize,
+    /// Max. number of significant digits.
+    pub max_precision: usize,
+    /// Number of integers with leading zeros, e.g. "00123".
+    pub num_leading_zeros: usize,
+    /// The width of the values, if all of them have the same.
+    pub fixed_width: Option<usize>,
+    /// Number of values, that don't survive a round trip through `Float64`, i.e. they would lose precision.
+    pub num_float_lossy: usize,
+    pub float_lossy_example: Option<String>,
+    pub recommended_type: ValueType,
+    /// Identifier-like columns (i.e. fixed width integers with leading zeros) should be kept as `String`.
+    pub preserve_leading_zeros: bool,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "jsonconf", derive(serde::Serialize))]
+#[cfg_attr(feature = "jsonconf", serde(rename_all = "camelCase"))]
+pub struct InferenceReport {
+    pub num_rows_sampled: usize,
+    pub columns: Vec<ColumnInference>,
+    pub null_marker_suggestions: Vec<NullMarkerSuggestion>,
+    /// Were the suggested null markers already treated as empty values for the inference? (See: `infer_types_lenient`)
+    pub null_markers_applied: bool,
+}
+
+/// A token, that probably means "no value", and should be mapped to `Value::None` (see: `NullPolicy::map_list`). A
+/// token is suggested, if it's a candidate (e.g. "NULL", "N/A" or "-") and it appears in several columns, or in a
+/// column whose other values are all numeric.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "jsonconf", derive(serde::Serialize))]
+#[cfg_attr(feature = "jsonconf", serde(rename_all = "camelCase"))]
+pub struct NullMarkerSuggestion {
+    pub token: String,
+    pub columns: Vec<usize>,
+    pub num_occurrences: usize,
+}
+
+impl InferenceReport {
+    /// The recommended typings, e.g. as a starting point for a config. If the null markers were applied, they are
+    /// mapped to `Value::None` in the columns they appeared in.
+    pub fn to_typings(&self) -> Vec<TypeColumnEntry> {
+        self.columns
+            .iter()
+            .map(|c| {
+                let tce = TypeColumnEntry::new(Some(c.name.clone()), c.recommended_type.clone())
+                    .with_preserve_leading_zeros(c.preserve_leading_zeros);
+                let map_list = self.null_markers_of_column(c.idx);
+                match self.null_markers_applied && !map_list.is_empty() {
+                    true => tce.with_null_policy(NullPolicy::default().with_map_list(map_list)),
+                    false => tce,
+                }
+            })
+            .collect()
+    }
+
+    /// The suggested null markers, that appeared in a given column.
+    pub fn null_markers_of_column(&self, idx: usize) -> Vec<String> {
+        self.null_marker_suggestions
+            .iter()
+            .filter(|s| s.columns.contains(&idx))
+            .map(|s| s.token.clone())
+            .collect()
+    }
+}
+
+enum NumberKind {
+    Int,
+    Decimal,
+    Float,
+    NonNumeric,
+}
+
+fn all_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn classify(token: &str) -> NumberKind {
+    let unsigned = token.strip_prefix(['+', '-']).unwrap_or(token);
+    if all_digits(unsigned) {
+        return NumberKind::Int;
+    }
+    if let Some((int_part, frac_part)) = unsigned.split_once('.') {
+        if (all_digits(int_part) || int_part.is_empty())
+            && (all_digits(frac_part) || frac_part.is_empty())
+            && !(int_part.is_empty() && frac_part.is_empty())
+        {
+            return NumberKind::Decimal;
+        }
+    }
+    match token.parse::<f64>() {
+        Ok(_) => NumberKind::Float,
+        Err(_) => NumberKind::NonNumeric,
+    }
+}
+
+/// A plain (i.e. not scientific) number, without sign, leading zeros, trailing fractional zeros and a trailing point.
+/// Returns (is negative, normalized digits). "-0" and "0" are the same.
+fn normalize_number(s: &str) -> (bool, String) {
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let mut normalized = String::from(unsigned.trim_start_matches('0'));
+    if normalized.contains('.') {
+        normalized.truncate(normalized.trim_end_matches('0').trim_end_matches('.').len());
+    }
+    if normalized.is_empty() || normalized == "." {
+        return (false, String::from("0"));
+    }
+    (negative, normalized)
+}
+
+/// Does the value survive a round trip through `f64`? (The `Display` of `f64` is the shortest representation, that
+/// parses back to the same float, and it never uses scientific notation.)
+fn is_float_lossless(token: &str) -> bool {
+    match token.parse::<f64>() {
+        Ok(f) => normalize_number(token) == normalize_number(&f.to_string()),
+        Err(_) => false,
+    }
+}
+
+/// Number of significant digits (including trailing zeros of the fraction, like a decimal has) and the scale of a
+/// plain number.
+fn precision_and_scale(token: &str) -> (usize, usize) {
+    let unsigned = token.strip_prefix(['+', '-']).unwrap_or(token);
+    let scale = unsigned.split_once('.').map_or(0, |(_, frac)| frac.len());
+    let significant = unsigned.trim_start_matches('0');
+    // For values < 1 the leading zeros of the fraction are not significant either.
+    let significant = match significant.strip_prefix('.') {
+        Some(frac) => frac.trim_start_matches('0'),
+        None => significant,
+    };
+    let digits = significant.bytes().filter(|b| b.is_ascii_digit()).count();
+    (digits.max(1), scale)
+}
+
+fn smallest_int_type(min: i128, max: i128) -> ValueType {
+    if min >= i128::from(i32::MIN) && max <= i128::from(i32::MAX) {
+        ValueType::Int32
+    } else if min >= i128::from(i64::MIN) && max <= i128::from(i64::MAX) {
+        ValueType::Int64
+    } else {
+        ValueType::Int128
+    }
+}
+
+/// Infers the type of one column from its (raw) values. Values are trimmed, empty values are ignored.
+pub fn infer_column<'a, I>(idx: usize, name: String, values: I) -> ColumnInference
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut ci = ColumnInference {
+        idx,
+        name,
+        num_values: 0,
+        num_empty: 0,
+        num_ints: 0,
+        num_decimals: 0,
+        num_floats: 0,
+        num_non_numeric: 0,
+        num_bools: 0,
+        num_dates: 0,
+        max_scale: 0,
+        max_precision: 0,
+        num_leading_zeros: 0,
+        fixed_width: None,
+        num_float_lossy: 0,
+        float_lossy_example: None,
+        recommended_type: ValueType::String,
+        preserve_leading_zeros: false,
+        reason: String::new(),
+    };
+    let mut widths_differ = false;
+    let mut int_range: Option<(i128, i128)> = None;
+    let mut int_overflow = false;
+
+    for value in values {
+        let value = value.trim();
+        if value.is_empty() {
+            ci.num_empty += 1;
+            continue;
+        }
+        ci.num_values += 1;
+        let width = value.chars().count();
+        if *ci.fixed_width.get_or_insert(width) != width {
+            widths_differ = true;
+        }
+        match classify(value) {
+            NumberKind::Int => {
+                ci.num_ints += 1;
+                if value.len() > 1 && value.starts_with('0') {
+                    ci.num_leading_zeros += 1;
+                }
+                match value.parse::<i128>() {
+                    Ok(i) => {
+                        let (min, max) = int_range.get_or_insert((i, i));
+                        *min = (*min).min(i);
+                        *max = (*max).max(i);
+                    }
+                    Err(_) => int_overflow = true,
+                }
+            }
+            NumberKind::Decimal => ci.num_decimals += 1,
+            NumberKind::Float => {
+                ci.num_floats += 1;
+                continue; // already a float, there's nothing to lose
+            }
+            NumberKind::NonNumeric => {
+                ci.num_non_numeric += 1;
+                if value == "true" || value == "false" {
+                    ci.num_bools += 1;
+                } else if NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+                    ci.num_dates += 1;
+                }
+                continue;
+            }
+        }
+        let (precision, scale) = precision_and_scale(value);
+        ci.max_precision = ci.max_precision.max(precision);
+        ci.max_scale = ci.max_scale.max(scale);
+        if !is_float_lossless(value) {
+            ci.num_float_lossy += 1;
+            ci.float_lossy_example
+                .get_or_insert_with(|| String::from(value));
+        }
+    }
+
+    if widths_differ {
+        ci.fixed_width = None;
+    }
+    ci.preserve_leading_zeros =
+        ci.num_leading_zeros > 0 && ci.fixed_width.is_some() && ci.num_ints == ci.num_values;
+
+    let (recommended_type, reason) = if ci.num_values == 0 {
+        (ValueType::String, String::from("no values"))
+    } else if ci.num_bools == ci.num_values {
+        (ValueType::Bool, String::from("booleans only"))
+    } else if ci.num_dates == ci.num_values {
+        (ValueType::NaiveDate, String::from("ISO dates only"))
+    } else if ci.num_non_numeric > 0 {
+        (ValueType::String, String::from("has non-numeric values"))
+    } else if ci.preserve_leading_zeros {
+        (
+            ValueType::String,
+            String::from("fixed width integers with leading zeros, i.e. probably identifiers"),
+        )
+    } else if ci.num_floats > 0 {
+        let mut reason = String::from("has values in scientific notation (or NaN/inf)");
+        if ci.num_float_lossy > 0 {
+            reason.push_str(", but other values lose precision as Float64");
+        }
+        (ValueType::Float64, reason)
+    } else if let (0, Some((min, max)), false) = (ci.num_decimals, int_range, int_overflow) {
+        let int_type = smallest_int_type(min, max);
+        let reason = if ci.num_float_lossy > 0 {
+            String::from("integers only (too large to be exact as Float64)")
+        } else {
+            String::from("integers only")
+        };
+        (int_type, reason)
+    } else if ci.max_precision > MAX_DECIMAL_PRECISION {
+        // i.e. also integers too large for Int128
+        (
+            ValueType::String,
+            format!(
+                "has values with more than {} significant digits, which don't fit any numeric type exactly",
+                MAX_DECIMAL_PRECISION
+            ),
+        )
+    } else if ci.num_float_lossy > 0 {
+        (
+            ValueType::Decimal,
+            String::from("values lose precision as Float64"),
+        )
+    } else {
+        (
+            ValueType::Float64,
+            String::from("all values are exact as Float64 (use Decimal for exact arithmetic)"),
+        )
+    };
+    ci.recommended_type = recommended_type;
+    ci.reason = reason;
+    ci
+}
+
+fn is_null_marker_candidate(token: &str) -> bool {
+    KNOWN_NULL_MARKERS
+        .iter()
+        .any(|m| m.eq_ignore_ascii_case(token))
+        || (!token.is_empty() && token.chars().all(|c| c.is_ascii_punctuation()))
+}
+
+/// Finds the null marker candidates, that appear in at least two columns, or in an otherwise numeric column.
+fn suggest_null_markers(num_cols: usize, rows: &[Vec<String>]) -> Vec<NullMarkerSuggestion> {
+    // token -> (column -> occurrences)
+    let mut candidates: BTreeMap<&str, BTreeMap<usize, usize>> = BTreeMap::new();
+    let mut numeric_cols = vec![(true, false); num_cols]; // (no other non-numeric values, any numeric value)
+    for row in rows {
+        for (idx, token) in row.iter().enumerate() {
+            let token = token.trim();
+            if is_null_marker_candidate(token) {
+                *candidates.entry(token).or_default().entry(idx).or_default() += 1;
+            } else if !token.is_empty() {
+                match classify(token) {
+                    NumberKind::NonNumeric => numeric_cols[idx].0 = false,
+                    _ => numeric_cols[idx].1 = true,
+                }
+            }
+        }
+    }
+    candidates
+        .into_iter()
+        .filter(|(_, cols)| {
+            cols.len() > 1 || cols.keys().any(|idx| numeric_cols[*idx] == (true, true))
+        })
+        .map(|(token, cols)| NullMarkerSuggestion {
+            token: String::from(token),
+            num_occurrences: cols.values().sum(),
+            columns: cols.into_keys().collect(),
+        })
+        .collect()
+}
+
+/// Infers the types of all columns from (raw) sample rows. Without a header, the columns are named by their index.
+/// Null markers are only suggested, i.e. they still count as (non-numeric) values.
+pub fn infer_types(header: Option<&[String]>, rows: &[Vec<String>]) -> InferenceReport {
+    infer_types_with_null_markers(header, rows, false)
+}
+
+/// Like `infer_types`, but the suggested null markers count as empty values, e.g. a column with "1", "2" and "NULL"
+/// is an integer column. The typings of the report map them to `Value::None`.
+pub fn infer_types_lenient(header: Option<&[String]>, rows: &[Vec<String>]) -> InferenceReport {
+    infer_types_with_null_markers(header, rows, true)
+}
+
+fn infer_types_with_null_markers(
+    header: Option<&[String]>,
+    rows: &[Vec<String>],
+    apply_null_markers: bool,
+) -> InferenceReport {
+    let num_cols = rows
+        .iter()
+        .map(Vec::len)
+        .chain(header.map(<[String]>::len))
+        .max()
+        .unwrap_or(0);
+    let null_marker_suggestions = suggest_null_markers(num_cols, rows);
+    let columns = (0..num_cols)
+        .map(|idx| {
+            let name = header
+                .and_then(|h| h.get(idx))
+                .cloned()
+                .unwrap_or_else(|| idx.to_string());
+            let null_markers = match apply_null_markers {
+                true => null_marker_suggestions
+                    .iter()
+                    .filter(|s| s.columns.contains(&idx))
+                    .map(|s| s.token.as_str())
+                    .collect(),
+                false => Vec::new(),
+            };
+            infer_column(
+                idx,
+                name,
+                rows.iter()
+                    .filter_map(|row| row.get(idx).map(String::as_str))
+                    .map(|v| match null_markers.contains(&v.trim()) {
+                        true => "",
+                        false => v,
+                    }),
+            )
+        })
+        .collect();
+    InferenceReport {
+        num_rows_sampled: rows.len(),
+        columns,
+        null_marker_suggestions,
+        null_markers_applied: apply_null_markers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infer(values: &[&str]) -> ColumnInference {
+        infer_column(0, String::from("c"), values.iter().copied())
+    }
+
+    #[test]
+    fn float_round_trip() {
+        assert!(is_float_lossless("0.1"));
+        assert!(is_float_lossless("12.50"));
+        assert!(is_float_lossless("-0.0"));
+        assert!(is_float_lossless("+007"));
+        assert!(is_float_lossless("9007199254740992"));
+        assert!(!is_float_lossless("9007199254740993"));
+        assert!(!is_float_lossless("12345678901234567.89"));
+        assert!(!is_float_lossless("0.12345678901234567890"));
+    }
+
+    #[test]
+    fn precision_and_scale_of_numbers() {
+        assert_eq!((4, 2), precision_and_scale("12.50"));
+        assert_eq!((3, 1), precision_and_scale("12.5"));
+        assert_eq!((1, 3), precision_and_scale("0.001"));
+        assert_eq!((3, 0), precision_and_scale("-123"));
+        assert_eq!((1, 0), precision_and_scale("000"));
+    }
+
+    #[test]
+    fn infer_ints() {
+        let ci = infer(&["1", " -5 ", "", "42"]);
+        assert_eq!(ValueType::Int32, ci.recommended_type);
+        assert_eq!((3, 1, 3), (ci.num_values, ci.num_empty, ci.num_ints));
+        assert_eq!(
+            ValueType::Int64,
+            infer(&["1", "3000000000"]).recommended_type
+        );
+        assert_eq!(
+            ValueType::Int128,
+            infer(&["1", "100000000000000000000"]).recommended_type
+        );
+        // more digits than a Decimal can hold, but still fits
+        let ci = infer(&["1", "12345678901234567890123456789012345678"]);
+        assert_eq!(ValueType::Int128, ci.recommended_type);
+        assert_eq!(
+            "integers only (too large to be exact as Float64)",
+            ci.reason
+        );
+        assert_eq!(
+            ValueType::String,
+            infer(&["123456789012345678901234567890123456789012"]).recommended_type
+        );
+    }
+
+    #[test]
+    fn infer_decimals_with_and_without_precision_loss() {
+        let ci = infer(&["12.50", "3.1"]);
+        assert_eq!(ValueType::Float64, ci.recommended_type);
+        assert_eq!(2, ci.max_scale);
+        assert_eq!(0, ci.num_float_lossy);
+
+        let ci = infer(&["12.50", "12345678901234567.89", "1"]);
+        assert_eq!(ValueType::Decimal, ci.recommended_type);
+        assert_eq!(1, ci.num_float_lossy);
+        assert_eq!(
+            Some(String::from("12345678901234567.89")),
+            ci.float_lossy_example
+        );
+        assert_eq!(19, ci.max_precision);
+    }
+
+    #[test]
+    fn infer_floats_strings_and_empty() {
+        assert_eq!(ValueType::Float64, infer(&["1.5e3", "2"]).recommended_type);
+        assert_eq!(ValueType::Float64, infer(&["NaN", "2.5"]).recommended_type);
+        assert_eq!(ValueType::String, infer(&["1", "foo"]).recommended_type);
+        assert_eq!(ValueType::String, infer(&["", " "]).recommended_type);
+        assert_eq!(
+            ValueType::Bool,
+            infer(&["true", "", "false"]).recommended_type
+        );
+        assert_eq!(ValueType::String, infer(&["true", "1"]).recommended_type);
+        assert_eq!(
+            ValueType::NaiveDate,
+            infer(&["2021-01-03", "1999-12-31"]).recommended_type
+        );
+        assert_eq!(
+            ValueType::String,
+            infer(&["2021-01-03", "2021-02-30"]).recommended_type
+        );
+        assert_eq!(
+            ValueType::String,
+            infer(&["1.12345678901234567890123456789"]).recommended_type
+        );
+    }
+
+    #[test]
+    fn infer_leading_zero_identifiers() {
+        let ci = infer(&["00123", "04711", "", "12345"]);
+        assert_eq!(ValueType::String, ci.recommended_type);
+        assert!(ci.preserve_leading_zeros);
+        assert_eq!((2, Some(5)), (ci.num_leading_zeros, ci.fixed_width));
+
+        // Not fixed width, so probably just sloppy numbers
+        let ci = infer(&["007", "12"]);
+        assert_eq!(ValueType::Int32, ci.recommended_type);
+        assert!(!ci.preserve_leading_zeros);
+        assert_eq!((1, None), (ci.num_leading_zeros, ci.fixed_width));
+
+        // Fixed width without leading zeros
+        assert!(!infer(&["123", "456"]).preserve_leading_zeros);
+        assert!(!infer(&["0", "1"]).preserve_leading_zeros);
+    }
+
+    #[test]
+    fn infer_types_of_rows() {
+        let header = vec![String::from("id"), String::from("amount")];
+        let rows = vec![
+            vec![String::from("1"), String::from("10.00")],
+            vec![String::from("2"), String::from("98765432109876543.21")],
+            vec![String::from("3")],
+        ];
+        let report = infer_types(Some(&header), &rows);
+        assert_eq!(3, report.num_rows_sampled);
+        assert_eq!(
+            vec![
+                TypeColumnEntry::new(Some(String::from("id")), ValueType::Int32),
+                TypeColumnEntry::new(Some(String::from("amount")), ValueType::Decimal),
+            ],
+            report.to_typings()
+        );
+        assert_eq!(2, report.columns[1].num_values);
+        assert!(report.null_marker_suggestions.is_empty());
+    }
+
+    #[test]
+    fn suggest_null_markers_in_rows() {
+        let rows = [
+            ["1", "foo", "NULL", "x"],
+            ["NULL", "-", "2.5", "n/a"],
+            ["3", "bar", "-", "y"],
+            ["4", "?", "N/A", "z"],
+        ]
+        .iter()
+        .map(|r| r.iter().map(|t| String::from(*t)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+        let report = infer_types(None, &rows);
+        assert_eq!(
+            vec![
+                NullMarkerSuggestion {
+                    token: String::from("-"),
+                    columns: vec![1, 2],
+                    num_occurrences: 2
+                },
+                NullMarkerSuggestion {
+                    token: String::from("N/A"),
+                    columns: vec![2],
+                    num_occurrences: 1
+                },
+                NullMarkerSuggestion {
+                    token: String::from("NULL"),
+                    columns: vec![0, 2],
+                    num_occurrences: 2
+                },
+            ],
+            report.null_marker_suggestions
+        );
+        // only suggested, so they are still in the way
+        assert_eq!(ValueType::String, report.columns[0].recommended_type);
+        assert_eq!(
+            TypeColumnEntry::new(Some(String::from("0")), ValueType::String),
+            report.to_typings()[0]
+        );
+
+        let report = infer_types_lenient(None, &rows);
+        assert!(report.null_markers_applied);
+        assert_eq!(ValueType::Int32, report.columns[0].recommended_type);
+        assert_eq!(1, report.columns[0].num_empty);
+        assert_eq!(ValueType::Float64, report.columns[2].recommended_type);
+        // "?" is only in a string column, "n/a" only in one (string) column
+        assert_eq!(ValueType::String, report.columns[1].recommended_type);
+        assert_eq!(ValueType::String, report.columns[3].recommended_type);
+        let typings = report.to_typings();
+        assert_eq!(
+            vec![String::from("-"), String::from("N/A"), String::from("NULL")],
+            typings[2].null_policy.map_list
+        );
+        assert_eq!(NullPolicy::default(), typings[3].null_policy);
+    }
+}
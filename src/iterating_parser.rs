@@ -1,28 +1,127 @@
-use std::{collections::HashMap, io::Read};
+use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    io::Read,
+    time::{Duration, Instant},
+};
 
 use venum::{value::Value, value_type::ValueType};
-use venum_tds::{data_cell::DataCell, data_cell_row::DataCellRow};
+use venum_tds::data_cell_row::DataCellRow;
+
+#[cfg(feature = "io")]
+use std::{io::Seek, path::Path};
 
+#[cfg(feature = "async")]
+use crate::async_stream::{RowStream, DEFAULT_CAPACITY};
+#[cfg(feature = "serde")]
+use crate::deserialize::DeserializeIter;
 use crate::{
-    errors::{PattiCsvError, Result},
+    cell_converter::{CellConverter, VenumCellConverter},
+    column_stats::{ColumnStats, ColumnStatsCollector},
+    derived_columns::{
+        append_derived_template_cells, check_derived_columns, DerivedColumn, DerivedSplit,
+    },
+    errors::{PattiCsvError, Result, RowError},
+    fixed_width::FixedWidthLineTokenizer,
+    inference::{infer_types, infer_types_lenient, InferenceReport},
     line_tokenizer::{
-        DelimitedLineTokenizer, DelimitedLineTokenizerIter, DelimitedLineTokenizerStats,
+        BoxedLineTokenizer, DelimitedLineTokenizer, DelimitedLineTokenizerIter,
+        DelimitedLineTokenizerStats, StageTimings, TokenMeta,
+    },
+    money::{check_money_columns_typings, set_combined_money_template_cells},
+    parser_common::{
+        apply_global_map_to_none, build_header_row, build_layout_template, check_column_count,
+        dedup_header_tokens, project_row, resolve_column_selection,
+        resolve_column_typings_by_header, resolve_named_transitizers, type_line_tokens_timed,
+        typed_columns_for, ResolvedColumns,
     },
-    parser_common::{build_layout_template, sanitize_tokenizer_iter_res},
-    parser_config::{TypeColumnEntry, VecOfTokenTransitizers},
+    parser_config::{
+        below_cap, is_numeric, BoxedCellConverter, ColumnCountMismatchPolicy, ColumnSelection,
+        DuplicateHeaderPolicy, DuplicateRowComparison, DuplicateRowPolicy, ErrorPolicy,
+        HeaderMatchMode, MemoryBudget, MoneyColumns, Preamble, PreambleLines, QuotedTokenPolicy,
+        SanitizerOrder, TypeColumnEntry, VecOfTokenTransitizers,
+    },
+    pipeline::{BoxedRowStage, BoxedTokenStage},
+    probe::{probe, sniff, ProbeResult, SniffReport},
+    progress_observer::{BoxedProgressObserver, ProgressInterval},
+    report::{
+        add_error_to_summaries, ComponentInfo, DialectReport, ErrorSummary, ParseReport,
+        SchemaColumnReport, SkipRuleReport,
+    },
+    retype::{RetypeIter, RetypeRow},
+    sample::RowReservoir,
+    simple_row::SimpleRowIter,
     skip_take_lines::SkipTakeLines,
+    split_columns::{splice_split_columns, split_token_meta, split_tokens, SplitColumn},
+    stream_diff::{DiffIter, DiffMode},
+    transform_sanitize_token::TransformSanitizeToken,
+    validation::ValidationReport,
+    value_transformer::BoxedValueTransformer,
+};
+#[cfg(feature = "io")]
+use crate::{
+    compression::{CompressionKind, DecompressedReader},
+    index::IndexedReader,
+    line_tokenizer::DEFAULT_READ_BUF_CAPACITY,
 };
 
+/// The (immutable) configuration of a parse run. All state of a run lives in the iterator (see: `parse_iter`), so a
+/// parser is `Send + Sync` and can be shared between threads, e.g. behind an `Arc`, with every thread parsing its own
+/// reader. This is why all pluggable parts (skip filters, transitizers, converters) have to be `Send + Sync` too.
 #[derive(Debug)]
 pub struct PattiCsvParser {
     pub first_data_line_is_header: bool,
-    dlt: DelimitedLineTokenizer,
+    pub(crate) dlt: DelimitedLineTokenizer,
     // This means:
     // a) if the first Option is None, we simply don't have transitizers.
     // b) if the second Option is None, this means we have transitizers that apply to all columns,
     //    not just a specific one. (i.e. this is the "global" option. Everything is applied "globally")
-    column_transitizers: Option<HashMap<Option<usize>, VecOfTokenTransitizers>>,
-    column_typings: Vec<TypeColumnEntry>,
+    pub(crate) column_transitizers: Option<HashMap<Option<usize>, VecOfTokenTransitizers>>,
+    pub(crate) column_transitizers_by_name: Option<HashMap<String, VecOfTokenTransitizers>>, // resolved by the iterator
+    pub(crate) sanitizer_order: Option<HashMap<usize, SanitizerOrder>>, // by column, the default is GlobalFirst
+    pub(crate) column_typings: Vec<TypeColumnEntry>,
+    pub(crate) typings_by_header: Option<HeaderMatchMode>,
+    // Columns (by final index) that don't use the default (venum based) converter.
+    pub(crate) column_converters: Option<HashMap<usize, BoxedCellConverter>>,
+    // Columns (by final index) with transformers of their typed values, in the order they run.
+    pub(crate) value_transformers: Option<HashMap<usize, Vec<BoxedValueTransformer>>>,
+    pub(crate) duplicate_rows: Option<(DuplicateRowPolicy, DuplicateRowComparison)>,
+    pub(crate) duplicate_headers: DuplicateHeaderPolicy,
+    pub(crate) column_count_mismatch: Option<ColumnCountMismatchPolicy>,
+    pub(crate) global_map_to_none: Vec<String>, // for columns without a map list of their own
+    pub(crate) unique_columns: Vec<Vec<usize>>, // by final index, see: PattiCsvParserBuilder::unique_columns
+    pub(crate) smart_sanitizer_defaults: bool,
+    pub(crate) money_columns: Option<Vec<MoneyColumns>>,
+    pub(crate) split_columns: Vec<SplitColumn>, // sorted by column (descending)
+    pub(crate) derived_columns: Vec<DerivedColumn>, // appended after all other columns
+    pub(crate) derived_splits: Vec<DerivedSplit>, // appended after the derived columns
+    pub(crate) uses_token_meta: bool, // some column needs to know, if its token was quoted (see: TokenMeta)
+    pub(crate) row_sample: Option<(usize, Option<u64>)>, // (capacity, seed)
+    pub(crate) column_stats: Option<usize>, // max. distinct values per column
+    pub(crate) collect_all_cell_errors: bool,
+    pub(crate) error_policy: ErrorPolicy,
+    pub(crate) column_selection: Option<ColumnSelection>,
+    pub(crate) token_stages: Vec<BoxedTokenStage>, // see: pipeline
+    pub(crate) row_stages: Vec<BoxedRowStage>,
+    pub(crate) stage_timings: bool,
+    pub(crate) emit_header_row: bool,
+    pub(crate) infer_column_types: Option<usize>, // number of sample rows, see: infer_column_types
+}
+
+// Make sure, we never (accidentally) lose this. See above.
+fn assert_send_sync<T: Send + Sync>() {}
+const _: fn() = assert_send_sync::<PattiCsvParser>;
+
+/// The version of the canonical form the fingerprint is built from (see: `PattiCsvParser::fingerprint`). Bump it, when
+/// the form changes.
+const FINGERPRINT_VERSION: &str = "v1";
+
+/// Helper method. The stable description of a pluggable part for the fingerprint, plus its `get_self_info()` if it's a
+/// custom one (which has no stable description).
+fn component_canonical<F: FnOnce() -> String>(info: ComponentInfo, self_info: F) -> String {
+    match info.kind.as_str() {
+        "custom" => format!("{}{:?}", info.canonical(), self_info()),
+        _ => info.canonical(),
+    }
 }
 
 impl PattiCsvParser {
@@ -35,6 +134,422 @@ impl PattiCsvParser {
     ) -> PattiCsvParserIterator<'pars, 'rd, R> {
         PattiCsvParserIterator::new(self, self.dlt.tokenize_iter(data))
     }
+
+    /// Opens the file at the path and parses it, i.e. the one call for the common case. The file is read through a
+    /// buffer sized to it, and decompressed while reading, if it's compressed (detected by the magic bytes, see:
+    /// `compression::DecompressedReader`). The compression ends up in the report (see: `finish_report`). A BOM is
+    /// handled as with `parse_iter` (see: `PattiCsvParserBuilder::strip_bom` and `encoding`).
+    #[cfg(feature = "io")]
+    pub fn parse_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<PattiCsvParserIterator<'_, 'static, DecompressedReader>> {
+        let reader = DecompressedReader::open(path, None)?;
+        let compression = reader.kind();
+        let buf_capacity = reader.buf_capacity().unwrap_or(DEFAULT_READ_BUF_CAPACITY);
+        let mut iter =
+            PattiCsvParserIterator::new(self, self.dlt.tokenize_iter_owned(reader, buf_capacity));
+        iter.compression = Some(compression);
+        Ok(iter)
+    }
+
+    /// Parses and deserializes every data row into a `T`, e.g. a struct whose fields are named like the columns (see:
+    /// `deserialize::from_row`). The header row (if any) is never yielded.
+    #[cfg(feature = "serde")]
+    pub fn parse_iter_deserialize<'pars, 'rd, T, R>(
+        &'pars self,
+        data: &'rd mut R,
+    ) -> DeserializeIter<PattiCsvParserIterator<'pars, 'rd, R>, T>
+    where
+        T: serde::de::DeserializeOwned,
+        R: Read,
+    {
+        let mut iter = self.parse_iter(data);
+        iter.emit_header_row = false;
+        DeserializeIter::new(iter)
+    }
+
+    /// Parses an async reader (e.g. a socket) as a `Stream` of rows, incl. the header row (if it's emitted), without
+    /// blocking a worker thread. See: `async_stream::RowStream`
+    ///
+    /// NOTE: Must be called from within a tokio runtime.
+    #[cfg(feature = "async")]
+    pub fn parse_stream<R>(self: std::sync::Arc<Self>, reader: R) -> RowStream
+    where
+        R: tokio::io::AsyncRead + Unpin + Send + 'static,
+    {
+        RowStream::new(self, reader, DEFAULT_CAPACITY)
+    }
+    /// Fingerprint of the dialect and the schema of this parser. Used to make sure persisted artifacts, like row
+    /// indexes, are only used with the configuration they were built with. It's stable between releases: the pluggable
+    /// parts go by their stable descriptions (see: `ComponentInfo`), and options only count, if they aren't the
+    /// default. (Custom parts have no stable description, so their `get_self_info()` is used too.)
+    pub fn fingerprint(&self) -> u64 {
+        let mut canonical = format!(
+            "{}|sep:{:?}|encl:{:?}|header:{}|",
+            FINGERPRINT_VERSION,
+            self.dlt.delim_char,
+            self.dlt.encl_char,
+            self.first_data_line_is_header
+        );
+        let mut push =
+            |key: &str, value: String| canonical.push_str(&format!("{}:{}|", key, value));
+        // Fieldless enums go by their variant name (which is part of the API).
+        if self.dlt.trim_trailing_whitespace {
+            push("trim_trailing_whitespace", String::from("true"));
+        }
+        if let Some(mode) = &self.typings_by_header {
+            push("typings_by_header", format!("{:?}", mode));
+        }
+        if let Some((policy, compare_by)) = &self.duplicate_rows {
+            push("duplicate_rows", format!("{:?}:{:?}", policy, compare_by));
+        }
+        if self.smart_sanitizer_defaults {
+            push("smart_sanitizer_defaults", String::from("true"));
+        }
+        if let Some(record_separator) = self.dlt.record_separator {
+            push("record_separator", format!("{:?}", record_separator));
+        }
+        if let Some(money_columns) = &self.money_columns {
+            money_columns.iter().for_each(|mc| {
+                push(
+                    "money_columns",
+                    format!("{}:{}:{}", mc.amount_col, mc.currency_col, mc.combine),
+                )
+            });
+        }
+        if self.dlt.detect_header_enclosure {
+            push("detect_header_enclosure", String::from("true"));
+        }
+        if self.dlt.skip_lines_from_end > 0 {
+            push(
+                "skip_lines_from_end",
+                self.dlt.skip_lines_from_end.to_string(),
+            );
+        }
+        if let Some(policy) = self.column_count_mismatch {
+            push("column_count_mismatch", format!("{:?}", policy));
+        }
+        if !self.global_map_to_none.is_empty() {
+            push(
+                "global_map_to_none",
+                format!("{:?}", self.global_map_to_none),
+            );
+        }
+        if !self.unique_columns.is_empty() {
+            push("unique_columns", format!("{:?}", self.unique_columns));
+        }
+        if self.duplicate_headers != DuplicateHeaderPolicy::KeepFirst {
+            push("duplicate_headers", format!("{:?}", self.duplicate_headers));
+        }
+        if let Some(comment_char) = self.dlt.comment_char {
+            push("comment_char", format!("{:?}", comment_char));
+        }
+        if let Some(line_tokenizer) = &self.dlt.line_tokenizer {
+            push(
+                "line_tokenizer",
+                component_canonical(line_tokenizer.describe(), || line_tokenizer.get_self_info()),
+            );
+        }
+        self.dlt.skip_take_lines_fns.iter().flatten().for_each(|f| {
+            push(
+                "skip_take_lines",
+                component_canonical(f.describe(), || f.get_self_info()),
+            )
+        });
+        if let Some(preamble) = &self.dlt.preamble {
+            let lines = match preamble.lines {
+                PreambleLines::Count(n) => n.to_string(),
+                PreambleLines::WhileKeyValue => String::from("whileKeyValue"),
+            };
+            push("preamble", format!("{}:{:?}", lines, preamble.kv_separator));
+        }
+        self.column_typings
+            .iter()
+            .for_each(|tce| push("typing", tce.canonical()));
+        self.split_columns.iter().for_each(|sc| {
+            push(
+                "split_column",
+                format!(
+                    "{}:{}",
+                    sc.column,
+                    component_canonical(sc.splitter.describe(), || sc.splitter.get_self_info())
+                ),
+            )
+        });
+        self.derived_columns.iter().for_each(|dc| {
+            push(
+                "derived_column",
+                format!("{}:{:?}:{:?}", dc.column, dc.part, dc.header),
+            )
+        });
+        self.derived_splits.iter().for_each(|ds| {
+            push(
+                "derived_split",
+                format!(
+                    "{}:{}:[{}]",
+                    ds.column,
+                    component_canonical(ds.splitter.describe(), || ds.splitter.get_self_info()),
+                    ds.destinations
+                        .iter()
+                        .map(TypeColumnEntry::canonical)
+                        .collect::<Vec<_>>()
+                        .join(";")
+                ),
+            )
+        });
+        if let Some(column_converters) = &self.column_converters {
+            let mut idxs = column_converters.keys().collect::<Vec<_>>();
+            idxs.sort();
+            idxs.into_iter().for_each(|i| {
+                let cc = &column_converters[i];
+                push(
+                    "column_converter",
+                    format!(
+                        "{}:{}",
+                        i,
+                        component_canonical(cc.describe(), || cc.get_self_info())
+                    ),
+                )
+            });
+        }
+        match &self.column_selection {
+            Some(ColumnSelection::Idxs(idxs)) => push("column_selection", format!("{:?}", idxs)),
+            Some(ColumnSelection::Names(names)) => push("column_selection", format!("{:?}", names)),
+            None => {}
+        }
+        if let Some(value_transformers) = &self.value_transformers {
+            let mut idxs = value_transformers.keys().collect::<Vec<_>>();
+            idxs.sort();
+            idxs.into_iter().for_each(|i| {
+                value_transformers[i].iter().for_each(|vt| {
+                    push(
+                        "value_transformer",
+                        format!(
+                            "{}:{}",
+                            i,
+                            component_canonical(vt.describe(), || vt.get_self_info())
+                        ),
+                    )
+                })
+            });
+        }
+
+        // FNV-1a. We can't use the std DefaultHasher, since its output is not guaranteed to be stable between releases.
+        canonical.bytes().fold(0xcbf29ce484222325_u64, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    /// The transitizers of a column (by final index), in the order they run, e.g. `["global: TrimAll", "local: ..."]`.
+    pub fn sanitizer_chain(&self, column: usize) -> Vec<String> {
+        self.sanitizer_chain_by(column, |t| t.get_self_info())
+            .into_iter()
+            .map(|(kind, info)| format!("{}: {}", kind, info))
+            .collect()
+    }
+
+    /// Same as `sanitizer_chain`, but with stable, machine-readable descriptions, e.g. to echo the config to tooling.
+    pub fn describe_sanitizer_chain(&self, column: usize) -> Vec<(&'static str, ComponentInfo)> {
+        self.sanitizer_chain_by(column, |t| t.describe())
+    }
+
+    /// Helper method. The transitizers of a column, in the order they run, as ("global" or "local", info).
+    fn sanitizer_chain_by<T, F>(&self, column: usize, info: F) -> Vec<(&'static str, T)>
+    where
+        F: Fn(&(dyn TransformSanitizeToken + Send + Sync)) -> T,
+    {
+        let ct = match &self.column_transitizers {
+            Some(ct) => ct,
+            None => return Vec::new(),
+        };
+        let infos = |key: Option<usize>, kind: &'static str| {
+            ct.get(&key)
+                .into_iter()
+                .flatten()
+                .map(|t| (kind, info(t.as_ref())))
+                .collect::<Vec<_>>()
+        };
+        let (global, local) = (infos(None, "global"), infos(Some(column), "local"));
+        let (first, second) = match self.sanitizer_order_of(column) {
+            SanitizerOrder::GlobalFirst => (global, local),
+            SanitizerOrder::LocalFirst => (local, global),
+        };
+        first.into_iter().chain(second).collect()
+    }
+
+    /// The skip filters, in the order they are checked. See: `ComponentInfo`
+    pub fn describe_skip_filters(&self) -> Vec<ComponentInfo> {
+        self.dlt
+            .skip_take_lines_fns
+            .iter()
+            .flatten()
+            .map(|f| f.describe())
+            .collect()
+    }
+
+    pub(crate) fn sanitizer_order_of(&self, column: usize) -> SanitizerOrder {
+        self.sanitizer_order
+            .as_ref()
+            .and_then(|so| so.get(&column).copied())
+            .unwrap_or_default()
+    }
+
+    /// Takes a quick look at the first (at most) `max_lines` lines with the dialect (and skip filters) of this parser,
+    /// without any sanitization or typing. Useful to show users what a file looks like, before writing a config.
+    pub fn probe<R: Read>(&self, reader: &mut R, max_lines: usize) -> Result<ProbeResult> {
+        probe(&self.dlt, reader, max_lines)
+    }
+
+    /// Sniffs (at most) `max_bytes` bytes of raw data, to guess its encoding and dialect. Doesn't need a parser, since
+    /// the point is usually to find out how to configure one. See also: `sniff::Sniffer`, which checks for a header line
+    /// as well, and configures a builder with the dialect.
+    pub fn sniff<R: Read>(reader: &mut R, max_bytes: usize) -> Result<SniffReport> {
+        sniff(reader, max_bytes)
+    }
+
+    /// Infers the column types from (at most) `max_lines` lines of raw data, and recommends target types, e.g. to avoid
+    /// `Float64` for values that would lose precision. If the first line is a header, it's used for the column names.
+    /// Likely null markers (e.g. "NULL" or "-") are suggested in the report.
+    pub fn infer_types<R: Read>(
+        &self,
+        reader: &mut R,
+        max_lines: usize,
+    ) -> Result<InferenceReport> {
+        let (header, sample) = self.inference_sample(reader, max_lines)?;
+        Ok(infer_types(header.as_deref(), &sample))
+    }
+
+    /// Like `infer_types`, but the suggested null markers are treated as empty values (and mapped to `Value::None` in
+    /// the recommended typings). Helpful for messy data, where e.g. "NULL" would make every column a `String` column.
+    pub fn infer_types_lenient<R: Read>(
+        &self,
+        reader: &mut R,
+        max_lines: usize,
+    ) -> Result<InferenceReport> {
+        let (header, sample) = self.inference_sample(reader, max_lines)?;
+        Ok(infer_types_lenient(header.as_deref(), &sample))
+    }
+
+    /// Dry run: sanitizes and types (at most) `max_rows` data rows, without yielding them, and reports what would go
+    /// wrong, i.e. lines with a different number of columns than the first one, the bad cells per column (with the
+    /// first offending values and their line numbers) and all other errors. E.g. to fail fast, with a useful diagnosis,
+    /// before a job starts to process the data. The row stages are not run. Duplicate rows are handled like when parsing
+    /// (see: `DuplicateRowPolicy`), i.e. dropped ones aren't validated rows, and flagged ones are errors.
+    ///
+    /// A config, that doesn't fit the data at all (e.g. the number of typings differs from the number of columns of the
+    /// first line), is an error, like when parsing.
+    pub fn validate<R: Read>(&self, data: &mut R, max_rows: usize) -> Result<ValidationReport> {
+        let mut iter = self.parse_iter(data);
+        iter.collect_type_errors = true;
+        let mut report = ValidationReport::default();
+
+        while report.num_rows_validated < max_rows {
+            let res = match iter.next_inner() {
+                Some(res) => res,
+                None => break,
+            };
+            if let Err(e @ PattiCsvError::ConfigError { .. }) = res {
+                return Err(e);
+            }
+            if report.columns.is_empty() {
+                report.set_columns(&iter.column_layout_template);
+            }
+            let line_num = iter.curr_line_num;
+            let is_duplicate = std::mem::take(&mut iter.curr_is_duplicate);
+            report.check_num_columns(line_num, iter.curr_num_tokens);
+            if iter.is_at_header_row() {
+                if let Err(e) = res {
+                    report.add_error(error_kind(&e), line_num, format!("{:?}", e));
+                }
+                continue;
+            }
+            if is_duplicate {
+                iter.take_row_errors(); // the row is reported as a duplicate only
+                                        // A dropped one isn't a validated row
+                if let Some((DuplicateRowPolicy::Flag, _)) = self.duplicate_rows {
+                    let e = duplicate_row_err(line_num);
+                    report.num_rows_validated += 1;
+                    report.num_rows_err += 1;
+                    report.add_error(error_kind(&e), line_num, format!("{:?}", e));
+                }
+                continue;
+            }
+
+            report.num_rows_validated += 1;
+            if res.is_ok() {
+                iter.commit_unique_keys();
+            }
+            let mut row_errors = iter.take_row_errors();
+            let row_is_err = match res {
+                Ok(_) | Err(PattiCsvError::DropRow { .. }) => !row_errors.is_empty(),
+                Err(PattiCsvError::Row(row_error)) => {
+                    row_errors.push(row_error);
+                    true
+                }
+                Err(e) => {
+                    report.add_error(error_kind(&e), line_num, format!("{:?}", e));
+                    true
+                }
+            };
+            if row_is_err {
+                report.num_rows_err += 1;
+            }
+            row_errors
+                .into_iter()
+                .for_each(|row_error| report.add_row_error(row_error));
+        }
+        Ok(report)
+    }
+
+    /// Helper method. The (optional) header and the raw sample rows.
+    #[allow(clippy::type_complexity)]
+    fn inference_sample<R: Read>(
+        &self,
+        reader: &mut R,
+        max_lines: usize,
+    ) -> Result<(Option<Vec<String>>, Vec<Vec<String>>)> {
+        let mut sample = probe(&self.dlt, reader, max_lines)?.sample;
+        let header = match self.first_data_line_is_header && !sample.is_empty() {
+            true => Some(sample.remove(0)),
+            false => None,
+        };
+        Ok((header, sample))
+    }
+
+    /// Types rows afterwards, that were parsed stringly (or are raw tokens), by the given column typings, e.g. once the
+    /// schema was decided by inspecting the data (see: `infer_types`), without reading the source again. The typings
+    /// are positional (and their headers win over the ones of the rows, like when parsing), the sanitizers, converters,
+    /// derived columns etc. are the ones of this parser. So if the rows were sanitized already, this should usually be
+    /// a different parser than the one of the first pass. The tokens have no metadata (i.e. none counts as quoted),
+    /// and the rows are numbered from 1 (in the errors), without a header row.
+    pub fn retype<I>(
+        &self,
+        rows: I,
+        column_typings: Vec<TypeColumnEntry>,
+    ) -> Result<RetypeIter<'_, I::IntoIter>>
+    where
+        I: IntoIterator,
+        I::Item: RetypeRow,
+    {
+        RetypeIter::new(self, rows.into_iter(), column_typings)
+    }
+
+    /// Builds a row index over the (seekable) data first, then allows random access to the rows.
+    #[cfg(feature = "io")]
+    pub fn indexed_reader<R: Read + Seek>(&self, data: R) -> Result<IndexedReader<'_, R>> {
+        IndexedReader::new(self, data)
+    }
+
+    /// Parses two inputs with this parser and yields their differences, row by row (see: `DiffMode`).
+    pub fn diff_iter<'pars, 'rd, L: Read, R: Read>(
+        &'pars self,
+        left: &'rd mut L,
+        right: &'rd mut R,
+        mode: DiffMode,
+    ) -> DiffIter<'pars, 'rd, L, R> {
+        DiffIter::new(self, left, right, mode)
+    }
 }
 
 pub struct PattiCsvParserBuilder {
@@ -43,8 +558,44 @@ pub struct PattiCsvParserBuilder {
     first_data_line_is_header: bool,
     skip_take_lines_fns: Option<Vec<Box<dyn SkipTakeLines + Send + Sync>>>,
     save_skipped_lines: bool,
+    trim_trailing_whitespace: bool,
+    detect_header_enclosure: bool,
+    skip_lines_from_end: usize,
+    comment_char: Option<char>,
+    progress_observer: Option<(BoxedProgressObserver, ProgressInterval)>,
+    encoding: Option<String>,
+    strip_bom: bool,
+    record_separator: Option<char>,
+    preamble: Option<Preamble>,
     column_transitizers: Option<HashMap<Option<usize>, VecOfTokenTransitizers>>,
+    column_transitizers_by_name: Option<HashMap<String, VecOfTokenTransitizers>>,
+    sanitizer_order: Option<HashMap<usize, SanitizerOrder>>,
     column_typings: Option<Vec<TypeColumnEntry>>,
+    typings_by_header: Option<HeaderMatchMode>,
+    column_converters: Option<HashMap<usize, BoxedCellConverter>>,
+    value_transformers: Option<HashMap<usize, Vec<BoxedValueTransformer>>>,
+    duplicate_rows: Option<(DuplicateRowPolicy, DuplicateRowComparison)>,
+    duplicate_headers: DuplicateHeaderPolicy,
+    column_count_mismatch: Option<ColumnCountMismatchPolicy>,
+    global_map_to_none: Vec<String>,
+    unique_columns: Vec<Vec<usize>>,
+    smart_sanitizer_defaults: bool,
+    money_columns: Option<Vec<MoneyColumns>>,
+    split_columns: Vec<SplitColumn>,
+    derived_columns: Vec<DerivedColumn>,
+    derived_splits: Vec<DerivedSplit>,
+    row_sample: Option<(usize, Option<u64>)>,
+    column_stats: Option<usize>,
+    collect_all_cell_errors: bool,
+    error_policy: ErrorPolicy,
+    column_selection: Option<ColumnSelection>,
+    token_stages: Vec<BoxedTokenStage>,
+    row_stages: Vec<BoxedRowStage>,
+    stage_timings: bool,
+    emit_header_row: bool,
+    memory_budget: MemoryBudget,
+    infer_column_types: Option<usize>,
+    line_tokenizer: Option<BoxedLineTokenizer>,
 }
 
 impl PattiCsvParserBuilder {
@@ -54,21 +605,52 @@ impl PattiCsvParserBuilder {
             enclosure_char: Some('"'),
             first_data_line_is_header: true,
             save_skipped_lines: false,
+            trim_trailing_whitespace: false,
+            detect_header_enclosure: false,
+            skip_lines_from_end: 0,
+            comment_char: None,
+            progress_observer: None,
+            encoding: None,
+            strip_bom: true,
+            record_separator: None,
+            preamble: None,
             skip_take_lines_fns: None,
             column_transitizers: None,
+            column_transitizers_by_name: None,
+            sanitizer_order: None,
             column_typings: None,
+            typings_by_header: None,
+            column_converters: None,
+            value_transformers: None,
+            duplicate_rows: None,
+            duplicate_headers: DuplicateHeaderPolicy::KeepFirst,
+            column_count_mismatch: None,
+            global_map_to_none: Vec::new(),
+            unique_columns: Vec::new(),
+            smart_sanitizer_defaults: false,
+            money_columns: None,
+            split_columns: Vec::new(),
+            derived_columns: Vec::new(),
+            derived_splits: Vec::new(),
+            row_sample: None,
+            column_stats: None,
+            collect_all_cell_errors: false,
+            error_policy: ErrorPolicy::default(),
+            column_selection: None,
+            token_stages: Vec::new(),
+            row_stages: Vec::new(),
+            stage_timings: false,
+            emit_header_row: true,
+            memory_budget: MemoryBudget::default(),
+            infer_column_types: None,
+            line_tokenizer: None,
         }
     }
 
     pub fn csv() -> Self {
         Self {
             separator_char: Some(','),
-            enclosure_char: Some('"'),
-            first_data_line_is_header: true,
-            save_skipped_lines: false,
-            skip_take_lines_fns: None,
-            column_transitizers: None,
-            column_typings: None,
+            ..Self::new()
         }
     }
 
@@ -77,10 +659,19 @@ impl PattiCsvParserBuilder {
             separator_char: Some('\t'),
             enclosure_char: None,
             first_data_line_is_header: false,
-            save_skipped_lines: false,
-            skip_take_lines_fns: None,
-            column_transitizers: None,
-            column_typings: None,
+            ..Self::new()
+        }
+    }
+
+    /// Fixed-width (columnar) data, e.g. mainframe exports, without a header line. There is no enclosure, and the
+    /// separator is not used (it's a TAB, e.g. for the dialect of the parse report). See: `FixedWidthLineTokenizer`
+    pub fn fixed_width(tokenizer: FixedWidthLineTokenizer) -> Self {
+        Self {
+            separator_char: Some('\t'),
+            enclosure_char: None,
+            first_data_line_is_header: false,
+            line_tokenizer: Some(Box::new(tokenizer)),
+            ..Self::new()
         }
     }
 
@@ -94,6 +685,14 @@ impl PattiCsvParserBuilder {
         self
     }
 
+    /// Splits the lines with this, instead of by separator and enclosure (e.g. see: `fixed_width`). Everything else,
+    /// like reading the records or skipping lines, stays the same. NOTE: The records are still read as configured,
+    /// i.e. with an enclosure character they may span lines, so there usually should be none.
+    pub fn line_tokenizer(mut self, t: BoxedLineTokenizer) -> PattiCsvParserBuilder {
+        self.line_tokenizer = Some(t);
+        self
+    }
+
     pub fn first_data_line_is_header(mut self, b: bool) -> PattiCsvParserBuilder {
         self.first_data_line_is_header = b;
         self
@@ -107,11 +706,77 @@ impl PattiCsvParserBuilder {
         self
     }
 
+    /// Skips the last n records of the data (e.g. footer or summation lines), regardless of the skip filters. Since the end of the data is
+    /// only known once it's reached, the parser reads n records ahead. The skipped records count as skipped lines.
+    pub fn skip_lines_from_end(mut self, n: usize) -> PattiCsvParserBuilder {
+        self.skip_lines_from_end = n;
+        self
+    }
+
+    /// Lines starting with this character (e.g. `#`) are comments, and skipped by the tokenizer itself, before the
+    /// skip filters. They count as skipped lines, and separately as comment lines in the stats. Only the very start of
+    /// a line counts, so e.g. `  # foo` is data.
+    pub fn comment_char(mut self, c: char) -> PattiCsvParserBuilder {
+        self.comment_char = Some(c);
+        self
+    }
+
+    /// The encoding of the data, by its label (e.g. `"windows-1252"`, `"latin1"` or `"utf-16"`, see:
+    /// `encoding::encoding_for_label`). The data is transcoded into UTF-8 while reading, before tokenizing, so the
+    /// bytes in the stats are the ones of the transcoded data. A BOM wins over the label. An unknown label, or a
+    /// missing feature `encoding`, is an error when building the parser.
+    pub fn encoding(mut self, label: &str) -> PattiCsvParserBuilder {
+        self.encoding = Some(String::from(label));
+        self
+    }
+
+    /// Whether a leading BOM (of any encoding) is removed, which is the default. Otherwise it's kept as `U+FEFF` at the
+    /// start of the first line, i.e. of the first header or value.
+    pub fn strip_bom(mut self, b: bool) -> PattiCsvParserBuilder {
+        self.strip_bom = b;
+        self
+    }
+
+    /// Calls the observer from the tokenizer whenever the interval is reached, with the stats so far, and once at the end
+    /// of the data. See: `progress_observer::ProgressObserver`
+    pub fn progress_observer(
+        mut self,
+        observer: BoxedProgressObserver,
+        interval: ProgressInterval,
+    ) -> PattiCsvParserBuilder {
+        self.progress_observer = Some((observer, interval));
+        self
+    }
+
     pub fn save_skipped_lines(mut self, b: bool) -> PattiCsvParserBuilder {
         self.save_skipped_lines = b;
         self
     }
 
+    /// Restores the old behaviour of trimming the end of a line (`trim_end`) before it's tokenized, instead of only
+    /// removing the line terminator. This trims whitespace after a closing enclosure, as well as whitespace delimiters,
+    /// i.e. a TSV line ending in tabs loses its empty last tokens. (Whitespace inside a last, enclosed field is kept,
+    /// since the enclosure ends the line.)
+    pub fn trim_trailing_whitespace(mut self, b: bool) -> PattiCsvParserBuilder {
+        self.trim_trailing_whitespace = b;
+        self
+    }
+
+    /// Only without an enclosure character and with a header line: Strips the quoting of the header line, if all of its
+    /// tokens are consistently quoted (with `"` or `'`), e.g. for files that quote the headers, but none of the data.
+    /// Otherwise, the quotes would end up in the header names.
+    pub fn detect_header_enclosure(mut self, b: bool) -> PattiCsvParserBuilder {
+        self.detect_header_enclosure = b;
+        self
+    }
+
+    /// Records are separated by this (ASCII) character, instead of by newlines. Record separators inside of enclosures
+    /// are part of the field, as are newlines (enclosed or not). Without, only enclosed newlines are part of the field.
+    pub fn record_separator(mut self, c: char) -> PattiCsvParserBuilder {
+        self.record_separator = Some(c);
+        self
+    }
+
     pub fn column_transitizers(
         mut self,
         t: HashMap<Option<usize>, VecOfTokenTransitizers>,
@@ -120,413 +785,4002 @@ impl PattiCsvParserBuilder {
         self
     }
 
+    /// Transitizers of columns by their (final) names, instead of by index, e.g. for files that reorder their columns.
+    /// The names are resolved once the header (or the first line) is known, and the transitizers run after all the ones
+    /// by index. An unknown name is an error then. Names are matched like the typings by header, i.e. also by the
+    /// header aliases of the typings, and with the match mode of `resolve_typings_by_header` (if set).
+    pub fn column_transitizers_by_name(
+        mut self,
+        t: HashMap<String, VecOfTokenTransitizers>,
+    ) -> PattiCsvParserBuilder {
+        self.column_transitizers_by_name = Some(t);
+        self
+    }
+
+    /// By default, the global transitizers run before the local ones of a column. This changes the order for the given
+    /// column. Can be used multiple times, for multiple columns. See: `PattiCsvParser::sanitizer_chain`
+    pub fn sanitizer_order(
+        mut self,
+        column: usize,
+        order: SanitizerOrder,
+    ) -> PattiCsvParserBuilder {
+        self.sanitizer_order
+            .get_or_insert_with(HashMap::new)
+            .insert(column, order);
+        self
+    }
+
     pub fn column_typings(mut self, t: Vec<TypeColumnEntry>) -> PattiCsvParserBuilder {
         self.column_typings = Some(t);
         self
     }
 
-    pub fn stringly_type_columns(mut self, num_columns: usize) -> PattiCsvParserBuilder {
-        self.column_typings = Some(
-            (0..num_columns)
-                .into_iter()
-                .map(|_| TypeColumnEntry::new(None, ValueType::String))
-                .collect(),
-        );
+    /// Resolves the column typings by header name (and header aliases) against the header line, instead of by
+    /// position. Only applies, if the first data line is a header.
+    pub fn resolve_typings_by_header(mut self, mode: HeaderMatchMode) -> PattiCsvParserBuilder {
+        self.typings_by_header = Some(mode);
         self
     }
 
-    pub fn build(mut self) -> Result<PattiCsvParser> {
-        if self.column_typings.is_none() {
-            return Err(PattiCsvError::Generic {
-                msg: String::from("mandatory 'column typings' are not set! (None)"),
-            });
-        }
-        if self.column_typings.is_some() && self.column_typings.as_ref().unwrap().is_empty() {
-            return Err(PattiCsvError::Generic {
-                msg: String::from("mandatory 'column typings' are not set! (Empty vec)"),
-            });
-        }
-        if self.separator_char.is_none() {
-            return Err(PattiCsvError::Generic {
-                msg: String::from("mandatory 'separator character' is not set! (use the convenience functions '::csv()' or '::tsv()' or set the separator character manually)"),
-            });
-        }
+    /// Uses a custom converter (instead of the default, venum based one) for the column with the given index.
+    pub fn column_converter(
+        mut self,
+        col_idx: usize,
+        converter: BoxedCellConverter,
+    ) -> PattiCsvParserBuilder {
+        self.column_converters
+            .get_or_insert_with(HashMap::new)
+            .insert(col_idx, converter);
+        self
+    }
 
-        Ok(PattiCsvParser {
-            first_data_line_is_header: self.first_data_line_is_header,
-            column_transitizers: std::mem::take(&mut self.column_transitizers),
-            column_typings: std::mem::take(&mut self.column_typings.unwrap()), // checked above!
-            dlt: DelimitedLineTokenizer::new(
-                self.separator_char.unwrap(), // checked above!
-                self.enclosure_char,
-                std::mem::take(&mut self.skip_take_lines_fns),
-                self.save_skipped_lines,
-            ),
-        })
+    /// Adds a transformer of the typed values of the column with the given index. They run after typing (whereas the
+    /// transitizers run on the string tokens before), in the order they were added.
+    pub fn value_transformer(
+        mut self,
+        col_idx: usize,
+        transformer: BoxedValueTransformer,
+    ) -> PattiCsvParserBuilder {
+        self.value_transformers
+            .get_or_insert_with(HashMap::new)
+            .entry(col_idx)
+            .or_default()
+            .push(transformer);
+        self
     }
-}
 
-pub struct PattiCsvParserIterator<'pars, 'rd, R: Read> {
-    parser: &'pars PattiCsvParser,
-    dlt_iter: DelimitedLineTokenizerIter<'pars, 'rd, R>,
-    column_layout_template: DataCellRow,
-}
+    /// Columns without explicitly configured (i.e. index specific) sanitizers get default sanitizers, depending on
+    /// their target type: numeric and date types get `TrimAll`, `Bool` gets `TrimAll` and `ToLowercase`.
+    pub fn smart_sanitizer_defaults(mut self, b: bool) -> PattiCsvParserBuilder {
+        self.smart_sanitizer_defaults = b;
+        self
+    }
 
-impl<'pars, 'rd, R: Read> PattiCsvParserIterator<'pars, 'rd, R> {
-    fn new(
-        parser: &'pars PattiCsvParser,
-        dlt_iter: DelimitedLineTokenizerIter<'pars, 'rd, R>,
-    ) -> Self {
-        Self {
-            parser,
-            dlt_iter,
-            column_layout_template: DataCellRow::default(),
-        }
+    /// Drops or flags rows, that are exactly identical to the row directly before them.
+    pub fn duplicate_row_policy(
+        mut self,
+        policy: DuplicateRowPolicy,
+        compare_by: DuplicateRowComparison,
+    ) -> PattiCsvParserBuilder {
+        self.duplicate_rows = Some((policy, compare_by));
+        self
     }
-    pub fn get_stats(&self) -> &DelimitedLineTokenizerStats {
-        self.dlt_iter.get_stats()
+
+    /// Keys (i.e. the columns of each, by final index), that must be unique across all rows, e.g. `vec![vec![0]]` for
+    /// a primary key in the first column, or `vec![vec![0, 1]]` for a composite one. A row with a key, that an earlier
+    /// row had already, is a `PattiCsvError::DuplicateKey`, with both lines. Like in SQL, keys with an empty value are
+    /// not checked (see: `ColumnConstraints::non_null`). Only the keys of yielded rows count, i.e. not those of rows,
+    /// that are filtered, dropped or duplicates (see: `duplicate_row_policy`). They are kept in memory, i.e. one entry
+    /// per row and key.
+    pub fn unique_columns(mut self, unique_columns: Vec<Vec<usize>>) -> PattiCsvParserBuilder {
+        self.unique_columns = unique_columns;
+        self
     }
-}
 
-impl<'pars, 'rd, R: Read> Iterator for PattiCsvParserIterator<'pars, 'rd, R> {
-    type Item = Result<DataCellRow>;
+    /// What to do, when the header line names two (or more) columns the same. The default is `KeepFirst`, i.e. lookups
+    /// by name give the first of them.
+    pub fn on_duplicate_headers(mut self, policy: DuplicateHeaderPolicy) -> PattiCsvParserBuilder {
+        self.duplicate_headers = policy;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        // .next() yields "Option<Result<(Vec<String>, DelimitedLineTokenizerStats)>>".
-        // We early "return" a None (i.e. end of parsing) through the ?, then we check for an error inside the Some(Result)
-        let dlt_iter_res_vec = match self.dlt_iter.next()? {
-            // returns a: Option<Result<(Vec<String>, DelimitedLineTokenizerStats)>>
-            Err(e) => return Some(Err(e)),
-            Ok(dlt_iter_res) => dlt_iter_res,
-        };
+    /// Checks the number of tokens (after splitting columns) of every data line against the number of columns, and
+    /// pads short lines or truncates long ones, depending on the policy. Without it, missing tokens are up to the
+    /// `NullPolicy` of their columns, and extra tokens are ignored.
+    pub fn on_column_count_mismatch(
+        mut self,
+        policy: ColumnCountMismatchPolicy,
+    ) -> PattiCsvParserBuilder {
+        self.column_count_mismatch = Some(policy);
+        self
+    }
 
-        // Special case for the first line, which might be a header line and must be treated differently either way. This is only run once!
-        if self
-            .dlt_iter
-            .get_stats()
-            .is_at_first_unskipped_line_to_parse()
-        {
-            // Sanity check columns (lengths)
-            let len_typings = self.parser.column_typings.len();
-            let len_data = dlt_iter_res_vec.len();
+    /// Tokens, that become `Value::None` in every column, e.g. `["NULL", "N/A", "-"]`, instead of repeating them in the
+    /// typing of each column. A column with a map list of its own (see: `NullPolicy::map_list`) uses only that one.
+    pub fn global_map_to_none(mut self, map_to_none: Vec<String>) -> PattiCsvParserBuilder {
+        self.global_map_to_none = map_to_none;
+        self
+    }
 
-            if len_typings != len_data {
-                return Some(Err(PattiCsvError::ConfigError { msg: format!("Column typings provided, but length {} differs from actual length of data with num columns {}", len_typings, len_data) }));
-            }
+    /// Validates an amount column together with its currency column (ISO 4217 code, decimal places). Can be used
+    /// multiple times, for multiple pairs.
+    pub fn money_columns(mut self, money_columns: MoneyColumns) -> PattiCsvParserBuilder {
+        self.money_columns
+            .get_or_insert_with(Vec::new)
+            .push(money_columns);
+        self
+    }
 
-            // Set the correct headers in our template, i.e. make a column layout template, then return the data as the first line.
-            if self.parser.first_data_line_is_header {
-                self.column_layout_template = match build_layout_template(
-                    Some(&dlt_iter_res_vec),
-                    &self.parser.column_typings,
-                ) {
-                    Ok(v) => v,
-                    Err(e) => return Some(Err(e)),
-                };
+    /// Splits a column into new (typed) columns, e.g. "Lovelace;Ada;1815" into last name, first name and year of
+    /// birth. Can be used multiple times, for multiple columns. See: `split_columns::SplitColumn`
+    pub fn split_column(mut self, split_column: SplitColumn) -> PattiCsvParserBuilder {
+        self.split_columns.push(split_column);
+        self
+    }
 
-                // We hardcode the datatype to ValueName::String for the header line.
-                let mut csv_header_data_cell_row: DataCellRow =
-                    DataCellRow::with_capacity(len_data);
-                dlt_iter_res_vec.into_iter().enumerate().for_each(|(i, _)| {
-                    // We have set the correct header-name above anyway, we can just use it here!
-                    let header_name = &self
-                        .column_layout_template
-                        .0 // TODO: is there a way we don't need to rely on the underlying vec?
-                        .get(i)
-                        .unwrap() // we're sure we have something here! We set it above!
-                        .name;
-
-                    // TODO: do we want transitization on the headers!?
-
-                    let new_csv_cell =
-                        DataCell::new(header_name.clone(), i, header_name.clone().into())
-                            .expect("data is never None, so the type_info can always be inferred from data correctly");
-                    csv_header_data_cell_row.push(new_csv_cell);
-                });
-                return Some(Ok(csv_header_data_cell_row));
-            } else {
-                // In this case, the first line is actual data, meaning, we first need to build the structure, without parsing and setting the headers.
-                // We do not(!) return this immediately as the first line, since we must first sanitize and then type the data.
-                self.column_layout_template =
-                    match build_layout_template(None, &self.parser.column_typings) {
-                        Ok(v) => v,
-                        Err(e) => return Some(Err(e)),
-                    };
-            }
-        }
+    /// Appends a new column with a part of a date column, e.g. the ISO week or the quarter as a partitioning key. Can
+    /// be used multiple times, for multiple parts. See: `derived_columns::DerivedColumn`
+    pub fn derived_column(mut self, derived_column: DerivedColumn) -> PattiCsvParserBuilder {
+        self.derived_columns.push(derived_column);
+        self
+    }
 
-        // --------------------------------------------------------------------------------------------------------------------------------
-        // ------------------------------------------------ Handle data rows --------------------------------------------------------------
-        // --------------------------------------------------------------------------------------------------------------------------------
-        let mut row_data: DataCellRow = self.column_layout_template.clone();
+    /// Appends new columns with the parts of a (typed) `String` column, each with its own typing, e.g. an amount and a
+    /// currency from "10.00 CHF". Unlike `split_column`, the column itself is kept. Can be used multiple times, for
+    /// multiple columns. See: `derived_columns::DerivedSplit`
+    pub fn derived_split(mut self, derived_split: DerivedSplit) -> PattiCsvParserBuilder {
+        self.derived_splits.push(derived_split);
+        self
+    }
 
-        let mut sanitized_tokens = match sanitize_tokenizer_iter_res(
-            self.dlt_iter.get_stats().curr_line_num,
-            dlt_iter_res_vec,
-            &self.parser.column_transitizers,
-        ) {
-            Ok(v) => v,
-            Err(e) => return Some(Err(e)),
-        };
+    /// Collects a random sample of (at most) `capacity` typed data rows while parsing, e.g. for a preview. With a
+    /// seed, the sample is reproducible. See: `PattiCsvParserIterator::take_row_sample`
+    pub fn row_sample(mut self, capacity: usize, seed: Option<u64>) -> PattiCsvParserBuilder {
+        self.row_sample = Some((capacity, seed));
+        self
+    }
 
-        let col_iter = row_data.0.iter_mut().enumerate(); // TODO: is there a way we don't need to rely on the underlying vec?
-        for (i, cell) in col_iter {
-            // We can safely unwrap here and be sure we won't have an illegal index access, because, above:
-            // a) if we have no typings, we use the same length (from the tokens/data) to build them, and ...
-            // b) if we have typings, we check against the length of the tokens/data, and...
-            // ...subsequently we build the column layout template from the typings, AND this layout template is then used (as a clone) here, as the rows_data.
-            // NOTE: Tried it with unsafe { ...get_unchecked(i) } but could not measure a significant speed improvement.
-            let curr_token = sanitized_tokens.pop_front().unwrap();
-            let curr_typing = self.parser.column_typings.get(i).unwrap();
-
-            // Special short-cut cases for Empty Strings, and String -> String "conversion". I.e. we don't have to do anything.
-            if curr_token.is_empty() {
-                cell.data = Value::None;
-            } else if curr_typing.target_type == ValueType::String
-                && (curr_typing.map_to_none.is_none()
-                    || curr_typing.map_to_none.as_ref().unwrap().is_empty())
-            {
-                cell.data = Value::String(curr_token);
-            } else {
-                cell.data = match Value::from_str_and_type_with_chrono_pattern_with_none_map(
-                    &curr_token,
-                    &cell.dtype,
-                    curr_typing.chrono_pattern.as_deref(),
-                    curr_typing
-                        .map_to_none
-                        .as_ref()
-                        .map(|e| e.iter().map(|ie| ie.as_str()).collect()), // TODO we really should be using a Vec<&str> here?
-                ) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        return Some(Err(PattiCsvError::Generic {
-                            msg: format!(
-                                "{:?}; line: {}; column: {}; header: {}",
-                                e,
-                                &self.dlt_iter.get_stats().curr_line_num,
-                                &i,
-                                &row_data.0.get(i).unwrap().get_name()
-                            ),
-                        }))
+    /// Gathers statistics per column while parsing (e.g. the null count and the min/max values), for data profiling.
+    /// The distinct values are counted up to `max_distinct` per column. See: `PattiCsvParserIterator::get_column_stats`
+    pub fn column_stats(mut self, max_distinct: usize) -> PattiCsvParserBuilder {
+        self.column_stats = Some(max_distinct);
+        self
+    }
+
+    /// Instead of failing on the first cell of a row, that can't be typed, all of them are collected and returned
+    /// together as `PattiCsvError::Row`.
+    pub fn collect_all_cell_errors(mut self, b: bool) -> PattiCsvParserBuilder {
+        self.collect_all_cell_errors = b;
+        self
+    }
+
+    /// What to do with rows, that have cells which can't be typed: fail them (the default), skip them, or type the bad
+    /// cells as `Value::None`. Either way but failing, the errors are collected (see:
+    /// `PattiCsvParserIterator::row_errors`), and are always complete, i.e. all bad cells of a row.
+    pub fn on_error(mut self, policy: ErrorPolicy) -> PattiCsvParserBuilder {
+        self.error_policy = policy;
+        self
+    }
+
+    /// Only emits these columns (by their final index), in this order, i.e. the rows of `parse_iter` (and of everything
+    /// built on it, but not of the `IndexedReader`). The other columns are not typed at all (their sanitizers still
+    /// run, e.g. to drop rows), unless a money or a derived column needs them. The header row, the row stages and
+    /// `column_idxs` see the selected columns only.
+    pub fn select_columns(mut self, idxs: Vec<usize>) -> PattiCsvParserBuilder {
+        self.column_selection = Some(ColumnSelection::Idxs(idxs));
+        self
+    }
+
+    /// Same as `select_columns`, but by the final names of the columns. Names are matched like the ones of
+    /// `column_transitizers_by_name`.
+    pub fn select_columns_by_name<T: Into<String>>(
+        mut self,
+        names: Vec<T>,
+    ) -> PattiCsvParserBuilder {
+        self.column_selection = Some(ColumnSelection::Names(
+            names.into_iter().map(Into::into).collect(),
+        ));
+        self
+    }
+
+    /// Adds a custom stage on the raw tokens of the data lines (see: `pipeline::Pipeline` for the order of stages).
+    pub fn token_stage(mut self, stage: BoxedTokenStage) -> PattiCsvParserBuilder {
+        self.token_stages.push(stage);
+        self
+    }
+
+    /// Adds a custom stage on the typed data rows (see: `pipeline::Pipeline` for the order of stages).
+    pub fn row_stage(mut self, stage: BoxedRowStage) -> PattiCsvParserBuilder {
+        self.row_stages.push(stage);
+        self
+    }
+
+    /// Measures the time spent in every stage (tokenize, sanitize, convert, ...) and sums it up in the stats (see:
+    /// `StageTimings`). Off by default.
+    pub fn stage_timings(mut self, b: bool) -> PattiCsvParserBuilder {
+        self.stage_timings = b;
+        self
+    }
+
+    /// If the first line is a header, the iterator yields it as the first row (the default). Without this, it only
+    /// yields data rows, and the header row is available via `PattiCsvParserIterator::header`.
+    pub fn emit_header_row(mut self, b: bool) -> PattiCsvParserBuilder {
+        self.emit_header_row = b;
+        self
+    }
+
+    /// Caps for what's buffered in the stats (skipped lines, dropped rows, ...), e.g. for long running services. Not
+    /// capped by default.
+    pub fn memory_budget(mut self, memory_budget: MemoryBudget) -> PattiCsvParserBuilder {
+        self.memory_budget = memory_budget;
+        self
+    }
+
+    /// Key-value metadata lines at the start of the data, before the header (e.g. `Report Date: 2024-01-01`). They
+    /// are available via `PattiCsvParserIterator::preamble`, instead of just being skipped.
+    pub fn preamble(mut self, preamble: Preamble) -> PattiCsvParserBuilder {
+        self.preamble = Some(preamble);
+        self
+    }
+
+    /// Instead of configured column typings, infers them from the first `sample_rows` data rows, which are buffered
+    /// until then: the narrowest fitting type of Int, Float, Decimal, Bool and (ISO) Date, String otherwise (see:
+    /// `inference::infer_column`). The rest is streamed with the inferred typings. The names are the ones of the header
+    /// line (if any).
+    pub fn infer_column_types(mut self, sample_rows: usize) -> PattiCsvParserBuilder {
+        self.infer_column_types = Some(sample_rows);
+        self
+    }
+
+    pub fn stringly_type_columns(mut self, num_columns: usize) -> PattiCsvParserBuilder {
+        self.column_typings = Some(
+            (0..num_columns)
+                .into_iter()
+                .map(|_| TypeColumnEntry::new(None, ValueType::String))
+                .collect(),
+        );
+        self
+    }
+
+    pub fn build(mut self) -> Result<PattiCsvParser> {
+        let mut column_typings = match self.column_typings.take() {
+            None if self.infer_column_types.is_some() => Vec::new(),
+            Some(_) if self.infer_column_types.is_some() => {
+                return Err(PattiCsvError::ConfigError {
+                    msg: String::from(
+                        "column typings are inferred (see: 'infer_column_types'), so they must not be set",
+                    ),
+                })
+            }
+            None => {
+                return Err(PattiCsvError::Generic {
+                    msg: String::from("mandatory 'column typings' are not set! (None)"),
+                })
+            }
+            Some(ct) if ct.is_empty() => {
+                return Err(PattiCsvError::Generic {
+                    msg: String::from("mandatory 'column typings' are not set! (Empty vec)"),
+                })
+            }
+            Some(ct) => ct,
+        };
+        column_typings
+            .iter_mut()
+            .for_each(TypeColumnEntry::apply_deprecated_map_to_none);
+        if let Some(sample_rows) = self.infer_column_types {
+            if sample_rows == 0
+                || self.typings_by_header.is_some()
+                || !self.split_columns.is_empty()
+            {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!(
+                        "inferring column typings needs at least one sample row (is: {}), and works neither with resolving typings by header, nor with split columns",
+                        sample_rows
+                    ),
+                });
+            }
+        }
+        let split_columns = splice_split_columns(
+            &mut column_typings,
+            std::mem::take(&mut self.split_columns),
+            self.typings_by_header.is_some(),
+        )?;
+        // Inferred typings are only checked, once they are inferred
+        check_derived_columns(
+            &column_typings,
+            &self.derived_columns,
+            &self.derived_splits,
+            self.typings_by_header.is_some() || self.infer_column_types.is_some(),
+        )?;
+        check_money_columns_typings(
+            &column_typings,
+            self.money_columns.as_deref().unwrap_or_default(),
+            self.typings_by_header.is_some() || self.infer_column_types.is_some(),
+        )?;
+        for (idx, tce) in column_typings.iter().enumerate() {
+            tce.check_chrono_pattern()
+                .map_err(|reason| PattiCsvError::ConfigError {
+                    msg: format!(
+                        "invalid chrono pattern {:?} for column {} (header: {:?}): {}",
+                        tce.chrono_pattern.as_deref().unwrap_or_default(),
+                        idx,
+                        tce.header,
+                        reason
+                    ),
+                })?;
+            if tce.tolerant_numeric.is_some() && !is_numeric(&tce.target_type) {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!(
+                        "tolerant numeric is only for numeric target types, but column {} (header: {:?}) is: {:?}",
+                        idx, tce.header, tce.target_type
+                    ),
+                });
+            }
+            if tce.number_format.is_some()
+                && (!is_numeric(&tce.target_type) || tce.tolerant_numeric.is_some())
+            {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!(
+                        "a number format is only for numeric target types, and not together with tolerant numeric, but column {} (header: {:?}) is: {:?}",
+                        idx, tce.header, tce.target_type
+                    ),
+                });
+            }
+            if let Some(nf) = tce
+                .number_format
+                .filter(|nf| Some(nf.decimal_separator) == nf.thousands_separator)
+            {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!(
+                        "decimal and thousands separator must be different, but both are {:?}, in column {} (header: {:?})",
+                        nf.decimal_separator, idx, tce.header
+                    ),
+                });
+            }
+            if (tce.map_to_true.is_some() || tce.map_to_false.is_some())
+                && tce.target_type != ValueType::Bool
+            {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!(
+                        "bool mapping is only for the target type Bool, but column {} (header: {:?}) is: {:?}",
+                        idx, tce.header, tce.target_type
+                    ),
+                });
+            }
+            if let Some(t) = tce
+                .map_to_true
+                .iter()
+                .flatten()
+                .find(|t| tce.map_to_false.iter().flatten().any(|f| f == *t))
+            {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!(
+                        "token {:?} is mapped to both true and false, in column {} (header: {:?})",
+                        t, idx, tce.header
+                    ),
+                });
+            }
+            // Only the default converter is checked, a custom one might read what venum doesn't.
+            if let (Some(default_value), false) = (
+                &tce.default_value,
+                self.column_converters
+                    .as_ref()
+                    .is_some_and(|cc| cc.contains_key(&idx)),
+            ) {
+                VenumCellConverter
+                    .convert(default_value.clone(), tce)
+                    .map_err(|e| PattiCsvError::ConfigError {
+                        msg: format!(
+                            "default value {:?} is no {:?}, in column {} (header: {:?}): {:?}",
+                            default_value, tce.target_type, idx, tce.header, e
+                        ),
+                    })?;
+            }
+            if tce.constraints.as_ref().is_some_and(|c| c.regex.is_some())
+                && tce.target_type != ValueType::String
+            {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!(
+                        "a constraint regex is only for the target type String, but column {} (header: {:?}) is: {:?}",
+                        idx, tce.header, tce.target_type
+                    ),
+                });
+            }
+        }
+        let separator_char = self.separator_char.ok_or_else(|| PattiCsvError::Generic {
+            msg: String::from("mandatory 'separator character' is not set! (use the convenience functions '::csv()' or '::tsv()' or set the separator character manually)"),
+        })?;
+        if let Some(rs) = self.record_separator {
+            if !rs.is_ascii() || rs == separator_char || Some(rs) == self.enclosure_char {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!("record separator {:?} must be an ASCII character, that is neither the separator nor the enclosure character", rs),
+                });
+            }
+        }
+
+        #[cfg(feature = "encoding")]
+        let encoding = self
+            .encoding
+            .as_deref()
+            .map(crate::encoding::encoding_for_label)
+            .transpose()?;
+        #[cfg(not(feature = "encoding"))]
+        if let Some(label) = &self.encoding {
+            crate::encoding::check_encoding_label(label)?;
+        }
+
+        if let Some(cc) = self.comment_char {
+            if cc == separator_char || Some(cc) == self.enclosure_char {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!("comment character {:?} must be neither the separator nor the enclosure character", cc),
+                });
+            }
+        }
+
+        if let Some(p) = self.preamble.as_ref().filter(|p| p.kv_separator.is_empty()) {
+            return Err(PattiCsvError::ConfigError {
+                msg: format!(
+                    "key-value separator of the preamble must not be empty: {:?}",
+                    p
+                ),
+            });
+        }
+
+        let num_selected = match &self.column_selection {
+            Some(ColumnSelection::Idxs(idxs)) => Some(idxs.len()),
+            Some(ColumnSelection::Names(names)) => Some(names.len()),
+            None => None,
+        };
+        if num_selected == Some(0) {
+            return Err(PattiCsvError::ConfigError {
+                msg: String::from("at least one column must be selected"),
+            });
+        }
+
+        let mut dlt = DelimitedLineTokenizer::new(
+            separator_char,
+            self.enclosure_char,
+            std::mem::take(&mut self.skip_take_lines_fns),
+            self.save_skipped_lines,
+        );
+        dlt.trim_trailing_whitespace = self.trim_trailing_whitespace;
+        dlt.detect_header_enclosure =
+            self.detect_header_enclosure && self.first_data_line_is_header;
+        dlt.record_separator = self.record_separator;
+        dlt.skip_lines_from_end = self.skip_lines_from_end;
+        dlt.comment_char = self.comment_char;
+        dlt.strip_bom = self.strip_bom;
+        dlt.progress_observer = self.progress_observer;
+        #[cfg(feature = "encoding")]
+        {
+            dlt.encoding = encoding;
+        }
+        dlt.preamble = self.preamble;
+        dlt.memory_budget = self.memory_budget;
+        dlt.line_tokenizer = self.line_tokenizer;
+
+        let uses_token_meta = column_typings.iter().any(|tce| {
+            tce.null_policy.quoted_empty_is_empty_string
+                || tce.quoted_policy != QuotedTokenPolicy::Convert
+        });
+
+        Ok(PattiCsvParser {
+            first_data_line_is_header: self.first_data_line_is_header,
+            column_transitizers: std::mem::take(&mut self.column_transitizers),
+            column_transitizers_by_name: std::mem::take(&mut self.column_transitizers_by_name),
+            sanitizer_order: self.sanitizer_order,
+            column_typings,
+            typings_by_header: self.typings_by_header,
+            column_converters: self.column_converters,
+            value_transformers: self.value_transformers,
+            duplicate_rows: self.duplicate_rows,
+            duplicate_headers: self.duplicate_headers,
+            column_count_mismatch: self.column_count_mismatch,
+            global_map_to_none: self.global_map_to_none,
+            unique_columns: self.unique_columns,
+            smart_sanitizer_defaults: self.smart_sanitizer_defaults,
+            money_columns: self.money_columns,
+            split_columns,
+            derived_columns: self.derived_columns,
+            derived_splits: self.derived_splits,
+            uses_token_meta,
+            row_sample: self.row_sample,
+            column_stats: self.column_stats,
+            collect_all_cell_errors: self.collect_all_cell_errors,
+            error_policy: self.error_policy,
+            column_selection: self.column_selection,
+            token_stages: self.token_stages,
+            row_stages: self.row_stages,
+            stage_timings: self.stage_timings,
+            emit_header_row: self.emit_header_row,
+            infer_column_types: self.infer_column_types,
+            dlt,
+        })
+    }
+}
+
+pub struct PattiCsvParserIterator<'pars, 'rd, R: Read> {
+    parser: &'pars PattiCsvParser,
+    dlt_iter: DelimitedLineTokenizerIter<'pars, 'rd, R>,
+    column_layout_template: DataCellRow,
+    column_idxs: HashMap<String, usize>, // by the (final) names of the template
+    selected_columns: Option<Vec<usize>>, // the resolved ColumnSelection (if any)
+    resolved_columns: ResolvedColumns<'pars>,
+    resolved_column_typings: Option<Vec<TypeColumnEntry>>, // only set, when the typings are resolved (or completed)
+    // For the parse report
+    started_at: Option<Instant>,
+    finished_at: Option<Instant>,
+    num_rows_ok: usize,
+    num_rows_err: usize,
+    error_summaries: Vec<ErrorSummary>,
+    // For the duplicate row check. Only one of them is used, depending on the DuplicateRowComparison.
+    prev_raw_tokens: Option<VecDeque<String>>,
+    prev_row: Option<DataCellRow>,
+    curr_is_duplicate: bool,
+    row_sample: Option<RowReservoir>,
+    column_stats: Option<ColumnStatsCollector>,
+    type_error_columns: Vec<usize>, // of the current line, only used with column stats
+    header_row: Option<DataCellRow>,
+    row_errors: Vec<RowError>,            // see: ErrorPolicy
+    pub(crate) emit_header_row: bool, // of the parser, unless a mode of the parser needs data rows only
+    sampled_lines: VecDeque<SampledLine>, // read ahead, to infer the column typings from
+    curr_line_num: usize, // of the line parsed last, which is not the one tokenized last, while there are sampled lines
+    curr_num_tokens: usize, // of the line parsed last, after splitting columns
+    at_first_line: bool,
+    collect_type_errors: bool, // as with ErrorPolicy::NullOnTypeError, see: PattiCsvParser::validate
+    seen_keys: Vec<HashMap<Vec<String>, usize>>, // by key (see: unique_columns), with the line it was first seen in
+    pending_keys: Vec<Option<Vec<String>>>, // of the current row, only kept once the row is yielded
+    #[cfg(feature = "io")]
+    compression: Option<CompressionKind>, // of the input, only if the parser opened it (see: parse_file)
+}
+
+/// The tokens of a line, with their metadata (if it's needed).
+type LineTokens = (VecDeque<String>, Option<Vec<TokenMeta>>);
+
+/// A line, that was read ahead (to infer the column typings from), and is parsed afterwards.
+struct SampledLine {
+    line_num: usize,
+    tokens: Result<LineTokens>,
+}
+
+impl<'pars, 'rd, R: Read> PattiCsvParserIterator<'pars, 'rd, R> {
+    fn new(
+        parser: &'pars PattiCsvParser,
+        mut dlt_iter: DelimitedLineTokenizerIter<'pars, 'rd, R>,
+    ) -> Self {
+        if parser.stage_timings {
+            dlt_iter.get_stats_mut().stage_timings = Some(StageTimings::default());
+        }
+        Self {
+            parser,
+            dlt_iter,
+            column_layout_template: DataCellRow::default(),
+            column_idxs: HashMap::new(),
+            selected_columns: None,
+            resolved_columns: ResolvedColumns::default(),
+            resolved_column_typings: None,
+            started_at: None,
+            finished_at: None,
+            num_rows_ok: 0,
+            num_rows_err: 0,
+            error_summaries: Vec::new(),
+            prev_raw_tokens: None,
+            prev_row: None,
+            curr_is_duplicate: false,
+            header_row: None,
+            row_errors: Vec::new(),
+            emit_header_row: parser.emit_header_row,
+            sampled_lines: VecDeque::new(),
+            curr_line_num: 0,
+            curr_num_tokens: 0,
+            at_first_line: false,
+            collect_type_errors: false,
+            seen_keys: vec![HashMap::new(); parser.unique_columns.len()],
+            pending_keys: Vec::new(),
+            #[cfg(feature = "io")]
+            compression: None,
+            row_sample: parser
+                .row_sample
+                .map(|(capacity, seed)| RowReservoir::new(capacity, seed)),
+            column_stats: parser.column_stats.map(ColumnStatsCollector::new),
+            type_error_columns: Vec::new(),
+        }
+    }
+    pub fn get_stats(&self) -> &DelimitedLineTokenizerStats {
+        self.dlt_iter.get_stats()
+    }
+
+    /// The number of the line of the row that was yielded last. Usually the line that was tokenized last (see:
+    /// `get_stats`), but not while the lines are parsed, that were read ahead to infer the column typings.
+    pub fn curr_line_num(&self) -> usize {
+        self.curr_line_num
+    }
+
+    /// Is the row that was yielded last the header row?
+    pub fn is_at_header_row(&self) -> bool {
+        self.parser.first_data_line_is_header && self.at_first_line
+    }
+
+    /// The metadata of the tokens of the line that was read last (as tokenized, i.e. before splitting columns), e.g.
+    /// to see why a token was (or wasn't) typed as `Value::None`.
+    pub fn token_meta(&self) -> &[TokenMeta] {
+        self.dlt_iter.token_meta()
+    }
+
+    /// The key-value pairs of the preamble (if one is configured). Complete after the first call to `next()`.
+    pub fn preamble(&self) -> &HashMap<String, String> {
+        self.dlt_iter.preamble()
+    }
+
+    /// The header row, if the first line is a header and we got past it, i.e. after the first call to `next()`. (No
+    /// matter, if the header row is emitted or not.)
+    pub fn header(&self) -> Option<&DataCellRow> {
+        self.header_row.as_ref()
+    }
+
+    /// The index of every column by its name, e.g. to resolve the columns a consumer needs once, instead of looking
+    /// them up by name in every row. The names are the final ones, i.e. the headers of the typings win over the ones
+    /// of the header line, and split and derived columns are included. If a name isn't unique, the first column with it
+    /// wins. Empty until the first call to `next()`.
+    pub fn column_idxs(&self) -> &HashMap<String, usize> {
+        &self.column_idxs
+    }
+
+    /// The sampled data rows (with their line numbers, in the order of the data), if a row sample was configured.
+    /// Meant to be called after parsing, since sampling stops after this.
+    pub fn take_row_sample(&mut self) -> Vec<(usize, DataCellRow)> {
+        self.row_sample
+            .take()
+            .map(RowReservoir::into_rows)
+            .unwrap_or_default()
+    }
+
+    /// The statistics of the (selected) columns so far, if they are gathered (see:
+    /// `PattiCsvParserBuilder::column_stats`). Empty until the header (or the first line) is known.
+    pub fn get_column_stats(&self) -> &[ColumnStats] {
+        self.column_stats
+            .as_ref()
+            .map(ColumnStatsCollector::columns)
+            .unwrap_or_default()
+    }
+
+    /// The errors of the rows, that were skipped or typed with `Value::None` for their bad cells so far (see:
+    /// `ErrorPolicy`).
+    pub fn row_errors(&self) -> &[RowError] {
+        &self.row_errors
+    }
+
+    /// Like `row_errors`, but takes them, e.g. to process them in batches while parsing.
+    pub fn take_row_errors(&mut self) -> Vec<RowError> {
+        std::mem::take(&mut self.row_errors)
+    }
+
+    /// Converts every row into a `SimpleRow`, i.e. plain names and values without `venum_tds` types.
+    pub fn simple_rows(self) -> SimpleRowIter<Self> {
+        SimpleRowIter::new(self)
+    }
+
+    /// Deserializes every row into a `T` (see: `deserialize::from_row`). Mind the header row, if the parser yields it,
+    /// or use `PattiCsvParser::parse_iter_deserialize`.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: serde::de::DeserializeOwned>(self) -> DeserializeIter<Self, T> {
+        DeserializeIter::new(self)
+    }
+
+    /// Builds the report of this parse run. The duration is measured from the first call to `next()` until the end of
+    /// the data was reached (or until now, if we didn't read everything).
+    pub fn finish_report(self) -> ParseReport {
+        let stats = self.dlt_iter.get_stats();
+        let duration_secs = match self.started_at {
+            Some(start) => self
+                .finished_at
+                .unwrap_or_else(Instant::now)
+                .duration_since(start)
+                .as_secs_f64(),
+            None => 0.0,
+        };
+        let per_sec = |n: usize| {
+            if duration_secs > 0.0 {
+                n as f64 / duration_secs
+            } else {
+                0.0
+            }
+        };
+
+        let schema = if self.column_layout_template.0.is_empty() {
+            // We never got to the first line, so all we have is the configuration
+            self.parser
+                .column_typings
+                .iter()
+                .enumerate()
+                .map(|(idx, tce)| SchemaColumnReport {
+                    idx,
+                    name: tce.header.clone().unwrap_or_else(|| idx.to_string()),
+                    target_type: tce.target_type.clone(),
+                })
+                .collect()
+        } else {
+            self.project(self.column_layout_template.clone())
+                .0
+                .iter()
+                .map(|cell| SchemaColumnReport {
+                    idx: cell.idx,
+                    name: cell.name.clone(),
+                    target_type: cell.dtype.clone(),
+                })
+                .collect()
+        };
+
+        let mut skipped_lines_by_rule = stats
+            .skipped_lines_by_filter
+            .iter()
+            .map(|(&filter_idx, &num_lines)| (filter_idx, num_lines))
+            .collect::<Vec<_>>();
+        skipped_lines_by_rule.sort();
+        let skipped_lines_by_rule = skipped_lines_by_rule
+            .into_iter()
+            .map(|(filter_idx, num_lines)| {
+                let filter = self
+                    .parser
+                    .dlt
+                    .skip_take_lines_fns
+                    .as_ref()
+                    .and_then(|f| f.get(filter_idx));
+                SkipRuleReport {
+                    rule: filter
+                        .map(|f| f.get_self_info())
+                        .unwrap_or_else(|| filter_idx.to_string()),
+                    filter: filter.map(|f| f.describe()),
+                    num_lines,
+                }
+            })
+            .collect();
+
+        ParseReport {
+            dialect: DialectReport {
+                separator_char: self.parser.dlt.delim_char,
+                enclosure_char: self.parser.dlt.encl_char,
+                first_data_line_is_header: self.parser.first_data_line_is_header,
+                trim_trailing_whitespace: self.parser.dlt.trim_trailing_whitespace,
+                comment_char: self.parser.dlt.comment_char,
+            },
+            schema,
+            num_lines_read: stats.num_lines_read,
+            num_lines_skipped: stats.num_lines_skipped,
+            num_comment_lines: stats.num_comment_lines,
+            num_duplicate_rows: stats.num_duplicate_rows,
+            num_rows_filtered: stats.num_rows_filtered,
+            num_rows_dropped: stats.num_rows_dropped,
+            num_rows_ok: self.num_rows_ok,
+            num_rows_err: self.num_rows_err,
+            skipped_lines_by_rule,
+            skipped_lines_metadata: stats.skipped_lines_metadata.clone(),
+            errors: self.error_summaries,
+            bytes_read: stats.bytes_read,
+            duration_secs,
+            rows_per_sec: per_sec(self.num_rows_ok + self.num_rows_err),
+            bytes_per_sec: per_sec(stats.bytes_read),
+            stage_timings: stats.stage_timings.clone(),
+            truncated_buffers: stats.truncated_buffers,
+            #[cfg(feature = "io")]
+            compression: self.compression,
+        }
+    }
+
+    /// Helper method. `None` if the row was filtered.
+    fn run_row_stages(&mut self, row: DataCellRow) -> Result<Option<DataCellRow>> {
+        let line_num = self.curr_line_num;
+        let row_stages_start = self.parser.stage_timings.then(Instant::now);
+        let res = self
+            .parser
+            .row_stages
+            .iter()
+            .try_fold(Some(row), |row, stage| match row {
+                Some(row) => stage.process(line_num, row),
+                None => Ok(None),
+            });
+        self.add_stage_time(row_stages_start, |t| &mut t.row_stages);
+        res
+    }
+
+    /// Helper method. Collects the errors of a row, that is skipped (see: `ErrorPolicy::SkipRow`). It still counts as
+    /// an erroneous row in the parse report.
+    fn skip_row(&mut self, e: PattiCsvError) {
+        self.num_rows_err += 1;
+        self.dlt_iter.get_stats_mut().num_rows_err += 1;
+        add_error_to_summaries(
+            &mut self.error_summaries,
+            error_kind(&e),
+            self.curr_line_num,
+            format!("{:?}", e),
+        );
+        if let PattiCsvError::Row(row_error) = e {
+            self.row_errors.push(row_error);
+        }
+    }
+
+    /// Helper method. Records the current row as dropped (as far as the memory budget allows).
+    fn drop_row(&mut self, reason: String) {
+        let max_dropped_rows = self.parser.dlt.memory_budget.max_dropped_rows;
+        let line_num = self.curr_line_num;
+        let stats = self.dlt_iter.get_stats_mut();
+        stats.num_rows_dropped += 1;
+        if below_cap(max_dropped_rows, stats.dropped_rows.len()) {
+            stats.dropped_rows.push((line_num, reason));
+        } else {
+            stats.truncated_buffers.dropped_rows = true;
+        }
+    }
+
+    /// Helper method. Adds the time since `start` to a stage of the timings, if we measure them.
+    fn add_stage_time(
+        &mut self,
+        start: Option<Instant>,
+        stage: fn(&mut StageTimings) -> &mut Duration,
+    ) {
+        if let (Some(start), Some(timings)) =
+            (start, self.dlt_iter.get_stats_mut().stage_timings.as_mut())
+        {
+            *stage(timings) += start.elapsed();
+        }
+    }
+
+    /// Helper method. Completes the column layout template (with the derived columns), resolves the column selection
+    /// (if any) against it, and indexes the names of the (selected) columns.
+    fn set_layout_template(&mut self, mut template: DataCellRow) -> Result<()> {
+        set_combined_money_template_cells(&mut template, self.parser.money_columns.as_ref());
+        append_derived_template_cells(
+            &mut template,
+            &self.parser.derived_columns,
+            &self.parser.derived_splits,
+        );
+        let column_typings = self
+            .resolved_column_typings
+            .as_deref()
+            .unwrap_or(&self.parser.column_typings);
+        if let Some(selection) = &self.parser.column_selection {
+            let selected =
+                resolve_column_selection(selection, &template, column_typings, self.parser)?;
+            self.resolved_columns.typed =
+                Some(typed_columns_for(&selected, template.0.len(), self.parser));
+            self.selected_columns = Some(selected);
+        }
+        self.resolved_columns.named_transitizers =
+            resolve_named_transitizers(self.parser, &template, column_typings)?;
+        if let Some(i) = self
+            .parser
+            .unique_columns
+            .iter()
+            .flatten()
+            .find(|&&i| i >= template.0.len())
+        {
+            return Err(PattiCsvError::ConfigError {
+                msg: format!(
+                    "unique column {} doesn't exist, there are {} columns",
+                    i,
+                    template.0.len()
+                ),
+            });
+        }
+        let names = match &self.selected_columns {
+            Some(selected) => selected.iter().map(|&i| &template.0[i].name).collect(),
+            None => template.0.iter().map(|cell| &cell.name).collect::<Vec<_>>(),
+        };
+        if let Some(column_stats) = &mut self.column_stats {
+            column_stats.set_columns(&template, self.selected_columns.as_deref());
+        }
+        self.column_idxs = HashMap::with_capacity(names.len());
+        for (idx, name) in names.into_iter().enumerate() {
+            self.column_idxs.entry(name.clone()).or_insert(idx);
+        }
+        self.column_layout_template = template;
+        Ok(())
+    }
+
+    /// Helper method. The keys of the (typed, not yet projected) row in their string form, `None` for a key with an
+    /// empty value (see: `unique_columns`).
+    fn unique_keys_of(
+        &self,
+        row: &DataCellRow,
+        line_num: usize,
+    ) -> Result<Vec<Option<Vec<String>>>> {
+        self.parser
+            .unique_columns
+            .iter()
+            .map(|columns| {
+                let values = columns
+                    .iter()
+                    .map(|&c| row.0[c].get_data())
+                    .collect::<Vec<_>>();
+                if values.contains(&&Value::None) {
+                    return Ok(None);
+                }
+                values
+                    .into_iter()
+                    .map(|v| {
+                        v.try_convert_to(&ValueType::String)
+                            .and_then(String::try_from)
+                            .map_err(|e| PattiCsvError::Generic {
+                                msg: format!(
+                                    "[ERROR_ON_UNIQUE_KEY] Key value has no string form: {:?}; line: {}",
+                                    e, line_num
+                                ),
+                            })
+                    })
+                    .collect::<Result<Vec<_>>>()
+                    .map(Some)
+            })
+            .collect()
+    }
+
+    /// Helper method. Checks the keys of the current row against the ones of the rows yielded before (see:
+    /// `unique_columns`).
+    fn check_unique_keys(&self, line_num: usize) -> Result<()> {
+        let keys = self.pending_keys.iter().zip(&self.seen_keys);
+        for ((key, seen), columns) in keys.zip(&self.parser.unique_columns) {
+            if let Some(&first_line) = key.as_ref().and_then(|k| seen.get(k)) {
+                return Err(PattiCsvError::DuplicateKey {
+                    line: line_num,
+                    first_line,
+                    columns: columns.clone(),
+                    key: key.clone().unwrap_or_default(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Helper method. Keeps the keys of the current row, once it's yielded (i.e. not filtered or dropped).
+    fn commit_unique_keys(&mut self) {
+        let line_num = self.curr_line_num;
+        let keys = std::mem::take(&mut self.pending_keys);
+        for (key, seen) in keys.into_iter().zip(self.seen_keys.iter_mut()) {
+            if let Some(Entry::Vacant(e)) = key.map(|k| seen.entry(k)) {
+                e.insert(line_num);
+            }
+        }
+    }
+
+    /// Helper method. Only the selected columns of the row, if there is a column selection.
+    fn project(&self, row: DataCellRow) -> DataCellRow {
+        match &self.selected_columns {
+            Some(selected) => project_row(row, selected),
+            None => row,
+        }
+    }
+
+    /// Helper method. Tokenizes the next line (and splits its columns), with the token metadata, if it's needed.
+    fn next_tokens(&mut self) -> Option<Result<LineTokens>> {
+        // .next() yields "Option<Result<(Vec<String>, DelimitedLineTokenizerStats)>>".
+        // We early "return" a None (i.e. end of parsing) through the ?, then we check for an error inside the Some(Result)
+        let tokenize_start = self.parser.stage_timings.then(Instant::now);
+        let dlt_iter_res = self.dlt_iter.next();
+        self.add_stage_time(tokenize_start, |t| &mut t.tokenize);
+        let dlt_iter_res_vec = match dlt_iter_res? {
+            // returns a: Option<Result<(Vec<String>, DelimitedLineTokenizerStats)>>
+            Err(e) => return Some(Err(e)),
+            Ok(dlt_iter_res) => dlt_iter_res,
+        };
+        let stats = self.dlt_iter.get_stats();
+        let dlt_iter_res_vec = match split_tokens(
+            stats.curr_line_num,
+            dlt_iter_res_vec,
+            &self.parser.split_columns,
+            stats.is_at_first_unskipped_line_to_parse() && self.parser.first_data_line_is_header,
+        ) {
+            Ok(v) => v,
+            Err(e) => return Some(Err(e)),
+        };
+        // Only copied, if it's needed at all
+        let token_meta = self.parser.uses_token_meta.then(|| {
+            let mut meta = self.dlt_iter.token_meta().to_vec();
+            split_token_meta(&mut meta, &self.parser.split_columns);
+            meta
+        });
+        Some(Ok((dlt_iter_res_vec, token_meta)))
+    }
+
+    /// Helper method. Reads ahead the sample rows (the first line is one of them, if it's no header), and infers the
+    /// column typings from them. The sampled lines are parsed afterwards, in order.
+    fn infer_column_typings(
+        &mut self,
+        header: Option<&[String]>,
+        sample_rows: usize,
+    ) -> Result<()> {
+        while self.sampled_lines.len() < sample_rows {
+            let tokens = match self.next_tokens() {
+                Some(tokens) => tokens,
+                None => break,
+            };
+            self.sampled_lines.push_back(SampledLine {
+                line_num: self.dlt_iter.get_stats().curr_line_num,
+                tokens,
+            });
+        }
+        let sample = self
+            .sampled_lines
+            .iter()
+            .filter_map(|line| line.tokens.as_ref().ok())
+            .map(|(tokens, _)| tokens.iter().cloned().collect())
+            .collect::<Vec<_>>();
+        let column_typings = infer_types(header, &sample).to_typings();
+        check_derived_columns(
+            &column_typings,
+            &self.parser.derived_columns,
+            &self.parser.derived_splits,
+            false,
+        )?;
+        check_money_columns_typings(
+            &column_typings,
+            self.parser.money_columns.as_deref().unwrap_or_default(),
+            false,
+        )?;
+        self.resolved_column_typings = Some(column_typings);
+        Ok(())
+    }
+
+    fn next_inner(&mut self) -> Option<Result<DataCellRow>> {
+        let tokens = match self.sampled_lines.pop_front() {
+            Some(line) => {
+                self.curr_line_num = line.line_num;
+                self.at_first_line = false;
+                line.tokens
+            }
+            None => {
+                let tokens = self.next_tokens()?;
+                let stats = self.dlt_iter.get_stats();
+                self.curr_line_num = stats.curr_line_num;
+                self.at_first_line = stats.is_at_first_unskipped_line_to_parse();
+                tokens
+            }
+        };
+        let (mut dlt_iter_res_vec, mut token_meta) = match tokens {
+            Ok(tokens) => tokens,
+            Err(e) => return Some(Err(e)),
+        };
+        self.curr_num_tokens = dlt_iter_res_vec.len();
+
+        // Special case for the first line, which might be a header line and must be treated differently either way. This is only run once!
+        if self.at_first_line {
+            if self.parser.first_data_line_is_header {
+                if let Err(e) =
+                    dedup_header_tokens(&mut dlt_iter_res_vec, self.parser.duplicate_headers)
+                {
+                    return Some(Err(e));
+                }
+            }
+            if let Some(sample_rows) = self.parser.infer_column_types {
+                let header = self
+                    .parser
+                    .first_data_line_is_header
+                    .then(|| dlt_iter_res_vec.iter().cloned().collect::<Vec<_>>());
+                if header.is_none() {
+                    // The first line is a data line, i.e. one of the sample rows
+                    self.sampled_lines.push_back(SampledLine {
+                        line_num: self.curr_line_num,
+                        tokens: Ok((std::mem::take(&mut dlt_iter_res_vec), token_meta.take())),
+                    });
+                }
+                if let Err(e) = self.infer_column_typings(header.as_deref(), sample_rows) {
+                    return Some(Err(e));
+                }
+                if header.is_none() {
+                    if let Some(SampledLine {
+                        tokens: Ok(tokens), ..
+                    }) = self.sampled_lines.pop_front()
+                    {
+                        (dlt_iter_res_vec, token_meta) = tokens;
                     }
+                }
+            }
+            if let (true, Some(mode)) = (
+                self.parser.first_data_line_is_header,
+                &self.parser.typings_by_header,
+            ) {
+                self.resolved_column_typings = match resolve_column_typings_by_header(
+                    &dlt_iter_res_vec,
+                    &self.parser.column_typings,
+                    mode,
+                )
+                .and_then(|v| {
+                    check_money_columns_typings(
+                        &v,
+                        self.parser.money_columns.as_deref().unwrap_or_default(),
+                        false,
+                    )
+                    .map(|_| v)
+                }) {
+                    Ok(v) => Some(v),
+                    Err(e) => return Some(Err(e)),
                 };
             }
+            if !self.parser.global_map_to_none.is_empty() {
+                let column_typings = self
+                    .resolved_column_typings
+                    .as_deref()
+                    .unwrap_or(&self.parser.column_typings);
+                self.resolved_column_typings = Some(apply_global_map_to_none(
+                    column_typings,
+                    &self.parser.global_map_to_none,
+                ));
+            }
+            let column_typings = self
+                .resolved_column_typings
+                .as_deref()
+                .unwrap_or(&self.parser.column_typings);
+
+            // Sanity check columns (lengths)
+            let len_typings = column_typings.len();
+            let len_data = dlt_iter_res_vec.len();
+
+            if len_typings != len_data {
+                return Some(Err(PattiCsvError::ConfigError { msg: format!("Column typings provided, but length {} differs from actual length of data with num columns {}", len_typings, len_data) }));
+            }
+
+            // Set the correct headers in our template, i.e. make a column layout template, then return the data as the first line.
+            if self.parser.first_data_line_is_header {
+                if let Err(e) = build_layout_template(Some(&dlt_iter_res_vec), column_typings)
+                    .and_then(|v| self.set_layout_template(v))
+                {
+                    return Some(Err(e));
+                }
+
+                // We hardcode the datatype to ValueName::String for the header line.
+                return Some(
+                    build_header_row(&self.column_layout_template).map(|r| self.project(r)),
+                );
+            } else {
+                // In this case, the first line is actual data, meaning, we first need to build the structure, without parsing and setting the headers.
+                // We do not(!) return this immediately as the first line, since we must first sanitize and then type the data.
+                if let Err(e) = build_layout_template(None, column_typings)
+                    .and_then(|v| self.set_layout_template(v))
+                {
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        // --------------------------------------------------------------------------------------------------------------------------------
+        // ------------------------------------------------ Handle data rows --------------------------------------------------------------
+        // --------------------------------------------------------------------------------------------------------------------------------
+        let line_num = self.curr_line_num;
+        let token_stages_start = self.parser.stage_timings.then(Instant::now);
+        let token_stages_res = self
+            .parser
+            .token_stages
+            .iter()
+            .try_for_each(|stage| stage.process(line_num, &mut dlt_iter_res_vec));
+        self.add_stage_time(token_stages_start, |t| &mut t.token_stages);
+        if let Err(e) = token_stages_res {
+            return Some(Err(e));
+        }
+        let column_typings = self
+            .resolved_column_typings
+            .as_deref()
+            .unwrap_or(&self.parser.column_typings);
+        if let Err(e) = check_column_count(
+            line_num,
+            &mut dlt_iter_res_vec,
+            token_meta.as_mut(),
+            column_typings.len(),
+            self.parser.column_count_mismatch,
+        ) {
+            return Some(Err(e));
+        }
+
+        let compare_by = self.parser.duplicate_rows.as_ref().map(|(_, c)| c);
+        if let Some(DuplicateRowComparison::RawTokens) = compare_by {
+            self.curr_is_duplicate = self.prev_raw_tokens.as_ref() == Some(&dlt_iter_res_vec);
+            self.prev_raw_tokens = Some(dlt_iter_res_vec.clone());
+        }
+
+        let stats = self.dlt_iter.get_stats_mut();
+        let row_res = type_line_tokens_timed(
+            line_num,
+            dlt_iter_res_vec,
+            token_meta.as_deref(),
+            &self.column_layout_template,
+            self.resolved_column_typings
+                .as_deref()
+                .unwrap_or(&self.parser.column_typings),
+            self.parser,
+            stats.stage_timings.as_mut(),
+            Some(&mut stats.stripped_tokens),
+            (self.parser.error_policy == ErrorPolicy::NullOnTypeError || self.collect_type_errors)
+                .then_some(&mut self.row_errors),
+            Some(&self.resolved_columns),
+            self.column_stats
+                .is_some()
+                .then_some(&mut self.type_error_columns),
+        );
+        if let Some(column_stats) = &mut self.column_stats {
+            column_stats.add_type_errors(&self.type_error_columns);
+            self.type_error_columns.clear();
+        }
+        // A line adds at most one entry per column, so we only cut off what this line added
+        if let Some(max) = self.parser.dlt.memory_budget.max_stripped_tokens {
+            if stats.stripped_tokens.len() > max {
+                stats.stripped_tokens.truncate(max);
+                stats.truncated_buffers.stripped_tokens = true;
+            }
+        }
+        self.pending_keys.clear();
+        let mut row_res = row_res
+            .and_then(|row| match self.parser.unique_columns.is_empty() {
+                true => Ok(row),
+                false => {
+                    self.pending_keys = self.unique_keys_of(&row, line_num)?;
+                    Ok(row)
+                }
+            })
+            .map(|row| self.project(row));
+
+        if let Some(DuplicateRowComparison::TypedRow) = compare_by {
+            match &row_res {
+                Ok(row) => {
+                    self.curr_is_duplicate = self.prev_row.as_ref() == Some(row);
+                    self.prev_row = Some(row.clone());
+                }
+                Err(_) => {
+                    self.curr_is_duplicate = false;
+                    self.prev_row = None;
+                }
+            }
+        }
+        // A duplicate row is handled as such (see: duplicate_rows), not as a duplicate key
+        if !self.curr_is_duplicate {
+            row_res = row_res.and_then(|row| self.check_unique_keys(line_num).map(|_| row));
+        }
+        Some(row_res)
+    }
+}
+
+impl<'pars, 'rd, R: Read> Iterator for PattiCsvParserIterator<'pars, 'rd, R> {
+    type Item = Result<DataCellRow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.started_at.get_or_insert_with(Instant::now);
+
+        let res = loop {
+            let res = match self.next_inner() {
+                Some(r) => r,
+                None => {
+                    self.finished_at.get_or_insert_with(Instant::now);
+                    return None;
+                }
+            };
+            if !self.curr_is_duplicate {
+                let is_header = self.is_at_header_row();
+                match res {
+                    Ok(row) if !is_header => match self.run_row_stages(row) {
+                        Ok(Some(row)) => {
+                            self.commit_unique_keys();
+                            break Ok(row);
+                        }
+                        Ok(None) => {
+                            self.dlt_iter.get_stats_mut().num_rows_filtered += 1;
+                            continue;
+                        }
+                        Err(PattiCsvError::DropRow { reason }) => {
+                            self.drop_row(reason);
+                            continue;
+                        }
+                        Err(e) => break Err(e),
+                    },
+                    Ok(row) => {
+                        self.header_row = Some(row.clone());
+                        if self.emit_header_row {
+                            break Ok(row);
+                        }
+                        continue;
+                    }
+                    Err(PattiCsvError::DropRow { reason }) => {
+                        self.drop_row(reason);
+                        continue;
+                    }
+                    Err(e @ PattiCsvError::Row(_))
+                        if self.parser.error_policy == ErrorPolicy::SkipRow =>
+                    {
+                        self.skip_row(e);
+                        continue;
+                    }
+                    res => break res,
+                }
+            }
+
+            self.curr_is_duplicate = false;
+            self.dlt_iter.get_stats_mut().num_duplicate_rows += 1;
+            match self.parser.duplicate_rows {
+                Some((DuplicateRowPolicy::Flag, _)) => {
+                    break Err(duplicate_row_err(self.curr_line_num))
+                }
+                _ => continue, // dropped
+            }
+        };
+
+        match &res {
+            // The header row is no data row, so we don't count it.
+            Ok(_) if self.is_at_header_row() => {}
+            Ok(row) => {
+                self.num_rows_ok += 1;
+                if let Some(row_sample) = &mut self.row_sample {
+                    row_sample.offer(self.curr_line_num, row);
+                }
+                if let Some(column_stats) = &mut self.column_stats {
+                    column_stats.add_row(row);
+                }
+            }
+            Err(e) => {
+                self.num_rows_err += 1;
+                self.dlt_iter.get_stats_mut().num_rows_err += 1;
+                add_error_to_summaries(
+                    &mut self.error_summaries,
+                    error_kind(e),
+                    self.curr_line_num,
+                    format!("{:?}", e),
+                );
+            }
+        }
+        Some(res)
+    }
+}
+
+fn duplicate_row_err(line_num: usize) -> PattiCsvError {
+    PattiCsvError::Generic {
+        msg: format!(
+            "[ERROR_ON_DUPLICATE_ROW] Row is identical to the previous row; line: {}",
+            line_num
+        ),
+    }
+}
+
+/// The kind of an error, for the parse report. This is the tag (e.g. "[ERROR_ON_MISSING_COLUMN]"), if the message has
+/// one, otherwise the kind of the error.
+fn error_kind(e: &PattiCsvError) -> String {
+    match e {
+        PattiCsvError::Generic { msg }
+        | PattiCsvError::ConfigError { msg }
+        | PattiCsvError::Validation { msg, .. }
+            if msg.starts_with('[') =>
+        {
+            match msg.find(']') {
+                Some(end) => String::from(&msg[..=end]),
+                None => e.to_string(),
+            }
+        }
+        _ => e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use chrono::NaiveDate;
+    use venum::value::Value;
+    use venum_tds::data_cell::DataCell;
+
+    use super::*;
+
+    use crate::{
+        cell_converter::CellConverter,
+        constraints::ColumnConstraints,
+        derived_columns::DatePart,
+        line_tokenizer::TruncatedBuffers,
+        parser_config::{
+            DuplicateRowComparison, DuplicateRowPolicy, NullPolicy, NumberFormat, TolerantNumeric,
+        },
+        report::{ComponentInfo, DialectReport, SchemaColumnReport, SkipRuleReport},
+        skip_take_lines::*,
+        split_columns::{SplitBySeparator, SplitValueAndUnit},
+        transform_sanitize_token::*,
+    };
+
+    pub mod iterating_parser_builder {
+        use super::*;
+
+        #[test]
+        fn test_iterating_parser_builder_all_opts() {
+            let mut transitizers: HashMap<Option<usize>, VecOfTokenTransitizers> =
+                HashMap::with_capacity(2);
+            transitizers.insert(None, vec![Box::new(ToLowercase)]);
+            transitizers.insert(Some(0), vec![Box::new(TrimAll)]);
+
+            let parser_builder = PattiCsvParserBuilder::new()
+                .separator_char(';')
+                .enclosure_char(Some('\''))
+                .first_data_line_is_header(false)
+                .skip_take_lines_fns(vec![Box::new(SkipLinesStartingWith::new(""))])
+                .save_skipped_lines(true)
+                .column_typings(vec![
+                    TypeColumnEntry::new(None, ValueType::Int32),
+                    TypeColumnEntry::new(None, ValueType::String),
+                    TypeColumnEntry::new(None, ValueType::Bool),
+                ])
+                .column_transitizers(transitizers);
+
+            assert_eq!(Some(';'), parser_builder.separator_char);
+            assert_eq!(Some('\''), parser_builder.enclosure_char);
+            assert_eq!(false, parser_builder.first_data_line_is_header);
+            assert_eq!(1, parser_builder.skip_take_lines_fns.unwrap().len());
+            assert_eq!(true, parser_builder.save_skipped_lines);
+            assert_eq!(3, parser_builder.column_typings.unwrap().len());
+            assert_eq!(false, parser_builder.column_transitizers.is_none());
+            assert_eq!(2, parser_builder.column_transitizers.unwrap().len());
+        }
+
+        #[test]
+        fn test_iterating_parser_builder_defaults_csv() {
+            let parser_builder = PattiCsvParserBuilder::csv().column_typings(vec![]);
+
+            assert_eq!(Some(','), parser_builder.separator_char);
+            assert_eq!(Some('"'), parser_builder.enclosure_char);
+            assert_eq!(true, parser_builder.first_data_line_is_header);
+            assert!(parser_builder.skip_take_lines_fns.is_none());
+            assert_eq!(false, parser_builder.save_skipped_lines);
+            assert!(parser_builder.column_transitizers.is_none());
+        }
+
+        #[test]
+        fn test_iterating_parser_builder_defaults_tsv() {
+            let parser_builder = PattiCsvParserBuilder::tsv().column_typings(vec![]);
+
+            assert_eq!(Some('\t'), parser_builder.separator_char);
+            assert_eq!(None, parser_builder.enclosure_char);
+            assert_eq!(false, parser_builder.first_data_line_is_header);
+            assert!(parser_builder.skip_take_lines_fns.is_none());
+            assert_eq!(false, parser_builder.save_skipped_lines);
+            assert!(parser_builder.column_transitizers.is_none());
+        }
+
+        #[test]
+        #[should_panic(
+            expected = "Generic { msg: \"mandatory 'column typings' are not set! (None)\" }"
+        )]
+        fn patti_csv_parser_from_patti_csv_parser_builder_err_no_column_typings() {
+            PattiCsvParserBuilder::new()
+                .separator_char(',')
+                .enclosure_char(Some('"'))
+                .first_data_line_is_header(true)
+                .build()
+                .unwrap();
+        }
+
+        #[test]
+        #[should_panic(
+            expected = "Generic { msg: \"mandatory 'column typings' are not set! (Empty vec)\" }"
+        )]
+        fn patti_csv_parser_from_patti_csv_parser_builder_err_empty_column_typings() {
+            PattiCsvParserBuilder::new()
+                .separator_char(',')
+                .column_typings(vec![])
+                .build()
+                .unwrap();
+        }
+
+        #[test]
+        #[should_panic(
+            expected = "Generic { msg: \"mandatory 'separator character' is not set! (use the convenience functions '::csv()' or '::tsv()' or set the separator character manually)\" }"
+        )]
+        fn patti_csv_parser_from_patti_csv_parser_builder_err_no_separator_char() {
+            PattiCsvParserBuilder::new()
+                .column_typings(vec![TypeColumnEntry::new(None, ValueType::Bool)])
+                .build()
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn parse_with_custom_parser() {
+        let mut test_data_cursor = std::io::Cursor::new("c1;c2;c3;c4;c5\n 1 ;'BaR';true;null;");
+
+        let mut transitizers: HashMap<Option<usize>, VecOfTokenTransitizers> = HashMap::new();
+        transitizers.insert(None, vec![Box::new(ToLowercase)]);
+        transitizers.insert(Some(0), vec![Box::new(TrimAll)]);
+
+        let parser = PattiCsvParserBuilder::new()
+            .separator_char(';')
+            .enclosure_char(Some('\''))
+            .first_data_line_is_header(true)
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(Some(String::from("col2")), ValueType::String),
+                TypeColumnEntry::new(Some(String::from("col3")), ValueType::Bool),
+                TypeColumnEntry::new_with_map_to_none(
+                    Some(String::from("col4")),
+                    ValueType::String,
+                    vec![String::from("null")],
+                ),
+                TypeColumnEntry::new(None, ValueType::Int32), // Empty String will automatically(!) be mapped to Value::None!
+            ])
+            .column_transitizers(transitizers)
+            .build()
+            .unwrap();
+
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let headers = iter.next().unwrap().unwrap();
+        let line_1 = iter.next().unwrap().unwrap();
+
+        // println!("{:?}", headers);
+        // println!("{:?}", line_1);
+
+        assert_eq!(
+            DataCellRow {
+                0: vec![
+                    DataCell {
+                        dtype: ValueType::String,
+                        idx: 0,
+                        name: String::from("c1"),
+                        data: Value::String(String::from("c1"))
+                    },
+                    DataCell {
+                        dtype: ValueType::String,
+                        idx: 1,
+                        name: String::from("col2"),
+                        data: Value::String(String::from("col2"))
+                    },
+                    DataCell {
+                        dtype: ValueType::String,
+                        idx: 2,
+                        name: String::from("col3"),
+                        data: Value::String(String::from("col3"))
+                    },
+                    DataCell {
+                        dtype: ValueType::String,
+                        idx: 3,
+                        name: String::from("col4"),
+                        data: Value::String(String::from("col4"))
+                    },
+                    DataCell {
+                        dtype: ValueType::String,
+                        idx: 4,
+                        name: String::from("c5"),
+                        data: Value::String(String::from("c5"))
+                    },
+                ]
+            },
+            headers
+        );
+
+        assert_eq!(
+            DataCellRow {
+                0: vec![
+                    DataCell {
+                        dtype: ValueType::Int32,
+                        idx: 0,
+                        name: String::from("c1"),
+                        data: Value::Int32(1)
+                    },
+                    DataCell {
+                        dtype: ValueType::String,
+                        idx: 1,
+                        name: String::from("col2"),
+                        data: Value::String(String::from("bar"))
+                    },
+                    DataCell {
+                        dtype: ValueType::Bool,
+                        idx: 2,
+                        name: String::from("col3"),
+                        data: Value::Bool(true)
+                    },
+                    DataCell {
+                        dtype: ValueType::String,
+                        idx: 3,
+                        name: String::from("col4"),
+                        data: Value::None
+                    },
+                    DataCell {
+                        dtype: ValueType::Int32,
+                        idx: 4,
+                        name: String::from("c5"),
+                        data: Value::None
+                    },
+                ]
+            },
+            line_1
+        )
+    }
+
+    fn parse_single_line_with_typings(
+        data: &str,
+        column_typings: Vec<TypeColumnEntry>,
+    ) -> Result<DataCellRow> {
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let parser = PattiCsvParserBuilder::csv()
+            .first_data_line_is_header(false)
+            .column_typings(column_typings)
+            .build()
+            .unwrap();
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        iter.next().unwrap()
+    }
+
+    #[test]
+    fn null_policy_default_empty_is_none() {
+        let row = parse_single_line_with_typings(
+            ",,null",
+            vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ],
+        )
+        .unwrap();
+        assert_eq!(&Value::None, row.get_by_name("0").unwrap().get_data());
+        assert_eq!(&Value::None, row.get_by_name("1").unwrap().get_data());
+        // Not in any map list, so it's just a string
+        assert_eq!(
+            &Value::String(String::from("null")),
+            row.get_by_name("2").unwrap().get_data()
+        );
+    }
+
+    #[test]
+    fn null_policy_empty_is_empty_string() {
+        let row = parse_single_line_with_typings(
+            ",null",
+            vec![
+                TypeColumnEntry::new(None, ValueType::String)
+                    .with_null_policy(NullPolicy::empty_is_empty_string()),
+                TypeColumnEntry::new(None, ValueType::String).with_null_policy(
+                    NullPolicy::empty_is_empty_string().with_map_list(vec![String::from("null")]),
+                ),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            &Value::String(String::new()),
+            row.get_by_name("0").unwrap().get_data()
+        );
+        assert_eq!(&Value::None, row.get_by_name("1").unwrap().get_data());
+
+        // For anything but strings, an empty token now goes to the converter, which fails.
+        let res = parse_single_line_with_typings(
+            ",1",
+            vec![
+                TypeColumnEntry::new(None, ValueType::Int32)
+                    .with_null_policy(NullPolicy::empty_is_empty_string()),
+                TypeColumnEntry::new(None, ValueType::Int32),
+            ],
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn null_policy_map_list() {
+        let row = parse_single_line_with_typings(
+            "n/a,,NULL",
+            vec![
+                TypeColumnEntry::new_with_map_to_none(
+                    None,
+                    ValueType::Int32,
+                    vec![String::from("n/a"), String::from("NULL")],
+                ),
+                TypeColumnEntry::new_with_map_to_none(
+                    None,
+                    ValueType::Int32,
+                    vec![String::from("n/a")],
+                ),
+                TypeColumnEntry::new_with_map_to_none(
+                    None,
+                    ValueType::String,
+                    vec![String::from("null")], // exact match only
+                ),
+            ],
+        )
+        .unwrap();
+        assert_eq!(&Value::None, row.get_by_name("0").unwrap().get_data());
+        assert_eq!(&Value::None, row.get_by_name("1").unwrap().get_data());
+        assert_eq!(
+            &Value::String(String::from("NULL")),
+            row.get_by_name("2").unwrap().get_data()
+        );
+    }
+
+    #[test]
+    fn null_policy_global_map_to_none() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new_with_map_to_none(
+                    None,
+                    ValueType::String,
+                    vec![String::from("n/a")], // overrides the global map list
+                ),
+            ])
+            .global_map_to_none(vec![String::from("NULL"), String::from("-")])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("a,b,c\nNULL,-,-\n-,x,n/a");
+        let rows = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(&Value::None, rows[1].get_by_name("a").unwrap().get_data());
+        assert_eq!(&Value::None, rows[1].get_by_name("b").unwrap().get_data());
+        assert_eq!(
+            &Value::String(String::from("-")),
+            rows[1].get_by_name("c").unwrap().get_data()
+        );
+        assert_eq!(&Value::None, rows[2].get_by_name("a").unwrap().get_data());
+        assert_eq!(&Value::None, rows[2].get_by_name("c").unwrap().get_data());
+    }
+
+    #[test]
+    fn parse_with_unique_columns() {
+        let parser = |unique_columns: Vec<Vec<usize>>| {
+            PattiCsvParserBuilder::csv()
+                .column_typings(vec![
+                    TypeColumnEntry::new(None, ValueType::Int32),
+                    TypeColumnEntry::new(None, ValueType::String),
+                    TypeColumnEntry::new(None, ValueType::String),
+                ])
+                .unique_columns(unique_columns)
+                .build()
+                .unwrap()
+        };
+        let data = "id,a,b\n1,x,y\n2,x,z\n1,x,z\n,x,y\n,x,y\n3,x,y";
+        let parse = |unique_columns: Vec<Vec<usize>>| {
+            let mut test_data_cursor = std::io::Cursor::new(data);
+            parser(unique_columns)
+                .parse_iter(&mut test_data_cursor)
+                .collect::<Vec<_>>()
+        };
+
+        let res = parse(vec![vec![0]]);
+        assert!(res[2].is_ok());
+        assert_eq!(
+            Err(PattiCsvError::DuplicateKey {
+                line: 4,
+                first_line: 2,
+                columns: vec![0],
+                key: vec![String::from("1")]
+            }),
+            res[3]
+        );
+        assert!(res[4].is_ok() && res[5].is_ok()); // no key
+        assert!(res[6].is_ok());
+
+        let res = parse(vec![vec![0], vec![1, 2]]);
+        assert!(res[2].is_ok());
+        assert!(matches!(
+            res[3],
+            Err(PattiCsvError::DuplicateKey {
+                line: 4,
+                first_line: 2,
+                ..
+            })
+        ));
+        assert!(matches!(
+            &res[4],
+            Err(PattiCsvError::DuplicateKey { line: 5, first_line: 2, key, .. })
+                if key == &vec![String::from("x"), String::from("y")]
+        ));
+
+        // Only the keys of yielded rows count, i.e. not of filtered (or dropped) ones, and duplicate rows are dropped
+        let filtering_parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .unique_columns(vec![vec![0]])
+            .duplicate_row_policy(DuplicateRowPolicy::Drop, DuplicateRowComparison::TypedRow)
+            .row_stage(Box::new(crate::pipeline::FnRowStage::new(
+                "no_tmp",
+                |_, row: DataCellRow| {
+                    Ok((row.0[1].get_data() != &Value::from("tmp")).then_some(row))
+                },
+            )))
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("id,a\n1,tmp\n1,x\n1,x\n2,y");
+        let rows = filtering_parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(3, rows.len());
+
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        assert!(matches!(
+            parser(vec![vec![3]])
+                .parse_iter(&mut test_data_cursor)
+                .next(),
+            Some(Err(PattiCsvError::ConfigError { .. }))
+        ));
+    }
+
+    #[test]
+    fn parse_with_constraints() {
+        let builder = || {
+            PattiCsvParserBuilder::csv().column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32).with_constraints(
+                    ColumnConstraints::new()
+                        .with_non_null(true)
+                        .with_min(Value::Int32(0)),
+                ),
+                TypeColumnEntry::new(None, ValueType::String)
+                    .with_constraints(ColumnConstraints::new().with_regex("^[A-Z]{3}$").unwrap()),
+            ])
+        };
+        let data = "amount,currency\n1,CHF\n-1,CHF\n2,chf\n,EUR";
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let res = builder()
+            .build()
+            .unwrap()
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Vec<_>>();
+        assert!(res[1].is_ok());
+        assert!(matches!(
+            &res[2],
+            Err(PattiCsvError::Validation { line: 3, column: 0, header, msg })
+                if header == "amount" && msg.contains("min")
+        ));
+        assert!(matches!(
+            &res[3],
+            Err(PattiCsvError::Validation {
+                line: 4,
+                column: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            &res[4],
+            Err(PattiCsvError::Validation {
+                line: 5,
+                column: 0,
+                ..
+            })
+        ));
+
+        // feeds the error policy
+        let parser = builder()
+            .on_error(ErrorPolicy::NullOnTypeError)
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let rows = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(&Value::None, rows[2].0[0].get_data());
+        assert_eq!(3, iter.row_errors().len());
+        assert!(iter.row_errors()[0].cells[0]
+            .msg
+            .starts_with("[ERROR_ON_CONSTRAINT]"));
+
+        // The padded cells of a short line are checked as well
+        let parser = PattiCsvParserBuilder::csv()
+            .on_column_count_mismatch(ColumnCountMismatchPolicy::PadWithNone)
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::Int32)
+                    .with_constraints(ColumnConstraints::new().with_non_null(true)),
+            ])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("currency,amount\nCHF,1\nEUR");
+        let res = parser.parse_iter(&mut test_data_cursor).collect::<Vec<_>>();
+        assert!(res[1].is_ok());
+        assert!(matches!(
+            &res[2],
+            Err(PattiCsvError::Validation { line: 3, column: 1, msg, .. }) if msg.contains("nonNull")
+        ));
+
+        let res = PattiCsvParserBuilder::csv()
+            .column_typings(vec![TypeColumnEntry::new(None, ValueType::Int32)
+                .with_constraints(ColumnConstraints::new().with_regex("^1").unwrap())])
+            .build();
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn default_value_for_none_cells() {
+        let parser = |default_value: &str| {
+            PattiCsvParserBuilder::csv()
+                .column_typings(vec![
+                    TypeColumnEntry::new(None, ValueType::Int32).with_default_value("0"),
+                    TypeColumnEntry::new(None, ValueType::String),
+                    TypeColumnEntry::new(None, ValueType::NaiveDate)
+                        .with_default_value(default_value)
+                        .with_null_policy(
+                            NullPolicy::default()
+                                .with_map_list(vec![String::from("n/a")])
+                                .with_missing_is_none(true),
+                        ),
+                ])
+                .build()
+        };
+        let mut test_data_cursor = std::io::Cursor::new("a,b,c\n,,n/a\n7,x\n8,x,2021-01-03");
+        let rows = parser("1970-01-01")
+            .unwrap()
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let epoch = Value::NaiveDate(NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+        assert_eq!(
+            &Value::Int32(0),
+            rows[1].get_by_name("a").unwrap().get_data()
+        );
+        assert_eq!(&Value::None, rows[1].get_by_name("b").unwrap().get_data());
+        assert_eq!(&epoch, rows[1].get_by_name("c").unwrap().get_data());
+        assert_eq!(
+            &Value::Int32(7),
+            rows[2].get_by_name("a").unwrap().get_data()
+        );
+        assert_eq!(&epoch, rows[2].get_by_name("c").unwrap().get_data()); // missing
+        assert_ne!(&epoch, rows[3].get_by_name("c").unwrap().get_data());
+
+        // A padded cell of a short line gets the default too
+        let padding_parser = PattiCsvParserBuilder::csv()
+            .on_column_count_mismatch(ColumnCountMismatchPolicy::PadWithNone)
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::Int32).with_default_value("0"),
+            ])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("a,b\nx\ny,5");
+        let rows = padding_parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Vec<_>>();
+        assert_eq!(&Value::Int32(0), rows[1].as_ref().unwrap().0[1].get_data());
+        assert_eq!(&Value::Int32(5), rows[2].as_ref().unwrap().0[1].get_data());
+
+        assert!(matches!(
+            parser("yesterday"),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn null_policy_missing_column() {
+        let mut test_data_cursor = std::io::Cursor::new("a,b,c\n1,2,3\n4,5");
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Int32),
+            ])
+            .build()
+            .unwrap();
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let _headers = iter.next().unwrap().unwrap();
+        let _line_1 = iter.next().unwrap().unwrap();
+        assert_eq!(
+            PattiCsvError::Generic {
+                msg: String::from(
+                    "[ERROR_ON_MISSING_COLUMN] No token for column; line: 3; column: 2; header: c"
+                )
+            },
+            iter.next().unwrap().unwrap_err()
+        );
+
+        let mut test_data_cursor = std::io::Cursor::new("a,b,c\n1,2,3\n4,5");
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Int32)
+                    .with_null_policy(NullPolicy::default().with_missing_is_none(true)),
+            ])
+            .build()
+            .unwrap();
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let _headers = iter.next().unwrap().unwrap();
+        let _line_1 = iter.next().unwrap().unwrap();
+        let line_2 = iter.next().unwrap().unwrap();
+        assert_eq!(
+            &Value::Int32(5),
+            line_2.get_by_name("b").unwrap().get_data()
+        );
+        assert_eq!(&Value::None, line_2.get_by_name("c").unwrap().get_data());
+    }
+
+    #[test]
+    fn null_policy_quoted_empty_is_empty_string() {
+        let data = "a,b,c\n\"\",,\"\"\n";
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String).with_null_policy(
+                    NullPolicy::default().with_quoted_empty_is_empty_string(true),
+                ),
+                TypeColumnEntry::new(None, ValueType::String).with_null_policy(
+                    NullPolicy::default().with_quoted_empty_is_empty_string(true),
+                ),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let rows = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            &Value::String(String::new()),
+            rows[1].get_by_name("a").unwrap().get_data()
+        );
+        assert_eq!(&Value::None, rows[1].get_by_name("b").unwrap().get_data());
+        assert_eq!(&Value::None, rows[1].get_by_name("c").unwrap().get_data());
+
+        // Same with random access
+        #[cfg(feature = "io")]
+        {
+            let mut reader = parser.indexed_reader(std::io::Cursor::new(data)).unwrap();
+            assert_eq!(rows[1], reader.get_row(0).unwrap().unwrap());
+        }
+    }
+
+    #[test]
+    fn quoted_token_policy() {
+        let mut test_data_cursor = std::io::Cursor::new("a,b\n\"NULL\",NULL\n\"1\",2\n");
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new_with_map_to_none(
+                    None,
+                    ValueType::String,
+                    vec![String::from("NULL")],
+                )
+                .with_quoted_policy(QuotedTokenPolicy::Literal),
+                TypeColumnEntry::new_with_map_to_none(
+                    None,
+                    ValueType::Int32,
+                    vec![String::from("NULL")],
+                )
+                .with_quoted_policy(QuotedTokenPolicy::Reject),
+            ])
+            .build()
+            .unwrap();
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let _headers = iter.next().unwrap().unwrap();
+        let line_1 = iter.next().unwrap().unwrap();
+        assert_eq!(
+            &Value::String(String::from("NULL")),
+            line_1.get_by_name("a").unwrap().get_data()
+        );
+        assert_eq!(&Value::None, line_1.get_by_name("b").unwrap().get_data());
+        assert!(iter.token_meta()[0].was_quoted);
+        assert!(!iter.token_meta()[1].was_quoted);
+
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::Int32)
+                    .with_quoted_policy(QuotedTokenPolicy::Reject),
+            ])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("a,b\n1,2\n1,\"2\"");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let _headers = iter.next().unwrap().unwrap();
+        let _line_1 = iter.next().unwrap().unwrap();
+        assert_eq!(
+            PattiCsvError::Generic {
+                msg: String::from(
+                    "[ERROR_ON_QUOTED_TOKEN] Quoted tokens are rejected for column; line: 3; column: 1; header: b"
+                )
+            },
+            iter.next().unwrap().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn parse_and_finish_report() {
+        let mut test_data_cursor =
+            std::io::Cursor::new("# comment\nid,name\n1,foo\n\nx,bar\n3,baz\ny,qux");
+
+        let parser = PattiCsvParserBuilder::csv()
+            .skip_take_lines_fns(vec![
+                Box::new(SkipLinesStartingWith::new("#")),
+                Box::new(SkipEmptyLines::new()),
+            ])
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap();
+
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let results = iter.by_ref().collect::<Vec<_>>();
+        assert_eq!(5, results.len()); // header + 4 data lines
+
+        let report = iter.finish_report();
+        assert_eq!(
+            DialectReport {
+                separator_char: ',',
+                enclosure_char: Some('"'),
+                first_data_line_is_header: true,
+                trim_trailing_whitespace: false,
+                comment_char: None,
+            },
+            report.dialect
+        );
+        assert_eq!(
+            vec![
+                SchemaColumnReport {
+                    idx: 0,
+                    name: String::from("id"),
+                    target_type: ValueType::Int32
+                },
+                SchemaColumnReport {
+                    idx: 1,
+                    name: String::from("name"),
+                    target_type: ValueType::String
+                }
+            ],
+            report.schema
+        );
+        assert_eq!(7, report.num_lines_read);
+        assert_eq!(2, report.num_lines_skipped);
+        assert_eq!(2, report.num_rows_ok);
+        assert_eq!(2, report.num_rows_err);
+        assert_eq!(
+            vec![
+                SkipRuleReport {
+                    rule: SkipLinesStartingWith::new("#").get_self_info(),
+                    filter: Some(
+                        ComponentInfo::new("skipLinesStartingWith").with_param("startsWith", "#")
+                    ),
+                    num_lines: 1
+                },
+                SkipRuleReport {
+                    rule: SkipEmptyLines::new().get_self_info(),
+                    filter: Some(ComponentInfo::new("skipEmptyLines")),
+                    num_lines: 1
+                }
+            ],
+            report.skipped_lines_by_rule
+        );
+        assert_eq!(1, report.errors.len());
+        assert_eq!(2, report.errors[0].count);
+        assert_eq!(5, report.errors[0].first_line_num);
+        assert_eq!(String::from("Generic"), report.errors[0].kind);
+        assert!(report.duration_secs >= 0.0);
+
+        #[cfg(all(feature = "serde", feature = "serde_json"))]
+        {
+            let json = report.to_json().unwrap();
+            assert!(json.contains(r#""numRowsOk":2"#));
+            assert!(json.contains(r#""separatorChar":",""#));
+        }
+    }
+
+    #[test]
+    fn error_kind_from_tag() {
+        assert_eq!(
+            String::from("[ERROR_ON_MISSING_COLUMN]"),
+            error_kind(&PattiCsvError::Generic {
+                msg: String::from("[ERROR_ON_MISSING_COLUMN] No token for column; line: 3")
+            })
+        );
+        assert_eq!(
+            String::from("ConfigError"),
+            error_kind(&PattiCsvError::ConfigError {
+                msg: String::from("foo")
+            })
+        );
+    }
+
+    const ADVERSARIAL_INPUTS: [&str; 16] = [
+        "",
+        "\n",
+        "\r\n\r\n",
+        "\u{feff}",
+        "\u{feff}a,b,c",
+        "a,b,c",
+        "a,b,c\n1,2",
+        "a,b,c\n1,2,3,4,5",
+        "a,b\n1,2,3",
+        ",,\n,,\n,",
+        "a,b,c\n\"1,2,3",
+        "a,b,c\n1\"2,3,4",
+        "a,b,c\n\"1\"x,2,3",
+        "a,b,c\n\"\"\"\",\"\",\"\"\"",
+        "a,b,c\n\"1\"   \n",
+        "ä,ö,ü\n€,\u{0},\u{1F600}",
+    ];
+
+    #[test]
+    fn adversarial_input_never_panics() {
+        for inp in ADVERSARIAL_INPUTS {
+            for first_data_line_is_header in [true, false] {
+                for trim_trailing_whitespace in [true, false] {
+                    let parser = PattiCsvParserBuilder::csv()
+                        .first_data_line_is_header(first_data_line_is_header)
+                        .trim_trailing_whitespace(trim_trailing_whitespace)
+                        .column_typings(vec![
+                            TypeColumnEntry::new(None, ValueType::Int32),
+                            TypeColumnEntry::new(None, ValueType::String),
+                            TypeColumnEntry::new(None, ValueType::Bool),
+                        ])
+                        .duplicate_row_policy(
+                            DuplicateRowPolicy::Flag,
+                            DuplicateRowComparison::TypedRow,
+                        )
+                        .build()
+                        .unwrap();
+                    let mut test_data_cursor = std::io::Cursor::new(inp);
+                    let mut iter = parser.parse_iter(&mut test_data_cursor);
+                    iter.by_ref().for_each(drop);
+                    let _report = iter.finish_report();
+
+                    let _probe = parser.probe(&mut std::io::Cursor::new(inp), 10);
+                    #[cfg(feature = "io")]
+                    {
+                        if let Ok(mut reader) = parser.indexed_reader(std::io::Cursor::new(inp)) {
+                            let _rows = reader.rows_range(0..100);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn adversarial_input_never_panics_with_typings_by_header() {
+        for inp in ADVERSARIAL_INPUTS {
+            let parser = PattiCsvParserBuilder::csv()
+                .resolve_typings_by_header(HeaderMatchMode::IgnoreCaseAndWhitespace)
+                .column_typings(vec![
+                    TypeColumnEntry::new(Some(String::from("A")), ValueType::Int32),
+                    TypeColumnEntry::new(Some(String::from("c")), ValueType::Bool),
+                ])
+                .build()
+                .unwrap();
+            let mut test_data_cursor = std::io::Cursor::new(inp);
+            parser.parse_iter(&mut test_data_cursor).for_each(drop);
+        }
+    }
+
+    #[test]
+    fn ragged_rows_without_column_count_policy() {
+        let parse = |builder: PattiCsvParserBuilder| {
+            let mut test_data_cursor = std::io::Cursor::new("a,b,c\n1,2\n1,2,3,4\n1,2,3");
+            builder
+                .stringly_type_columns(3)
+                .build()
+                .unwrap()
+                .parse_iter(&mut test_data_cursor)
+                .skip(1) // header
+                .map(|r| r.is_ok())
+                .collect::<Vec<_>>()
+        };
+        // Without a `ColumnCountMismatchPolicy`, missing tokens are errors, but extra tokens are ignored...
+        assert_eq!(vec![false, true, true], parse(PattiCsvParserBuilder::csv()));
+        // ...unless the policy says so.
+        assert_eq!(
+            vec![false, false, true],
+            parse(
+                PattiCsvParserBuilder::csv()
+                    .on_column_count_mismatch(ColumnCountMismatchPolicy::Error)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_with_money_columns() {
+        let mut test_data_cursor =
+            std::io::Cursor::new("amount,ccy\n12.5,CHF\n100,JPY\n1.5,JPY\n2.00,XYZ\n,EUR");
+        let parser = PattiCsvParserBuilder::csv()
+            .first_data_line_is_header(true)
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Decimal),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .money_columns(MoneyColumns::new(0, 1).combined())
+            .build()
+            .unwrap();
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .skip(1) // header
+            .map(|r| r.map(|row| row.get_by_name("amount").unwrap().clone()))
+            .collect::<Vec<_>>();
+        let amount = |i: usize| {
+            res[i]
+                .as_ref()
+                .map(|c| (c.dtype.clone(), c.get_data().clone()))
+        };
+        assert_eq!(
+            Ok((ValueType::String, Value::String(String::from("CHF 12.50")))),
+            amount(0)
+        );
+        assert_eq!(
+            Ok((ValueType::String, Value::String(String::from("JPY 100")))),
+            amount(1)
+        );
+        assert!(res[2].is_err());
+        assert!(res[3].is_err());
+        assert_eq!(Ok((ValueType::String, Value::None)), amount(4));
+    }
+
+    #[test]
+    fn parse_with_row_sample() {
+        let data = (1..=200).map(|i| format!("{}\n", i)).collect::<String>();
+        let mut test_data_cursor = std::io::Cursor::new(format!("n\n{}", data));
+        let parser = PattiCsvParserBuilder::csv()
+            .first_data_line_is_header(true)
+            .column_typings(vec![TypeColumnEntry::new(None, ValueType::UInt32)])
+            .row_sample(10, Some(7))
+            .build()
+            .unwrap();
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        assert_eq!(201, iter.by_ref().filter(|r| r.is_ok()).count());
+
+        let sample = iter.take_row_sample();
+        assert_eq!(10, sample.len());
+        for (line_num, row) in &sample {
+            // The header (line 1) is no data row, so it's never sampled.
+            assert!(*line_num > 1);
+            assert_eq!(
+                &Value::UInt32(*line_num as u32 - 1),
+                row.get_by_name("n").unwrap().get_data()
+            );
+        }
+        assert!(iter.take_row_sample().is_empty());
+    }
+
+    #[test]
+    fn parse_with_column_stats() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .column_stats(100)
+            .build()
+            .unwrap();
+        let mut test_data_cursor =
+            std::io::Cursor::new("id,city\n3,Bern\n1,\nx,Bern\n7,Zurich\n-2,Bern");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        assert!(iter.get_column_stats().is_empty());
+        let res = iter.by_ref().collect::<Vec<_>>();
+        assert!(res[3].is_err());
+
+        let stats = iter.get_column_stats();
+        assert_eq!(2, stats.len());
+        assert_eq!("id", stats[0].name);
+        assert_eq!(
+            (4, 0, 1),
+            (
+                stats[0].num_non_null,
+                stats[0].num_null,
+                stats[0].num_type_errors
+            )
+        );
+        assert_eq!(Some(Value::Int32(-2)), stats[0].min);
+        assert_eq!(Some(Value::Int32(7)), stats[0].max);
+        assert_eq!((3, 1), (stats[1].num_non_null, stats[1].num_null));
+        assert_eq!(2, stats[1].num_distinct());
+        assert!(!stats[1].is_distinct_capped());
+
+        // not gathered
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("id,city\n3,Bern");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        assert_eq!(2, iter.by_ref().count());
+        assert!(iter.get_column_stats().is_empty());
+    }
+
+    #[test]
+    fn parse_collecting_all_cell_errors() {
+        let mut test_data_cursor = std::io::Cursor::new("a,b,c,d\n1,x,true,y\n2,3,false,4\n5,6");
+        let parser = PattiCsvParserBuilder::csv()
+            .first_data_line_is_header(true)
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Bool),
+                TypeColumnEntry::new(None, ValueType::Int32),
+            ])
+            .collect_all_cell_errors(true)
+            .build()
+            .unwrap();
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .skip(1) // header
+            .collect::<Vec<_>>();
+        match &res[0] {
+            Err(PattiCsvError::Row(re)) => {
+                assert_eq!(2, re.line);
+                assert_eq!(
+                    vec![(1, "b"), (3, "d")],
+                    re.cells
+                        .iter()
+                        .map(|c| (c.column, c.header.as_str()))
+                        .collect::<Vec<_>>()
+                );
+            }
+            other => panic!("expected a row error, got: {:?}", other),
+        }
+        assert!(res[1].is_ok());
+        match &res[2] {
+            Err(PattiCsvError::Row(re)) => {
+                assert_eq!(2, re.cells.len());
+                assert!(re.cells[0].msg.starts_with("[ERROR_ON_MISSING_COLUMN]"));
+            }
+            other => panic!("expected a row error, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_with_error_policies() {
+        let data = "a,b,c\n1,x,true\n2,3,false\n4,5,y\n6";
+        let builder = || {
+            PattiCsvParserBuilder::csv()
+                .column_typings(vec![
+                    TypeColumnEntry::new(None, ValueType::Int32),
+                    TypeColumnEntry::new(None, ValueType::Int32),
+                    TypeColumnEntry::new(None, ValueType::Bool),
+                ])
+                .emit_header_row(false)
+        };
+
+        let parser = builder().on_error(ErrorPolicy::SkipRow).build().unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let rows = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(1, rows.len());
+        assert_eq!(&Value::Int32(2), rows[0].0[0].get_data());
+        assert_eq!(
+            vec![(2, vec![1]), (4, vec![2]), (5, vec![1, 2])],
+            iter.row_errors()
+                .iter()
+                .map(|re| (re.line, re.cells.iter().map(|c| c.column).collect()))
+                .collect::<Vec<(usize, Vec<usize>)>>()
+        );
+        assert_eq!(3, iter.take_row_errors().len());
+        assert!(iter.row_errors().is_empty());
+        let report = iter.finish_report();
+        assert_eq!((1, 3), (report.num_rows_ok, report.num_rows_err));
+
+        let parser = builder()
+            .on_error(ErrorPolicy::NullOnTypeError)
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let rows = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(4, rows.len());
+        assert_eq!(
+            vec![Value::Int32(1), Value::None, Value::Bool(true)],
+            rows[0]
+                .0
+                .iter()
+                .map(|c| c.get_data().clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(&Value::None, rows[3].0[2].get_data());
+        assert_eq!(3, iter.row_errors().len());
+        assert_eq!(String::from("c"), iter.row_errors()[1].cells[0].header);
+
+        // The default fails the row, without collecting anything
+        let parser = builder().build().unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.row_errors().is_empty());
+    }
+
+    #[test]
+    fn infer_types_with_header() {
+        let mut test_data_cursor =
+            std::io::Cursor::new("id,price,name\n1,0.10,foo\n2,12345678901234567.89,bar");
+        let parser = PattiCsvParserBuilder::csv()
+            .first_data_line_is_header(true)
+            .stringly_type_columns(3)
+            .build()
+            .unwrap();
+        let report = parser.infer_types(&mut test_data_cursor, 100).unwrap();
+        assert_eq!(2, report.num_rows_sampled);
+        assert_eq!(
+            vec![
+                TypeColumnEntry::new(Some(String::from("id")), ValueType::Int32),
+                TypeColumnEntry::new(Some(String::from("price")), ValueType::Decimal),
+                TypeColumnEntry::new(Some(String::from("name")), ValueType::String),
+            ],
+            report.to_typings()
+        );
+    }
+
+    #[test]
+    fn infer_types_lenient_with_null_markers() {
+        let mut test_data_cursor =
+            std::io::Cursor::new("id,price,name\n1,0.10,foo\nNULL,NULL,bar\n3,-,baz");
+        let parser = PattiCsvParserBuilder::csv()
+            .first_data_line_is_header(true)
+            .stringly_type_columns(3)
+            .build()
+            .unwrap();
+        let report = parser
+            .infer_types_lenient(&mut test_data_cursor, 100)
+            .unwrap();
+        assert_eq!(2, report.null_marker_suggestions.len());
+        let typings = report.to_typings();
+        assert_eq!(ValueType::Int32, typings[0].target_type);
+        assert_eq!(vec![String::from("NULL")], typings[0].null_policy.map_list);
+        assert_eq!(ValueType::Float64, typings[1].target_type);
+        assert_eq!(
+            vec![String::from("-"), String::from("NULL")],
+            typings[1].null_policy.map_list
+        );
+        assert_eq!(ValueType::String, typings[2].target_type);
+    }
+
+    #[test]
+    fn parse_with_inferred_column_types() {
+        let mut test_data_cursor = std::io::Cursor::new(
+            "id,amount,ok,day,name\n1,1.5,true,2021-01-03,foo\n2,,false,2021-01-04,bar\n3,2.25,true,,baz\n4,n/a,true,2021-01-05,qux\n5,3,false,2021-01-06,quux",
+        );
+        let parser = PattiCsvParserBuilder::csv()
+            .infer_column_types(3)
+            .build()
+            .unwrap();
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let header = iter.next().unwrap().unwrap();
+        assert!(iter.is_at_header_row());
+        assert_eq!(4, iter.get_stats().curr_line_num); // read ahead
+        assert_eq!(1, iter.curr_line_num());
+        assert_eq!(
+            vec!["id", "amount", "ok", "day", "name"],
+            header.0.iter().map(|c| c.get_name()).collect::<Vec<_>>()
+        );
+        let row = iter.next().unwrap().unwrap();
+        assert_eq!((2, false), (iter.curr_line_num(), iter.is_at_header_row()));
+        assert_eq!(
+            vec![
+                ValueType::Int32,
+                ValueType::Float64,
+                ValueType::Bool,
+                ValueType::NaiveDate,
+                ValueType::String
+            ],
+            row.0.iter().map(|c| c.dtype.clone()).collect::<Vec<_>>()
+        );
+        assert_eq!(&Value::Bool(true), row.0[2].get_data());
+        assert_eq!(&Value::None, iter.next().unwrap().unwrap().0[1].get_data());
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_err()); // "n/a" wasn't in the sample
+        assert_eq!(5, iter.curr_line_num());
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().is_none());
+
+        // Without a header, the first line is a sample row as well
+        let mut test_data_cursor = std::io::Cursor::new("1\ttrue\n2\tfalse\n3\tmaybe");
+        let parser = PattiCsvParserBuilder::tsv()
+            .infer_column_types(10)
+            .build()
+            .unwrap();
+        let rows = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(3, rows.len());
+        assert_eq!(
+            &Value::Int32(1),
+            rows[0].get_by_name("0").unwrap().get_data()
+        );
+        assert_eq!(ValueType::String, rows[2].0[1].dtype);
+
+        assert!(PattiCsvParserBuilder::csv()
+            .infer_column_types(0)
+            .build()
+            .is_err());
+        assert!(PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .infer_column_types(10)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn parse_with_preamble() {
+        let data = "Report: Sales\nReport Date: 2024-01-01\n\n# comment\nid,name\n1,foo\n2,bar";
+        let parser = PattiCsvParserBuilder::csv()
+            .preamble(Preamble::while_key_value())
+            .skip_take_lines_fns(vec![Box::new(SkipLinesStartingWith::new("#"))])
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let header = iter.next().unwrap().unwrap();
+        assert_eq!(
+            &Value::String(String::from("id")),
+            header.get_by_name("id").unwrap().get_data()
+        );
+        assert_eq!(
+            Some(&String::from("2024-01-01")),
+            iter.preamble().get("Report Date")
+        );
+        assert_eq!(2, iter.preamble().len());
+        assert_eq!(2, iter.by_ref().count());
+        assert_eq!(1, iter.get_stats().skipped_lines.len());
+
+        // The index skips the preamble lines as well
+        #[cfg(feature = "io")]
+        {
+            let indexed = parser.indexed_reader(std::io::Cursor::new(data)).unwrap();
+            assert_eq!(2, indexed.len());
+        }
+
+        let res = PattiCsvParserBuilder::csv()
+            .preamble(Preamble::lines(2).with_kv_separator(""))
+            .stringly_type_columns(2)
+            .build();
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn parse_without_emitting_the_header_row() {
+        let mut test_data_cursor = std::io::Cursor::new("id,name\n1,foo\n2,bar");
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .emit_header_row(false)
+            .build()
+            .unwrap();
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        assert!(iter.header().is_none());
+        let rows = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(2, rows.len());
+        assert_eq!(
+            &Value::String(String::from("1")),
+            rows[0].get_by_name("id").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::String(String::from("name")),
+            iter.header()
+                .unwrap()
+                .get_by_name("name")
+                .unwrap()
+                .get_data()
+        );
+        assert_eq!(2, iter.finish_report().num_rows_ok);
+
+        // The default: the header row is emitted, and available anyway
+        let mut test_data_cursor = std::io::Cursor::new("id,name\n1,foo\n2,bar");
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let header = iter.next().unwrap().unwrap();
+        assert_eq!(Some(&header), iter.header());
+        assert_eq!(2, iter.count());
+    }
+
+    #[test]
+    fn parse_with_stage_timings() {
+        let build = |stage_timings| {
+            PattiCsvParserBuilder::csv()
+                .stringly_type_columns(2)
+                .stage_timings(stage_timings)
+                .row_stage(Box::new(crate::pipeline::FnRowStage::new(
+                    "sleep",
+                    |_, row| {
+                        std::thread::sleep(std::time::Duration::from_millis(2));
+                        Ok(Some(row))
+                    },
+                )))
+                .build()
+                .unwrap()
+        };
+        let data = "a,b\n1,2\n3,4";
+
+        let parser = build(false);
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        assert_eq!(3, iter.by_ref().count());
+        assert_eq!(None, iter.get_stats().stage_timings);
+
+        let parser = build(true);
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        assert_eq!(3, iter.by_ref().count());
+        let timings = iter.get_stats().stage_timings.clone().unwrap();
+        assert!(timings.row_stages >= std::time::Duration::from_millis(4));
+        assert!(timings.tokenize < timings.row_stages);
+        assert_eq!(Some(timings), iter.finish_report().stage_timings);
+    }
+
+    #[test]
+    fn parse_into_simple_rows() {
+        let mut test_data_cursor = std::io::Cursor::new("id,name\n1,foo\n2,\n");
+        let parser = PattiCsvParserBuilder::csv()
+            .first_data_line_is_header(true)
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap();
+        let mut iter = parser.parse_iter(&mut test_data_cursor).simple_rows();
+        let header = iter.next().unwrap().unwrap();
+        assert_eq!(vec![String::from("id"), String::from("name")], header.names);
+        let row = iter.next().unwrap().unwrap();
+        assert_eq!(
+            vec![Value::Int32(1), Value::String(String::from("foo"))],
+            row.values
+        );
+        let row = iter.next().unwrap().unwrap();
+        assert_eq!(Some(&Value::None), row.get_by_name("name"));
+        assert!(iter.next().is_none());
+        assert_eq!(3, iter.into_inner().get_stats().num_lines_tokenized);
+    }
+
+    #[test]
+    fn parse_with_tolerant_numeric() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new_with_map_to_none(
+                    Some(String::from("amount")),
+                    ValueType::Float64,
+                    vec![String::from("n/a")],
+                )
+                .with_tolerant_numeric(TolerantNumeric::new().with_record_stripped(true)),
+            ])
+            .build()
+            .unwrap();
+        let mut test_data_cursor =
+            std::io::Cursor::new("id,amount\n1,\"USD 1,000.50\"\n2,n/a\n3,42 (est.)\n4,junk");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let res = iter.by_ref().collect::<Vec<_>>();
+        let amounts = res[1..4]
+            .iter()
+            .map(|r| {
+                r.as_ref()
+                    .unwrap()
+                    .get_by_name("amount")
+                    .unwrap()
+                    .get_data()
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![Value::Float64(1000.5), Value::None, Value::Float64(42.0)],
+            amounts
+        );
+        assert!(res[4].is_err());
+        assert_eq!(
+            vec![
+                (2, 1, String::from("USD ,")),
+                (4, 1, String::from(" (est.)"))
+            ],
+            iter.get_stats().stripped_tokens
+        );
+
+        let res = PattiCsvParserBuilder::csv()
+            .column_typings(vec![TypeColumnEntry::new(None, ValueType::String)
+                .with_tolerant_numeric(TolerantNumeric::new())])
+            .build();
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn parse_with_number_format() {
+        let parser = PattiCsvParserBuilder::csv()
+            .separator_char(';')
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32)
+                    .with_number_format(NumberFormat::decimal_comma()),
+                TypeColumnEntry::new(Some(String::from("amount")), ValueType::Decimal)
+                    .with_number_format(NumberFormat::decimal_comma()),
+            ])
+            .build()
+            .unwrap();
+        let mut test_data_cursor =
+            std::io::Cursor::new("qty;amount\n1.000;1.234,56\n2;-0,5\n3;1.5\n4;EUR 1,5");
+        let res = parser.parse_iter(&mut test_data_cursor).collect::<Vec<_>>();
+        let row = res[1].as_ref().unwrap();
+        assert_eq!(&Value::Int32(1000), row.0[0].get_data());
+        assert_eq!(
+            &Value::Decimal(rust_decimal::Decimal::new(123456, 2)),
+            row.get_by_name("amount").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Decimal(rust_decimal::Decimal::new(-5, 1)),
+            res[2].as_ref().unwrap().0[1].get_data()
+        );
+        assert!(res[3].is_err()); // not grouped by three
+        assert!(res[4].is_err()); // strict, unlike tolerant numeric
+
+        let res = PattiCsvParserBuilder::csv()
+            .column_typings(vec![TypeColumnEntry::new(None, ValueType::Float64)
+                .with_number_format(NumberFormat::new(',', Some(',')))])
+            .build();
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+        let res = PattiCsvParserBuilder::csv()
+            .column_typings(vec![TypeColumnEntry::new(None, ValueType::Float64)
+                .with_number_format(NumberFormat::decimal_comma())
+                .with_tolerant_numeric(TolerantNumeric::new())])
+            .build();
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn parse_with_bool_mapping() {
+        let yes_no = |tce: TypeColumnEntry| {
+            tce.with_bool_mapping(
+                vec![String::from("Y"), String::from("1")],
+                vec![String::from("N"), String::from("0")],
+            )
+        };
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![yes_no(TypeColumnEntry::new_with_map_to_none(
+                Some(String::from("active")),
+                ValueType::Bool,
+                vec![String::from("-")],
+            ))])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("active\nY\n0\nfalse\n-\ny");
+        let res = parser.parse_iter(&mut test_data_cursor).collect::<Vec<_>>();
+        let values = res[1..5]
+            .iter()
+            .map(|r| r.as_ref().unwrap().0[0].get_data().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Value::Bool(true),
+                Value::Bool(false),
+                Value::Bool(false), // the standard conversion
+                Value::None
+            ],
+            values
+        );
+        assert!(res[5].is_err()); // case-sensitive
+
+        let res = PattiCsvParserBuilder::csv()
+            .column_typings(vec![yes_no(TypeColumnEntry::new(None, ValueType::String))])
+            .build();
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+        let res = PattiCsvParserBuilder::csv()
+            .column_typings(vec![TypeColumnEntry::new(None, ValueType::Bool)
+                .with_bool_mapping(vec![String::from("x")], vec![String::from("x")])])
+            .build();
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn column_idxs_by_final_names() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(Some(String::from("id")), ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::NaiveDate),
+            ])
+            .split_column(SplitColumn::new(
+                1,
+                Box::new(SplitBySeparator::new(";")),
+                vec![
+                    TypeColumnEntry::new(Some(String::from("last")), ValueType::String),
+                    TypeColumnEntry::new(Some(String::from("first")), ValueType::String),
+                ],
+            ))
+            .derived_column(DerivedColumn::new(4, DatePart::Year, "year"))
+            .build()
+            .unwrap();
+        let mut test_data_cursor =
+            std::io::Cursor::new("ID,person,last,born\n1,Lovelace;Ada,x,1815-12-10");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        assert!(iter.column_idxs().is_empty());
+        iter.next();
+
+        let exp = [
+            ("id", 0),
+            ("last", 1),
+            ("first", 2),
+            ("born", 4),
+            ("year", 5),
+        ];
+        assert_eq!(
+            exp.iter()
+                .map(|(name, idx)| (String::from(*name), *idx))
+                .collect::<HashMap<_, _>>(),
+            *iter.column_idxs()
+        );
+        let row = iter.next().unwrap().unwrap();
+        assert_eq!(
+            &Value::String(String::from("Lovelace")),
+            row.0[iter.column_idxs()["last"]].get_data()
+        );
+    }
+
+    #[test]
+    fn parse_with_derived_splits() {
+        let builder = || {
+            PattiCsvParserBuilder::csv()
+                .column_typings(vec![
+                    TypeColumnEntry::new(None, ValueType::Int32),
+                    TypeColumnEntry::new(None, ValueType::String)
+                        .with_null_policy(NullPolicy::empty_is_none()),
+                ])
+                .derived_split(DerivedSplit::new(
+                    1,
+                    Box::new(SplitValueAndUnit::new()),
+                    vec![
+                        TypeColumnEntry::new(Some(String::from("amount")), ValueType::Float64),
+                        TypeColumnEntry::new(Some(String::from("ccy")), ValueType::String),
+                    ],
+                ))
+        };
+        let data = "id,price\n1,10.50 CHF\n2,\n3,abc";
+
+        let parser = builder().build().unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let res = parser.parse_iter(&mut test_data_cursor).collect::<Vec<_>>();
+        let header = res[0].as_ref().unwrap();
+        assert_eq!(
+            vec!["id", "price", "amount", "ccy"],
+            header
+                .0
+                .iter()
+                .map(|c| String::try_from(c.get_data()).unwrap())
+                .collect::<Vec<_>>()
+        );
+        // the split column is kept
+        let row = res[1].as_ref().unwrap();
+        assert_eq!(
+            &Value::from(String::from("10.50 CHF")),
+            row.get_by_name("price").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Float64(10.5),
+            row.get_by_name("amount").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::from(String::from("CHF")),
+            row.get_by_name("ccy").unwrap().get_data()
+        );
+        let row = res[2].as_ref().unwrap();
+        assert_eq!(&Value::None, row.get_by_name("amount").unwrap().get_data());
+        assert!(matches!(
+            &res[3],
+            Err(PattiCsvError::Generic { msg }) if msg.starts_with("[ERROR_ON_DERIVE]")
+        ));
+
+        // the split column is typed, even if it's not selected
+        let parser = builder()
+            .select_columns_by_name(vec!["id", "amount"])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let row = parser
+            .parse_iter(&mut test_data_cursor)
+            .nth(1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(2, row.0.len());
+        assert_eq!(
+            &Value::Float64(10.5),
+            row.get_by_name("amount").unwrap().get_data()
+        );
+
+        // the split column must be a String one
+        let res = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Float64),
+            ])
+            .derived_split(DerivedSplit::new(
+                1,
+                Box::new(SplitBySeparator::new(" ")),
+                vec![TypeColumnEntry::new(
+                    Some(String::from("a")),
+                    ValueType::String,
+                )],
+            ))
+            .build();
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn parse_with_selected_columns() {
+        let builder = || {
+            PattiCsvParserBuilder::csv()
+                .column_typings(vec![
+                    TypeColumnEntry::new(None, ValueType::Int32),
+                    TypeColumnEntry::new(None, ValueType::Int32), // never typed, so never an error
+                    TypeColumnEntry::new(None, ValueType::NaiveDate),
+                    TypeColumnEntry::new(None, ValueType::String),
+                ])
+                .derived_column(DerivedColumn::new(2, DatePart::Year, "year"))
+        };
+        let data = "id,junk,born,name\n1,x,1815-12-10,Ada\n2,y,1791-12-26,Charles";
+
+        let parser = builder().select_columns(vec![3, 4, 0]).build().unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let res = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(
+            vec!["name", "year", "id"],
+            res[0]
+                .0
+                .iter()
+                .map(|c| String::try_from(c.get_data()).unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                Value::from(String::from("Charles")),
+                Value::Int32(1791),
+                Value::Int32(2)
+            ],
+            res[2]
+                .0
+                .iter()
+                .map(|c| c.get_data().clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&1), iter.column_idxs().get("year"));
+        assert_eq!(None, iter.column_idxs().get("born"));
+
+        let parser = builder()
+            .select_columns_by_name(vec!["id", "born"])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(2, res[1].0.len());
+        assert_eq!(1, res[1].get_by_name("born").unwrap().idx);
+
+        for parser in [
+            builder().select_columns_by_name(vec!["nope"]).build(),
+            builder().select_columns(vec![0, 5]).build(),
+            builder().select_columns(vec![0, 0]).build(),
+        ] {
+            let mut test_data_cursor = std::io::Cursor::new(data);
+            assert!(matches!(
+                parser.unwrap().parse_iter(&mut test_data_cursor).next(),
+                Some(Err(PattiCsvError::ConfigError { .. }))
+            ));
+        }
+        assert!(builder().select_columns(Vec::new()).build().is_err());
+    }
+
+    #[test]
+    fn validate_without_producing_rows() {
+        let builder = || {
+            PattiCsvParserBuilder::csv().column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Float64),
+                TypeColumnEntry::new(None, ValueType::NaiveDate),
+            ])
+        };
+        let parser = builder().build().unwrap();
+        let data =
+            "id,amount,day\n1,1.5,2021-01-01\nx,2.0,2021-01-02\n3,abc\n4,5.0,2021-13-01,extra";
+
+        let report = parser
+            .validate(&mut std::io::Cursor::new(data), 100)
+            .unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(4, report.num_rows_validated);
+        assert_eq!(3, report.num_rows_err);
+        assert_eq!(Some(3), report.expected_num_columns);
+        assert_eq!(
+            vec![(4, 2), (5, 4)],
+            report
+                .column_count_mismatches
+                .iter()
+                .map(|m| (m.line_num, m.num_columns))
+                .collect::<Vec<_>>()
+        );
+        let errors = |idx: usize| {
+            report.columns[idx]
+                .samples
+                .iter()
+                .map(|s| (s.line_num, s.token.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![(3, Some(String::from("x")))], errors(0));
+        assert_eq!(vec![(4, Some(String::from("abc")))], errors(1));
+        assert_eq!(
+            vec![(4, None), (5, Some(String::from("2021-13-01")))],
+            errors(2)
+        );
+        assert_eq!(String::from("day"), report.columns[2].name);
+        assert!(report.columns[2].samples[0]
+            .msg
+            .starts_with("[ERROR_ON_MISSING_COLUMN]"));
+        assert!(report.errors.is_empty());
+
+        let report = parser.validate(&mut std::io::Cursor::new(data), 1).unwrap();
+        assert_eq!(1, report.num_rows_validated);
+        assert!(report.is_valid());
+
+        // the sanitizers run, their errors are reported as such
+        let parser = builder()
+            .column_transitizers(HashMap::from([(
+                Some(0),
+                vec![Box::new(RegexTake::new("^(\\d+)$").unwrap())
+                    as Box<dyn TransformSanitizeToken + Send + Sync>],
+            )]))
+            .build()
+            .unwrap();
+        let report = parser.validate(&mut std::io::Cursor::new(data), 2).unwrap();
+        assert_eq!(1, report.num_rows_err);
+        assert_eq!(1, report.errors.len());
+        assert_eq!(3, report.errors[0].first_line_num);
+
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            parser.validate(&mut std::io::Cursor::new(data), 100),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn validate_duplicate_rows() {
+        let parser = |policy: DuplicateRowPolicy| {
+            PattiCsvParserBuilder::csv()
+                .column_typings(vec![
+                    TypeColumnEntry::new(None, ValueType::Int32),
+                    TypeColumnEntry::new(None, ValueType::String),
+                ])
+                .column_transitizers(HashMap::from([(
+                    Some(1),
+                    vec![Box::new(RegexTake::new("^([a-c])$").unwrap())
+                        as Box<dyn TransformSanitizeToken + Send + Sync>],
+                )]))
+                .unique_columns(vec![vec![0]])
+                .duplicate_row_policy(policy, DuplicateRowComparison::TypedRow)
+                .build()
+                .unwrap()
+        };
+        // a duplicate row, followed by a bad row, and a duplicate key
+        let data = "id,name\n1,a\n1,a\n2,bad\n1,c";
+
+        let report = parser(DuplicateRowPolicy::Flag)
+            .validate(&mut std::io::Cursor::new(data), 100)
+            .unwrap();
+        assert_eq!(4, report.num_rows_validated);
+        assert_eq!(3, report.num_rows_err);
+        assert_eq!(
+            vec![(3, 1), (4, 1), (5, 1)],
+            report
+                .errors
+                .iter()
+                .map(|e| (e.first_line_num, e.count))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("[ERROR_ON_DUPLICATE_ROW]", report.errors[0].kind);
+        assert!(report.errors[2].first_msg.starts_with("DuplicateKey"));
+
+        let report = parser(DuplicateRowPolicy::Drop)
+            .validate(&mut std::io::Cursor::new(data), 100)
+            .unwrap();
+        assert_eq!(3, report.num_rows_validated);
+        assert_eq!(2, report.num_rows_err);
+        assert_eq!(
+            vec![4, 5],
+            report
+                .errors
+                .iter()
+                .map(|e| e.first_line_num)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn parse_with_transitizers_by_name() {
+        let builder = |name: &str| {
+            let transitizers: VecOfTokenTransitizers = vec![
+                Box::new(Eradicate::new("EUR")),
+                Box::new(ReplaceWith::new("_", ".")),
+            ];
+            PattiCsvParserBuilder::csv()
+                .column_typings(vec![
+                    TypeColumnEntry::new(None, ValueType::String),
+                    TypeColumnEntry::new(None, ValueType::String),
+                ])
+                .column_transitizers(HashMap::from([(
+                    None,
+                    vec![Box::new(TrimAll::new()) as Box<dyn TransformSanitizeToken + Send + Sync>],
+                )]))
+                .column_transitizers_by_name(HashMap::from([(String::from(name), transitizers)]))
+                .build()
+                .unwrap()
+        };
+        let parser = builder("amount");
+
+        // the same config, for both column orders
+        for (data, idx) in [("id,amount\n1, EUR 1_50", 1), ("amount,id\nEUR 1_50 ,1", 0)] {
+            let mut test_data_cursor = std::io::Cursor::new(data);
+            let res = parser
+                .parse_iter(&mut test_data_cursor)
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            // after the global trim, so the space after EUR stays
+            assert_eq!(
+                &Value::from(String::from(" 1.50")),
+                res[1].0[idx].get_data()
+            );
+            assert_eq!(
+                &Value::from(String::from("1")),
+                res[1].0[1 - idx].get_data()
+            );
+
+            #[cfg(feature = "io")]
+            {
+                let mut reader = parser.indexed_reader(std::io::Cursor::new(data)).unwrap();
+                assert_eq!(res[1], reader.get_row(0).unwrap().unwrap());
+            }
         }
-        Some(Ok(row_data))
+
+        let mut test_data_cursor = std::io::Cursor::new("id,amount\n1,EUR 1_50");
+        assert!(matches!(
+            builder("nope").parse_iter(&mut test_data_cursor).next(),
+            Some(Err(PattiCsvError::ConfigError { .. }))
+        ));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::convert::TryFrom;
+    #[test]
+    fn names_resolved_like_typings_by_header() {
+        let transitizers: VecOfTokenTransitizers = vec![Box::new(Eradicate::new("EUR "))];
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![TypeColumnEntry::new(
+                Some(String::from("Amount")),
+                ValueType::Decimal,
+            )
+            .with_header_aliases(vec![String::from("Betrag")])])
+            .resolve_typings_by_header(HeaderMatchMode::IgnoreCaseAndWhitespace)
+            .column_transitizers_by_name(HashMap::from([(String::from(" amount"), transitizers)]))
+            .select_columns_by_name(vec!["BETRAG"])
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("id,betrag\n1,EUR 1.50");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(1, res[1].0.len());
+        assert_eq!(
+            &Value::Decimal(rust_decimal::Decimal::new(150, 2)),
+            res[1].get_by_name("Amount").unwrap().get_data()
+        );
+    }
 
-    use super::*;
+    #[test]
+    fn parse_with_split_columns() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::Bool),
+            ])
+            .split_column(SplitColumn::new(
+                1,
+                Box::new(SplitBySeparator::new(";")),
+                vec![
+                    TypeColumnEntry::new(Some(String::from("last")), ValueType::String),
+                    TypeColumnEntry::new(Some(String::from("first")), ValueType::String),
+                    TypeColumnEntry::new(None, ValueType::Int16),
+                ],
+            ))
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(
+            "id,person,alive\n1,Lovelace;Ada;1815,false\n2,,true\n3,Turing;Alan,false",
+        );
+        let res = parser.parse_iter(&mut test_data_cursor).collect::<Vec<_>>();
+        let header = res[0].as_ref().unwrap();
+        assert_eq!(
+            vec!["id", "last", "first", "person_3", "alive"],
+            header.0.iter().map(|c| c.get_name()).collect::<Vec<_>>()
+        );
+        let row = res[1].as_ref().unwrap();
+        assert_eq!(
+            &Value::Int16(1815),
+            row.get_by_name("person_3").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Bool(false),
+            row.get_by_name("alive").unwrap().get_data()
+        );
+        let row = res[2].as_ref().unwrap();
+        assert_eq!(&Value::None, row.get_by_name("first").unwrap().get_data());
+        assert!(res[3].is_err());
+
+        #[cfg(feature = "io")]
+        {
+            let mut indexed = parser
+                .indexed_reader(std::io::Cursor::new(
+                    "id,person,alive\n1,Lovelace;Ada;1815,false",
+                ))
+                .unwrap();
+            assert_eq!(
+                &Value::String(String::from("Ada")),
+                indexed
+                    .get_row(0)
+                    .unwrap()
+                    .unwrap()
+                    .get_by_name("first")
+                    .unwrap()
+                    .get_data()
+            );
+        }
+    }
 
-    use crate::{skip_take_lines::*, transform_sanitize_token::*};
+    #[test]
+    fn parse_with_value_and_unit_split() {
+        let parser = PattiCsvParserBuilder::csv()
+            .separator_char(';')
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .split_column(SplitColumn::value_and_unit(
+                1,
+                TypeColumnEntry::new(Some(String::from("price")), ValueType::Decimal),
+                TypeColumnEntry::new(Some(String::from("currency")), ValueType::String),
+            ))
+            .build()
+            .unwrap();
+        let mut test_data_cursor =
+            std::io::Cursor::new("item;price\nbook;10.00 CHF\npen;-2.5USD\nfree;0\nbad;CHF 10");
+        let res = parser.parse_iter(&mut test_data_cursor).collect::<Vec<_>>();
+        let row = res[1].as_ref().unwrap();
+        assert_eq!(
+            &Value::Decimal(rust_decimal::Decimal::new(1000, 2)),
+            row.get_by_name("price").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::String(String::from("CHF")),
+            row.get_by_name("currency").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::String(String::from("USD")),
+            res[2]
+                .as_ref()
+                .unwrap()
+                .get_by_name("currency")
+                .unwrap()
+                .get_data()
+        );
+        assert_eq!(
+            &Value::None,
+            res[3]
+                .as_ref()
+                .unwrap()
+                .get_by_name("currency")
+                .unwrap()
+                .get_data()
+        );
+        assert!(res[4].is_err()); // no leading value
+    }
 
-    pub mod iterating_parser_builder {
-        use super::*;
+    #[test]
+    fn parse_with_duplicate_headers() {
+        let parser = |policy: DuplicateHeaderPolicy| {
+            PattiCsvParserBuilder::csv()
+                .stringly_type_columns(3)
+                .on_duplicate_headers(policy)
+                .build()
+                .unwrap()
+        };
+        let data = "id,name,id\n1,foo,2";
 
-        #[test]
-        fn test_iterating_parser_builder_all_opts() {
-            let mut transitizers: HashMap<Option<usize>, VecOfTokenTransitizers> =
-                HashMap::with_capacity(2);
-            transitizers.insert(None, vec![Box::new(ToLowercase)]);
-            transitizers.insert(Some(0), vec![Box::new(TrimAll)]);
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let parser_rename = parser(DuplicateHeaderPolicy::RenameWithSuffix);
+        let mut iter = parser_rename.parse_iter(&mut test_data_cursor);
+        let header = iter.next().unwrap().unwrap();
+        assert_eq!(
+            vec!["id", "name", "id_2"],
+            header.0.iter().map(|c| c.get_name()).collect::<Vec<_>>()
+        );
+        let row = iter.next().unwrap().unwrap();
+        assert_eq!(
+            &Value::String(String::from("2")),
+            row.get_by_name("id_2").unwrap().get_data()
+        );
 
-            let parser_builder = PattiCsvParserBuilder::new()
-                .separator_char(';')
-                .enclosure_char(Some('\''))
-                .first_data_line_is_header(false)
-                .skip_take_lines_fns(vec![Box::new(SkipLinesStartingWith::new(""))])
-                .save_skipped_lines(true)
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let parser_error = parser(DuplicateHeaderPolicy::Error);
+        let res = parser_error
+            .parse_iter(&mut test_data_cursor)
+            .next()
+            .unwrap();
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+        #[cfg(feature = "io")]
+        {
+            let res = parser_error.indexed_reader(std::io::Cursor::new(data));
+            assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+        }
+
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let res = parser(DuplicateHeaderPolicy::KeepFirst)
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            &Value::String(String::from("1")),
+            res[1].get_by_name("id").unwrap().get_data()
+        );
+    }
+
+    #[test]
+    fn parse_with_column_count_mismatch_policy() {
+        let parser = |policy: ColumnCountMismatchPolicy| {
+            PattiCsvParserBuilder::csv()
                 .column_typings(vec![
                     TypeColumnEntry::new(None, ValueType::Int32),
                     TypeColumnEntry::new(None, ValueType::String),
-                    TypeColumnEntry::new(None, ValueType::Bool),
                 ])
-                .column_transitizers(transitizers);
+                .on_column_count_mismatch(policy)
+                .build()
+                .unwrap()
+        };
+        let data = "id,name\n1,foo\n2\n3,bar,baz";
+        let parse = |policy: ColumnCountMismatchPolicy| {
+            let mut test_data_cursor = std::io::Cursor::new(data);
+            parser(policy)
+                .parse_iter(&mut test_data_cursor)
+                .collect::<Vec<_>>()
+        };
 
-            assert_eq!(Some(';'), parser_builder.separator_char);
-            assert_eq!(Some('\''), parser_builder.enclosure_char);
-            assert_eq!(false, parser_builder.first_data_line_is_header);
-            assert_eq!(1, parser_builder.skip_take_lines_fns.unwrap().len());
-            assert_eq!(true, parser_builder.save_skipped_lines);
-            assert_eq!(3, parser_builder.column_typings.unwrap().len());
-            assert_eq!(false, parser_builder.column_transitizers.is_none());
-            assert_eq!(2, parser_builder.column_transitizers.unwrap().len());
-        }
+        let res = parse(ColumnCountMismatchPolicy::Error);
+        assert!(res[1].is_ok());
+        assert_eq!(
+            Err(PattiCsvError::ColumnCountMismatch {
+                line: 3,
+                expected: 2,
+                found: 1
+            }),
+            res[2]
+        );
+        assert!(matches!(
+            res[3],
+            Err(PattiCsvError::ColumnCountMismatch { line: 4, .. })
+        ));
 
-        #[test]
-        fn test_iterating_parser_builder_defaults_csv() {
-            let parser_builder = PattiCsvParserBuilder::csv().column_typings(vec![]);
+        let res = parse(ColumnCountMismatchPolicy::PadWithNone);
+        assert_eq!(&Value::None, res[2].as_ref().unwrap().0[1].get_data());
+        assert!(res[3].is_err());
 
-            assert_eq!(Some(','), parser_builder.separator_char);
-            assert_eq!(Some('"'), parser_builder.enclosure_char);
-            assert_eq!(true, parser_builder.first_data_line_is_header);
-            assert!(parser_builder.skip_take_lines_fns.is_none());
-            assert_eq!(false, parser_builder.save_skipped_lines);
-            assert!(parser_builder.column_transitizers.is_none());
+        let res = parse(ColumnCountMismatchPolicy::TruncateExtra);
+        assert!(res[2].is_err()); // no NullPolicy allows missing tokens here
+        let row = res[3].as_ref().unwrap();
+        assert_eq!(2, row.0.len());
+        assert_eq!(&Value::String(String::from("bar")), row.0[1].get_data());
+
+        #[cfg(feature = "io")]
+        {
+            let parser_error = parser(ColumnCountMismatchPolicy::Error);
+            let mut indexed = parser_error
+                .indexed_reader(std::io::Cursor::new(data))
+                .unwrap();
+            assert!(matches!(
+                indexed.get_row(1).unwrap(),
+                Err(PattiCsvError::ColumnCountMismatch { line: 3, .. })
+            ));
         }
+    }
 
-        #[test]
-        fn test_iterating_parser_builder_defaults_tsv() {
-            let parser_builder = PattiCsvParserBuilder::tsv().column_typings(vec![]);
+    #[test]
+    fn parse_with_skipped_lines_metadata() {
+        let parser = PattiCsvParserBuilder::csv()
+            .skip_take_lines_fns(vec![Box::new(
+                SkipLinesByRegex::new(r"^Report Date: (?P<report_date>[\d-]+)").unwrap(),
+            )])
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let mut test_data_cursor =
+            std::io::Cursor::new("Report Date: 2024-01-01\r\nid,name\r\n1,foo");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        assert_eq!(2, iter.by_ref().filter(|r| r.is_ok()).count());
+        assert_eq!(
+            Some(&String::from("2024-01-01")),
+            iter.get_stats().skipped_lines_metadata.get("report_date")
+        );
+        let report = iter.finish_report();
+        assert_eq!(1, report.skipped_lines_metadata.len());
+    }
 
-            assert_eq!(Some('\t'), parser_builder.separator_char);
-            assert_eq!(None, parser_builder.enclosure_char);
-            assert_eq!(false, parser_builder.first_data_line_is_header);
-            assert!(parser_builder.skip_take_lines_fns.is_none());
-            assert_eq!(false, parser_builder.save_skipped_lines);
-            assert!(parser_builder.column_transitizers.is_none());
+    #[test]
+    fn parse_with_quoted_header_only() {
+        let data = "\"id\",\"name\"\n1,\"foo\"\n2,bar";
+        let parser = PattiCsvParserBuilder::csv()
+            .enclosure_char(None)
+            .detect_header_enclosure(true)
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            &Value::String(String::from("\"foo\"")),
+            res[1].get_by_name("name").unwrap().get_data()
+        );
+
+        #[cfg(feature = "io")]
+        {
+            let reader = parser.indexed_reader(std::io::Cursor::new(data)).unwrap();
+            assert!(reader.header_row().unwrap().get_by_name("name").is_some());
         }
+    }
 
-        #[test]
-        #[should_panic(
-            expected = "Generic { msg: \"mandatory 'column typings' are not set! (None)\" }"
-        )]
-        fn patti_csv_parser_from_patti_csv_parser_builder_err_no_column_typings() {
-            PattiCsvParserBuilder::new()
-                .separator_char(',')
-                .enclosure_char(Some('"'))
+    #[test]
+    fn parse_with_dropped_rows() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Float64),
+            ])
+            .column_transitizers(HashMap::from([(
+                Some(1),
+                vec![
+                    Box::new(TrimAll) as Box<dyn TransformSanitizeToken + Send + Sync>,
+                    Box::new(DropRowOn::new(vec!["VOID"])),
+                ],
+            )]))
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("id,amount\n1,1.5\n2, VOID\n3,2.5");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let res = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(3, res.len()); // header + 2 rows
+        assert_eq!(
+            vec![(3, String::from("token: \"VOID\"; column: 1"))],
+            iter.get_stats().dropped_rows
+        );
+        let report = iter.finish_report();
+        assert_eq!(
+            (2, 0, 1),
+            (
+                report.num_rows_ok,
+                report.num_rows_err,
+                report.num_rows_dropped
+            )
+        );
+    }
+
+    #[test]
+    fn memory_budget_truncates_buffers() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Float64),
+            ])
+            .skip_take_lines_fns(vec![Box::new(SkipLinesStartingWith::new("#"))])
+            .save_skipped_lines(true)
+            .column_transitizers(HashMap::from([(
+                Some(1),
+                vec![Box::new(DropRowOn::new(vec!["VOID"]))
+                    as Box<dyn TransformSanitizeToken + Send + Sync>],
+            )]))
+            .memory_budget(
+                MemoryBudget::default()
+                    .with_max_skipped_lines(2)
+                    .with_max_saved_skipped_lines_bytes(4)
+                    .with_max_dropped_rows(1),
+            )
+            .build()
+            .unwrap();
+        let mut test_data_cursor =
+            std::io::Cursor::new("#a\nid,amount\n#b\n1,VOID\n#c\n2,VOID\n3,2.5");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let res = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(2, res.len()); // header + 1 row
+
+        let stats = iter.get_stats();
+        assert_eq!(
+            vec![(1, Some(String::from("#a\n"))), (3, None)],
+            stats.skipped_lines
+        );
+        assert_eq!(
+            vec![(4, String::from("token: \"VOID\"; column: 1"))],
+            stats.dropped_rows
+        );
+        assert_eq!(
+            TruncatedBuffers {
+                skipped_lines: true,
+                dropped_rows: true,
+                stripped_tokens: false
+            },
+            stats.truncated_buffers
+        );
+        let report = iter.finish_report();
+        assert_eq!((3, 2), (report.num_lines_skipped, report.num_rows_dropped));
+        assert!(report.truncated_buffers.skipped_lines);
+    }
+
+    #[test]
+    fn shared_parser_across_threads() {
+        let parser = std::sync::Arc::new(
+            PattiCsvParserBuilder::csv()
                 .first_data_line_is_header(true)
+                .column_typings(vec![
+                    TypeColumnEntry::new(None, ValueType::Int32),
+                    TypeColumnEntry::new(None, ValueType::String),
+                ])
+                .column_transitizers(HashMap::from([(
+                    None,
+                    vec![Box::new(TrimAll) as Box<dyn TransformSanitizeToken + Send + Sync>],
+                )]))
                 .build()
-                .unwrap();
+                .unwrap(),
+        );
+        let handles = (0..4)
+            .map(|t| {
+                let parser = std::sync::Arc::clone(&parser);
+                std::thread::spawn(move || {
+                    let data = (0..100)
+                        .map(|i| format!("{}, thread-{} \n", i, t))
+                        .collect::<String>();
+                    let mut test_data_cursor = std::io::Cursor::new(format!("id,text\n{}", data));
+                    let mut iter = parser.parse_iter(&mut test_data_cursor);
+                    let num_ok = iter.by_ref().filter(|r| r.is_ok()).count();
+                    (num_ok, iter.get_stats().num_lines_tokenized)
+                })
+            })
+            .collect::<Vec<_>>();
+        for h in handles {
+            assert_eq!((101, 101), h.join().unwrap());
         }
+    }
 
-        #[test]
-        #[should_panic(
-            expected = "Generic { msg: \"mandatory 'column typings' are not set! (Empty vec)\" }"
-        )]
-        fn patti_csv_parser_from_patti_csv_parser_builder_err_empty_column_typings() {
-            PattiCsvParserBuilder::new()
-                .separator_char(',')
-                .column_typings(vec![])
-                .build()
-                .unwrap();
+    #[test]
+    fn invalid_chrono_pattern_is_config_error() {
+        let res = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new_with_chrono_pattern(
+                    Some(String::from("created")),
+                    ValueType::NaiveDateTime,
+                    "%Y-%m-%d %Q",
+                ),
+            ])
+            .build();
+        assert!(
+            matches!(&res, Err(PattiCsvError::ConfigError { msg }) if msg.contains("\"%Y-%m-%d %Q\"") && msg.contains("column 1") && msg.contains("created")),
+            "{:?}",
+            res.err()
+        );
+    }
+
+    #[test]
+    fn money_columns_must_differ() {
+        let res = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .money_columns(MoneyColumns::new(1, 1))
+            .build();
+        assert!(matches!(res, Err(PattiCsvError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn money_columns_checked_against_typings() {
+        let builder = || {
+            PattiCsvParserBuilder::csv().column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Decimal),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+        };
+        assert!(builder()
+            .money_columns(MoneyColumns::new(0, 1))
+            .build()
+            .is_ok());
+        for mc in [MoneyColumns::new(0, 2), MoneyColumns::new(1, 0)] {
+            let res = builder().money_columns(mc).build();
+            assert!(
+                matches!(&res, Err(PattiCsvError::ConfigError { msg }) if msg.starts_with("[ERROR_ON_MONEY]")),
+                "{:?}",
+                res.err()
+            );
+        }
+    }
+
+    #[test]
+    fn parse_multi_line_enclosed_fields() {
+        let data =
+            "id,text\n1,\"multi\nline\"\n2,\"a \"\"quoted\"\"\r\nfield\"\r\n3,a\"b\n4,plain\n";
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap();
+
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let rows = parser
+            .parse_iter(&mut test_data_cursor)
+            .skip(1) // header
+            .collect::<Vec<_>>();
+        let text = |i: usize| {
+            String::try_from(
+                rows[i]
+                    .as_ref()
+                    .unwrap()
+                    .get_by_name("text")
+                    .unwrap()
+                    .get_data(),
+            )
+            .unwrap()
+        };
+        assert_eq!("multi\nline", text(0));
+        assert_eq!("a \"quoted\"\r\nfield", text(1));
+        assert!(rows[2].is_err()); // a stray enclosure character doesn't start an enclosure...
+        assert_eq!("plain", text(3)); // ...so it doesn't swallow the next line
+
+        #[cfg(feature = "io")]
+        {
+            let mut reader = parser.indexed_reader(std::io::Cursor::new(data)).unwrap();
+            assert_eq!(4, reader.len());
+            assert_eq!(
+                &Value::String(String::from("plain")),
+                reader
+                    .get_row(3)
+                    .unwrap()
+                    .unwrap()
+                    .get_by_name("text")
+                    .unwrap()
+                    .get_data()
+            );
         }
+    }
 
-        #[test]
-        #[should_panic(
-            expected = "Generic { msg: \"mandatory 'separator character' is not set! (use the convenience functions '::csv()' or '::tsv()' or set the separator character manually)\" }"
-        )]
-        fn patti_csv_parser_from_patti_csv_parser_builder_err_no_separator_char() {
-            PattiCsvParserBuilder::new()
-                .column_typings(vec![TypeColumnEntry::new(None, ValueType::Bool)])
-                .build()
+    #[test]
+    fn parse_with_record_separator() {
+        let mut test_data_cursor = std::io::Cursor::new(
+            "id,text\x1e1,\"multi\nline\"\x1e2,\"with \x1e inside\"\x1e3,plain\nnewline",
+        );
+        let parser = PattiCsvParserBuilder::csv()
+            .record_separator('\x1e')
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap();
+
+        let rows = parser
+            .parse_iter(&mut test_data_cursor)
+            .map(|r| String::try_from(r.unwrap().get_by_name("text").unwrap().get_data()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec!["text", "multi\nline", "with \x1e inside", "plain\nnewline"],
+            rows
+        );
+
+        // Same with random access
+        #[cfg(feature = "io")]
+        {
+            let mut reader = parser
+                .indexed_reader(std::io::Cursor::new(
+                    "id,text\x1e1,\"multi\nline\"\x1e2,\"with \x1e inside\"\x1e",
+                ))
                 .unwrap();
+            assert_eq!(2, reader.len());
+            assert_eq!(
+                &Value::String(String::from("with \x1e inside")),
+                reader
+                    .get_row(1)
+                    .unwrap()
+                    .unwrap()
+                    .get_by_name("text")
+                    .unwrap()
+                    .get_data()
+            );
         }
     }
 
     #[test]
-    fn parse_with_custom_parser() {
-        let mut test_data_cursor = std::io::Cursor::new("c1;c2;c3;c4;c5\n 1 ;'BaR';true;null;");
+    fn record_separator_must_be_ascii_and_distinct() {
+        assert!(PattiCsvParserBuilder::csv()
+            .record_separator('€')
+            .stringly_type_columns(1)
+            .build()
+            .is_err());
+        assert!(PattiCsvParserBuilder::csv()
+            .record_separator(',')
+            .stringly_type_columns(1)
+            .build()
+            .is_err());
+    }
 
+    #[test]
+    fn parse_with_smart_sanitizer_defaults() {
+        let mut test_data_cursor = std::io::Cursor::new("a,b,c,d\n 1 , TRUE , foo ,x2 ");
+
+        // Explicit sanitizers for column 3 win, i.e. there is no trimming
         let mut transitizers: HashMap<Option<usize>, VecOfTokenTransitizers> = HashMap::new();
-        transitizers.insert(None, vec![Box::new(ToLowercase)]);
-        transitizers.insert(Some(0), vec![Box::new(TrimAll)]);
+        transitizers.insert(Some(3), vec![Box::new(Eradicate::new("x"))]);
 
-        let parser = PattiCsvParserBuilder::new()
-            .separator_char(';')
-            .enclosure_char(Some('\''))
-            .first_data_line_is_header(true)
+        let parser = PattiCsvParserBuilder::csv()
+            .smart_sanitizer_defaults(true)
+            .column_transitizers(transitizers)
             .column_typings(vec![
                 TypeColumnEntry::new(None, ValueType::Int32),
-                TypeColumnEntry::new(Some(String::from("col2")), ValueType::String),
-                TypeColumnEntry::new(Some(String::from("col3")), ValueType::Bool),
-                TypeColumnEntry::new_with_map_to_none(
-                    Some(String::from("col4")),
-                    ValueType::String,
-                    vec![String::from("null")],
-                ),
-                TypeColumnEntry::new(None, ValueType::Int32), // Empty String will automatically(!) be mapped to Value::None!
+                TypeColumnEntry::new(None, ValueType::Bool),
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::String),
             ])
-            .column_transitizers(transitizers)
             .build()
             .unwrap();
 
         let mut iter = parser.parse_iter(&mut test_data_cursor);
-        let headers = iter.next().unwrap().unwrap();
+        let _headers = iter.next().unwrap().unwrap();
         let line_1 = iter.next().unwrap().unwrap();
+        assert_eq!(
+            &Value::Int32(1),
+            line_1.get_by_name("a").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Bool(true),
+            line_1.get_by_name("b").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::String(String::from(" foo ")),
+            line_1.get_by_name("c").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::String(String::from("2 ")),
+            line_1.get_by_name("d").unwrap().get_data()
+        );
+    }
 
-        // println!("{:?}", headers);
-        // println!("{:?}", line_1);
+    #[test]
+    fn duplicate_rows_drop_typed() {
+        // Line 4 is only identical to line 3 after sanitization
+        let mut test_data_cursor = std::io::Cursor::new("a,b\n1,x\n1,x\n1, x\n2,x\n1,x");
 
+        let mut transitizers: HashMap<Option<usize>, VecOfTokenTransitizers> = HashMap::new();
+        transitizers.insert(None, vec![Box::new(TrimAll)]);
+
+        let parser = PattiCsvParserBuilder::csv()
+            .column_transitizers(transitizers)
+            .stringly_type_columns(2)
+            .duplicate_row_policy(DuplicateRowPolicy::Drop, DuplicateRowComparison::TypedRow)
+            .build()
+            .unwrap();
+
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let rows = iter
+            .by_ref()
+            .skip(1) // header
+            .map(|r| String::try_from(r.unwrap().get_by_name("a").unwrap().get_data()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["1", "2", "1"], rows); // only consecutive duplicates are dropped
+        assert_eq!(2, iter.get_stats().num_duplicate_rows);
+    }
+
+    #[test]
+    fn duplicate_rows_flag_raw_tokens() {
+        let mut test_data_cursor = std::io::Cursor::new("a,b\n1,x\n1,x\n1, x");
+
+        let mut transitizers: HashMap<Option<usize>, VecOfTokenTransitizers> = HashMap::new();
+        transitizers.insert(None, vec![Box::new(TrimAll)]);
+
+        let parser = PattiCsvParserBuilder::csv()
+            .column_transitizers(transitizers)
+            .stringly_type_columns(2)
+            .duplicate_row_policy(DuplicateRowPolicy::Flag, DuplicateRowComparison::RawTokens)
+            .build()
+            .unwrap();
+
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let _headers = iter.next().unwrap().unwrap();
+        assert!(iter.next().unwrap().is_ok());
         assert_eq!(
-            DataCellRow {
-                0: vec![
-                    DataCell {
-                        dtype: ValueType::String,
-                        idx: 0,
-                        name: String::from("c1"),
-                        data: Value::String(String::from("c1"))
-                    },
-                    DataCell {
-                        dtype: ValueType::String,
-                        idx: 1,
-                        name: String::from("col2"),
-                        data: Value::String(String::from("col2"))
-                    },
-                    DataCell {
-                        dtype: ValueType::String,
-                        idx: 2,
-                        name: String::from("col3"),
-                        data: Value::String(String::from("col3"))
-                    },
-                    DataCell {
-                        dtype: ValueType::String,
-                        idx: 3,
-                        name: String::from("col4"),
-                        data: Value::String(String::from("col4"))
-                    },
-                    DataCell {
-                        dtype: ValueType::String,
-                        idx: 4,
-                        name: String::from("c5"),
-                        data: Value::String(String::from("c5"))
-                    },
-                ]
+            PattiCsvError::Generic {
+                msg: String::from(
+                    "[ERROR_ON_DUPLICATE_ROW] Row is identical to the previous row; line: 3"
+                )
             },
-            headers
+            iter.next().unwrap().unwrap_err()
+        );
+        // The raw tokens differ (" x" vs "x"), so this is no duplicate
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().is_none());
+        assert_eq!(1, iter.get_stats().num_duplicate_rows);
+    }
+
+    #[derive(Debug)]
+    struct CentsConverter;
+    impl CellConverter for CentsConverter {
+        fn convert(&self, token: String, _column_typing: &TypeColumnEntry) -> Result<Value> {
+            let (units, cents) = token.split_once('.').unwrap_or((&token, "0"));
+            match (units.parse::<i64>(), cents.parse::<i64>()) {
+                (Ok(u), Ok(c)) => Ok(Value::Int64(u * 100 + c)),
+                _ => Err(PattiCsvError::Generic {
+                    msg: format!("not an amount: {}", token),
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_with_custom_column_converter() {
+        let mut test_data_cursor = std::io::Cursor::new("id,amount\n1,9.95\n2,x");
+
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Int64),
+            ])
+            .column_converter(1, Box::new(CentsConverter))
+            .build()
+            .unwrap();
+
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let _headers = iter.next().unwrap().unwrap();
+        let line_1 = iter.next().unwrap().unwrap();
+        assert_eq!(
+            &Value::Int32(1),
+            line_1.get_by_name("id").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Int64(995),
+            line_1.get_by_name("amount").unwrap().get_data()
         );
 
+        let line_2_err = iter.next().unwrap().unwrap_err();
         assert_eq!(
-            DataCellRow {
-                0: vec![
-                    DataCell {
-                        dtype: ValueType::Int32,
-                        idx: 0,
-                        name: String::from("c1"),
-                        data: Value::Int32(1)
-                    },
-                    DataCell {
-                        dtype: ValueType::String,
-                        idx: 1,
-                        name: String::from("col2"),
-                        data: Value::String(String::from("bar"))
-                    },
-                    DataCell {
-                        dtype: ValueType::Bool,
-                        idx: 2,
-                        name: String::from("col3"),
-                        data: Value::Bool(true)
-                    },
-                    DataCell {
-                        dtype: ValueType::String,
-                        idx: 3,
-                        name: String::from("col4"),
-                        data: Value::None
-                    },
-                    DataCell {
-                        dtype: ValueType::Int32,
-                        idx: 4,
-                        name: String::from("c5"),
-                        data: Value::None
-                    },
-                ]
+            PattiCsvError::Generic {
+                msg: String::from("not an amount: x; line: 3; column: 1; header: amount")
             },
-            line_1
-        )
+            line_2_err
+        );
+    }
+
+    #[test]
+    fn parse_with_typings_resolved_by_header() {
+        let mut test_data_cursor =
+            std::io::Cursor::new("ID,quantity ,Price,Comment\n1,10,9.5,foo\n2,20,19.5,bar");
+
+        let parser = PattiCsvParserBuilder::csv()
+            .resolve_typings_by_header(HeaderMatchMode::IgnoreCaseAndWhitespace)
+            .column_typings(vec![
+                TypeColumnEntry::new(Some(String::from("Price")), ValueType::Float64),
+                TypeColumnEntry::new(Some(String::from("Qty")), ValueType::Int32)
+                    .with_header_aliases(vec![String::from("Quantity")]),
+                TypeColumnEntry::new(Some(String::from("id")), ValueType::UInt8),
+            ])
+            .build()
+            .unwrap();
+
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let headers = iter.next().unwrap().unwrap();
+        let line_1 = iter.next().unwrap().unwrap();
+
+        let header_string = headers
+            .into_iter()
+            .map(|e| String::try_from(e.get_data()).unwrap())
+            .collect::<Vec<_>>()
+            .join(",");
+        assert_eq!(String::from("id,Qty,Price,Comment"), header_string);
+
+        assert_eq!(
+            &Value::UInt8(1),
+            line_1.get_by_name("id").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Int32(10),
+            line_1.get_by_name("Qty").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Float64(9.5),
+            line_1.get_by_name("Price").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::String(String::from("foo")),
+            line_1.get_by_name("Comment").unwrap().get_data()
+        );
     }
 
     #[test]
@@ -709,6 +4963,87 @@ mod tests {
         assert_eq!(3, *&iter.get_stats().skipped_lines.len());
     }
 
+    #[test]
+    fn parse_with_skip_lines_from_end() {
+        let parser = PattiCsvParserBuilder::csv()
+            .first_data_line_is_header(true)
+            .stringly_type_columns(2)
+            .skip_take_lines_fns(vec![Box::new(SkipLinesStartingWith::new("#"))])
+            .skip_lines_from_end(2)
+            .save_skipped_lines(true)
+            .build()
+            .unwrap();
+
+        let mut test_data_cursor =
+            std::io::Cursor::new("# comment\nc1,c2\na,1\nb,2\n# total\nsum,3\n");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let res = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(3, res.len()); // incl. header
+        assert_eq!(
+            &Value::from(String::from("b")),
+            res[2].get_by_name("c1").unwrap().get_data()
+        );
+        let stats = iter.get_stats();
+        assert_eq!(6, stats.num_lines_read);
+        assert_eq!(3, stats.num_lines_skipped);
+        assert_eq!(
+            vec![
+                (1, Some(String::from("# comment\n"))),
+                (5, Some(String::from("# total\n"))),
+                (6, Some(String::from("sum,3\n")))
+            ],
+            stats.skipped_lines
+        );
+
+        // fewer lines than to skip
+        let mut test_data_cursor = std::io::Cursor::new("c1,c2");
+        assert!(parser.parse_iter(&mut test_data_cursor).next().is_none());
+    }
+
+    #[test]
+    fn parse_with_comment_char() {
+        let parser = PattiCsvParserBuilder::csv()
+            .first_data_line_is_header(true)
+            .stringly_type_columns(2)
+            .comment_char('#')
+            .skip_take_lines_fns(vec![Box::new(SkipLinesFromStart::new(2))]) // physical lines, incl. comments
+            .save_skipped_lines(true)
+            .build()
+            .unwrap();
+
+        let mut test_data_cursor =
+            std::io::Cursor::new("# comment\ntitle\nc1,c2\na,1\n# note\n #b,2\n");
+        let mut iter = parser.parse_iter(&mut test_data_cursor);
+        let res = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(3, res.len()); // incl. header
+        assert_eq!(
+            &Value::from(String::from(" #b")), // not at the very start
+            res[2].get_by_name("c1").unwrap().get_data()
+        );
+        let stats = iter.get_stats();
+        assert_eq!(2, stats.num_comment_lines);
+        assert_eq!(3, stats.num_lines_skipped);
+        assert_eq!(
+            vec![
+                (1, Some(String::from("# comment\n"))),
+                (2, Some(String::from("title\n"))),
+                (5, Some(String::from("# note\n")))
+            ],
+            stats.skipped_lines
+        );
+        let report = iter.finish_report();
+        assert_eq!(2, report.num_comment_lines);
+        assert_eq!(Some('#'), report.dialect.comment_char);
+
+        assert!(matches!(
+            PattiCsvParserBuilder::csv()
+                .stringly_type_columns(2)
+                .comment_char(',')
+                .build(),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+
     #[test]
     fn test_parser_date_default_patterns() {
         let mut test_data_cursor =
@@ -832,4 +5167,62 @@ mod tests {
             String::try_from(date_time_val).unwrap()
         );
     }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn parse_file() {
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let path =
+            std::env::temp_dir().join(format!("patti_csv_parse_file_{}.csv", std::process::id()));
+        std::fs::write(&path, "\u{feff}id,name\n1,foo\n2,bar").unwrap();
+        let mut iter = parser.parse_file(&path).unwrap();
+        let rows = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(3, rows.len());
+        // the BOM is stripped
+        assert_eq!(
+            &Value::from(String::from("1")),
+            rows[1].get_by_name("id").unwrap().get_data()
+        );
+        assert_eq!(
+            Some(CompressionKind::Plain),
+            iter.finish_report().compression
+        );
+        // the (only) read buffer is sized to the file, within bounds
+        assert_eq!(
+            Some(8 * 1024),
+            DecompressedReader::open(&path, None)
+                .unwrap()
+                .buf_capacity()
+        );
+        std::fs::write(&path, "id,name\n1,foo\n".repeat(2_000)).unwrap();
+        assert_eq!(
+            Some(28_000),
+            DecompressedReader::open(&path, None)
+                .unwrap()
+                .buf_capacity()
+        );
+        assert_eq!(4_000, parser.parse_file(&path).unwrap().count());
+
+        #[cfg(feature = "gzip")]
+        {
+            use std::io::Write;
+
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(b"id,name\n1,foo").unwrap();
+            std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+            let mut iter = parser.parse_file(&path).unwrap();
+            assert_eq!(2, iter.by_ref().filter(|r| r.is_ok()).count());
+            assert_eq!(
+                Some(CompressionKind::Gzip),
+                iter.finish_report().compression
+            );
+        }
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(parser.parse_file(&path).is_err());
+    }
 }
@@ -0,0 +1,263 @@
+use std::{io::Write, str::FromStr};
+
+use serde_json::{Map, Number};
+use venum::{value::Value, value_type::ValueType};
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::errors::{PattiCsvError, Result};
+
+/// Helper method. Numbers, that don't fit a json number (e.g. an `Int128` beyond `i64`), as string.
+fn number_or_string<N>(n: N) -> serde_json::Value
+where
+    N: TryInto<i64> + TryInto<u64> + ToString + Copy,
+{
+    if let Ok(i) = TryInto::<i64>::try_into(n) {
+        return serde_json::Value::from(i);
+    }
+    if let Ok(u) = TryInto::<u64>::try_into(n) {
+        return serde_json::Value::from(u);
+    }
+    serde_json::Value::String(n.to_string())
+}
+
+/// The typed value as json value: numbers and bools as such (non-finite floats as `null`), `Value::None` as `null`,
+/// dates and times as ISO 8601 strings, and everything else in its string form (see: `Value::try_convert_to`).
+/// Decimals are json numbers, too, so mind that most json readers read them as `f64`.
+pub fn value_to_json(value: &Value) -> Result<serde_json::Value> {
+    Ok(match value {
+        Value::None => serde_json::Value::Null,
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::Int8(v) => serde_json::Value::from(*v),
+        Value::Int16(v) => serde_json::Value::from(*v),
+        Value::Int32(v) => serde_json::Value::from(*v),
+        Value::Int64(v) => serde_json::Value::from(*v),
+        Value::Int128(v) => number_or_string(*v),
+        Value::UInt8(v) => serde_json::Value::from(*v),
+        Value::UInt16(v) => serde_json::Value::from(*v),
+        Value::UInt32(v) => serde_json::Value::from(*v),
+        Value::UInt64(v) => serde_json::Value::from(*v),
+        Value::UInt128(v) => number_or_string(*v),
+        Value::Float32(v) => {
+            Number::from_f64(*v as f64).map_or(serde_json::Value::Null, Into::into)
+        }
+        Value::Float64(v) => Number::from_f64(*v).map_or(serde_json::Value::Null, Into::into),
+        Value::Decimal(d) => Number::from_str(&d.to_string())
+            .map_or_else(|_| serde_json::Value::String(d.to_string()), Into::into),
+        Value::NaiveDate(d) => serde_json::Value::String(d.format("%Y-%m-%d").to_string()),
+        Value::NaiveDateTime(d) => {
+            serde_json::Value::String(d.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+        }
+        Value::DateTime(d) => serde_json::Value::String(d.to_rfc3339()),
+        Value::String(s) => serde_json::Value::String(s.clone()),
+        v => v
+            .try_convert_to(&ValueType::String)
+            .and_then(String::try_from)
+            .map(serde_json::Value::String)
+            .map_err(|e| PattiCsvError::Generic {
+                msg: format!("[ERROR_ON_JSON] Value has no string form: {:?}", e),
+            })?,
+    })
+}
+
+/// Helper method. The cells of the row as (name, json value), in the order of the cells. If a name isn't unique, the
+/// first cell with it wins, like with `get_by_name` (see also: `PattiCsvParserBuilder::on_duplicate_headers`).
+fn json_entries(row: &DataCellRow) -> Result<Vec<(&str, serde_json::Value)>> {
+    let mut entries: Vec<(&str, serde_json::Value)> = Vec::with_capacity(row.0.len());
+    for cell in row.0.iter() {
+        if entries.iter().any(|(name, _)| *name == cell.get_name()) {
+            continue;
+        }
+        let value = value_to_json(cell.get_data()).map_err(|e| match e {
+            PattiCsvError::Generic { msg } => PattiCsvError::Generic {
+                msg: format!("{}; header: {}", msg, cell.get_name()),
+            },
+            e => e,
+        })?;
+        entries.push((cell.get_name(), value));
+    }
+    Ok(entries)
+}
+
+/// The row as json object, keyed by the names of the cells (i.e. the final headers). If a name isn't unique, the
+/// first cell with it wins, like with `get_by_name` (see also: `PattiCsvParserBuilder::on_duplicate_headers`).
+pub fn row_to_json(row: &DataCellRow) -> Result<Map<String, serde_json::Value>> {
+    Ok(json_entries(row)?
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect())
+}
+
+/// Writes rows as JSON Lines (aka ndjson), i.e. one json object per row and line (see: `row_to_json`, but the keys are
+/// written in the order of the cells), e.g. for the `NdJson` output format. There is no header, so if the rows come
+/// from a parser, that yields the header row, skip it (or see: `PattiCsvParserBuilder::emit_header_row`).
+#[derive(Debug)]
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+    num_rows: usize,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            num_rows: 0,
+        }
+    }
+
+    pub fn write_row(&mut self, row: &DataCellRow) -> Result<()> {
+        let row_num = self.num_rows + 1;
+        let entries = json_entries(row).map_err(|e| match e {
+            PattiCsvError::Generic { msg } => PattiCsvError::Generic {
+                msg: format!("{}; row: {}", msg, row_num),
+            },
+            e => e,
+        })?;
+        let mut line = String::from("{");
+        for (i, (name, value)) in entries.iter().enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            let name = serde_json::to_string(name).map_err(|e| PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_JSON] Could not write row: {}; row: {}",
+                    e, row_num
+                ),
+            })?;
+            line.push_str(&name);
+            line.push(':');
+            line.push_str(&value.to_string());
+        }
+        line.push_str("}\n");
+        self.writer.write_all(line.as_bytes())?;
+        self.num_rows += 1;
+        Ok(())
+    }
+
+    /// The number of rows written so far.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Writes the (parsed) rows as JSON Lines (see: `JsonLinesWriter`), and returns the number of rows written. Stops at
+/// the first error, i.e. a row, that failed to parse, is returned as such.
+///
+/// ```
+/// use patti_csv::{jsonl::to_jsonl, prelude::*};
+///
+/// let parser = PattiCsvParserBuilder::csv()
+///     .stringly_type_columns(2)
+///     .emit_header_row(false)
+///     .build()
+///     .unwrap();
+/// let mut data = std::io::Cursor::new("id,name\n1,foo");
+/// let mut out = Vec::new();
+/// assert_eq!(1, to_jsonl(parser.parse_iter(&mut data), &mut out).unwrap());
+/// assert_eq!("{\"id\":\"1\",\"name\":\"foo\"}\n", String::from_utf8(out).unwrap());
+/// ```
+pub fn to_jsonl<I, W>(rows: I, writer: W) -> Result<usize>
+where
+    I: IntoIterator<Item = Result<DataCellRow>>,
+    W: Write,
+{
+    let mut jsonl_writer = JsonLinesWriter::new(writer);
+    for row in rows {
+        jsonl_writer.write_row(&row?)?;
+    }
+    jsonl_writer.flush()?;
+    Ok(jsonl_writer.num_rows())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use crate::{iterating_parser::PattiCsvParserBuilder, parser_config::TypeColumnEntry};
+
+    use super::*;
+
+    #[test]
+    fn typed_json_values() {
+        assert_eq!(
+            serde_json::json!(null),
+            value_to_json(&Value::None).unwrap()
+        );
+        assert_eq!(
+            serde_json::json!(-3),
+            value_to_json(&Value::Int128(-3)).unwrap()
+        );
+        assert_eq!(
+            serde_json::json!(u128::MAX.to_string()),
+            value_to_json(&Value::UInt128(u128::MAX)).unwrap()
+        );
+        assert_eq!(
+            serde_json::json!(null),
+            value_to_json(&Value::Float64(f64::NAN)).unwrap()
+        );
+        assert_eq!(
+            serde_json::json!("2021-01-03T10:30:00"),
+            value_to_json(&Value::NaiveDateTime(
+                NaiveDateTime::parse_from_str("2021-01-03 10:30:00", "%Y-%m-%d %H:%M:%S").unwrap()
+            ))
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_to_jsonl() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Float64),
+                TypeColumnEntry::new(None, ValueType::Bool),
+                TypeColumnEntry::new(None, ValueType::NaiveDate),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .emit_header_row(false)
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new(
+            "id,amount,paid,due,note\n1,12.5,true,2021-01-03,\"say \"\"hi\"\"\"\n2,,false,,",
+        );
+        let mut out = Vec::new();
+        assert_eq!(
+            2,
+            to_jsonl(parser.parse_iter(&mut test_data_cursor), &mut out).unwrap()
+        );
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(
+            r#"{"id":1,"amount":12.5,"paid":true,"due":"2021-01-03","note":"say \"hi\""}"#,
+            lines[0]
+        );
+        assert_eq!(
+            r#"{"id":2,"amount":null,"paid":false,"due":null,"note":null}"#,
+            lines[1]
+        );
+
+        let mut test_data_cursor = std::io::Cursor::new("id,amount,paid,due,note\nx,,,,");
+        assert!(to_jsonl(parser.parse_iter(&mut test_data_cursor), Vec::new()).is_err());
+
+        let object = row_to_json(
+            &parser
+                .parse_iter(&mut std::io::Cursor::new(
+                    "id,amount,paid,due,note\n3,,,2021-01-03,",
+                ))
+                .next()
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Some(&serde_json::json!(3)), object.get("id"));
+        assert_eq!(Some(&serde_json::json!("2021-01-03")), object.get("due"));
+    }
+}
@@ -1,8 +1,138 @@
+//! Parse, type, transform and iterate CSV.
+//!
+//! The crate is layered: the core is always there, everything else is behind a feature. The default features
+//! (`jsonconf`, `io`, `sinks` and `unicode`) give the whole crate as it was before the split, so embedders that need
+//! less turn them off (`default-features = false`) and pick what they need.
+//!
+//! - **core** (always, needs `regex`, `chrono` and `rust_decimal`): the parser (`iterating_parser`), its config
+//!   (`parser_config`), tokenizing (`line_tokenizer`, `skip_take_lines`), reading the data as UTF-8 (`encoding`),
+//!   sanitizing (`transform_sanitize_token`), typing (`cell_converter`), transforming the typed values
+//!   (`value_transformer`), progress callbacks (`progress_observer`), fixed-width data (`fixed_width`), date parts and
+//!   split parts as new columns (`derived_columns`), the errors and reports, the custom stages and sinks (`pipeline`),
+//!   the output description (`output`), case-insensitive access to the cells of parsed rows (`row_ext`), typing
+//!   stringly parsed rows afterwards (`retype`), dry runs over the data (`validation`), constraints of the typed values
+//!   (`constraints`), per-column statistics while parsing (`column_stats`), column oriented data, also in chunks
+//!   (`columnar`), and sniffing the dialect of unknown data (`sniff`).
+//! - **config** (feature `jsonconf`): configuring the parser via json (`conf`), and json reports. The same
+//!   configuration as yaml (feature `yamlconf`) or toml (feature `tomlconf`). A config can be inferred from a sample
+//!   of the data (`conf::infer`).
+//! - **serde** (feature `serde`, part of `jsonconf`): deserializing rows into user types (`deserialize`), and writing
+//!   them as JSON Lines (`jsonl`, feature `serde_json`, part of `jsonconf`).
+//! - **io** (feature `io`): the row index (`index`, see: `PattiCsvParser::indexed_reader`), and opening files, that
+//!   may be compressed (`compression`, see: `PattiCsvParser::parse_file`), with decoders behind the features `gzip`,
+//!   `zstd` and `zip` (which turn on `io`). Other adapters have a feature of their own: a progress bar (`progress`,
+//!   feature `indicatif`), async channels (`async_sink`, features `tokio` and/or `async_channel`), async readers as
+//!   streams of rows (`async_stream`, feature `async`), arrow record batches (`arrow_batch`, feature `arrow`), and
+//!   Parquet files (`parquet_sink`, feature `parquet`).
+//! - **sinks** (feature `sinks`): writing typed rows (`writer`) and untyped records (`record_writer`) as csv,
+//!   rendering rows as text (`render`), and routing rows into several sinks (`router_sink`).
+//! - **unicode** (feature `unicode`): substrings by graphemes (`Substring::graphemes`) and Unicode normalization
+//!   (`NormalizeUnicode`) of the tokens. Without it, a config that uses them is an error.
+//! - **testing** (feature `testing`, not a default): helpers and assertion macros for the tests of custom transitizers
+//!   and line filters (`testing`).
+//!
+//! Text is UTF-8 (a leading BOM is skipped), other encodings are transcoded into UTF-8 while reading (`encoding`,
+//! feature `encoding`). It's handled by chars (i.e. Unicode scalar values), never by bytes: the separator and the
+//! enclosure char may be any char (the record separator must be ASCII), and nothing (tokenizing, trimming,
+//! `Substring`, writing) ever cuts a char in half. Graphemes are not taken into account, unless asked for
+//! (`Substring::graphemes`, feature `unicode`), so a combining mark right after a separator (e.g. the breve of "y̆")
+//! starts the next token, and literals (e.g. of `Eradicate`) match chars. Trims remove Unicode whitespace (e.g. a no-break space), but
+//! not zero-width chars. Nothing is normalized, i.e. "é" and "e" followed by a combining acute accent differ.
+//!
+//! The common entry points are re-exported in the `prelude`:
+//!
+//! ```
+//! use patti_csv::prelude::*;
+//!
+//! let parser = PattiCsvParserBuilder::csv()
+//!     .stringly_type_columns(2)
+//!     .build()
+//!     .unwrap();
+//! let mut data = std::io::Cursor::new("id,name\n1,foo");
+//! let rows = parser.parse_iter(&mut data).collect::<Result<Vec<_>>>().unwrap();
+//! assert_eq!(2, rows.len()); // incl. the header row
+//! ```
+
+#[cfg(feature = "arrow")]
+pub mod arrow_batch;
+#[cfg(any(feature = "tokio", feature = "async_channel"))]
+pub mod async_sink;
+#[cfg(feature = "async")]
+pub mod async_stream;
+pub mod cell_converter;
+pub mod column_stats;
+pub mod columnar;
+#[cfg(feature = "io")]
+pub mod compression;
 pub mod conf;
+pub mod constraints;
+pub mod derived_columns;
+#[cfg(feature = "serde")]
+pub mod deserialize;
+pub mod encoding;
 pub mod errors;
+pub mod fixed_width;
+#[cfg(feature = "io")]
+pub mod index;
+pub mod inference;
 pub mod iterating_parser;
+#[cfg(feature = "serde_json")]
+pub mod jsonl;
 pub mod line_tokenizer;
+pub mod money;
+pub mod output;
+#[cfg(feature = "parquet")]
+pub mod parquet_sink;
 pub mod parser_common;
 pub mod parser_config;
+pub mod pipeline;
+pub mod preamble;
+pub mod probe;
+#[cfg(feature = "indicatif")]
+pub mod progress;
+pub mod progress_observer;
+#[cfg(feature = "sinks")]
+pub mod record_writer;
+#[cfg(feature = "sinks")]
+pub mod render;
+pub mod report;
+pub mod retype;
+#[cfg(feature = "sinks")]
+pub mod router_sink;
+pub mod row_ext;
+pub mod sample;
+pub mod schema_evolution;
+pub mod simple_row;
 pub mod skip_take_lines;
+pub mod sniff;
+pub mod split_columns;
+pub mod stream_diff;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod time_converter;
+pub mod token_storage;
 pub mod transform_sanitize_token;
+pub mod validation;
+pub mod value_transformer;
+#[cfg(feature = "sinks")]
+pub mod writer;
+
+/// The common entry points, i.e. `use patti_csv::prelude::*;`. Everything else stays in its module.
+pub mod prelude {
+    #[cfg(feature = "jsonconf")]
+    pub use crate::conf::jsonconf::ConfigRoot;
+    #[cfg(feature = "sinks")]
+    pub use crate::writer::{PattiCsvWriter, PattiCsvWriterBuilder, QuotingPolicy};
+    pub use crate::{
+        errors::{PattiCsvError, Result},
+        iterating_parser::{PattiCsvParser, PattiCsvParserBuilder, PattiCsvParserIterator},
+        parser_config::{NullPolicy, TypeColumnEntry, VecOfTokenTransitizers},
+        pipeline::{Pipeline, RowSink, RowStage, TokenStage},
+        report::ParseReport,
+        row_ext::DataCellRowExt,
+        skip_take_lines::SkipTakeLines,
+        transform_sanitize_token::TransformSanitizeToken,
+        validation::ValidationReport,
+        value_transformer::ValueTransformer,
+    };
+}
@@ -1,16 +1,24 @@
-use compact_str::CompactString;
 use std::{
-    collections::VecDeque,
-    io::{BufRead, BufReader, Read},
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    io::{self, BufRead, BufReader, Read},
+    time::Duration,
 };
 
+use super::encoding::{RawData, Source};
 use super::errors::{PattiCsvError, Result, TokenizerError};
+use super::parser_config::{below_cap, MemoryBudget, Preamble};
+use super::preamble::PreambleState;
+use super::progress_observer::{BoxedProgressObserver, ProgressInterval, ProgressState};
+use super::report::ComponentInfo;
 use super::skip_take_lines::SkipTakeLines;
+use super::token_storage::{TokenBuf, TokenStorage};
 
 // = UTF-8-BOM = EF BB BF = 239, 187, 191 as uint8 = UCS character U+FEFF "ZERO WIDTH NO-BREAK SPACE"
 // see https://www.rfc-editor.org/rfc/rfc3629#page-6
 // see https://philbooth.gitlab.io/unicode-bom/unicode_bom/
-const UTF8BOM: [u8; 3] = [239, 187, 191];
+pub(crate) const UTF8BOM: [u8; 3] = [239, 187, 191];
+pub(crate) const DEFAULT_READ_BUF_CAPACITY: usize = 8 * 1024; // like std's BufReader
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DelimitedLineTokenizerStats {
@@ -18,7 +26,48 @@ pub struct DelimitedLineTokenizerStats {
     pub num_lines_read: usize,      // needed for internal state while iterating
     pub num_lines_tokenized: usize, // needed for internal state while iterating
     pub skipped_lines: Vec<(usize, Option<String>)>,
+    pub skipped_lines_by_filter: HashMap<usize, usize>, // index of the (first) skip filter that skipped the line -> number of lines
+    pub skipped_lines_metadata: HashMap<String, String>, // see: SkipTakeLines::metadata (later lines overwrite earlier ones)
     pub bytes_read: usize,
+    pub num_duplicate_rows: usize, // only counted, if a DuplicateRowPolicy is configured
+    pub num_rows_filtered: usize,  // dropped by a (custom) RowStage
+    pub num_rows_err: usize,       // counted by the parser, so far (see: ProgressObserver)
+    pub dropped_rows: Vec<(usize, String)>, // line number and reason of the rows dropped by a transitizer (see: PattiCsvError::DropRow)
+    pub stage_timings: Option<StageTimings>, // only measured, if enabled on the parser
+    pub stripped_tokens: Vec<(usize, usize, String)>, // line number, column and what was stripped by a tolerant numeric column (only if it records them)
+    pub num_lines_skipped: usize,                     // exact, even if skipped_lines is truncated
+    pub num_comment_lines: usize, // skipped because of the comment character (also counted in num_lines_skipped)
+    pub num_rows_dropped: usize,  // exact, even if dropped_rows is truncated
+    pub saved_skipped_lines_bytes: usize,
+    pub truncated_buffers: TruncatedBuffers, // see: MemoryBudget
+}
+
+/// Which buffers of the stats reached their cap (see: `MemoryBudget`), i.e. are incomplete.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TruncatedBuffers {
+    pub skipped_lines: bool, // entries, or the saved lines themselves
+    pub dropped_rows: bool,
+    pub stripped_tokens: bool,
+}
+
+/// The (summed up) time spent in the stages of a parse run, to see where the time goes for a given config and data.
+/// This is opt-in, since measuring has its own (small) cost on every line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct StageTimings {
+    /// Reading, skipping/taking lines and tokenizing.
+    pub tokenize: Duration,
+    /// Custom token stages (see: `pipeline::TokenStage`).
+    pub token_stages: Duration,
+    /// The configured transitizers. (The smart sanitizer defaults are part of `convert`.)
+    pub sanitize: Duration,
+    /// Converting the tokens into typed values, incl. the built-in row checks (e.g. money columns).
+    pub convert: Duration,
+    /// Custom row stages (see: `pipeline::RowStage`).
+    pub row_stages: Duration,
 }
 
 impl DelimitedLineTokenizerStats {
@@ -28,7 +77,20 @@ impl DelimitedLineTokenizerStats {
             num_lines_read: 0,
             num_lines_tokenized: 0,
             skipped_lines: Vec::with_capacity(5),
+            skipped_lines_by_filter: HashMap::new(),
+            skipped_lines_metadata: HashMap::new(),
             bytes_read: 0,
+            num_duplicate_rows: 0,
+            num_rows_filtered: 0,
+            num_rows_err: 0,
+            dropped_rows: Vec::new(),
+            stage_timings: None,
+            stripped_tokens: Vec::new(),
+            num_lines_skipped: 0,
+            num_comment_lines: 0,
+            num_rows_dropped: 0,
+            saved_skipped_lines_bytes: 0,
+            truncated_buffers: TruncatedBuffers::default(),
         }
     }
     pub fn is_at_first_unskipped_line_to_parse(&self) -> bool {
@@ -50,13 +112,111 @@ enum State {
     QuoteInQuotedField, // we need this to do proper escape checking of the enclosure character
 }
 
+/// Strips the line terminator ("\n" or "\r\n") and nothing else.
+pub(crate) fn strip_line_terminator(line: &str) -> &str {
+    match line.strip_suffix('\n') {
+        Some(l) => l.strip_suffix('\r').unwrap_or(l),
+        None => line,
+    }
+}
+
+/// What the tokenizer knows about a token, besides its content. See: `DelimitedLineTokenizerIter::token_meta`. It's
+/// always collected (it's two flags per token), but only copied for typing, if a column uses it (see:
+/// `QuotedTokenPolicy` and `NullPolicy::quoted_empty_is_empty_string`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TokenMeta {
+    pub was_quoted: bool, // e.g. to tell an explicitly empty `""` from nothing between the separators
+    pub had_escapes: bool, // a (quoted) token with escaped enclosure characters, e.g. `"say ""hi"""`
+}
+
+/// The token we're currently in. We know for sure it exists (i.e. is the last one), when we're in any field state, since
+/// we only get there after pushing it. Still, we don't want to panic, if this is ever violated.
+fn curr_token(buf: &mut [TokenBuf], line_num: usize) -> Result<&mut TokenBuf> {
+    debug_assert!(!buf.is_empty());
+    buf.last_mut().ok_or_else(|| PattiCsvError::Generic {
+        msg: format!("[ERROR_ON_TOKENIZE] No current token; line: {}", line_num),
+    })
+}
+
+/// Splits one line (i.e. record, without its terminator) into its tokens. The `DelimitedLineTokenizer` does all the
+/// rest, i.e. reading the records, skipping lines, the stats etc., and by default it also splits the lines itself, by
+/// separator and enclosure. Another one can be plugged in, e.g. for fixed-width data (see: `FixedWidthLineTokenizer`
+/// and `PattiCsvParserBuilder::line_tokenizer`). Its tokens have no metadata, i.e. none counts as quoted.
+pub trait LineTokenizer: Debug {
+    fn tokenize_line(&self, line_num: usize, line: &str) -> Result<VecDeque<String>>;
+    fn get_self_info(&self) -> String {
+        String::from("n/a")
+    }
+    /// The stable id of this kind of tokenizer. See: `ComponentInfo`
+    fn kind(&self) -> &'static str {
+        "custom"
+    }
+    /// The kind and the parameters, e.g. for reports.
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind())
+    }
+}
+
+pub type BoxedLineTokenizer = Box<dyn LineTokenizer + Send + Sync>;
+
 #[derive(Debug)]
 pub struct DelimitedLineTokenizer {
-    max_inline_str_size: usize, // helper for compact string. This is the max that can get stack allocated. CompactString::with_capacity(0) does actually exactly this we well.
+    max_inline_str_size: usize, // helper for the token storage (e.g. compact string). This is the max that can get stack allocated. CompactString::with_capacity(0) does actually exactly this we well.
     save_skipped_lines: bool,
+    pub trim_trailing_whitespace: bool, // old behaviour: the (raw) line is trimmed at the end (`trim_end`) before it's tokenized
+    pub record_separator: Option<char>, // None means: records are lines. Otherwise records may span lines (must be ASCII)
+    pub preamble: Option<Preamble>, // key-value metadata lines at the start, before the skip filters
     pub delim_char: char,
     pub encl_char: Option<char>,
+    pub detect_header_enclosure: bool, // only without encl_char: strip consistent quoting of the first (i.e. header) line
     pub skip_take_lines_fns: Option<Vec<Box<dyn SkipTakeLines + Send + Sync>>>, // needed here to skip lines while iterating
+    pub memory_budget: MemoryBudget, // caps for the buffers of the stats
+    pub skip_lines_from_end: usize, // the last n records (e.g. a footer) are skipped. Needs a lookahead of n records
+    pub line_tokenizer: Option<BoxedLineTokenizer>, // splits the lines instead of the separator and enclosure
+    pub comment_char: Option<char>, // lines starting with it are skipped, before the skip filters
+    pub strip_bom: bool,            // a leading BOM is removed (default)
+    #[cfg(feature = "encoding")]
+    pub encoding: Option<&'static encoding_rs::Encoding>, // None means UTF-8 as is, otherwise it's transcoded into UTF-8
+    pub progress_observer: Option<(BoxedProgressObserver, ProgressInterval)>,
+}
+
+/// Helper for `read_record`, to know whether a record continues after a newline (or record separator). Like the
+/// tokenizer, an enclosure only starts at the start of a field, so a stray enclosure character within an unenclosed
+/// field (e.g. `a"b`) doesn't swallow the following lines.
+#[derive(Debug)]
+struct EnclosureState {
+    in_enclosure: bool,
+    at_field_start: bool,
+    after_closing: bool, // i.e. another enclosure character is an escaped one
+}
+
+impl Default for EnclosureState {
+    fn default() -> Self {
+        Self {
+            in_enclosure: false,
+            at_field_start: true,
+            after_closing: false,
+        }
+    }
+}
+
+impl EnclosureState {
+    fn scan(&mut self, chunk: &str, delim: char, encl: char) {
+        for c in chunk.chars() {
+            if self.in_enclosure {
+                if c == encl {
+                    self.in_enclosure = false;
+                    self.after_closing = true;
+                }
+                continue;
+            }
+            if c == encl && (self.at_field_start || self.after_closing) {
+                self.in_enclosure = true;
+            }
+            self.at_field_start = c == delim;
+            self.after_closing = false;
+        }
+    }
 }
 
 impl DelimitedLineTokenizer {
@@ -69,9 +229,21 @@ impl DelimitedLineTokenizer {
         DelimitedLineTokenizer {
             max_inline_str_size: std::mem::size_of::<String>(),
             save_skipped_lines,
+            trim_trailing_whitespace: false,
+            record_separator: None,
+            preamble: None,
             delim_char: delim,
             encl_char: enclc,
+            detect_header_enclosure: false,
             skip_take_lines_fns,
+            memory_budget: MemoryBudget::default(),
+            skip_lines_from_end: 0,
+            line_tokenizer: None,
+            comment_char: None,
+            strip_bom: true,
+            #[cfg(feature = "encoding")]
+            encoding: None,
+            progress_observer: None,
         }
     }
 
@@ -93,28 +265,183 @@ impl DelimitedLineTokenizer {
         &'dlt self,
         data: &'rd mut R,
     ) -> DelimitedLineTokenizerIter<'dlt, 'rd, R> {
-        DelimitedLineTokenizerIter::new(self, data)
+        DelimitedLineTokenizerIter::new(self, Source::Borrowed(data), DEFAULT_READ_BUF_CAPACITY)
+    }
+
+    /// Like `tokenize_iter`, but the iterator owns the data, e.g. a file it was opened for, and reads it through a buffer
+    /// of the given capacity (e.g. sized to the file).
+    #[cfg(feature = "io")]
+    pub(crate) fn tokenize_iter_owned<R: Read + 'static>(
+        &self,
+        data: R,
+        buf_capacity: usize,
+    ) -> DelimitedLineTokenizerIter<'_, 'static, R> {
+        DelimitedLineTokenizerIter::new(self, Source::Owned(data), buf_capacity)
+    }
+
+    /// Reads the next record (including its terminator) into `buf` and returns the number of bytes read (0 at the end).
+    /// The record ends at the first newline (or record separator, if there is one) outside of an enclosure, i.e. an
+    /// enclosed field may span several lines (RFC 4180). With a record separator, records may also contain unenclosed
+    /// newlines. NOTE: An unterminated enclosure consumes the rest of the data.
+    ///
+    /// The first record of the data may start with a BOM, which doesn't count as the start of the first field (if it's
+    /// stripped, see: `strip_bom`), e.g. for Excel exports with an enclosed first field.
+    pub(crate) fn read_record<B: BufRead>(
+        &self,
+        reader: &mut B,
+        buf: &mut String,
+        first_record: bool,
+    ) -> io::Result<usize> {
+        let sep = match (self.record_separator, self.encl_char) {
+            (None, None) => return reader.read_line(buf),
+            (None, Some(_)) => b'\n',
+            (Some(sep), _) => sep as u8, // checked to be ASCII when building the parser
+        };
+
+        let mut bytes = Vec::new();
+        let mut state = EnclosureState::default();
+        loop {
+            let chunk_start = bytes.len();
+            if reader.read_until(sep, &mut bytes)? == 0 {
+                break;
+            }
+            // Every chunk is valid UTF-8 on its own (if the data is), since it always ends on an ASCII byte.
+            if let Some(encl) = self.encl_char {
+                let mut chunk = std::str::from_utf8(&bytes[chunk_start..])
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                if first_record && chunk_start == 0 && self.strip_bom {
+                    chunk = chunk.strip_prefix('\u{feff}').unwrap_or(chunk);
+                }
+                state.scan(chunk, self.delim_char, encl);
+            }
+            if !state.in_enclosure || bytes.last() != Some(&sep) {
+                break;
+            }
+        }
+
+        let num_bytes = bytes.len();
+        buf.push_str(
+            &String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        );
+        Ok(num_bytes)
+    }
+
+    /// Strips the terminator of a record read through `read_record`, i.e. the line terminator or the record separator.
+    pub(crate) fn strip_record_terminator<'a>(&self, record: &'a str) -> &'a str {
+        match self.record_separator {
+            None => strip_line_terminator(record),
+            Some(sep) => record.strip_suffix(sep).unwrap_or(record),
+        }
     }
 
-    fn skip_line_by_skiptake_sanitizer(&self, line_counter: usize, line: &str) -> bool {
+    /// Whether the line starts with the comment character (if one is set). Only at the very start, i.e. not after
+    /// leading whitespace, and not within a record that spans lines.
+    pub(crate) fn is_comment_line(&self, line: &str) -> bool {
+        self.comment_char.is_some_and(|c| line.starts_with(c))
+    }
+
+    #[cfg(feature = "io")] // only the index needs it
+    pub(crate) fn skip_line_by_skiptake_sanitizer(&self, line_counter: usize, line: &str) -> bool {
+        self.skipping_filter_idx(line_counter, line).is_some()
+    }
+
+    /// The metadata of a line, that was skipped by the filter with the given index.
+    pub(crate) fn skipped_line_metadata(
+        &self,
+        filter_idx: usize,
+        line: &str,
+    ) -> Vec<(String, String)> {
+        self.skip_take_lines_fns
+            .as_ref()
+            .and_then(|f| f.get(filter_idx))
+            .map(|f| f.metadata(self.strip_record_terminator(line)))
+            .unwrap_or_default()
+    }
+
+    /// Index of the first skip filter, that wants to skip this line (if any).
+    pub(crate) fn skipping_filter_idx(&self, line_counter: usize, line: &str) -> Option<usize> {
         // If we have filters, we apply them and see if we need to skip this line.
-        if let Some(ref skip_take_lines) = self.skip_take_lines_fns {
-            skip_take_lines
-                .iter()
-                .any(|filter| filter.skip(line_counter, line))
-        } else {
-            // If we have no filters, well, then don't skip anything.
-            false
+        // If we have no filters, well, then don't skip anything.
+        self.skip_take_lines_fns
+            .as_ref()?
+            .iter()
+            .position(|filter| filter.skip(line_counter, line))
+    }
+
+    /// Helper method. The line to tokenize, i.e. trimmed at the end, if we're configured to trim trailing whitespace.
+    fn trim_line<'a>(&self, s: &'a str) -> &'a str {
+        match self.trim_trailing_whitespace {
+            true => s.trim_end(),
+            false => s,
         }
     }
 
     fn tokenize_inner(
         &self,
-        buf: &mut Vec<CompactString>,
+        buf: &mut Vec<TokenBuf>,
+        meta: &mut Vec<TokenMeta>,
+        line_num: usize,
+        s: &str,
+    ) -> Result<VecDeque<String>> {
+        let s = self.trim_line(s);
+        match &self.line_tokenizer {
+            Some(lt) => {
+                let tokens = lt.tokenize_line(line_num, s)?;
+                meta.clear();
+                meta.resize(tokens.len(), TokenMeta::default());
+                Ok(tokens)
+            }
+            None => self.tokenize_with_encl(buf, meta, line_num, s, self.encl_char),
+        }
+    }
+
+    /// Tokenizes the header line. Same as `tokenize_inner`, unless we detect the enclosure of the header (see:
+    /// `detect_header_enclosure`): A header line that starts and ends with `"` (or `'`) is tokenized with that
+    /// enclosure character, if this gives us consistently enclosed tokens, i.e. every token is enclosed.
+    pub(crate) fn tokenize_header(
+        &self,
+        buf: &mut Vec<TokenBuf>,
+        meta: &mut Vec<TokenMeta>,
+        line_num: usize,
+        s: &str,
+    ) -> Result<VecDeque<String>> {
+        let s = self.trim_line(s);
+        if self.detect_header_enclosure && self.encl_char.is_none() && self.line_tokenizer.is_none()
+        {
+            for encl in ['"', '\''] {
+                if s.len() < 2 || !s.starts_with(encl) || !s.ends_with(encl) {
+                    continue;
+                }
+                let res = self.tokenize_with_encl(buf, meta, line_num, s, Some(encl));
+                buf.clear();
+                if let Ok(tokens) = res {
+                    // Every enclosed token has two enclosure characters, plus one for every escaped one. Summed up
+                    // with the tokens and the separators, this only is the length of the line, if all are enclosed.
+                    let len_enclosed = tokens
+                        .iter()
+                        .map(|t| t.chars().count() + 2 + t.matches(encl).count())
+                        .sum::<usize>()
+                        + tokens.len().saturating_sub(1);
+                    if len_enclosed == s.chars().count() {
+                        return Ok(tokens);
+                    }
+                }
+            }
+        }
+        self.tokenize_inner(buf, meta, line_num, s)
+    }
+
+    /// Tokenizes into `buf`, and records the metadata of every token in `meta` (which is cleared first).
+    fn tokenize_with_encl(
+        &self,
+        buf: &mut Vec<TokenBuf>,
+        meta: &mut Vec<TokenMeta>,
         line_num: usize,
         s: &str,
+        encl_char: Option<char>,
     ) -> Result<VecDeque<String>> {
         let mut state = State::Start;
+        meta.clear();
 
         // A small FSM here...
         for c in s.chars() {
@@ -123,48 +450,57 @@ impl DelimitedLineTokenizer {
                     _ if c == self.delim_char => {
                         State::Scan // ready for next field
                     }
-                    _ if Some(c) == self.encl_char => {
+                    _ if Some(c) == encl_char => {
                         return Err(PattiCsvError::Tokenize(TokenizerError::IllegalEnclChar {
                             line: line_num,
                             token_num: buf.len(),
                         }))
                     }
                     _ => {
-                        buf.last_mut().unwrap().push(c); // we know for sure, this is the last index and it exists!
+                        curr_token(buf, line_num)?.push_char(c);
                         State::Field
                     }
                 },
                 State::QuotedField => match c {
-                    _ if Some(c) == self.encl_char => State::QuoteInQuotedField,
+                    _ if Some(c) == encl_char => State::QuoteInQuotedField,
                     _ => {
-                        buf.last_mut().unwrap().push(c); // we know for sure, this is the last index and it exists!
+                        curr_token(buf, line_num)?.push_char(c);
                         State::QuotedField
                     }
                 },
                 State::Scan | State::Start => match c {
                     _ if c == self.delim_char => {
                         // this means: empty field at start
-                        buf.push(CompactString::with_capacity(self.max_inline_str_size));
+                        buf.push(TokenBuf::new_token(self.max_inline_str_size));
+                        meta.push(TokenMeta::default());
                         State::Scan
                     }
-                    _ if Some(c) == self.encl_char => {
+                    _ if Some(c) == encl_char => {
                         // enclosure symbol (start) found
-                        buf.push(CompactString::with_capacity(self.max_inline_str_size));
+                        buf.push(TokenBuf::new_token(self.max_inline_str_size));
+                        meta.push(TokenMeta {
+                            was_quoted: true,
+                            had_escapes: false,
+                        });
                         State::QuotedField
                     }
                     _ => {
                         // start of regular, un-enclosed field
-                        let mut cs = CompactString::with_capacity(self.max_inline_str_size);
-                        cs.push(c);
+                        let mut cs = TokenBuf::new_token(self.max_inline_str_size);
+                        cs.push_char(c);
                         buf.push(cs);
+                        meta.push(TokenMeta::default());
                         State::Field
                     }
                 },
                 State::QuoteInQuotedField => match c {
                     _ if c == self.delim_char => State::Scan, // enlosure closed, ready for next field
-                    _ if Some(c) == self.encl_char => {
+                    _ if Some(c) == encl_char => {
                         // enclosure character escaped successfully
-                        buf.last_mut().unwrap().push(c); // we know for sure, this is the last index and it exists!
+                        curr_token(buf, line_num)?.push_char(c);
+                        if let Some(m) = meta.last_mut() {
+                            m.had_escapes = true;
+                        }
                         State::QuotedField
                     }
                     _ => {
@@ -183,7 +519,8 @@ impl DelimitedLineTokenizer {
         //    we'd need to end on State:QuoteInQuotedField instead.
         match state {
             State::Scan => {
-                buf.push(CompactString::new(""));
+                buf.push(TokenBuf::new_token(self.max_inline_str_size));
+                meta.push(TokenMeta::default());
             }
             State::QuotedField => {
                 return Err(PattiCsvError::Tokenize(TokenizerError::UnescapedEnclChar {
@@ -196,37 +533,160 @@ impl DelimitedLineTokenizer {
 
         let mut res: VecDeque<String> = VecDeque::with_capacity(buf.len());
         buf.iter()
-            .for_each(|cs| res.push_back(String::from(cs.as_str())));
+            .for_each(|cs| res.push_back(String::from(cs.as_token_str())));
 
         Ok(res)
     }
 
     pub fn tokenize(&self, line_num: usize, s: &str) -> Result<VecDeque<String>> {
-        let mut buf: Vec<CompactString> = Vec::with_capacity(10);
-        self.tokenize_inner(&mut buf, line_num, s)
+        Ok(self.tokenize_with_meta(line_num, s)?.0)
+    }
+
+    /// Same as `tokenize`, plus the metadata of every token.
+    pub fn tokenize_with_meta(
+        &self,
+        line_num: usize,
+        s: &str,
+    ) -> Result<(VecDeque<String>, Vec<TokenMeta>)> {
+        let mut buf: Vec<TokenBuf> = Vec::with_capacity(10);
+        let mut meta = Vec::with_capacity(10);
+        let tokens = self.tokenize_inner(&mut buf, &mut meta, line_num, s)?;
+        Ok((tokens, meta))
+    }
+}
+
+/// Splits by separator and enclosure only, i.e. never by the plugged in `line_tokenizer`.
+impl LineTokenizer for DelimitedLineTokenizer {
+    fn tokenize_line(&self, line_num: usize, line: &str) -> Result<VecDeque<String>> {
+        let mut buf: Vec<TokenBuf> = Vec::with_capacity(10);
+        let line = self.trim_line(line);
+        self.tokenize_with_encl(&mut buf, &mut Vec::new(), line_num, line, self.encl_char)
+    }
+    fn get_self_info(&self) -> String {
+        format!(
+            "DelimitedLineTokenizer {{ delim_char: {:?}, encl_char: {:?} }}",
+            self.delim_char, self.encl_char
+        )
+    }
+    fn kind(&self) -> &'static str {
+        "delimited"
+    }
+    fn describe(&self) -> ComponentInfo {
+        let info = ComponentInfo::new(self.kind()).with_param("separator", self.delim_char);
+        match self.encl_char {
+            Some(c) => info.with_param("enclosure", c),
+            None => info,
+        }
     }
 }
 
 pub struct DelimitedLineTokenizerIter<'dlt, 'rd, R: Read> {
     dlt: &'dlt DelimitedLineTokenizer,
-    buf_raw_data: BufReader<&'rd mut R>,
-    line_token_buf: Vec<CompactString>,
+    buf_raw_data: BufReader<RawData<'rd, R>>,
+    line_token_buf: Vec<TokenBuf>,
+    line_token_meta: Vec<TokenMeta>,
     stats: DelimitedLineTokenizerStats,
+    preamble: PreambleState<'dlt>,
+    lookahead: VecDeque<(String, usize)>, // records (and their bytes) read ahead, see: skip_lines_from_end
+    lookahead_eof: bool,
+    progress: ProgressState,
 }
 
 impl<'dlt, 'rd, R: Read> DelimitedLineTokenizerIter<'dlt, 'rd, R> {
-    fn new(dlt: &'dlt DelimitedLineTokenizer, data: &'rd mut R) -> Self {
+    fn new(dlt: &'dlt DelimitedLineTokenizer, data: Source<'rd, R>, buf_capacity: usize) -> Self {
         Self {
             dlt,
-            buf_raw_data: BufReader::new(data),
+            buf_raw_data: BufReader::with_capacity(buf_capacity, RawData::new(dlt, data)),
             stats: DelimitedLineTokenizerStats::default(),
             line_token_buf: Vec::with_capacity(10), // we default hard to 10 because, well, we gotta start somewhere
+            line_token_meta: Vec::with_capacity(10),
+            preamble: PreambleState::new(dlt.preamble.as_ref()),
+            lookahead: VecDeque::with_capacity(dlt.skip_lines_from_end + 1),
+            lookahead_eof: false,
+            progress: ProgressState::default(),
         }
     }
 
+    /// The key-value pairs of the preamble (if one is configured). Complete, once the first line was tokenized.
+    pub fn preamble(&self) -> &HashMap<String, String> {
+        &self.preamble.values
+    }
+
     pub fn get_stats(&self) -> &DelimitedLineTokenizerStats {
         &self.stats
     }
+
+    /// The metadata of the tokens of the line that was tokenized last, one entry per token.
+    pub fn token_meta(&self) -> &[TokenMeta] {
+        &self.line_token_meta
+    }
+
+    pub(crate) fn get_stats_mut(&mut self) -> &mut DelimitedLineTokenizerStats {
+        &mut self.stats
+    }
+
+    /// Helper method. Reads the next record into `line`, like `read_record`. With `skip_lines_from_end`, the records are
+    /// read n records ahead, so that the last n of them are never handed out, but recorded as skipped lines once the end
+    /// of the data is reached.
+    fn read_next_record(&mut self, line: &mut String) -> io::Result<usize> {
+        let n = self.dlt.skip_lines_from_end;
+        if n == 0 {
+            let first_record = self.stats.num_lines_read == 0;
+            return self
+                .dlt
+                .read_record(&mut self.buf_raw_data, line, first_record);
+        }
+        while self.lookahead.len() <= n && !self.lookahead_eof {
+            let mut ahead = String::new();
+            let first_record = self.stats.num_lines_read == 0 && self.lookahead.is_empty();
+            match self
+                .dlt
+                .read_record(&mut self.buf_raw_data, &mut ahead, first_record)?
+            {
+                0 => self.lookahead_eof = true,
+                num_bytes => self.lookahead.push_back((ahead, num_bytes)),
+            }
+        }
+        if self.lookahead.len() > n {
+            let (ahead, num_bytes) = self.lookahead.pop_front().expect("lookahead is not empty");
+            line.push_str(&ahead);
+            return Ok(num_bytes);
+        }
+        // the end of the data, the remaining records are the ones to skip
+        while let Some((ahead, num_bytes)) = self.lookahead.pop_front() {
+            self.stats.num_lines_read += 1;
+            self.stats.bytes_read += num_bytes;
+            self.stats.num_lines_skipped += 1;
+            self.push_skipped_line(&ahead);
+            self.stats.curr_line_num += 1;
+        }
+        Ok(0)
+    }
+
+    /// Helper method. Records the skipped line (additional info, only when configured), as far as the memory budget
+    /// allows.
+    fn push_skipped_line(&mut self, line: &str) {
+        let budget = &self.dlt.memory_budget;
+        if !below_cap(budget.max_skipped_lines, self.stats.skipped_lines.len()) {
+            self.stats.truncated_buffers.skipped_lines = true;
+            return;
+        }
+        let saved_line = if !self.dlt.save_skipped_lines {
+            None
+        } else if budget
+            .max_saved_skipped_lines_bytes
+            .is_none_or(|max| self.stats.saved_skipped_lines_bytes + line.len() <= max)
+        {
+            self.stats.saved_skipped_lines_bytes += line.len();
+            Some(String::from(line))
+        } else {
+            self.stats.truncated_buffers.skipped_lines = true;
+            None
+        };
+        self.stats
+            .skipped_lines
+            .push((self.stats.curr_line_num, saved_line));
+    }
 }
 
 impl<'dlt, 'rd, R: Read> Iterator for DelimitedLineTokenizerIter<'dlt, 'rd, R> {
@@ -240,11 +700,14 @@ impl<'dlt, 'rd, R: Read> Iterator for DelimitedLineTokenizerIter<'dlt, 'rd, R> {
             line.clear();
 
             self.stats.curr_line_num += 1;
-            let bytes_read = match self.buf_raw_data.read_line(&mut line) {
-                Ok(num_bytes) => match num_bytes {
-                    _ if num_bytes == 0_usize => return None, // returns "normal", i.e. end of "stream". ('return' always returns from a funtion!)
-                    _ => Some(num_bytes),
-                },
+            let bytes_read = match self.read_next_record(&mut line) {
+                Ok(0) => {
+                    if let Some(progress) = &self.dlt.progress_observer {
+                        self.progress.finish(progress, &self.stats);
+                    }
+                    return None; // returns "normal", i.e. end of "stream". ('return' always returns from a funtion!)
+                }
+                Ok(num_bytes) => num_bytes,
                 Err(e) => {
                     let msg = format!("error reading line {}. {}", self.stats.curr_line_num, e);
                     return Some(Err(PattiCsvError::Generic { msg }));
@@ -252,38 +715,70 @@ impl<'dlt, 'rd, R: Read> Iterator for DelimitedLineTokenizerIter<'dlt, 'rd, R> {
             };
 
             // Check very first line, if a BOM (UTF-8-BOM) is present and if so, remove it.
-            if self.stats.curr_line_num == 1 && line.as_bytes().starts_with(&UTF8BOM) {
+            if self.dlt.strip_bom
+                && self.stats.curr_line_num == 1
+                && line.as_bytes().starts_with(&UTF8BOM)
+            {
                 line.remove(0); // we remove the char(!) that consists of these 3 bytes, not the bytes!
             }
 
             self.stats.num_lines_read += 1;
-            self.stats.bytes_read += bytes_read.unwrap(); // unwrap is OK here, we checked every other path
+            self.stats.bytes_read += bytes_read;
+
+            if self
+                .preamble
+                .consume(self.dlt.strip_record_terminator(&line))
+            {
+                continue; // skip_this_line is still true
+            }
+
+            if self.dlt.is_comment_line(&line) {
+                self.stats.num_comment_lines += 1;
+                self.stats.num_lines_skipped += 1;
+                self.push_skipped_line(&line);
+                continue; // skip_this_line is still true
+            }
 
-            skip_this_line = self
+            let skipping_filter_idx = self
                 .dlt
-                .skip_line_by_skiptake_sanitizer(self.stats.curr_line_num, &line);
-
-            if skip_this_line {
-                // additional info, only when configured
-                self.stats.skipped_lines.push((
-                    self.stats.curr_line_num,
-                    if self.dlt.save_skipped_lines {
-                        Some(line.clone())
-                    } else {
-                        None
-                    },
-                ));
+                .skipping_filter_idx(self.stats.curr_line_num, &line);
+            skip_this_line = skipping_filter_idx.is_some();
+
+            if let Some(filter_idx) = skipping_filter_idx {
+                *self
+                    .stats
+                    .skipped_lines_by_filter
+                    .entry(filter_idx)
+                    .or_insert(0) += 1;
+                self.stats
+                    .skipped_lines_metadata
+                    .extend(self.dlt.skipped_line_metadata(filter_idx, &line));
+                self.stats.num_lines_skipped += 1;
+                self.push_skipped_line(&line);
             }
         }
 
-        let tok_res = self.dlt.tokenize_inner(
-            &mut self.line_token_buf,
-            self.stats.curr_line_num,
-            line.trim_end(),
-        );
+        let tok_res = if self.stats.num_lines_tokenized == 0 {
+            self.dlt.tokenize_header(
+                &mut self.line_token_buf,
+                &mut self.line_token_meta,
+                self.stats.curr_line_num,
+                self.dlt.strip_record_terminator(&line),
+            )
+        } else {
+            self.dlt.tokenize_inner(
+                &mut self.line_token_buf,
+                &mut self.line_token_meta,
+                self.stats.curr_line_num,
+                self.dlt.strip_record_terminator(&line),
+            )
+        };
         if tok_res.is_ok() {
             self.stats.num_lines_tokenized += 1;
         }
+        if let Some(progress) = &self.dlt.progress_observer {
+            self.progress.update(progress, &self.stats);
+        }
 
         self.line_token_buf.clear();
 
@@ -525,6 +1020,140 @@ mod tests {
         assert_eq!(res, vec!["foo", "b|ar", "baz"]);
     }
 
+    #[test]
+    fn read_record_with_record_separator() {
+        let mut dlt = DelimitedLineTokenizer::csv(None, false);
+        dlt.record_separator = Some(';');
+        let mut reader = BufReader::new(std::io::Cursor::new("a,\"b;\"\"c;\"\nd;e;"));
+
+        let mut rec = String::new();
+        assert_eq!(13, dlt.read_record(&mut reader, &mut rec, false).unwrap());
+        assert_eq!("a,\"b;\"\"c;\"\nd;", rec);
+        assert_eq!("a,\"b;\"\"c;\"\nd", dlt.strip_record_terminator(&rec));
+
+        rec.clear();
+        assert_eq!(2, dlt.read_record(&mut reader, &mut rec, false).unwrap());
+        assert_eq!("e", dlt.strip_record_terminator(&rec));
+
+        rec.clear();
+        assert_eq!(0, dlt.read_record(&mut reader, &mut rec, false).unwrap());
+    }
+
+    #[test]
+    fn enclosed_newline_in_first_field_after_bom() {
+        let data = "\u{feff}\"a\nb\",c\nd,e";
+        let dlt = DelimitedLineTokenizer::csv(None, false);
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let mut dlt_iter = dlt.tokenize_iter(&mut test_data_cursor);
+        assert_eq!(dlt_iter.next().unwrap().unwrap(), vec!["a\nb", "c"]);
+        assert_eq!(dlt_iter.next().unwrap().unwrap(), vec!["d", "e"]);
+        assert!(dlt_iter.next().is_none());
+
+        // also while reading ahead
+        let mut dlt = DelimitedLineTokenizer::csv(None, false);
+        dlt.skip_lines_from_end = 1;
+        let mut test_data_cursor = std::io::Cursor::new(data);
+        let mut dlt_iter = dlt.tokenize_iter(&mut test_data_cursor);
+        assert_eq!(dlt_iter.next().unwrap().unwrap(), vec!["a\nb", "c"]);
+        assert!(dlt_iter.next().is_none());
+
+        // a BOM that's kept is data, i.e. the first field isn't enclosed
+        let mut reader = BufReader::new(std::io::Cursor::new(data));
+        let mut rec = String::new();
+        dlt.strip_bom = false;
+        dlt.read_record(&mut reader, &mut rec, true).unwrap();
+        assert_eq!("\u{feff}\"a\n", rec);
+    }
+
+    #[test]
+    fn keep_trailing_whitespace_by_default() {
+        let mut test_data_cursor = std::io::Cursor::new("foo,\"bar  \",baz  \r\n");
+
+        let dlt = DelimitedLineTokenizer::csv(None, false);
+        let mut dlt_iter = dlt.tokenize_iter(&mut test_data_cursor);
+
+        let res = dlt_iter.next().unwrap().unwrap();
+        assert_eq!(res, vec!["foo", "bar  ", "baz  "]);
+
+        let mut test_data_cursor = std::io::Cursor::new("foo\tbar\t\n");
+        let dlt = DelimitedLineTokenizer::tsv(None, false);
+        let mut dlt_iter = dlt.tokenize_iter(&mut test_data_cursor);
+
+        let res = dlt_iter.next().unwrap().unwrap();
+        assert_eq!(res, vec!["foo", "bar", ""]);
+    }
+
+    #[test]
+    fn trim_trailing_whitespace_like_trim_end_of_line() {
+        let mut test_data_cursor =
+            std::io::Cursor::new("foo,baz  \r\nfoo,\"bar  \"  \nfoo,\"bar\"  ,baz");
+
+        let mut dlt = DelimitedLineTokenizer::csv(None, false);
+        dlt.trim_trailing_whitespace = true;
+        let mut dlt_iter = dlt.tokenize_iter(&mut test_data_cursor);
+
+        let res = dlt_iter.next().unwrap().unwrap();
+        assert_eq!(res, vec!["foo", "baz"]);
+
+        let res = dlt_iter.next().unwrap().unwrap();
+        assert_eq!(res, vec!["foo", "bar  "]);
+
+        let res = dlt_iter.next().unwrap();
+        assert_eq!(
+            Err(PattiCsvError::Tokenize(TokenizerError::UnescapedEnclChar {
+                line: 3,
+                token_num: 2
+            })),
+            res
+        );
+
+        // Trailing (whitespace) delimiters are trimmed as well, i.e. the empty last tokens are gone
+        let mut test_data_cursor = std::io::Cursor::new("foo\tbar \t\nfoo\tbar\t \t \nfoo\t\tbar");
+        let mut dlt = DelimitedLineTokenizer::tsv(None, false);
+        dlt.trim_trailing_whitespace = true;
+        let mut dlt_iter = dlt.tokenize_iter(&mut test_data_cursor);
+        assert_eq!(dlt_iter.next().unwrap().unwrap(), vec!["foo", "bar"]);
+        assert_eq!(dlt_iter.next().unwrap().unwrap(), vec!["foo", "bar"]);
+        assert_eq!(dlt_iter.next().unwrap().unwrap(), vec!["foo", "", "bar"]);
+    }
+
+    #[test]
+    fn unicode_separators_and_enclosures() {
+        let tokenize = |dlt: &DelimitedLineTokenizer, inp: &str| {
+            let mut test_data_cursor = std::io::Cursor::new(inp);
+            dlt.tokenize_iter(&mut test_data_cursor)
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+        };
+
+        // 2, 3 and 4 byte separators, with a 3 byte enclosure char
+        for sep in ['¦', '→', '🦀'] {
+            let dlt = DelimitedLineTokenizer::new(sep, Some('„'), None, false);
+            let line = format!("y̆es{0}„a{0}„„b„{0}{0}👩‍👩‍👧", sep);
+            assert_eq!(
+                tokenize(&dlt, &line),
+                vec![vec!["y̆es", format!("a{}„b", sep).as_str(), "", "👩‍👩‍👧"]]
+            );
+        }
+
+        // Tokens are split by chars, so a combining mark after a separator belongs to the next token. It is never lost.
+        let dlt = DelimitedLineTokenizer::csv(None, false);
+        assert_eq!(tokenize(&dlt, "y,\u{306}es"), vec![vec!["y", "\u{306}es"]]);
+        // Ligatures, title case digraphs and a BOM in the middle of a line are just chars, too.
+        assert_eq!(
+            tokenize(&dlt, "ﬁ,ǅ,\u{feff}x"),
+            vec![vec!["ﬁ", "ǅ", "\u{feff}x"]]
+        );
+
+        // Trailing Unicode whitespace (e.g. a no-break space) is trimmed as well, without garbling the last char.
+        let mut dlt = DelimitedLineTokenizer::new('·', None, None, false);
+        dlt.trim_trailing_whitespace = true;
+        assert_eq!(
+            tokenize(&dlt, "ä·y̆\u{a0}y̆\u{a0}\u{3000}"),
+            vec![vec!["ä", "y̆\u{a0}y̆"]]
+        );
+    }
+
     #[test]
     fn multiple_lines_test_simple() {
         let mut test_data_cursor = std::io::Cursor::new("a,b,c\n1,2,3");
@@ -548,4 +1177,68 @@ mod tests {
 
         println!("{:?}", &dlt_iter.get_stats())
     }
+
+    #[test]
+    fn token_meta_was_quoted() {
+        let dlt = DelimitedLineTokenizer::csv(None, false);
+        let quoted = |s: &str| {
+            dlt.tokenize_with_meta(1, s)
+                .unwrap()
+                .1
+                .iter()
+                .map(|m| (m.was_quoted, m.had_escapes))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            quoted(r#""",,"a",b,"#),
+            vec![
+                (true, false),
+                (false, false),
+                (true, false),
+                (false, false),
+                (false, false)
+            ]
+        );
+        assert_eq!(quoted(r#""a""b",x"#), vec![(true, true), (false, false)]);
+
+        let mut test_data_cursor = std::io::Cursor::new("a,\"b\"\n\"\",");
+        let mut dlt_iter = dlt.tokenize_iter(&mut test_data_cursor);
+        dlt_iter.next().unwrap().unwrap();
+        assert!(dlt_iter.token_meta()[1].was_quoted);
+        dlt_iter.next().unwrap().unwrap();
+        assert_eq!(
+            dlt_iter.token_meta(),
+            &[
+                TokenMeta {
+                    was_quoted: true,
+                    had_escapes: false
+                },
+                TokenMeta::default()
+            ]
+        );
+    }
+
+    #[test]
+    fn detect_header_enclosure() {
+        let mut dlt = DelimitedLineTokenizer::new(',', None, None, false);
+        dlt.detect_header_enclosure = true;
+        let header = |s: &str| {
+            dlt.tokenize_header(&mut Vec::new(), &mut Vec::new(), 1, s)
+                .unwrap()
+        };
+
+        assert_eq!(
+            header(r#""id","a,b","say ""hi""""#),
+            vec!["id", "a,b", "say \"hi\""]
+        );
+        assert_eq!(header("'id','name'"), vec!["id", "name"]);
+        // not consistently quoted, so we leave it as is
+        assert_eq!(header(r#""id",name,"x""#), vec!["\"id\"", "name", "\"x\""]);
+
+        // data lines are never affected
+        let mut test_data_cursor = std::io::Cursor::new("\"id\",\"name\"\n\"1\",\"foo\"");
+        let mut dlt_iter = dlt.tokenize_iter(&mut test_data_cursor);
+        assert_eq!(dlt_iter.next().unwrap().unwrap(), vec!["id", "name"]);
+        assert_eq!(dlt_iter.next().unwrap().unwrap(), vec!["\"1\"", "\"foo\""]);
+    }
 }
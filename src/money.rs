@@ -0,0 +1,270 @@
+use venum::{value::Value, value_type::ValueType};
+use venum_tds::{data_cell::DataCell, data_cell_row::DataCellRow};
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    parser_config::{MoneyColumns, TypeColumnEntry},
+};
+
+/// Active ISO 4217 currency codes with their minor units (i.e. decimal places). Sorted by code.
+#[rustfmt::skip]
+const ISO_4217_MINOR_UNITS: &[(&str, u32)] = &[
+    ("AED", 2), ("AFN", 2), ("ALL", 2), ("AMD", 2), ("ANG", 2), ("AOA", 2), ("ARS", 2), ("AUD", 2),
+    ("AWG", 2), ("AZN", 2), ("BAM", 2), ("BBD", 2), ("BDT", 2), ("BGN", 2), ("BHD", 3), ("BIF", 0),
+    ("BMD", 2), ("BND", 2), ("BOB", 2), ("BRL", 2), ("BSD", 2), ("BTN", 2), ("BWP", 2), ("BYN", 2),
+    ("BZD", 2), ("CAD", 2), ("CDF", 2), ("CHF", 2), ("CLF", 4), ("CLP", 0), ("CNY", 2), ("COP", 2),
+    ("CRC", 2), ("CUP", 2), ("CVE", 2), ("CZK", 2), ("DJF", 0), ("DKK", 2), ("DOP", 2), ("DZD", 2),
+    ("EGP", 2), ("ERN", 2), ("ETB", 2), ("EUR", 2), ("FJD", 2), ("FKP", 2), ("GBP", 2), ("GEL", 2),
+    ("GHS", 2), ("GIP", 2), ("GMD", 2), ("GNF", 0), ("GTQ", 2), ("GYD", 2), ("HKD", 2), ("HNL", 2),
+    ("HTG", 2), ("HUF", 2), ("IDR", 2), ("ILS", 2), ("INR", 2), ("IQD", 3), ("IRR", 2), ("ISK", 0),
+    ("JMD", 2), ("JOD", 3), ("JPY", 0), ("KES", 2), ("KGS", 2), ("KHR", 2), ("KMF", 0), ("KPW", 2),
+    ("KRW", 0), ("KWD", 3), ("KYD", 2), ("KZT", 2), ("LAK", 2), ("LBP", 2), ("LKR", 2), ("LRD", 2),
+    ("LSL", 2), ("LYD", 3), ("MAD", 2), ("MDL", 2), ("MGA", 2), ("MKD", 2), ("MMK", 2), ("MNT", 2),
+    ("MOP", 2), ("MRU", 2), ("MUR", 2), ("MVR", 2), ("MWK", 2), ("MXN", 2), ("MYR", 2), ("MZN", 2),
+    ("NAD", 2), ("NGN", 2), ("NIO", 2), ("NOK", 2), ("NPR", 2), ("NZD", 2), ("OMR", 3), ("PAB", 2),
+    ("PEN", 2), ("PGK", 2), ("PHP", 2), ("PKR", 2), ("PLN", 2), ("PYG", 0), ("QAR", 2), ("RON", 2),
+    ("RSD", 2), ("RUB", 2), ("RWF", 0), ("SAR", 2), ("SBD", 2), ("SCR", 2), ("SDG", 2), ("SEK", 2),
+    ("SGD", 2), ("SHP", 2), ("SLE", 2), ("SOS", 2), ("SRD", 2), ("SSP", 2), ("STN", 2), ("SVC", 2),
+    ("SYP", 2), ("SZL", 2), ("THB", 2), ("TJS", 2), ("TMT", 2), ("TND", 3), ("TOP", 2), ("TRY", 2),
+    ("TTD", 2), ("TWD", 2), ("TZS", 2), ("UAH", 2), ("UGX", 0), ("USD", 2), ("UYI", 0), ("UYU", 2),
+    ("UYW", 4), ("UZS", 2), ("VES", 2), ("VND", 0), ("VUV", 0), ("WST", 2), ("XAF", 0), ("XCD", 2),
+    ("XOF", 0), ("XPF", 0), ("YER", 2), ("ZAR", 2), ("ZMW", 2), ("ZWL", 2),
+];
+
+/// The minor units (i.e. decimal places) of an (active) ISO 4217 currency, e.g. 2 for "CHF" and 0 for "JPY".
+pub fn currency_minor_units(code: &str) -> Option<u32> {
+    ISO_4217_MINOR_UNITS
+        .binary_search_by(|(c, _)| (*c).cmp(code))
+        .ok()
+        .map(|idx| ISO_4217_MINOR_UNITS[idx].1)
+}
+
+fn money_err(msg: String) -> PattiCsvError {
+    PattiCsvError::ConfigError {
+        msg: format!("[ERROR_ON_MONEY] {}", msg),
+    }
+}
+
+/// Helper method. Checks the money columns against the (final) column typings: the amount has to be typed as
+/// `Decimal`, and the currency as `String`. If the typings are resolved by header, the columns are only known per file,
+/// so only the columns themselves are checked.
+pub(crate) fn check_money_columns_typings(
+    column_typings: &[TypeColumnEntry],
+    money_columns: &[MoneyColumns],
+    by_header: bool,
+) -> Result<()> {
+    for mc in money_columns {
+        if mc.amount_col == mc.currency_col {
+            return Err(money_err(format!(
+                "Amount and currency of money columns must be different columns, but both are: {}",
+                mc.amount_col
+            )));
+        }
+        if by_header {
+            continue;
+        }
+        for (col, what, value_type) in [
+            (mc.amount_col, "Amount", ValueType::Decimal),
+            (mc.currency_col, "Currency", ValueType::String),
+        ] {
+            match column_typings.get(col) {
+                None => {
+                    return Err(money_err(format!(
+                        "{} column {} doesn't exist, there are only {} column typings",
+                        what,
+                        col,
+                        column_typings.len()
+                    )))
+                }
+                Some(tce) if tce.target_type != value_type => {
+                    return Err(money_err(format!(
+                        "{} column {} must be typed as {:?}, but is: {:?}",
+                        what, col, value_type, tce.target_type
+                    )))
+                }
+                Some(_) => (),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Helper method. Combined amount cells are `Value::String`s (see: `MoneyColumns::combine`), so they're typed as such
+/// in the column layout template.
+pub(crate) fn set_combined_money_template_cells(
+    template: &mut DataCellRow,
+    money_columns: Option<&Vec<MoneyColumns>>,
+) {
+    for mc in money_columns.into_iter().flatten().filter(|mc| mc.combine) {
+        if let Some(cell) = template.0.get(mc.amount_col) {
+            let name = String::from(cell.get_name());
+            template.0[mc.amount_col] =
+                DataCell::new_without_data(ValueType::String, name, mc.amount_col);
+        }
+    }
+}
+
+/// Helper method. Validates (and possibly combines) the amount/currency pairs of a typed row.
+pub(crate) fn check_money_columns(
+    row: &mut DataCellRow,
+    money_columns: &[MoneyColumns],
+    line_num: usize,
+) -> Result<()> {
+    for mc in money_columns {
+        let err = |msg: String| PattiCsvError::Generic {
+            msg: format!(
+                "[ERROR_ON_MONEY] {}; line: {}; amount column: {}; currency column: {}",
+                msg, line_num, mc.amount_col, mc.currency_col
+            ),
+        };
+        let (amount_cell, currency_cell) =
+            match (row.0.get(mc.amount_col), row.0.get(mc.currency_col)) {
+                (Some(a), Some(c)) => (a, c),
+                _ => return Err(err(String::from("No such column"))),
+            };
+
+        let amount = match amount_cell.get_data() {
+            Value::None => continue, // nothing to validate, a currency without an amount is fine
+            Value::Decimal(d) => *d,
+            other => {
+                return Err(err(format!(
+                    "Amount must be typed as Decimal, but is: {:?}",
+                    other
+                )))
+            }
+        };
+        let (currency, minor_units) = match currency_cell.get_data() {
+            Value::None => return Err(err(String::from("Amount without currency"))),
+            Value::String(c) => match currency_minor_units(c) {
+                Some(mu) => (c.clone(), mu),
+                None => return Err(err(format!("Unknown currency code: '{}'", c))),
+            },
+            other => {
+                return Err(err(format!(
+                    "Currency must be typed as String, but is: {:?}",
+                    other
+                )))
+            }
+        };
+        if amount.scale() > minor_units {
+            return Err(err(format!(
+                "Amount {} has more decimal places than {} allows ({})",
+                amount, currency, minor_units
+            )));
+        }
+
+        if mc.combine {
+            let mut scaled = amount;
+            scaled.rescale(minor_units);
+            let name = String::from(amount_cell.get_name());
+            row.0[mc.amount_col] = DataCell::new_with_type_info(
+                ValueType::String,
+                name,
+                mc.amount_col,
+                Value::String(format!("{} {}", currency, scaled)),
+            )
+            .map_err(|e| err(format!("{:?}", e)))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(amount: Value, currency: Value) -> DataCellRow {
+        let mut row = DataCellRow::new();
+        row.push(
+            DataCell::new_with_type_info(ValueType::Decimal, String::from("amount"), 0, amount)
+                .unwrap(),
+        );
+        row.push(
+            DataCell::new_with_type_info(ValueType::String, String::from("ccy"), 1, currency)
+                .unwrap(),
+        );
+        row
+    }
+
+    fn decimal(s: &str) -> Value {
+        Value::from_str_and_type(s, &ValueType::Decimal).unwrap()
+    }
+
+    #[test]
+    fn iso_4217_table_is_sorted() {
+        assert!(ISO_4217_MINOR_UNITS.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(Some(2), currency_minor_units("CHF"));
+        assert_eq!(Some(0), currency_minor_units("JPY"));
+        assert_eq!(Some(3), currency_minor_units("KWD"));
+        assert_eq!(None, currency_minor_units("chf"));
+        assert_eq!(None, currency_minor_units("XYZ"));
+    }
+
+    #[test]
+    fn money_columns_valid() {
+        let mcs = [MoneyColumns::new(0, 1)];
+        for (a, c) in [
+            (decimal("12.5"), "CHF"),
+            (decimal("12.50"), "EUR"),
+            (decimal("1200"), "JPY"),
+        ] {
+            let mut r = row(a, Value::String(String::from(c)));
+            assert!(check_money_columns(&mut r, &mcs, 1).is_ok());
+        }
+        let mut r = row(Value::None, Value::String(String::from("CHF")));
+        assert!(check_money_columns(&mut r, &mcs, 1).is_ok());
+        let mut r = row(Value::None, Value::None);
+        assert!(check_money_columns(&mut r, &mcs, 1).is_ok());
+    }
+
+    #[test]
+    fn money_columns_invalid() {
+        let mcs = [MoneyColumns::new(0, 1)];
+        for (a, c) in [
+            (decimal("12.555"), Value::String(String::from("CHF"))),
+            (decimal("12.5"), Value::String(String::from("JPY"))),
+            (decimal("12.5"), Value::String(String::from("XYZ"))),
+            (decimal("12.5"), Value::None),
+            (Value::Float64(12.5), Value::String(String::from("CHF"))),
+        ] {
+            let mut r = row(a, c);
+            let res = check_money_columns(&mut r, &mcs, 7);
+            assert!(
+                matches!(&res, Err(PattiCsvError::Generic { msg }) if msg.starts_with("[ERROR_ON_MONEY]") && msg.contains("line: 7")),
+                "{:?}",
+                res
+            );
+        }
+        let mut r = row(decimal("1"), Value::String(String::from("CHF")));
+        assert!(check_money_columns(&mut r, &[MoneyColumns::new(0, 2)], 1).is_err());
+    }
+
+    #[test]
+    fn money_columns_typings() {
+        let typings = vec![
+            TypeColumnEntry::new(Some(String::from("amount")), ValueType::Decimal),
+            TypeColumnEntry::new(Some(String::from("ccy")), ValueType::String),
+        ];
+        let check =
+            |mc: MoneyColumns, by_header| check_money_columns_typings(&typings, &[mc], by_header);
+        assert!(check(MoneyColumns::new(0, 1), false).is_ok());
+        assert!(check(MoneyColumns::new(1, 0), false).is_err());
+        assert!(check(MoneyColumns::new(0, 2), false).is_err());
+        assert!(check(MoneyColumns::new(0, 2), true).is_ok());
+        assert!(check(MoneyColumns::new(1, 1), true).is_err());
+    }
+
+    #[test]
+    fn money_columns_combined() {
+        let mut r = row(decimal("12.5"), Value::String(String::from("CHF")));
+        check_money_columns(&mut r, &[MoneyColumns::new(0, 1).combined()], 1).unwrap();
+        assert_eq!(
+            &Value::String(String::from("CHF 12.50")),
+            r.get_by_name("amount").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::String(String::from("CHF")),
+            r.get_by_name("ccy").unwrap().get_data()
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+/// Where and how the rows of a (read-transform-write) job should be written. This is only the description of the
+/// target, e.g. from the `output` section of a json config. Writing is up to the application (or CLI) running the job
+/// (see `record_writer` for csv).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTarget {
+    pub format: OutputFormat,
+    pub destination: OutputDestination,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputFormat {
+    Csv(OutputDialect),
+    /// One json object per row and line.
+    NdJson,
+    Arrow,
+}
+
+/// The dialect of a csv output. The default is: `,` as separator, `"` as enclosure, and a header line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputDialect {
+    pub separator_char: char,
+    pub enclosure_char: Option<char>,
+    pub write_header: bool,
+}
+
+impl Default for OutputDialect {
+    fn default() -> Self {
+        Self {
+            separator_char: ',',
+            enclosure_char: Some('"'),
+            write_header: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputDestination {
+    Stdout,
+    Path(PathBuf),
+}
+
+impl From<&str> for OutputDestination {
+    /// "stdout" (or "-") is the standard output, everything else a path.
+    fn from(s: &str) -> Self {
+        match s {
+            "stdout" | "-" => OutputDestination::Stdout,
+            path => OutputDestination::Path(PathBuf::from(path)),
+        }
+    }
+}
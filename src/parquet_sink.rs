@@ -0,0 +1,247 @@
+use std::{fs::File, io::Read, path::Path, sync::Arc};
+
+use arrow::datatypes::{Field, Schema};
+use parquet::{
+    arrow::ArrowWriter,
+    basic::{Compression, GzipLevel, ZstdLevel},
+    errors::ParquetError,
+    file::properties::WriterProperties,
+};
+
+use crate::{
+    arrow_batch::{arrow_data_type, ArrowBatchBuilder, DEFAULT_DECIMAL_SCALE},
+    errors::{PattiCsvError, Result},
+    iterating_parser::PattiCsvParser,
+    report::ParseReport,
+};
+
+/// The compression of the column chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParquetCompression {
+    Uncompressed,
+    #[default]
+    Snappy,
+    /// With the level (0-10).
+    Gzip(u32),
+    /// With the level (1-22).
+    Zstd(i32),
+}
+
+/// The options of `write_parquet`. The rows are written in batches (see: `ArrowBatchBuilder`), and a row group is
+/// finished once it has `max_row_group_size` rows, i.e. a larger file has several row groups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParquetWriteOpts {
+    pub batch_size: usize,
+    pub max_row_group_size: usize,
+    pub compression: ParquetCompression,
+    pub decimal_scale: i8, // see: `ArrowBatchBuilder::with_decimal_scale`
+}
+
+impl Default for ParquetWriteOpts {
+    fn default() -> Self {
+        Self {
+            batch_size: 8192,
+            max_row_group_size: 1024 * 1024,
+            compression: ParquetCompression::default(),
+            decimal_scale: DEFAULT_DECIMAL_SCALE,
+        }
+    }
+}
+
+impl ParquetWriteOpts {
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn with_max_row_group_size(mut self, max_row_group_size: usize) -> Self {
+        self.max_row_group_size = max_row_group_size;
+        self
+    }
+
+    pub fn with_compression(mut self, compression: ParquetCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn with_decimal_scale(mut self, decimal_scale: i8) -> Self {
+        self.decimal_scale = decimal_scale;
+        self
+    }
+
+    /// Helper method.
+    fn writer_properties(&self) -> Result<WriterProperties> {
+        let compression = match self.compression {
+            ParquetCompression::Uncompressed => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Gzip(level) => {
+                Compression::GZIP(GzipLevel::try_new(level).map_err(config_err)?)
+            }
+            ParquetCompression::Zstd(level) => {
+                Compression::ZSTD(ZstdLevel::try_new(level).map_err(config_err)?)
+            }
+        };
+        Ok(WriterProperties::builder()
+            .set_max_row_group_size(self.max_row_group_size.max(1))
+            .set_compression(compression)
+            .build())
+    }
+}
+
+/// Helper method.
+fn config_err(e: ParquetError) -> PattiCsvError {
+    PattiCsvError::ConfigError {
+        msg: format!("invalid parquet compression level: {}", e),
+    }
+}
+
+/// Helper method.
+fn parquet_err(e: ParquetError) -> PattiCsvError {
+    PattiCsvError::Generic {
+        msg: format!("[ERROR_ON_PARQUET] Could not write parquet: {}", e),
+    }
+}
+
+/// Parses everything and writes the data rows (i.e. never the header row) into a new Parquet file at the path, and
+/// returns the report of the parse run. The schema is the one of the typed columns (see: `arrow_batch::arrow_schema`),
+/// so a file without data rows still gets it. Stops at the first error, i.e. a row, that failed to parse, is returned
+/// as such, and the file is left incomplete then.
+pub fn write_parquet<R: Read, P: AsRef<Path>>(
+    parser: &PattiCsvParser,
+    reader: &mut R,
+    path: P,
+    opts: &ParquetWriteOpts,
+) -> Result<ParseReport> {
+    let props = opts.writer_properties()?;
+    let file = File::create(path)?;
+    let mut batch_builder =
+        ArrowBatchBuilder::new(opts.batch_size).with_decimal_scale(opts.decimal_scale);
+    let mut writer: Option<ArrowWriter<File>> = None;
+
+    let mut iter = parser.parse_iter(reader);
+    iter.emit_header_row = false;
+    for row in iter.by_ref() {
+        if let Some(batch) = batch_builder.push_row(&row?)? {
+            if writer.is_none() {
+                writer = Some(
+                    ArrowWriter::try_new(file.try_clone()?, batch.schema(), Some(props.clone()))
+                        .map_err(parquet_err)?,
+                );
+            }
+            if let Some(w) = writer.as_mut() {
+                w.write(&batch).map_err(parquet_err)?;
+            }
+        }
+    }
+    let report = iter.finish_report();
+
+    let last_batch = batch_builder.finish()?;
+    let mut writer = match writer {
+        Some(w) => w,
+        None => {
+            let schema = match &last_batch {
+                Some(batch) => batch.schema(),
+                None => Arc::new(Schema::new(
+                    report
+                        .schema
+                        .iter()
+                        .map(|column| {
+                            Field::new(
+                                &column.name,
+                                arrow_data_type(&column.target_type, opts.decimal_scale),
+                                true,
+                            )
+                        })
+                        .collect::<Vec<Field>>(),
+                )),
+            };
+            ArrowWriter::try_new(file, schema, Some(props)).map_err(parquet_err)?
+        }
+    };
+    if let Some(batch) = last_batch {
+        writer.write(&batch).map_err(parquet_err)?;
+    }
+    writer.close().map_err(parquet_err)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Array, Int32Array, StringArray};
+    use parquet::{arrow::arrow_reader::ParquetRecordBatchReaderBuilder, file::reader::FileReader};
+    use venum::value_type::ValueType;
+
+    use crate::{iterating_parser::PattiCsvParserBuilder, parser_config::TypeColumnEntry};
+
+    use super::*;
+
+    #[test]
+    fn parse_into_parquet() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "patti_csv_parse_into_parquet_{}.parquet",
+            std::process::id()
+        ));
+        let mut test_data_cursor = std::io::Cursor::new("id,name\n1,foo\n2,\n3,baz");
+        let opts = ParquetWriteOpts::default()
+            .with_batch_size(2)
+            .with_max_row_group_size(2)
+            .with_compression(ParquetCompression::Zstd(3));
+        let report = write_parquet(&parser, &mut test_data_cursor, &path, &opts).unwrap();
+        assert_eq!(3, report.num_rows_ok);
+
+        let file_reader =
+            parquet::file::reader::SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(2, file_reader.metadata().num_row_groups());
+
+        let batches = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        let ids = batches
+            .iter()
+            .flat_map(|b| {
+                b.column(0)
+                    .as_any()
+                    .downcast_ref::<Int32Array>()
+                    .unwrap()
+                    .values()
+                    .to_vec()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3], ids);
+        let names = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!("foo", names.value(0));
+        assert!(names.is_null(1));
+
+        // no data rows, but the schema
+        let mut test_data_cursor = std::io::Cursor::new("id,name");
+        write_parquet(&parser, &mut test_data_cursor, &path, &opts).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap();
+        assert_eq!("name", builder.schema().field(1).name());
+        assert_eq!(0, builder.metadata().file_metadata().num_rows());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            write_parquet(
+                &parser,
+                &mut std::io::Cursor::new("id,name"),
+                &path,
+                &opts.with_compression(ParquetCompression::Gzip(42))
+            ),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+    }
+}
@@ -1,11 +1,27 @@
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    time::Instant,
+};
 
+use venum::{value::Value, value_type::ValueType};
 use venum_tds::data_cell::DataCell;
 use venum_tds::data_cell_row::DataCellRow;
 
-use crate::errors::{PattiCsvError, Result, SanitizeError};
+use crate::{
+    cell_converter::{CellConverter, VenumCellConverter},
+    derived_columns::{derive_columns, num_derived_cells},
+    errors::{CellError, PattiCsvError, Result, RowError, SanitizeError},
+    iterating_parser::PattiCsvParser,
+    line_tokenizer::{StageTimings, TokenMeta},
+    money::check_money_columns,
+    transform_sanitize_token::apply_smart_sanitizer_defaults,
+    value_transformer::transform_value,
+};
 
-use super::parser_config::{TypeColumnEntry, VecOfTokenTransitizers};
+use super::parser_config::{
+    ColumnCountMismatchPolicy, ColumnSelection, DuplicateHeaderPolicy, ErrorPolicy,
+    HeaderMatchMode, QuotedTokenPolicy, SanitizerOrder, TypeColumnEntry, VecOfTokenTransitizers,
+};
 
 pub fn build_layout_template(
     header_tokens: Option<&VecDeque<String>>,
@@ -50,16 +66,657 @@ pub fn build_layout_template(
     Ok(csv_cell_templ_row)
 }
 
+/// Re-orders the (otherwise positional) column typings, so that they match the columns of the header line, by header
+/// name and header aliases. Columns of the header line without a matching typing are typed as String.
+pub fn resolve_column_typings_by_header(
+    header_tokens: &VecDeque<String>,
+    column_typings: &[TypeColumnEntry],
+    mode: &HeaderMatchMode,
+) -> Result<Vec<TypeColumnEntry>> {
+    let mut resolved: Vec<Option<TypeColumnEntry>> = vec![None; header_tokens.len()];
+
+    for (typing_idx, tce) in column_typings.iter().enumerate() {
+        let col_idx = header_tokens
+            .iter()
+            .position(|h| tce.matches_header(h, mode))
+            .ok_or_else(|| PattiCsvError::ConfigError {
+                msg: format!(
+                    "No column in the header line matches typing#{} (header: {:?}, aliases: {:?})",
+                    typing_idx, tce.header, tce.header_aliases
+                ),
+            })?;
+
+        if resolved[col_idx].is_some() {
+            return Err(PattiCsvError::ConfigError {
+                msg: format!(
+                    "Column#{} ({}) is matched by more than one typing",
+                    col_idx, header_tokens[col_idx]
+                ),
+            });
+        }
+        resolved[col_idx] = Some(tce.clone());
+    }
+
+    Ok(resolved
+        .into_iter()
+        .map(|tce| tce.unwrap_or_else(|| TypeColumnEntry::new(None, ValueType::String)))
+        .collect())
+}
+
+/// The column typings with the parser wide map list (see: `PattiCsvParserBuilder::global_map_to_none`), i.e. every
+/// column without a map list of its own (see: `NullPolicy::map_list`) gets a copy of it.
+pub(crate) fn apply_global_map_to_none(
+    column_typings: &[TypeColumnEntry],
+    global_map_to_none: &[String],
+) -> Vec<TypeColumnEntry> {
+    column_typings
+        .iter()
+        .map(|tce| {
+            let mut tce = tce.clone();
+            if tce.null_policy.map_list.is_empty() {
+                tce.null_policy.map_list = global_map_to_none.to_vec();
+            }
+            tce
+        })
+        .collect()
+}
+
+/// Handles header names, that occur more than once in the header line. See: `DuplicateHeaderPolicy`
+pub fn dedup_header_tokens(
+    header_tokens: &mut VecDeque<String>,
+    policy: DuplicateHeaderPolicy,
+) -> Result<()> {
+    match policy {
+        DuplicateHeaderPolicy::KeepFirst => Ok(()),
+        DuplicateHeaderPolicy::Error => {
+            let mut seen: HashMap<&String, usize> = HashMap::with_capacity(header_tokens.len());
+            for (idx, header) in header_tokens.iter().enumerate() {
+                if let Some(first_idx) = seen.insert(header, idx) {
+                    return Err(PattiCsvError::ConfigError {
+                        msg: format!(
+                            "duplicate header '{}' in the header line, in column#{} and column#{}",
+                            header, first_idx, idx
+                        ),
+                    });
+                }
+            }
+            Ok(())
+        }
+        DuplicateHeaderPolicy::RenameWithSuffix => {
+            let mut taken: HashSet<String> = header_tokens.iter().cloned().collect();
+            let mut seen: HashSet<String> = HashSet::with_capacity(header_tokens.len());
+            for header in header_tokens.iter_mut() {
+                if seen.insert(header.clone()) {
+                    continue;
+                }
+                let renamed = (2..)
+                    .map(|n| format!("{}_{}", header, n))
+                    .find(|candidate| !taken.contains(candidate))
+                    .expect("there is always a free suffix");
+                taken.insert(renamed.clone());
+                seen.insert(renamed.clone());
+                *header = renamed;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Helper method. Checks the number of tokens of a data line against the number of columns, and truncates the tokens
+/// (and their metadata, if any) of a long line, depending on the policy. Short lines are padded while typing, i.e.
+/// their missing tokens are typed as `Value::None`. See: `ColumnCountMismatchPolicy`
+pub(crate) fn check_column_count(
+    line_num: usize,
+    tokens: &mut VecDeque<String>,
+    token_meta: Option<&mut Vec<TokenMeta>>,
+    num_columns: usize,
+    policy: Option<ColumnCountMismatchPolicy>,
+) -> Result<()> {
+    let found = tokens.len();
+    match policy {
+        None => Ok(()),
+        Some(_) if found == num_columns => Ok(()),
+        Some(ColumnCountMismatchPolicy::PadWithNone) if found < num_columns => Ok(()),
+        Some(ColumnCountMismatchPolicy::TruncateExtra) if found > num_columns => {
+            tokens.truncate(num_columns);
+            if let Some(token_meta) = token_meta {
+                token_meta.truncate(num_columns);
+            }
+            Ok(())
+        }
+        Some(_) => Err(PattiCsvError::ColumnCountMismatch {
+            line: line_num,
+            expected: num_columns,
+            found,
+        }),
+    }
+}
+
+/// Builds the header row from the column layout template. We hardcode the datatype to ValueName::String for the header line.
+pub fn build_header_row(column_layout_template: &DataCellRow) -> Result<DataCellRow> {
+    let mut csv_header_data_cell_row: DataCellRow =
+        DataCellRow::with_capacity(column_layout_template.0.len());
+    for (i, templ_cell) in column_layout_template
+        .0 // TODO: is there a way we don't need to rely on the underlying vec?
+        .iter()
+        .enumerate()
+    {
+        // We have set the correct header-name in the template anyway, we can just use it here!
+        let header_name = &templ_cell.name;
+
+        // TODO: do we want transitization on the headers!?
+
+        // Data is never None, so the type_info can always be inferred from data correctly. Still, no reason to panic.
+        let new_csv_cell = DataCell::new(header_name.clone(), i, header_name.clone().into())
+            .map_err(|e| PattiCsvError::Generic {
+                msg: format!("[ERROR_ON_HEADER] {:?}; column: {}", e, i),
+            })?;
+        csv_header_data_cell_row.push(new_csv_cell);
+    }
+    Ok(csv_header_data_cell_row)
+}
+
+/// Types one (sanitized) token, i.e. decides what becomes `Value::None` (see: `NullPolicy`), and leaves everything else
+/// to the converter of the column. `Value::None` is replaced by the default value of the column, if there is one. The
+/// errors have no line/column context yet.
+pub(crate) fn type_cell(
+    token: Option<String>,
+    token_meta: TokenMeta,
+    column_typing: &TypeColumnEntry,
+    converter: &dyn CellConverter,
+) -> Result<Value> {
+    let value = type_token(token, token_meta, column_typing, converter)?;
+    default_if_none(value, column_typing, converter)
+}
+
+/// Helper method. The default value of the column (if any), if the value is `Value::None`.
+fn default_if_none(
+    value: Value,
+    column_typing: &TypeColumnEntry,
+    converter: &dyn CellConverter,
+) -> Result<Value> {
+    match (value, &column_typing.default_value) {
+        (Value::None, Some(default_value)) => converter
+            .convert(default_value.clone(), column_typing)
+            .map_err(|e| PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_DEFAULT_VALUE] Could not convert the default value {:?}: {}",
+                    default_value,
+                    match e {
+                        PattiCsvError::Generic { msg } => msg,
+                        other => format!("{:?}", other),
+                    }
+                ),
+            }),
+        (value, _) => Ok(value),
+    }
+}
+
+/// Helper method. See: `type_cell`
+fn type_token(
+    token: Option<String>,
+    token_meta: TokenMeta,
+    column_typing: &TypeColumnEntry,
+    converter: &dyn CellConverter,
+) -> Result<Value> {
+    match token {
+        None if column_typing.null_policy.missing_is_none => Ok(Value::None),
+        None => Err(PattiCsvError::Generic {
+            msg: String::from("[ERROR_ON_MISSING_COLUMN] No token for column"),
+        }),
+        Some(_)
+            if token_meta.was_quoted
+                && column_typing.quoted_policy == QuotedTokenPolicy::Reject =>
+        {
+            Err(PattiCsvError::Generic {
+                msg: String::from("[ERROR_ON_QUOTED_TOKEN] Quoted tokens are rejected for column"),
+            })
+        }
+        Some(t) if column_typing.maps_to_none(&t, token_meta) => Ok(Value::None),
+        Some(t) => match (column_typing.mapped_bool(&t), &column_typing.number_format) {
+            (Some(b), _) => Ok(Value::Bool(b)),
+            (None, Some(number_format)) => {
+                converter.convert(number_format.normalize(&t)?, column_typing)
+            }
+            (None, None) => converter.convert(t, column_typing),
+        },
+    }
+}
+
+/// Sanitizes and types the tokens of one data line, using (a clone of) the column layout template as the resulting row.
+/// The sanitizers, converters, etc. are taken from the parser, the column typings are given explicitly, since they
+/// might have been resolved by header first. Without the metadata of the tokens, none of them counts as quoted (see:
+/// `NullPolicy::quoted_empty_is_empty_string`).
+pub fn type_line_tokens(
+    line_num: usize, // for error context
+    line_tokens: VecDeque<String>,
+    column_layout_template: &DataCellRow,
+    column_typings: &[TypeColumnEntry],
+    parser: &PattiCsvParser,
+) -> Result<DataCellRow> {
+    type_line_tokens_timed(
+        line_num,
+        line_tokens,
+        None,
+        column_layout_template,
+        column_typings,
+        parser,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+}
+
+/// Same as `type_line_tokens`, but with the metadata of the tokens (if any), adds the time spent sanitizing and
+/// converting to the given timings (if any), and records what tolerant numeric columns stripped (if they record it,
+/// see: `TolerantNumeric`) and the columns of the cells, that can't be typed (if wanted). With resolved columns, the
+/// sanitizers by name run as well, and only the columns flagged as typed (if any) are typed, the others stay
+/// `Value::None`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn type_line_tokens_timed(
+    line_num: usize, // for error context
+    line_tokens: VecDeque<String>,
+    token_meta: Option<&[TokenMeta]>,
+    column_layout_template: &DataCellRow,
+    column_typings: &[TypeColumnEntry],
+    parser: &PattiCsvParser,
+    mut timings: Option<&mut StageTimings>,
+    stripped_tokens: Option<&mut Vec<(usize, usize, String)>>,
+    null_on_type_error: Option<&mut Vec<RowError>>,
+    resolved_columns: Option<&ResolvedColumns>,
+    type_error_columns: Option<&mut Vec<usize>>,
+) -> Result<DataCellRow> {
+    let sanitize_start = timings.is_some().then(Instant::now);
+    let sanitized_tokens = sanitize_tokenizer_iter_res(
+        line_num,
+        line_tokens,
+        &parser.column_transitizers,
+        parser.sanitizer_order.as_ref(),
+    )
+    .and_then(|mut tokens| {
+        if let Some(rc) = resolved_columns.filter(|rc| !rc.named_transitizers.is_empty()) {
+            apply_named_sanitizers(&mut tokens, &rc.named_transitizers, line_num)?;
+        }
+        Ok(tokens)
+    });
+    let convert_start = timings.is_some().then(Instant::now);
+    if let (Some(t), Some(start), Some(end)) =
+        (timings.as_deref_mut(), sanitize_start, convert_start)
+    {
+        t.sanitize += end.duration_since(start);
+    }
+
+    let mut row_data: DataCellRow = column_layout_template.clone();
+    let res = convert_sanitized_tokens(
+        line_num,
+        sanitized_tokens?,
+        token_meta,
+        &mut row_data,
+        column_typings,
+        parser,
+        stripped_tokens,
+        null_on_type_error,
+        resolved_columns,
+        type_error_columns,
+    );
+    if let (Some(t), Some(start)) = (timings, convert_start) {
+        t.convert += start.elapsed();
+    }
+    res.map(|_| row_data)
+}
+
+/// Helper method. Types the sanitized tokens into the (template) row. With `null_on_type_error`, bad cells are
+/// `Value::None`, and the errors of the row are pushed to it, instead of failing the row.
+#[allow(clippy::too_many_arguments)]
+fn convert_sanitized_tokens(
+    line_num: usize,
+    mut sanitized_tokens: VecDeque<String>,
+    token_meta: Option<&[TokenMeta]>,
+    row_data: &mut DataCellRow,
+    column_typings: &[TypeColumnEntry],
+    parser: &PattiCsvParser,
+    mut stripped_tokens: Option<&mut Vec<(usize, usize, String)>>,
+    null_on_type_error: Option<&mut Vec<RowError>>,
+    resolved_columns: Option<&ResolvedColumns>,
+    mut type_error_columns: Option<&mut Vec<usize>>,
+) -> Result<()> {
+    let column_transitizers = &parser.column_transitizers;
+    let column_converters = parser.column_converters.as_ref();
+    let value_transformers = parser.value_transformers.as_ref();
+    let typed_columns = resolved_columns.and_then(|rc| rc.typed.as_deref());
+    let mut cell_errors = Vec::new(); // only used, if we collect all cell errors of the row
+    let collect_cell_errors = parser.collect_all_cell_errors
+        || parser.error_policy != ErrorPolicy::Fail
+        || null_on_type_error.is_some();
+
+    // The derived cells (if any) are filled last, from the typed ones.
+    let num_typed = row_data.0.len().saturating_sub(num_derived_cells(
+        &parser.derived_columns,
+        &parser.derived_splits,
+    ));
+    let col_iter = row_data.0.iter_mut().take(num_typed).enumerate(); // TODO: is there a way we don't need to rely on the underlying vec?
+    for (i, cell) in col_iter {
+        // There always is a typing for every column of the template, because:
+        // a) if we have no typings, we use the same length (from the tokens/data) to build them, and ...
+        // b) if we have typings, we check against the length of the tokens/data, and...
+        // ...subsequently we build the column layout template from the typings, AND this layout template is then used (as a clone) here, as the rows_data.
+        // Still, we don't panic on this, if it's ever violated.
+        let curr_typing = column_typings
+            .get(i)
+            .ok_or_else(|| PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_TYPING] No typing for column; line: {}; column: {}",
+                    &line_num, &i
+                ),
+            })?;
+
+        let mut curr_token = sanitized_tokens.pop_front();
+        if typed_columns.is_some_and(|tc| !tc.get(i).copied().unwrap_or(true)) {
+            continue;
+        }
+        // A padded cell of a short line is `Value::None` (or the default), no matter the null policy
+        let padded = curr_token.is_none()
+            && parser.column_count_mismatch == Some(ColumnCountMismatchPolicy::PadWithNone);
+        let curr_token_meta = token_meta
+            .and_then(|m| m.get(i).copied())
+            .unwrap_or_default();
+
+        // Explicitly configured sanitizers for this column always win over the smart defaults.
+        if parser.smart_sanitizer_defaults
+            && !column_transitizers
+                .as_ref()
+                .is_some_and(|ct| ct.contains_key(&Some(i)))
+            && !resolved_columns.is_some_and(|rc| rc.named_transitizers.contains_key(&i))
+        {
+            curr_token = curr_token
+                .map(|t| apply_smart_sanitizer_defaults(&t, &curr_typing.target_type))
+                .transpose()?;
+        }
+
+        if let (Some(tolerant_numeric), Some(t)) = (&curr_typing.tolerant_numeric, &curr_token) {
+            if !curr_typing.maps_to_none(t, curr_token_meta) {
+                let (cleaned, stripped) = tolerant_numeric.clean(t);
+                if let (true, false, Some(st)) = (
+                    tolerant_numeric.record_stripped,
+                    stripped.is_empty(),
+                    stripped_tokens.as_deref_mut(),
+                ) {
+                    st.push((line_num, i, stripped));
+                }
+                curr_token = Some(cleaned);
+            }
+        }
+
+        let converter: &dyn CellConverter = match column_converters.and_then(|m| m.get(&i)) {
+            Some(c) => c.as_ref(),
+            None => &VenumCellConverter,
+        };
+        let failed_token = collect_cell_errors.then(|| curr_token.clone()).flatten();
+        let typed = match padded {
+            true => default_if_none(Value::None, curr_typing, converter),
+            false => type_cell(curr_token, curr_token_meta, curr_typing, converter),
+        }
+        .and_then(|v| match value_transformers.and_then(|m| m.get(&i)) {
+            Some(vts) => transform_value(v, vts),
+            None => Ok(v),
+        })
+        .and_then(|v| match &curr_typing.constraints {
+            Some(constraints) => match constraints.check(&v) {
+                Ok(()) => Ok(v),
+                Err(msg) => Err(PattiCsvError::Validation {
+                    line: line_num,
+                    column: i,
+                    header: String::from(cell.get_name()),
+                    msg,
+                }),
+            },
+            None => Ok(v),
+        });
+        match typed {
+            Ok(v) => cell.data = v,
+            Err(e) => {
+                let msg = match e {
+                    PattiCsvError::Generic { msg } => msg,
+                    PattiCsvError::Validation { msg, .. } if collect_cell_errors => msg,
+                    e @ PattiCsvError::Validation { .. } => return Err(e),
+                    other => format!("{:?}", other),
+                };
+                if let Some(tec) = type_error_columns.as_deref_mut() {
+                    tec.push(i);
+                }
+                if collect_cell_errors {
+                    if null_on_type_error.is_some() {
+                        cell.data = Value::None;
+                    }
+                    cell_errors.push(CellError {
+                        column: i,
+                        header: String::from(cell.get_name()),
+                        msg,
+                        token: failed_token,
+                    });
+                } else {
+                    return Err(PattiCsvError::Generic {
+                        msg: format!(
+                            "{}; line: {}; column: {}; header: {}",
+                            msg,
+                            &line_num,
+                            &i,
+                            &cell.get_name()
+                        ),
+                    });
+                }
+            }
+        }
+    }
+    if !cell_errors.is_empty() {
+        let row_error = RowError {
+            line: line_num,
+            cells: cell_errors,
+        };
+        match null_on_type_error {
+            Some(row_errors) => row_errors.push(row_error),
+            None => return Err(PattiCsvError::Row(row_error)),
+        }
+    }
+    if let Some(money_columns) = &parser.money_columns {
+        check_money_columns(row_data, money_columns, line_num)?;
+    }
+    if !parser.derived_columns.is_empty() || !parser.derived_splits.is_empty() {
+        derive_columns(
+            row_data,
+            &parser.derived_columns,
+            &parser.derived_splits,
+            line_num,
+        )?;
+    }
+    Ok(())
+}
+
+/// What the iterator resolved against the (complete) column layout template, i.e. once the header (or the first line)
+/// is known, to type the data rows with.
+#[derive(Debug, Default)]
+pub(crate) struct ResolvedColumns<'p> {
+    pub(crate) typed: Option<Vec<bool>>, // only with a column selection, see: typed_columns_for
+    pub(crate) named_transitizers: BTreeMap<usize, &'p VecOfTokenTransitizers>, // by column
+}
+
+/// Helper method. The first column of the (complete) column layout template with the given name. Names are matched
+/// like the typings by header (see: `TypeColumnEntry::matches_header`), i.e. against the name of the column, as well as
+/// the header and the header aliases of its typing, with the match mode of the parser (exact, if there is none).
+fn find_column_by_name(
+    name: &str,
+    template: &DataCellRow,
+    column_typings: &[TypeColumnEntry],
+    parser: &PattiCsvParser,
+) -> Option<usize> {
+    let mode = parser
+        .typings_by_header
+        .as_ref()
+        .unwrap_or(&HeaderMatchMode::Exact);
+    template.0.iter().enumerate().position(|(idx, cell)| {
+        mode.matches(&cell.name, name)
+            || column_typings
+                .get(idx)
+                .is_some_and(|tce| tce.matches_header(name, mode))
+    })
+}
+
+/// Helper method. Resolves the transitizers by name of the parser against the (complete) column layout template, to
+/// their columns. The first column with the name wins (see: `find_column_by_name`).
+pub(crate) fn resolve_named_transitizers<'p>(
+    parser: &'p PattiCsvParser,
+    template: &DataCellRow,
+    column_typings: &[TypeColumnEntry],
+) -> Result<BTreeMap<usize, &'p VecOfTokenTransitizers>> {
+    parser
+        .column_transitizers_by_name
+        .iter()
+        .flatten()
+        .map(|(name, transitizers)| {
+            find_column_by_name(name, template, column_typings, parser)
+                .map(|idx| (idx, transitizers))
+                .ok_or_else(|| PattiCsvError::ConfigError {
+                    msg: format!("no column named '{}' for its transitizers", name),
+                })
+        })
+        .collect()
+}
+
+/// Helper method. Resolves the selection against the (complete) column layout template, i.e. into the indexes of the
+/// selected columns, in the order of the selection. Names are matched like the typings by header (see:
+/// `find_column_by_name`).
+pub(crate) fn resolve_column_selection(
+    selection: &ColumnSelection,
+    column_layout_template: &DataCellRow,
+    column_typings: &[TypeColumnEntry],
+    parser: &PattiCsvParser,
+) -> Result<Vec<usize>> {
+    let num_columns = column_layout_template.0.len();
+    let idxs = match selection {
+        ColumnSelection::Idxs(idxs) => match idxs.iter().find(|&&i| i >= num_columns) {
+            Some(i) => {
+                return Err(PattiCsvError::ConfigError {
+                    msg: format!(
+                        "selected column {} doesn't exist, there are {} columns",
+                        i, num_columns
+                    ),
+                })
+            }
+            None => idxs.clone(),
+        },
+        ColumnSelection::Names(names) => names
+            .iter()
+            .map(|name| {
+                find_column_by_name(name, column_layout_template, column_typings, parser)
+                    .ok_or_else(|| PattiCsvError::ConfigError {
+                        msg: format!("selected column '{}' doesn't exist", name),
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?,
+    };
+    if let Some((pos, i)) = idxs
+        .iter()
+        .enumerate()
+        .find(|(pos, i)| idxs[..*pos].contains(i))
+    {
+        return Err(PattiCsvError::ConfigError {
+            msg: format!(
+                "columns can only be selected once, but column {} is selected again at position {}",
+                i, pos
+            ),
+        });
+    }
+    Ok(idxs)
+}
+
+/// Helper method. Which columns have to be typed for the selected ones: the selected ones themselves, and the ones the
+/// checks of money columns, the derived columns and the unique keys need.
+pub(crate) fn typed_columns_for(
+    selected: &[usize],
+    num_columns: usize,
+    parser: &PattiCsvParser,
+) -> Vec<bool> {
+    let mut typed = vec![false; num_columns];
+    let needed = parser
+        .money_columns
+        .iter()
+        .flatten()
+        .flat_map(|mc| [mc.amount_col, mc.currency_col])
+        .chain(parser.derived_columns.iter().map(|dc| dc.column))
+        .chain(parser.derived_splits.iter().map(|ds| ds.column))
+        .chain(parser.unique_columns.iter().flatten().copied());
+    for i in selected.iter().copied().chain(needed) {
+        if let Some(t) = typed.get_mut(i) {
+            *t = true;
+        }
+    }
+    typed
+}
+
+/// Helper method. The selected cells of the row, in the order of the selection (and indexed accordingly).
+pub(crate) fn project_row(row: DataCellRow, selected: &[usize]) -> DataCellRow {
+    let mut cells = row.0.into_iter().map(Some).collect::<Vec<_>>();
+    DataCellRow(
+        selected
+            .iter()
+            .enumerate()
+            .filter_map(|(pos, &i)| {
+                cells.get_mut(i).and_then(Option::take).map(|mut cell| {
+                    cell.idx = pos;
+                    cell
+                })
+            })
+            .collect(),
+    )
+}
+
 pub fn sanitize_token<T: Into<String>>(
     token: T,
     column_sanitizers: &HashMap<Option<usize>, VecOfTokenTransitizers>,
     line_num: usize, // for error context
     col_num: usize,  // used internally AND for error context
+) -> Result<String> {
+    sanitize_token_ordered(
+        token,
+        column_sanitizers,
+        SanitizerOrder::GlobalFirst,
+        line_num,
+        col_num,
+    )
+}
+
+/// Same as `sanitize_token`, but with the given order of the global and the local sanitizers.
+pub fn sanitize_token_ordered<T: Into<String>>(
+    token: T,
+    column_sanitizers: &HashMap<Option<usize>, VecOfTokenTransitizers>,
+    order: SanitizerOrder,
+    line_num: usize, // for error context
+    col_num: usize,  // used internally AND for error context
+) -> Result<String> {
+    let token = token.into();
+    match order {
+        SanitizerOrder::GlobalFirst => {
+            let token = apply_global_sanitizers(token, column_sanitizers, line_num, col_num)?;
+            apply_local_sanitizers(token, column_sanitizers, line_num, col_num)
+        }
+        SanitizerOrder::LocalFirst => {
+            let token = apply_local_sanitizers(token, column_sanitizers, line_num, col_num)?;
+            apply_global_sanitizers(token, column_sanitizers, line_num, col_num)
+        }
+    }
+}
+
+fn apply_global_sanitizers(
+    token: String,
+    column_sanitizers: &HashMap<Option<usize>, VecOfTokenTransitizers>,
+    line_num: usize,
+    col_num: usize,
 ) -> Result<String> {
     // If we have sanitizers for index=None, that means, we have global sanitizers, not bound to any index. I.e. they will always be applied.
     // Note that this strongly differs from getting None as a result of a .get on the HashMap!
-    let token = token.into();
-    let token = match column_sanitizers.get(&None) {
+    match column_sanitizers.get(&None) {
         Some(tst) => tst.iter().try_fold(token, |acc, transitizer| {
             transitizer
                 .transitize(&acc) // apply filter, then yield
@@ -75,46 +732,86 @@ pub fn sanitize_token<T: Into<String>>(
                             Some(line_num),
                             None,
                         ))
+                    } else if let PattiCsvError::DropRow { reason } = e {
+                        PattiCsvError::DropRow {
+                            reason: format!("{}; column: {}", reason, col_num),
+                        }
                     } else {
-                        panic!("If we end up here, we mixed errors!");
+                        e // If we end up here, we mixed errors! Still, we pass them on as is.
                     }
                 })
         }),
         None => Ok(token), // no global sanitizers. move on.
-    }?;
+    }
+}
 
+fn apply_local_sanitizers(
+    token: String,
+    column_sanitizers: &HashMap<Option<usize>, VecOfTokenTransitizers>,
+    line_num: usize,
+    col_num: usize,
+) -> Result<String> {
     // "local" (aka indexed) column sanitizers
     match column_sanitizers.get(&Some(col_num)) {
         // We don't have a local sanitizer for the specific "column", return token as is
         None => Ok(token),
         // Apply all sanitizers and return the sanitized token in the end
-        Some(tst) => tst.iter().try_fold(token, |acc, transitizer| {
-            transitizer
-                .transitize(&acc)
-                // Supply more error context
-                .map_err(|e| {
-                    if let PattiCsvError::Sanitize(se) = e {
-                        PattiCsvError::Sanitize(SanitizeError::extend(
-                            se,
-                            Some(format!(
-                                " Error in/from local sanitizer: {}.",
-                                &transitizer.get_self_info(),
-                            )),
-                            Some(line_num),
-                            Some(col_num),
-                        ))
-                    } else {
-                        panic!("If we end up here, we mixed errors!");
+        Some(tst) => apply_local_chain(token, tst, line_num, col_num),
+    }
+}
+
+/// Helper method. Applies the sanitizers of one column (configured by index or by name).
+fn apply_local_chain(
+    token: String,
+    tst: &VecOfTokenTransitizers,
+    line_num: usize,
+    col_num: usize,
+) -> Result<String> {
+    tst.iter().try_fold(token, |acc, transitizer| {
+        transitizer
+            .transitize(&acc)
+            // Supply more error context
+            .map_err(|e| {
+                if let PattiCsvError::Sanitize(se) = e {
+                    PattiCsvError::Sanitize(SanitizeError::extend(
+                        se,
+                        Some(format!(
+                            " Error in/from local sanitizer: {}.",
+                            &transitizer.get_self_info(),
+                        )),
+                        Some(line_num),
+                        Some(col_num),
+                    ))
+                } else if let PattiCsvError::DropRow { reason } = e {
+                    PattiCsvError::DropRow {
+                        reason: format!("{}; column: {}", reason, col_num),
                     }
-                })
-        }),
+                } else {
+                    e // If we end up here, we mixed errors! Still, we pass them on as is.
+                }
+            })
+    })
+}
+
+/// Helper method. Applies the sanitizers configured by the name of a column, after the ones configured by index.
+fn apply_named_sanitizers(
+    tokens: &mut VecDeque<String>,
+    named_transitizers: &BTreeMap<usize, &VecOfTokenTransitizers>,
+    line_num: usize,
+) -> Result<()> {
+    for (&i, &tst) in named_transitizers {
+        if let Some(token) = tokens.get_mut(i) {
+            *token = apply_local_chain(std::mem::take(token), tst, line_num, i)?;
+        }
     }
+    Ok(())
 }
 
 pub fn sanitize_tokenizer_iter_res(
     line_number: usize,
     line_tokens: VecDeque<String>,
     column_transitizers: &Option<HashMap<Option<usize>, VecOfTokenTransitizers>>,
+    sanitizer_order: Option<&HashMap<usize, SanitizerOrder>>, // by column, the default is GlobalFirst
 ) -> Result<VecDeque<String>> {
     match column_transitizers {
         None => Ok(line_tokens),
@@ -124,7 +821,10 @@ pub fn sanitize_tokenizer_iter_res(
             for (i, token) in line_tokens.into_iter().enumerate() {
                 // On first glance, borrowing and transforming inplace is smarter, however, all the transformations
                 // we use, allocate a new String anyway, so it doesn't make much sense.
-                ret.push_back(sanitize_token(token, ct, line_number, i)?);
+                let order = sanitizer_order
+                    .and_then(|so| so.get(&i).copied())
+                    .unwrap_or_default();
+                ret.push_back(sanitize_token_ordered(token, ct, order, line_number, i)?);
             }
             Ok(ret)
         }
@@ -133,9 +833,6 @@ pub fn sanitize_tokenizer_iter_res(
 
 #[cfg(test)]
 mod tests {
-    use venum::value::Value;
-    use venum::value_type::ValueType;
-
     use crate::transform_sanitize_token::*;
 
     use super::*;
@@ -241,6 +938,133 @@ mod tests {
         assert_eq!(exp, res);
     }
 
+    #[test]
+    fn test_resolve_column_typings_by_header() {
+        let header_tokens: VecDeque<String> = VecDeque::from(vec![
+            String::from("id"),
+            String::from("quantity "),
+            String::from("Price"),
+        ]);
+        let column_typings = vec![
+            TypeColumnEntry::new(Some(String::from("Price")), ValueType::Float64),
+            TypeColumnEntry::new(Some(String::from("Qty")), ValueType::Int32)
+                .with_header_aliases(vec![String::from("Quantity")]),
+        ];
+
+        let res = resolve_column_typings_by_header(
+            &header_tokens,
+            &column_typings,
+            &HeaderMatchMode::IgnoreCaseAndWhitespace,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                TypeColumnEntry::new(None, ValueType::String),
+                column_typings[1].clone(),
+                column_typings[0].clone(),
+            ],
+            res
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "No column in the header line matches typing#1")]
+    fn test_resolve_column_typings_by_header_exact_err() {
+        let header_tokens: VecDeque<String> =
+            VecDeque::from(vec![String::from("Price"), String::from("quantity ")]);
+        let column_typings = vec![
+            TypeColumnEntry::new(Some(String::from("Price")), ValueType::Float64),
+            TypeColumnEntry::new(Some(String::from("Qty")), ValueType::Int32)
+                .with_header_aliases(vec![String::from("Quantity")]),
+        ];
+
+        resolve_column_typings_by_header(&header_tokens, &column_typings, &HeaderMatchMode::Exact)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_dedup_header_tokens() {
+        let headers = |v: &[&str]| v.iter().map(|s| String::from(*s)).collect::<VecDeque<_>>();
+
+        let mut header_tokens = headers(&["id", "name", "id", "id_2", "id"]);
+        dedup_header_tokens(&mut header_tokens, DuplicateHeaderPolicy::RenameWithSuffix).unwrap();
+        assert_eq!(
+            headers(&["id", "name", "id_3", "id_2", "id_4"]),
+            header_tokens
+        );
+
+        let mut header_tokens = headers(&["id", "name", "id"]);
+        dedup_header_tokens(&mut header_tokens, DuplicateHeaderPolicy::KeepFirst).unwrap();
+        assert_eq!(headers(&["id", "name", "id"]), header_tokens);
+        assert!(matches!(
+            dedup_header_tokens(&mut header_tokens, DuplicateHeaderPolicy::Error),
+            Err(PattiCsvError::ConfigError { .. })
+        ));
+        assert!(
+            dedup_header_tokens(&mut headers(&["id", "Id"]), DuplicateHeaderPolicy::Error).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_check_column_count() {
+        let tokens = |v: &[&str]| v.iter().map(|s| String::from(*s)).collect::<VecDeque<_>>();
+
+        let mut long = tokens(&["a", "b", "c"]);
+        let mut token_meta = vec![TokenMeta::default(); 3];
+        assert!(check_column_count(1, &mut long, None, 2, None).is_ok());
+        assert_eq!(
+            Err(PattiCsvError::ColumnCountMismatch {
+                line: 1,
+                expected: 2,
+                found: 3
+            }),
+            check_column_count(
+                1,
+                &mut long,
+                None,
+                2,
+                Some(ColumnCountMismatchPolicy::Error)
+            )
+        );
+        assert!(check_column_count(
+            1,
+            &mut long,
+            None,
+            2,
+            Some(ColumnCountMismatchPolicy::PadWithNone)
+        )
+        .is_err());
+        check_column_count(
+            1,
+            &mut long,
+            Some(&mut token_meta),
+            2,
+            Some(ColumnCountMismatchPolicy::TruncateExtra),
+        )
+        .unwrap();
+        assert_eq!(tokens(&["a", "b"]), long);
+        assert_eq!(2, token_meta.len());
+
+        let mut short = tokens(&["a"]);
+        assert!(check_column_count(
+            2,
+            &mut short,
+            None,
+            2,
+            Some(ColumnCountMismatchPolicy::PadWithNone)
+        )
+        .is_ok());
+        assert!(check_column_count(
+            2,
+            &mut short,
+            None,
+            2,
+            Some(ColumnCountMismatchPolicy::TruncateExtra)
+        )
+        .is_err());
+    }
+
     #[test]
     fn test_sanitize_token_global() {
         let mut san_hm: HashMap<Option<usize>, VecOfTokenTransitizers> = HashMap::with_capacity(1);
@@ -269,6 +1093,20 @@ mod tests {
         assert_eq!(String::from("10.00"), res);
     }
 
+    #[test]
+    fn test_sanitize_token_local_first() {
+        let mut san_hm: HashMap<Option<usize>, VecOfTokenTransitizers> = HashMap::with_capacity(2);
+        san_hm.insert(None, vec![Box::new(ReplaceWith::new(" ", ""))]);
+        san_hm.insert(Some(0), vec![Box::new(RegexTake::new("^(\\S+)").unwrap())]);
+
+        let res = sanitize_token("10 000 (CHF)", &san_hm, 112, 0).unwrap();
+        assert_eq!(String::from("10000(CHF)"), res);
+        let res =
+            sanitize_token_ordered("10 000 (CHF)", &san_hm, SanitizerOrder::LocalFirst, 112, 0)
+                .unwrap();
+        assert_eq!(String::from("10"), res);
+    }
+
     #[test]
     #[should_panic(
         expected = "Sanitize(SanitizeError { msg: \"No captures, but we need exactly one. Error in/from global sanitizer: RegexTake { regex: "
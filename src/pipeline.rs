@@ -0,0 +1,372 @@
+use std::{collections::VecDeque, fmt::Debug, io::Read};
+
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    iterating_parser::{PattiCsvParser, PattiCsvParserBuilder},
+    report::ParseReport,
+};
+
+/// A custom stage on the raw tokens of a data line, i.e. after tokenizing (and skipping/taking lines), but before the
+/// tokens are sanitized and typed. E.g. to merge or split columns, or to fix up broken exports.
+///
+/// NOTE: The header line doesn't go through these stages. If a stage changes the number of tokens, the typings must
+/// match the new number.
+pub trait TokenStage: Debug {
+    fn process(&self, line_num: usize, tokens: &mut VecDeque<String>) -> Result<()>;
+    fn get_self_info(&self) -> String {
+        String::from("n/a")
+    }
+}
+
+/// A custom stage on the typed data rows, i.e. after sanitizing, typing and the built-in checks. A stage can:
+/// - validate: return an error for the row
+/// - enrich: change the row, e.g. add derived cells
+/// - filter: return `None`, i.e. drop the row (it's counted in the stats, see: `num_rows_filtered`)
+///
+/// NOTE: The header row doesn't go through these stages.
+pub trait RowStage: Debug {
+    fn process(&self, line_num: usize, row: DataCellRow) -> Result<Option<DataCellRow>>;
+    fn get_self_info(&self) -> String {
+        String::from("n/a")
+    }
+}
+
+pub type BoxedTokenStage = Box<dyn TokenStage + Send + Sync>;
+pub type BoxedRowStage = Box<dyn RowStage + Send + Sync>;
+
+/// A `RowStage` from a closure, for the quick cases.
+pub struct FnRowStage<F> {
+    name: String,
+    f: F,
+}
+impl<F> FnRowStage<F>
+where
+    F: Fn(usize, DataCellRow) -> Result<Option<DataCellRow>>,
+{
+    pub fn new<S: Into<String>>(name: S, f: F) -> Self {
+        Self {
+            name: name.into(),
+            f,
+        }
+    }
+}
+impl<F> Debug for FnRowStage<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnRowStage")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+impl<F> RowStage for FnRowStage<F>
+where
+    F: Fn(usize, DataCellRow) -> Result<Option<DataCellRow>>,
+{
+    fn process(&self, line_num: usize, row: DataCellRow) -> Result<Option<DataCellRow>> {
+        (self.f)(line_num, row)
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Where the rows of a `Pipeline` end up.
+pub trait RowSink {
+    fn write_header(&mut self, _header: &DataCellRow) -> Result<()> {
+        Ok(())
+    }
+    fn write_row(&mut self, line_num: usize, row: DataCellRow) -> Result<()>;
+    /// Called for every row that failed. The default is to fail fast, i.e. the error ends the run.
+    fn on_error(&mut self, _line_num: usize, e: PattiCsvError) -> Result<()> {
+        Err(e)
+    }
+    /// Called for every row dropped by a transitizer (see: `PattiCsvError::DropRow`), e.g. to quarantine it.
+    fn on_dropped(&mut self, _line_num: usize, _reason: &str) -> Result<()> {
+        Ok(())
+    }
+    /// Called once at the end of a (successful) run, e.g. to flush or close an output.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// E.g. for sinks of different types behind one `Box<dyn RowSink>`.
+impl<S: RowSink + ?Sized> RowSink for Box<S> {
+    fn write_header(&mut self, header: &DataCellRow) -> Result<()> {
+        (**self).write_header(header)
+    }
+    fn write_row(&mut self, line_num: usize, row: DataCellRow) -> Result<()> {
+        (**self).write_row(line_num, row)
+    }
+    fn on_error(&mut self, line_num: usize, e: PattiCsvError) -> Result<()> {
+        (**self).on_error(line_num, e)
+    }
+    fn on_dropped(&mut self, line_num: usize, reason: &str) -> Result<()> {
+        (**self).on_dropped(line_num, reason)
+    }
+    fn finish(&mut self) -> Result<()> {
+        (**self).finish()
+    }
+}
+
+/// Collects all (data) rows with their line numbers. Fails fast.
+impl RowSink for Vec<(usize, DataCellRow)> {
+    fn write_row(&mut self, line_num: usize, row: DataCellRow) -> Result<()> {
+        self.push((line_num, row));
+        Ok(())
+    }
+}
+
+/// A parser with custom stages, that runs into a sink. The stages of a run are (in this order):
+///
+/// 1. skip/take lines (see: `SkipTakeLines`)
+/// 2. tokenize
+/// 3. split columns (see: `SplitColumn`)
+/// 4. custom `TokenStage`s (data lines only)
+/// 5. sanitize (see: `TransformSanitizeToken`)
+/// 6. type (see: `CellConverter`), incl. the built-in checks (e.g. money columns, duplicate rows)
+/// 7. custom `RowStage`s (data rows only), i.e. validate, enrich, filter
+/// 8. the `RowSink`
+///
+/// The custom stages are part of the parser, i.e. `parse_iter` runs them as well. This is just the explicit way to put
+/// it all together.
+#[derive(Debug)]
+pub struct Pipeline {
+    parser: PattiCsvParser,
+}
+
+impl Pipeline {
+    pub fn builder(parser_builder: PattiCsvParserBuilder) -> PipelineBuilder {
+        PipelineBuilder {
+            parser_builder,
+            token_stages: Vec::new(),
+            row_stages: Vec::new(),
+        }
+    }
+
+    pub fn parser(&self) -> &PattiCsvParser {
+        &self.parser
+    }
+
+    /// Parses everything into the sink, and returns the report of the run.
+    pub fn run<R: Read, S: RowSink>(&self, reader: &mut R, sink: &mut S) -> Result<ParseReport> {
+        let mut iter = self.parser.parse_iter(reader);
+        let mut header_written = false;
+        let mut num_dropped = 0;
+        while let Some(res) = iter.next() {
+            // Dropped rows are not yielded, so we catch up on them before every yielded row.
+            for (line_num, reason) in &iter.get_stats().dropped_rows[num_dropped..] {
+                sink.on_dropped(*line_num, reason)?;
+            }
+            num_dropped = iter.get_stats().dropped_rows.len();
+            // The header row may not be emitted (see: `emit_header_row`), so we take it from the iterator.
+            if let (false, Some(header)) = (header_written, iter.header()) {
+                sink.write_header(header)?;
+                header_written = true;
+            }
+            let line_num = iter.curr_line_num();
+            match res {
+                Ok(_) if iter.is_at_header_row() => (),
+                Ok(row) => sink.write_row(line_num, row)?,
+                Err(e) => sink.on_error(line_num, e)?,
+            }
+        }
+        for (line_num, reason) in &iter.get_stats().dropped_rows[num_dropped..] {
+            sink.on_dropped(*line_num, reason)?;
+        }
+        sink.finish()?;
+        Ok(iter.finish_report())
+    }
+}
+
+pub struct PipelineBuilder {
+    parser_builder: PattiCsvParserBuilder,
+    token_stages: Vec<BoxedTokenStage>,
+    row_stages: Vec<BoxedRowStage>,
+}
+
+impl PipelineBuilder {
+    /// Custom stages run in the order they were added.
+    pub fn token_stage(mut self, stage: BoxedTokenStage) -> PipelineBuilder {
+        self.token_stages.push(stage);
+        self
+    }
+
+    /// Custom stages run in the order they were added.
+    pub fn row_stage(mut self, stage: BoxedRowStage) -> PipelineBuilder {
+        self.row_stages.push(stage);
+        self
+    }
+
+    pub fn build(self) -> Result<Pipeline> {
+        let mut parser_builder = self.parser_builder;
+        for stage in self.token_stages {
+            parser_builder = parser_builder.token_stage(stage);
+        }
+        for stage in self.row_stages {
+            parser_builder = parser_builder.row_stage(stage);
+        }
+        Ok(Pipeline {
+            parser: parser_builder.build()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::{value::Value, value_type::ValueType};
+    use venum_tds::data_cell::DataCell;
+
+    use std::collections::HashMap;
+
+    use crate::{
+        parser_config::TypeColumnEntry,
+        transform_sanitize_token::{DropRowOn, TransformSanitizeToken},
+    };
+
+    use super::*;
+
+    /// Joins the first two tokens, e.g. a first and a last name.
+    #[derive(Debug)]
+    struct JoinFirstTwo;
+    impl TokenStage for JoinFirstTwo {
+        fn process(&self, _line_num: usize, tokens: &mut VecDeque<String>) -> Result<()> {
+            let first = tokens.pop_front().unwrap_or_default();
+            if let Some(second) = tokens.front_mut() {
+                *second = format!("{} {}", first, second);
+            }
+            Ok(())
+        }
+    }
+
+    fn pipeline() -> Pipeline {
+        Pipeline::builder(PattiCsvParserBuilder::csv().column_typings(vec![
+            TypeColumnEntry::new(Some(String::from("name")), ValueType::String),
+            TypeColumnEntry::new(Some(String::from("age")), ValueType::UInt8),
+        ]))
+        .token_stage(Box::new(JoinFirstTwo))
+        // validate
+        .row_stage(Box::new(FnRowStage::new(
+            "age<120",
+            |line_num, row| match row.get_by_name("age").unwrap().get_data() {
+                Value::UInt8(age) if *age >= 120 => Err(PattiCsvError::Generic {
+                    msg: format!("[ERROR_ON_AGE] line: {}", line_num),
+                }),
+                _ => Ok(Some(row)),
+            },
+        )))
+        // filter
+        .row_stage(Box::new(FnRowStage::new("no minors", |_, row| {
+            match row.get_by_name("age").unwrap().get_data() {
+                Value::UInt8(age) if *age < 18 => Ok(None),
+                _ => Ok(Some(row)),
+            }
+        })))
+        // enrich
+        .row_stage(Box::new(FnRowStage::new("line", |line_num, mut row| {
+            let idx = row.0.len();
+            row.push(
+                DataCell::new(String::from("line"), idx, Value::UInt64(line_num as u64)).unwrap(),
+            );
+            Ok(Some(row))
+        })))
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn pipeline_with_custom_stages() {
+        let mut test_data_cursor =
+            std::io::Cursor::new("name,age\nAda,Lovelace,36\nKid,Doe,7\nAlan,Turing,41");
+        let mut rows = Vec::new();
+        let report = pipeline().run(&mut test_data_cursor, &mut rows).unwrap();
+
+        assert_eq!(
+            vec![
+                (2, String::from("Ada Lovelace")),
+                (4, String::from("Alan Turing"))
+            ],
+            rows.iter()
+                .map(|(l, r)| (
+                    *l,
+                    String::try_from(r.get_by_name("name").unwrap().get_data()).unwrap()
+                ))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            &Value::UInt64(4),
+            rows[1].1.get_by_name("line").unwrap().get_data()
+        );
+        assert_eq!((2, 1), (report.num_rows_ok, report.num_rows_filtered));
+    }
+
+    #[test]
+    fn pipeline_fails_fast_by_default() {
+        let mut test_data_cursor =
+            std::io::Cursor::new("name,age\nAda,Lovelace,36\nOld,Timer,130\nAlan,Turing,41");
+        let mut rows = Vec::new();
+        let res = pipeline().run(&mut test_data_cursor, &mut rows);
+        assert!(
+            matches!(&res, Err(PattiCsvError::Generic { msg }) if msg == "[ERROR_ON_AGE] line: 3"),
+            "{:?}",
+            res
+        );
+        assert_eq!(1, rows.len());
+    }
+
+    /// Collects the rows and quarantines the dropped ones.
+    #[derive(Default)]
+    struct QuarantineSink {
+        rows: Vec<(usize, DataCellRow)>,
+        quarantined: Vec<(usize, String)>,
+    }
+    impl RowSink for QuarantineSink {
+        fn write_row(&mut self, line_num: usize, row: DataCellRow) -> Result<()> {
+            self.rows.write_row(line_num, row)
+        }
+        fn on_dropped(&mut self, line_num: usize, reason: &str) -> Result<()> {
+            self.quarantined.push((line_num, String::from(reason)));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pipeline_routes_dropped_rows_to_the_sink() {
+        let pipeline = Pipeline::builder(
+            PattiCsvParserBuilder::csv()
+                .stringly_type_columns(2)
+                .column_transitizers(HashMap::from([(
+                    Some(1),
+                    vec![Box::new(DropRowOn::new(vec!["VOID"]))
+                        as Box<dyn TransformSanitizeToken + Send + Sync>],
+                )])),
+        )
+        .build()
+        .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("id,status\n1,VOID\n2,ok\n3,VOID\n4,VOID");
+        let mut sink = QuarantineSink::default();
+        let report = pipeline.run(&mut test_data_cursor, &mut sink).unwrap();
+        assert_eq!(
+            vec![3],
+            sink.rows.iter().map(|(l, _)| *l).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![2, 4, 5],
+            sink.quarantined.iter().map(|(l, _)| *l).collect::<Vec<_>>()
+        );
+        assert_eq!(3, report.num_rows_dropped);
+    }
+
+    #[test]
+    fn parse_iter_runs_custom_stages() {
+        let mut test_data_cursor = std::io::Cursor::new("name,age\nAda,Lovelace,36\nKid,Doe,7");
+        let res = pipeline()
+            .parser()
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(2, res.len()); // header + one row
+        assert_eq!(3, res[1].0.len());
+    }
+}
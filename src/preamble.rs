@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use crate::parser_config::{Preamble, PreambleLines};
+
+/// Keeps track of the preamble (if any) while reading the first lines, and collects its key-value pairs.
+#[derive(Debug, Clone)]
+pub(crate) struct PreambleState<'a> {
+    preamble: Option<&'a Preamble>,
+    num_lines: usize,
+    pub(crate) values: HashMap<String, String>,
+}
+
+impl<'a> PreambleState<'a> {
+    pub(crate) fn new(preamble: Option<&'a Preamble>) -> Self {
+        Self {
+            preamble,
+            num_lines: 0,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Is the line (without its terminator) part of the preamble? If so, its key-value pair is taken. Must be called
+    /// for every line from the start, until it returns `false` (after that, it always does).
+    pub(crate) fn consume(&mut self, line: &str) -> bool {
+        let preamble = match self.preamble {
+            Some(p) => p,
+            None => return false,
+        };
+        let kv = line.split_once(preamble.kv_separator.as_str());
+        let is_preamble = match preamble.lines {
+            PreambleLines::Count(n) => self.num_lines < n,
+            PreambleLines::WhileKeyValue => kv.is_some() || line.trim().is_empty(),
+        };
+        if !is_preamble {
+            self.preamble = None; // done
+            return false;
+        }
+        self.num_lines += 1;
+        match kv {
+            Some((key, value)) => {
+                self.values
+                    .insert(String::from(key.trim()), String::from(value.trim()));
+            }
+            None if preamble.lines == PreambleLines::WhileKeyValue => self.preamble = None, // empty line, i.e. done
+            None => (),
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn consume_all(preamble: &Preamble, lines: &[&str]) -> (Vec<bool>, HashMap<String, String>) {
+        let mut state = PreambleState::new(Some(preamble));
+        let mask = lines.iter().map(|l| state.consume(l)).collect();
+        (mask, state.values)
+    }
+
+    #[test]
+    fn preamble_by_count() {
+        let (mask, values) = consume_all(
+            &Preamble::lines(3),
+            &["Report: Sales", "", "Date: 2024-01-01", "a,b", "x:y"],
+        );
+        assert_eq!(vec![true, true, true, false, false], mask);
+        assert_eq!(
+            HashMap::from([
+                (String::from("Report"), String::from("Sales")),
+                (String::from("Date"), String::from("2024-01-01"))
+            ]),
+            values
+        );
+    }
+
+    #[test]
+    fn preamble_while_key_value() {
+        let (mask, values) = consume_all(
+            &Preamble::while_key_value().with_kv_separator("="),
+            &["report = Sales", "date=2024-01-01=x", "", "a,b", "c=d"],
+        );
+        assert_eq!(vec![true, true, true, false, false], mask);
+        assert_eq!(Some(&String::from("2024-01-01=x")), values.get("date"));
+        assert_eq!(2, values.len());
+
+        // Without an empty line, the first line without a separator is no part of it.
+        let (mask, _) = consume_all(&Preamble::while_key_value(), &["k: v", "a,b", "c: d"]);
+        assert_eq!(vec![true, false, false], mask);
+    }
+
+    #[test]
+    fn no_preamble() {
+        let mut state = PreambleState::new(None);
+        assert!(!state.consume("k: v"));
+        assert!(state.values.is_empty());
+    }
+}
@@ -0,0 +1,508 @@
+use std::{collections::HashSet, io::Read};
+
+#[cfg(feature = "jsonconf")]
+use crate::errors::PattiCsvError;
+use crate::{errors::Result, line_tokenizer::DelimitedLineTokenizer};
+
+/// Separators we look for when sniffing, in order of preference (if the counts are equally consistent).
+const SNIFF_SEPARATORS: [char; 4] = [',', ';', '\t', '|'];
+/// Enclosure characters we look for when sniffing, in order of preference.
+const SNIFF_ENCLOSURES: [char; 2] = ['"', '\''];
+
+/// Result of a quick look at the first lines of some data. See: `PattiCsvParser::probe`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeResult {
+    /// (line number, number of columns) for every sampled line.
+    pub column_counts: Vec<(usize, usize)>,
+    /// Do all sampled lines have the same number of columns?
+    pub consistent: bool,
+    /// The tokens of the first sampled line, if it looks like a header line.
+    pub candidate_header: Option<Vec<String>>,
+    /// The (raw) tokens of all sampled lines, including the candidate header.
+    pub sample: Vec<Vec<String>>,
+}
+
+impl ProbeResult {
+    /// The column count, if all sampled lines agree on it.
+    pub fn column_count(&self) -> Option<usize> {
+        match (self.consistent, self.column_counts.first()) {
+            (true, Some((_, n))) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "jsonconf", derive(serde::Serialize))]
+pub enum SniffedEncoding {
+    Ascii,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+    /// Neither valid UTF-8, nor does it look like UTF-16. Probably some legacy single-byte encoding.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "jsonconf", derive(serde::Serialize))]
+pub enum LineTerminator {
+    Lf,
+    CrLf,
+    Cr,
+    Mixed,
+    /// No line terminator in the sniffed bytes, i.e. (at most) one line.
+    None,
+}
+
+/// What sniffing the first bytes of some data found out, and how sure we are about it. See: `PattiCsvParser::sniff`.
+///
+/// The confidence scores are in `[0.0, 1.0]`. With the `jsonconf` feature, this is serializable, so ingestion services
+/// can log why a dialect was chosen.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "jsonconf", derive(serde::Serialize))]
+#[cfg_attr(feature = "jsonconf", serde(rename_all = "camelCase"))]
+pub struct SniffReport {
+    pub num_bytes_sniffed: usize,
+    pub encoding: SniffedEncoding,
+    pub bom: bool,
+    pub line_terminator: LineTerminator,
+    pub num_lines_sniffed: usize,
+    pub separator_char: Option<char>,
+    /// The share of sniffed lines, that have the most common number of separators.
+    pub separator_confidence: f64,
+    pub enclosure_char: Option<char>,
+    /// The share of enclosure characters, that are where they belong, i.e. at the start/end of a field or escaped
+    /// (doubled) within one.
+    pub enclosure_confidence: f64,
+    /// Human-readable notes on how the dialect was chosen.
+    pub notes: Vec<String>,
+}
+
+impl SniffReport {
+    #[cfg(feature = "jsonconf")]
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| PattiCsvError::Generic {
+            msg: format!("[ERROR_ON_REPORT] Could not serialize sniff report: {}", e),
+        })
+    }
+}
+
+/// Reads (at most) `max_bytes` bytes and guesses the encoding, line terminator, separator and enclosure char. This
+/// only looks at the raw data, i.e. it doesn't need (or use) any parser config.
+pub fn sniff<R: Read>(reader: &mut R, max_bytes: usize) -> Result<SniffReport> {
+    let mut bytes = Vec::with_capacity(max_bytes.min(64 * 1024));
+    reader.take(max_bytes as u64).read_to_end(&mut bytes)?;
+    let truncated = bytes.len() == max_bytes;
+
+    let mut notes = Vec::new();
+    let (encoding, bom_len) = sniff_encoding(&bytes);
+    let text = match decode(&bytes[bom_len..], encoding) {
+        Some(text) => text,
+        None => {
+            notes.push(String::from(
+                "Encoding is unknown, so no dialect could be sniffed",
+            ));
+            String::new()
+        }
+    };
+
+    let line_terminator = sniff_line_terminator(&text);
+    let mut lines: Vec<&str> = match line_terminator {
+        LineTerminator::Cr => text.split('\r').collect(),
+        _ => text.lines().collect(),
+    };
+    // The last line is probably cut off (unless we've read everything), so we don't use it.
+    if truncated && lines.len() > 1 {
+        lines.pop();
+    }
+    lines.retain(|l| !l.is_empty());
+
+    let enclosure_char = SNIFF_ENCLOSURES
+        .into_iter()
+        .filter(|e| text.contains(*e))
+        .max_by(|a, b| {
+            let count = |c: &char| text.matches(*c).count();
+            count(a).cmp(&count(b)).then(b.cmp(a))
+        });
+    let (separator_char, separator_confidence) = sniff_separator(&lines, enclosure_char);
+    match separator_char {
+        Some(sep) => notes.push(format!(
+            "Separator {:?}, {:.0}% of {} lines have the same number of it",
+            sep,
+            separator_confidence * 100.0,
+            lines.len()
+        )),
+        None if !lines.is_empty() => {
+            notes.push(String::from("No separator found, probably only one column"))
+        }
+        None => (),
+    }
+    let enclosure_confidence = match (enclosure_char, separator_char) {
+        (Some(encl), Some(sep)) => {
+            let conf = sniff_enclosure_confidence(&lines, sep, encl);
+            notes.push(format!(
+                "Enclosure char {:?}, {:.0}% of its occurrences are at field boundaries or escaped",
+                encl,
+                conf * 100.0
+            ));
+            conf
+        }
+        _ => 0.0,
+    };
+
+    Ok(SniffReport {
+        num_bytes_sniffed: bytes.len(),
+        encoding,
+        bom: bom_len > 0,
+        line_terminator,
+        num_lines_sniffed: lines.len(),
+        separator_char,
+        separator_confidence,
+        enclosure_char: enclosure_char.filter(|_| separator_char.is_some()),
+        enclosure_confidence,
+        notes,
+    })
+}
+
+/// The encoding and the length of the BOM (0 if there is none).
+pub(crate) fn sniff_encoding(bytes: &[u8]) -> (SniffedEncoding, usize) {
+    // UTF-32 first, since the UTF-32LE BOM starts with the UTF-16LE one.
+    match bytes {
+        [0xFF, 0xFE, 0x00, 0x00, ..] => return (SniffedEncoding::Utf32Le, 4),
+        [0x00, 0x00, 0xFE, 0xFF, ..] => return (SniffedEncoding::Utf32Be, 4),
+        [0xEF, 0xBB, 0xBF, ..] => return (SniffedEncoding::Utf8, 3),
+        [0xFF, 0xFE, ..] => return (SniffedEncoding::Utf16Le, 2),
+        [0xFE, 0xFF, ..] => return (SniffedEncoding::Utf16Be, 2),
+        _ => (),
+    }
+    // Without BOM, (mostly ASCII) UTF-16 has a zero byte in every other position. (Zero bytes are valid ASCII and
+    // UTF-8, but they don't occur in text, so we check this first.)
+    let zeros_at = |start: usize| {
+        bytes
+            .iter()
+            .skip(start)
+            .step_by(2)
+            .filter(|b| **b == 0)
+            .count()
+    };
+    let half = bytes.len() / 2;
+    match (zeros_at(0), zeros_at(1)) {
+        (_, odd) if half > 0 && odd * 10 >= half * 9 => (SniffedEncoding::Utf16Le, 0),
+        (even, _) if half > 0 && even * 10 >= half * 9 => (SniffedEncoding::Utf16Be, 0),
+        _ if bytes.is_ascii() => (SniffedEncoding::Ascii, 0),
+        _ => match std::str::from_utf8(bytes) {
+            Ok(_) => (SniffedEncoding::Utf8, 0),
+            // The sniffed bytes may end in the middle of a multi-byte char. That's fine.
+            Err(e) if e.error_len().is_none() => (SniffedEncoding::Utf8, 0),
+            Err(_) => (SniffedEncoding::Unknown, 0),
+        },
+    }
+}
+
+/// Lossy, since the sniffed bytes may end in the middle of a char.
+pub(crate) fn decode(bytes: &[u8], encoding: SniffedEncoding) -> Option<String> {
+    let units_16 = |f: fn([u8; 2]) -> u16| {
+        char::decode_utf16(bytes.chunks_exact(2).map(|c| f([c[0], c[1]])))
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    };
+    let units_32 = |f: fn([u8; 4]) -> u32| {
+        bytes
+            .chunks_exact(4)
+            .map(|c| {
+                char::from_u32(f([c[0], c[1], c[2], c[3]])).unwrap_or(char::REPLACEMENT_CHARACTER)
+            })
+            .collect()
+    };
+    match encoding {
+        SniffedEncoding::Ascii | SniffedEncoding::Utf8 => {
+            Some(String::from_utf8_lossy(bytes).into_owned())
+        }
+        SniffedEncoding::Utf16Le => Some(units_16(u16::from_le_bytes)),
+        SniffedEncoding::Utf16Be => Some(units_16(u16::from_be_bytes)),
+        SniffedEncoding::Utf32Le => Some(units_32(u32::from_le_bytes)),
+        SniffedEncoding::Utf32Be => Some(units_32(u32::from_be_bytes)),
+        SniffedEncoding::Unknown => None,
+    }
+}
+
+fn sniff_line_terminator(text: &str) -> LineTerminator {
+    let num_crlf = text.matches("\r\n").count();
+    let num_lf = text.matches('\n').count() - num_crlf;
+    let num_cr = text.matches('\r').count() - num_crlf;
+    match (num_lf, num_crlf, num_cr) {
+        (0, 0, 0) => LineTerminator::None,
+        (_, 0, 0) => LineTerminator::Lf,
+        (0, _, 0) => LineTerminator::CrLf,
+        (0, 0, _) => LineTerminator::Cr,
+        _ => LineTerminator::Mixed,
+    }
+}
+
+/// Splits a line into fields, ignoring separators in enclosed parts. (Naively, i.e. enclosures spanning lines are
+/// not handled. Good enough for sniffing.)
+fn split_fields(line: &str, sep: char, encl: Option<char>) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut enclosed = false;
+    let mut field_start = 0;
+    for (pos, c) in line.char_indices() {
+        if Some(c) == encl {
+            enclosed = !enclosed;
+        } else if c == sep && !enclosed {
+            fields.push(&line[field_start..pos]);
+            field_start = pos + c.len_utf8();
+        }
+    }
+    fields.push(&line[field_start..]);
+    fields
+}
+
+/// The separator, whose count is the same in most lines. Its confidence is the share of these lines.
+fn sniff_separator(lines: &[&str], encl: Option<char>) -> (Option<char>, f64) {
+    let mut best: (Option<char>, f64) = (None, 0.0);
+    for sep in SNIFF_SEPARATORS {
+        let mut counts = lines
+            .iter()
+            .map(|l| split_fields(l, sep, encl).len() - 1)
+            .collect::<Vec<_>>();
+        counts.sort_unstable();
+        // (num lines, count) of the most common count
+        let (num_lines, count) = counts
+            .chunk_by(|a, b| a == b)
+            .map(|c| (c.len(), c[0]))
+            .max()
+            .unwrap_or((0, 0));
+        if count == 0 {
+            continue;
+        }
+        let confidence = num_lines as f64 / lines.len() as f64;
+        if confidence > best.1 {
+            best = (Some(sep), confidence);
+        }
+    }
+    best
+}
+
+/// The share of enclosure chars at field boundaries (or escaped, i.e. doubled, within enclosed fields).
+fn sniff_enclosure_confidence(lines: &[&str], sep: char, encl: char) -> f64 {
+    let (mut num_total, mut num_ok) = (0, 0);
+    for line in lines {
+        for field in split_fields(line, sep, Some(encl)) {
+            let field = field.trim();
+            let num = field.matches(encl).count();
+            num_total += num;
+            let enclosed = num >= 2 && field.starts_with(encl) && field.ends_with(encl);
+            if enclosed
+                && field[encl.len_utf8()..field.len() - encl.len_utf8()]
+                    .replace(&format!("{0}{0}", encl), "")
+                    .find(encl)
+                    .is_none()
+            {
+                num_ok += num;
+            }
+        }
+    }
+    match num_total {
+        0 => 0.0,
+        _ => num_ok as f64 / num_total as f64,
+    }
+}
+
+/// Tokenizes (at most) `max_lines` lines, honoring the skip filters of the tokenizer. Fails fast, i.e. the first
+/// line that can't be tokenized is returned as error.
+pub fn probe<R: Read>(
+    dlt: &DelimitedLineTokenizer,
+    reader: &mut R,
+    max_lines: usize,
+) -> Result<ProbeResult> {
+    let mut dlt_iter = dlt.tokenize_iter(reader);
+    let mut column_counts = Vec::with_capacity(max_lines);
+    let mut sample = Vec::with_capacity(max_lines);
+
+    while sample.len() < max_lines {
+        let tokens = match dlt_iter.next() {
+            None => break,
+            Some(res) => res?,
+        };
+        column_counts.push((dlt_iter.get_stats().curr_line_num, tokens.len()));
+        sample.push(Vec::from(tokens));
+    }
+
+    let consistent = column_counts.windows(2).all(|w| w[0].1 == w[1].1);
+    let candidate_header = sample
+        .first()
+        .filter(|first| looks_like_header(first))
+        .cloned();
+
+    Ok(ProbeResult {
+        column_counts,
+        consistent,
+        candidate_header,
+        sample,
+    })
+}
+
+/// Heuristic: A header has only non-empty, unique tokens, none of which is a number.
+fn looks_like_header(tokens: &[String]) -> bool {
+    let mut seen = HashSet::with_capacity(tokens.len());
+    tokens.iter().all(|t| {
+        let t = t.trim();
+        !t.is_empty() && t.parse::<f64>().is_err() && seen.insert(t)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{errors::PattiCsvError, errors::TokenizerError, skip_take_lines::*};
+
+    use super::*;
+
+    #[test]
+    fn probe_consistent_with_header() {
+        let mut test_data_cursor = std::io::Cursor::new("# comment\nid,name\n1,foo\n2,bar\n3,baz");
+        let dlt = DelimitedLineTokenizer::csv(
+            Some(vec![Box::new(SkipLinesStartingWith::new("#"))]),
+            false,
+        );
+
+        let res = probe(&dlt, &mut test_data_cursor, 3).unwrap();
+        assert_eq!(vec![(2, 2), (3, 2), (4, 2)], res.column_counts);
+        assert!(res.consistent);
+        assert_eq!(Some(2), res.column_count());
+        assert_eq!(
+            Some(vec![String::from("id"), String::from("name")]),
+            res.candidate_header
+        );
+        assert_eq!(3, res.sample.len());
+        assert_eq!(vec![String::from("2"), String::from("bar")], res.sample[2]);
+    }
+
+    #[test]
+    fn probe_inconsistent_without_header() {
+        let mut test_data_cursor = std::io::Cursor::new("1,foo\n2,bar,x\n");
+        let dlt = DelimitedLineTokenizer::csv(None, false);
+
+        let res = probe(&dlt, &mut test_data_cursor, 10).unwrap();
+        assert_eq!(vec![(1, 2), (2, 3)], res.column_counts);
+        assert!(!res.consistent);
+        assert_eq!(None, res.column_count());
+        assert_eq!(None, res.candidate_header);
+    }
+
+    #[test]
+    fn probe_no_header_if_duplicate_or_empty_tokens() {
+        assert!(!looks_like_header(&[String::from("a"), String::from("a")]));
+        assert!(!looks_like_header(&[String::from("a"), String::new()]));
+        assert!(!looks_like_header(&[
+            String::from("a"),
+            String::from("1.5")
+        ]));
+        assert!(looks_like_header(&[String::from("a"), String::from("b")]));
+    }
+
+    #[test]
+    fn sniff_utf8_bom_crlf_semicolon() {
+        let data = "\u{feff}id;name;note\r\n1;\"foo\";x\r\n2;\"b;a\"\"r\";y\r\n";
+        let res = sniff(&mut std::io::Cursor::new(data), 1024).unwrap();
+        assert_eq!(data.len(), res.num_bytes_sniffed);
+        assert_eq!(SniffedEncoding::Utf8, res.encoding);
+        assert!(res.bom);
+        assert_eq!(LineTerminator::CrLf, res.line_terminator);
+        assert_eq!(3, res.num_lines_sniffed);
+        assert_eq!(Some(';'), res.separator_char);
+        assert_eq!(1.0, res.separator_confidence);
+        assert_eq!(Some('"'), res.enclosure_char);
+        assert_eq!(1.0, res.enclosure_confidence);
+        assert_eq!(2, res.notes.len());
+    }
+
+    #[test]
+    fn sniff_utf16_with_and_without_bom() {
+        let text = "a\tb\n1\t2\n";
+        let le = text.encode_utf16().flat_map(|u| u.to_le_bytes());
+        let with_bom = [0xFF, 0xFE]
+            .into_iter()
+            .chain(le.clone())
+            .collect::<Vec<_>>();
+        let res = sniff(&mut with_bom.as_slice(), 1024).unwrap();
+        assert_eq!((SniffedEncoding::Utf16Le, true), (res.encoding, res.bom));
+        assert_eq!(Some('\t'), res.separator_char);
+        assert_eq!(None, res.enclosure_char);
+        assert_eq!(LineTerminator::Lf, res.line_terminator);
+
+        let be = text
+            .encode_utf16()
+            .flat_map(|u| u.to_be_bytes())
+            .collect::<Vec<_>>();
+        let res = sniff(&mut be.as_slice(), 1024).unwrap();
+        assert_eq!((SniffedEncoding::Utf16Be, false), (res.encoding, res.bom));
+        assert_eq!(Some('\t'), res.separator_char);
+    }
+
+    #[test]
+    fn sniff_unknown_encoding() {
+        // "café,1" in latin-1
+        let data = [b'c', b'a', b'f', 0xE9, b',', b'1', b'\n'];
+        let res = sniff(&mut data.as_slice(), 1024).unwrap();
+        assert_eq!(SniffedEncoding::Unknown, res.encoding);
+        assert_eq!(None, res.separator_char);
+        assert_eq!(0, res.num_lines_sniffed);
+        assert_eq!(1, res.notes.len());
+    }
+
+    #[test]
+    fn sniff_confidence_scores() {
+        // One line with a different column count, and one stray enclosure char.
+        let data = "a,b\n1,\"2\"\n3,4\n5,6,7\n8,x\"y\n";
+        let res = sniff(&mut std::io::Cursor::new(data), 1024).unwrap();
+        assert_eq!(SniffedEncoding::Ascii, res.encoding);
+        assert_eq!(Some(','), res.separator_char);
+        assert_eq!(0.8, res.separator_confidence);
+        assert_eq!(Some('"'), res.enclosure_char);
+        assert_eq!(2.0 / 3.0, res.enclosure_confidence);
+    }
+
+    #[test]
+    fn sniff_truncated_and_mixed() {
+        // The last, incomplete line is ignored.
+        let data = "a|b\r\n1|2\n3|4\n5";
+        let res = sniff(&mut std::io::Cursor::new(data), data.len()).unwrap();
+        assert_eq!(LineTerminator::Mixed, res.line_terminator);
+        assert_eq!(3, res.num_lines_sniffed);
+        assert_eq!(
+            (Some('|'), 1.0),
+            (res.separator_char, res.separator_confidence)
+        );
+
+        let res = sniff(&mut std::io::Cursor::new("abc"), 1024).unwrap();
+        assert_eq!(LineTerminator::None, res.line_terminator);
+        assert_eq!((None, 0.0), (res.separator_char, res.separator_confidence));
+    }
+
+    #[cfg(feature = "jsonconf")]
+    #[test]
+    fn sniff_report_to_json() {
+        let res = sniff(&mut std::io::Cursor::new("a,b\n1,2\n"), 1024).unwrap();
+        let json = res.to_json().unwrap();
+        assert!(json.contains("\"encoding\":\"Ascii\""), "{}", json);
+        assert!(json.contains("\"lineTerminator\":\"Lf\""), "{}", json);
+        assert!(json.contains("\"separatorChar\":\",\""), "{}", json);
+    }
+
+    #[test]
+    fn probe_fails_fast() {
+        let mut test_data_cursor = std::io::Cursor::new("a,b\n1,x\"y\n2,z");
+        let dlt = DelimitedLineTokenizer::csv(None, false);
+
+        let res = probe(&dlt, &mut test_data_cursor, 10);
+        assert!(matches!(
+            res,
+            Err(PattiCsvError::Tokenize(TokenizerError::IllegalEnclChar {
+                line: 2,
+                ..
+            }))
+        ));
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::Read;
+
+use indicatif::ProgressBar;
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{
+    errors::Result, iterating_parser::PattiCsvParserIterator,
+    line_tokenizer::DelimitedLineTokenizerStats, progress_observer::ProgressObserver,
+};
+
+/// What the progress bar counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressUnit {
+    /// Bytes read, e.g. for a bar with the file size as length.
+    Bytes,
+    /// Lines tokenized (i.e. rows, including the header row, but without skipped lines).
+    Rows,
+}
+
+/// Drives an `indicatif::ProgressBar` from the tokenizer stats.
+#[derive(Debug, Clone)]
+pub struct IndicatifProgress {
+    bar: ProgressBar,
+    unit: ProgressUnit,
+}
+
+impl IndicatifProgress {
+    pub fn new(bar: ProgressBar, unit: ProgressUnit) -> Self {
+        Self { bar, unit }
+    }
+
+    /// A bar with the file size as length, counting bytes.
+    pub fn for_bytes(total_bytes: u64) -> Self {
+        Self::new(ProgressBar::new(total_bytes), ProgressUnit::Bytes)
+    }
+
+    pub fn bar(&self) -> &ProgressBar {
+        &self.bar
+    }
+
+    pub fn update(&self, stats: &DelimitedLineTokenizerStats) {
+        let pos = match self.unit {
+            ProgressUnit::Bytes => stats.bytes_read,
+            ProgressUnit::Rows => stats.num_lines_tokenized,
+        };
+        self.bar.set_position(pos as u64);
+    }
+
+    pub fn finish(&self, stats: &DelimitedLineTokenizerStats) {
+        self.update(stats);
+        self.bar.finish();
+    }
+
+    /// Wraps the iterator, so that the bar is updated on every row, and finished at the end.
+    pub fn wrap<'pars, 'rd, R: Read>(
+        self,
+        iter: PattiCsvParserIterator<'pars, 'rd, R>,
+    ) -> ProgressIter<'pars, 'rd, R> {
+        ProgressIter {
+            inner: iter,
+            progress: self,
+        }
+    }
+}
+
+/// The bar can also be driven from within the parser, e.g. if the iterator isn't at hand (see:
+/// `PattiCsvParserBuilder::progress_observer`).
+impl ProgressObserver for IndicatifProgress {
+    fn on_progress(&self, stats: &DelimitedLineTokenizerStats) {
+        self.update(stats);
+    }
+    fn on_finish(&self, stats: &DelimitedLineTokenizerStats) {
+        self.finish(stats);
+    }
+}
+
+pub struct ProgressIter<'pars, 'rd, R: Read> {
+    inner: PattiCsvParserIterator<'pars, 'rd, R>,
+    progress: IndicatifProgress,
+}
+
+impl<'pars, 'rd, R: Read> ProgressIter<'pars, 'rd, R> {
+    pub fn into_inner(self) -> PattiCsvParserIterator<'pars, 'rd, R> {
+        self.inner
+    }
+}
+
+impl<'pars, 'rd, R: Read> Iterator for ProgressIter<'pars, 'rd, R> {
+    type Item = Result<DataCellRow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.inner.next();
+        match res {
+            Some(_) => self.progress.update(self.inner.get_stats()),
+            None => self.progress.finish(self.inner.get_stats()),
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indicatif::ProgressDrawTarget;
+
+    use super::*;
+    use crate::iterating_parser::PattiCsvParserBuilder;
+
+    #[test]
+    fn progress_bar_rows() {
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("a,b\n1,2\n3,4\n");
+
+        let bar = ProgressBar::with_draw_target(Some(3), ProgressDrawTarget::hidden());
+        let progress = IndicatifProgress::new(bar.clone(), ProgressUnit::Rows);
+
+        let mut iter = progress.wrap(parser.parse_iter(&mut test_data_cursor));
+        iter.next().unwrap().unwrap();
+        assert_eq!(1, bar.position());
+        iter.by_ref().for_each(drop);
+        assert_eq!(3, bar.position());
+        assert!(bar.is_finished());
+    }
+
+    #[test]
+    fn progress_bar_bytes() {
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let data = "a,b\n1,2\n3,4\n";
+        let mut test_data_cursor = std::io::Cursor::new(data);
+
+        let progress = IndicatifProgress::for_bytes(data.len() as u64);
+        progress.bar().set_draw_target(ProgressDrawTarget::hidden());
+        let bar = progress.bar().clone();
+
+        progress
+            .wrap(parser.parse_iter(&mut test_data_cursor))
+            .for_each(drop);
+        assert_eq!(data.len() as u64, bar.position());
+    }
+}
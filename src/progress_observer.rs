@@ -0,0 +1,134 @@
+use std::fmt::Debug;
+
+use crate::line_tokenizer::DelimitedLineTokenizerStats;
+
+/// How often a `ProgressObserver` is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressInterval {
+    /// Every n lines tokenized (i.e. rows, including the header row, but without skipped lines).
+    Rows(usize),
+    /// Every n bytes read (or the next line after them).
+    Bytes(usize),
+}
+
+impl ProgressInterval {
+    /// Helper method. The position of the stats in this unit.
+    pub(crate) fn position(&self, stats: &DelimitedLineTokenizerStats) -> usize {
+        match self {
+            ProgressInterval::Rows(_) => stats.num_lines_tokenized,
+            ProgressInterval::Bytes(_) => stats.bytes_read,
+        }
+    }
+
+    /// Helper method. The step, at least 1.
+    pub(crate) fn step(&self) -> usize {
+        match self {
+            ProgressInterval::Rows(n) | ProgressInterval::Bytes(n) => (*n).max(1),
+        }
+    }
+}
+
+/// Gets the stats of the tokenizer while parsing (see: `PattiCsvParserBuilder::progress_observer`), e.g. to drive a
+/// progress bar or to log the throughput of long running imports. It's called from the tokenizer, i.e. on the thread
+/// that parses, so it should be quick. Since the parser is shared, the observer is too, i.e. state needs interior
+/// mutability (e.g. atomics).
+///
+/// NOTE: The stats are the ones so far, i.e. the row of the line that was just tokenized isn't typed yet, so its
+/// error (if any) is not counted yet.
+pub trait ProgressObserver: Debug {
+    fn on_progress(&self, stats: &DelimitedLineTokenizerStats);
+    /// Called once, at the end of the data, with the final stats of the tokenizer.
+    fn on_finish(&self, stats: &DelimitedLineTokenizerStats) {
+        self.on_progress(stats)
+    }
+}
+
+pub type BoxedProgressObserver = Box<dyn ProgressObserver + Send + Sync>;
+
+/// Helper for the tokenizer iterator. Calls the observer, whenever the next interval is reached.
+#[derive(Debug, Default)]
+pub(crate) struct ProgressState {
+    next_at: usize,
+    finished: bool,
+}
+
+impl ProgressState {
+    pub(crate) fn update(
+        &mut self,
+        progress: &(BoxedProgressObserver, ProgressInterval),
+        stats: &DelimitedLineTokenizerStats,
+    ) {
+        let (observer, interval) = progress;
+        let pos = interval.position(stats);
+        if pos >= self.next_at.max(interval.step()) {
+            observer.on_progress(stats);
+            self.next_at = pos + interval.step();
+        }
+    }
+
+    pub(crate) fn finish(
+        &mut self,
+        progress: &(BoxedProgressObserver, ProgressInterval),
+        stats: &DelimitedLineTokenizerStats,
+    ) {
+        if !self.finished {
+            self.finished = true;
+            progress.0.on_finish(stats);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{errors::Result, iterating_parser::PattiCsvParserBuilder};
+
+    use super::*;
+
+    type Call = (usize, usize, usize, bool); // tokenized, skipped, errors, finished
+
+    #[derive(Debug, Default)]
+    struct Recorder {
+        calls: Arc<Mutex<Vec<Call>>>,
+    }
+    impl ProgressObserver for Recorder {
+        fn on_progress(&self, stats: &DelimitedLineTokenizerStats) {
+            self.calls.lock().unwrap().push((
+                stats.num_lines_tokenized,
+                stats.num_lines_skipped,
+                stats.num_rows_err,
+                false,
+            ));
+        }
+        fn on_finish(&self, stats: &DelimitedLineTokenizerStats) {
+            self.calls.lock().unwrap().push((
+                stats.num_lines_tokenized,
+                stats.num_lines_skipped,
+                stats.num_rows_err,
+                true,
+            ));
+        }
+    }
+
+    #[test]
+    fn observe_progress() {
+        let recorder = Recorder::default();
+        let calls = Arc::clone(&recorder.calls);
+        let parser = PattiCsvParserBuilder::csv()
+            .comment_char('#')
+            .stringly_type_columns(2)
+            .progress_observer(Box::new(recorder), ProgressInterval::Rows(2))
+            .build()
+            .unwrap();
+        let mut test_data_cursor = std::io::Cursor::new("a,b\n1,2\n# note\n3\n5,6\n");
+        let res = parser
+            .parse_iter(&mut test_data_cursor)
+            .collect::<Vec<Result<_>>>();
+        assert_eq!(4, res.len());
+        assert_eq!(
+            vec![(2, 0, 0, false), (4, 1, 1, false), (4, 1, 1, true)],
+            *calls.lock().unwrap()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::io::Write;
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    output::OutputDialect,
+    writer::{push_field, QuotingPolicy},
+};
+
+/// Writes (untyped) records, i.e. lines of tokens, as csv in the given dialect. This is the low-level part of writing,
+/// e.g. for a sanitize-only run, or for tokens from an external source: fields are only enclosed if needed (i.e. if
+/// they contain the separator, the enclosure character or a line break), and enclosure characters are escaped by
+/// doubling them. Records are terminated by `\n`. For typed rows, see: `writer`.
+#[derive(Debug)]
+pub struct RecordWriter<W: Write> {
+    writer: W,
+    dialect: OutputDialect,
+    num_records: usize,
+    buf: String,
+}
+
+impl<W: Write> RecordWriter<W> {
+    pub fn new(writer: W, dialect: OutputDialect) -> Self {
+        Self {
+            writer,
+            dialect,
+            num_records: 0,
+            buf: String::new(),
+        }
+    }
+
+    /// Writes the header record, unless the dialect says not to.
+    pub fn write_header<I, S>(&mut self, header: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        if self.dialect.write_header {
+            self.write_record(header)?;
+        }
+        Ok(())
+    }
+
+    /// Without an enclosure character, a field that would need one is an error.
+    pub fn write_record<I, S>(&mut self, record: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.buf.clear();
+        for (i, field) in record.into_iter().enumerate() {
+            if i > 0 {
+                self.buf.push(self.dialect.separator_char);
+            }
+            self.push_field(i, field.as_ref())?;
+        }
+        self.buf.push('\n');
+        self.writer.write_all(self.buf.as_bytes())?;
+        self.num_records += 1;
+        Ok(())
+    }
+
+    fn push_field(&mut self, column: usize, field: &str) -> Result<()> {
+        if !push_field(
+            &mut self.buf,
+            field,
+            self.dialect.separator_char,
+            self.dialect.enclosure_char,
+            QuotingPolicy::Minimal,
+        ) {
+            return Err(PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_WRITE] Field needs an enclosure, but there is no enclosure character; record: {}; column: {}; field: {:?}",
+                    self.num_records + 1,
+                    column,
+                    field
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// The number of records written so far, incl. the header.
+    pub fn num_records(&self) -> usize {
+        self.num_records
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_records() {
+        let mut w = RecordWriter::new(Vec::new(), OutputDialect::default());
+        w.write_header(["id", "name"]).unwrap();
+        w.write_record(vec![String::from("1"), String::from("foo, \"bar\"")])
+            .unwrap();
+        w.write_record(["2", "multi\nline"]).unwrap();
+        assert_eq!(3, w.num_records());
+        assert_eq!(
+            "id,name\n1,\"foo, \"\"bar\"\"\"\n2,\"multi\nline\"\n",
+            String::from_utf8(w.into_inner()).unwrap()
+        );
+
+        let mut w = RecordWriter::new(
+            Vec::new(),
+            OutputDialect {
+                separator_char: '\t',
+                enclosure_char: None,
+                write_header: false,
+            },
+        );
+        w.write_header(["id", "name"]).unwrap();
+        w.write_record(["1", "foo, bar"]).unwrap();
+        assert!(w.write_record(["2", "foo\tbar"]).is_err());
+        assert_eq!("1\tfoo, bar\n", String::from_utf8(w.into_inner()).unwrap());
+    }
+}
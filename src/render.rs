@@ -0,0 +1,391 @@
+use std::fmt::{self, Debug, Display};
+
+use venum::{value::Value, value_type::ValueType};
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    writer::{push_field, QuotingPolicy},
+};
+
+/// Turns a (typed) row back into text, e.g. for debugging, snapshots in tests or round-tripping. Values are rendered in
+/// their string form (see: `Value::try_convert_to`), `Value::None` as nothing (or `null`, where there is one).
+pub trait RowFormatter: Debug {
+    /// One row, without a line break at the end.
+    fn format_row(&self, row: &DataCellRow) -> Result<String>;
+    /// The header (from the names of the cells), if the format has one.
+    fn format_header(&self, _row: &DataCellRow) -> Result<Option<String>> {
+        Ok(None)
+    }
+    /// All rows, with the header of the first one (if the format has one), each on its own line.
+    fn format_rows(&self, rows: &[DataCellRow]) -> Result<String> {
+        let mut lines = Vec::with_capacity(rows.len() + 1);
+        if let Some(header) = rows.first().map(|r| self.format_header(r)).transpose()? {
+            lines.extend(header);
+        }
+        for row in rows {
+            lines.push(self.format_row(row)?);
+        }
+        Ok(lines.join("\n"))
+    }
+    /// The row as `Display`, e.g. for `format!` or `to_string()`. A row, that can't be rendered, is a `fmt::Error`.
+    fn display<'r>(&'r self, row: &'r DataCellRow) -> DisplayRow<'r, Self>
+    where
+        Self: Sized,
+    {
+        DisplayRow {
+            row,
+            formatter: self,
+        }
+    }
+}
+
+/// A row with its formatter. See: `RowFormatter::display`
+#[derive(Debug)]
+pub struct DisplayRow<'r, F> {
+    row: &'r DataCellRow,
+    formatter: &'r F,
+}
+
+impl<F: RowFormatter> Display for DisplayRow<'_, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = self
+            .formatter
+            .format_row(self.row)
+            .map_err(|_| fmt::Error)?;
+        f.write_str(&line)
+    }
+}
+
+/// Helper method. The string form of a value, `None` for `Value::None`.
+fn value_to_text(value: &Value, column: usize) -> Result<Option<String>> {
+    match value {
+        Value::None => Ok(None),
+        Value::String(s) => Ok(Some(s.clone())),
+        v => v
+            .try_convert_to(&ValueType::String)
+            .and_then(String::try_from)
+            .map(Some)
+            .map_err(|e| PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_RENDER] Value has no string form: {:?}; column: {}",
+                    e, column
+                ),
+            }),
+    }
+}
+
+/// Separated values, e.g. csv or tsv, quoted like the `PattiCsvWriter` does (i.e. enclosure characters within a field
+/// are escaped by doubling them).
+#[derive(Debug, Clone)]
+pub struct DelimitedFormatter {
+    separator_char: char,
+    enclosure_char: Option<char>,
+    quoting: QuotingPolicy,
+}
+
+impl DelimitedFormatter {
+    pub fn new(separator_char: char, enclosure_char: Option<char>, quoting: QuotingPolicy) -> Self {
+        Self {
+            separator_char,
+            enclosure_char,
+            quoting,
+        }
+    }
+
+    /// `,` as separator, `"` as enclosure (if needed).
+    pub fn csv() -> Self {
+        Self::new(',', Some('"'), QuotingPolicy::Minimal)
+    }
+
+    /// A tab as separator, `"` as enclosure (if needed).
+    pub fn tsv() -> Self {
+        Self::new('\t', Some('"'), QuotingPolicy::Minimal)
+    }
+
+    fn join<I>(&self, fields: I) -> Result<String>
+    where
+        I: IntoIterator<Item = (usize, Option<String>)>,
+    {
+        let mut buf = String::new();
+        for (i, field) in fields {
+            if i > 0 {
+                buf.push(self.separator_char);
+            }
+            let field = match field {
+                Some(f) => f,
+                None => continue,
+            };
+            if !push_field(
+                &mut buf,
+                &field,
+                self.separator_char,
+                self.enclosure_char,
+                self.quoting,
+            ) {
+                return Err(PattiCsvError::Generic {
+                    msg: format!(
+                        "[ERROR_ON_RENDER] Field needs an enclosure, but the quoting policy is {:?} and the enclosure character is {:?}; column: {}; field: {:?}",
+                        self.quoting, self.enclosure_char, i, field
+                    ),
+                });
+            }
+        }
+        Ok(buf)
+    }
+}
+
+impl RowFormatter for DelimitedFormatter {
+    fn format_row(&self, row: &DataCellRow) -> Result<String> {
+        let fields = row
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, c)| value_to_text(c.get_data(), i).map(|f| (i, f)))
+            .collect::<Result<Vec<_>>>()?;
+        self.join(fields)
+    }
+    fn format_header(&self, row: &DataCellRow) -> Result<Option<String>> {
+        self.join(
+            row.0
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (i, Some(c.get_name().to_string()))),
+        )
+        .map(Some)
+    }
+}
+
+/// A json object per row, keyed by the names of the cells (in the order of the cells). Numbers and bools are json
+/// numbers and bools (non-finite floats are `null`, decimals are numbers as well), `Value::None` is `null`, everything
+/// else (e.g. dates, in their ISO 8601 string form) is a string.
+#[derive(Debug, Clone, Default)]
+pub struct JsonObjectFormatter;
+
+impl JsonObjectFormatter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Helper method. Pushes the string as json string, i.e. enclosed and escaped.
+pub(crate) fn push_json_string(buf: &mut String, s: &str) {
+    buf.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            c if (c as u32) < 0x20 => buf.push_str(&format!("\\u{:04x}", c as u32)),
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+/// Helper method. Pushes the value as json value. See: `JsonObjectFormatter`
+pub(crate) fn push_json_value(buf: &mut String, value: &Value, column: usize) -> Result<()> {
+    match value {
+        Value::None => buf.push_str("null"),
+        Value::Bool(b) => buf.push_str(if *b { "true" } else { "false" }),
+        Value::Int8(v) => buf.push_str(&v.to_string()),
+        Value::Int16(v) => buf.push_str(&v.to_string()),
+        Value::Int32(v) => buf.push_str(&v.to_string()),
+        Value::Int64(v) => buf.push_str(&v.to_string()),
+        Value::Int128(v) => buf.push_str(&v.to_string()),
+        Value::UInt8(v) => buf.push_str(&v.to_string()),
+        Value::UInt16(v) => buf.push_str(&v.to_string()),
+        Value::UInt32(v) => buf.push_str(&v.to_string()),
+        Value::UInt64(v) => buf.push_str(&v.to_string()),
+        Value::UInt128(v) => buf.push_str(&v.to_string()),
+        Value::Float32(v) if v.is_finite() => buf.push_str(&v.to_string()),
+        Value::Float64(v) if v.is_finite() => buf.push_str(&v.to_string()),
+        Value::Float32(_) | Value::Float64(_) => buf.push_str("null"),
+        Value::Decimal(d) => buf.push_str(&d.to_string()),
+        v => match value_to_text(v, column)? {
+            Some(s) => push_json_string(buf, &s),
+            None => buf.push_str("null"),
+        },
+    }
+    Ok(())
+}
+
+impl RowFormatter for JsonObjectFormatter {
+    fn format_row(&self, row: &DataCellRow) -> Result<String> {
+        let mut buf = String::from("{");
+        for (i, cell) in row.0.iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            push_json_string(&mut buf, cell.get_name());
+            buf.push(':');
+            push_json_value(&mut buf, cell.get_data(), i)?;
+        }
+        buf.push('}');
+        Ok(buf)
+    }
+}
+
+/// A (markdown style) table, for humans. The columns are only aligned with `format_rows`, i.e. a single row is not
+/// padded. `|` within a value is escaped as `\|`, line breaks are shown as `\n`.
+#[derive(Debug, Clone, Default)]
+pub struct PrettyTableFormatter;
+
+impl PrettyTableFormatter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn cells(row: &DataCellRow) -> Result<Vec<String>> {
+        row.0
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                value_to_text(c.get_data(), i).map(|t| escape_cell(&t.unwrap_or_default()))
+            })
+            .collect()
+    }
+
+    fn line(cells: &[String], widths: &[usize]) -> String {
+        let padded = cells
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let width = widths.get(i).copied().unwrap_or(0);
+                format!(
+                    "{}{}",
+                    c,
+                    " ".repeat(width.saturating_sub(c.chars().count()))
+                )
+            })
+            .collect::<Vec<_>>();
+        format!("| {} |", padded.join(" | "))
+    }
+}
+
+/// Helper method. Escapes what would break the table.
+fn escape_cell(s: &str) -> String {
+    s.replace('|', "\\|")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+impl RowFormatter for PrettyTableFormatter {
+    fn format_row(&self, row: &DataCellRow) -> Result<String> {
+        Ok(Self::line(&Self::cells(row)?, &[]))
+    }
+    fn format_header(&self, row: &DataCellRow) -> Result<Option<String>> {
+        let names = row
+            .0
+            .iter()
+            .map(|c| escape_cell(c.get_name()))
+            .collect::<Vec<_>>();
+        Ok(Some(Self::line(&names, &[])))
+    }
+    fn format_rows(&self, rows: &[DataCellRow]) -> Result<String> {
+        let header = match rows.first() {
+            Some(first) => first
+                .0
+                .iter()
+                .map(|c| escape_cell(c.get_name()))
+                .collect::<Vec<_>>(),
+            None => return Ok(String::new()),
+        };
+        let body = rows.iter().map(Self::cells).collect::<Result<Vec<_>>>()?;
+        let mut widths = header.iter().map(|h| h.chars().count()).collect::<Vec<_>>();
+        for cells in &body {
+            for (i, c) in cells.iter().enumerate() {
+                match widths.get_mut(i) {
+                    Some(w) => *w = (*w).max(c.chars().count()),
+                    None => widths.push(c.chars().count()),
+                }
+            }
+        }
+        let rule = widths
+            .iter()
+            .map(|w| "-".repeat((*w).max(1)))
+            .collect::<Vec<_>>();
+        let mut lines = Vec::with_capacity(body.len() + 2);
+        lines.push(Self::line(&header, &widths));
+        lines.push(format!("|-{}-|", rule.join("-|-")));
+        lines.extend(body.iter().map(|cells| Self::line(cells, &widths)));
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use venum_tds::data_cell::DataCell;
+
+    use super::*;
+
+    fn row() -> DataCellRow {
+        let mut row = DataCellRow::new();
+        row.push(DataCell::new(String::from("id"), 0, Value::Int32(1)).unwrap());
+        row.push(
+            DataCell::new(
+                String::from("name"),
+                1,
+                Value::from(String::from("say \"hi\", ok")),
+            )
+            .unwrap(),
+        );
+        row.push(DataCell::new_without_data(
+            ValueType::Float64,
+            String::from("amount"),
+            2,
+        ));
+        row.push(
+            DataCell::new(
+                String::from("due"),
+                3,
+                Value::NaiveDate(NaiveDate::from_ymd_opt(2021, 1, 3).unwrap()),
+            )
+            .unwrap(),
+        );
+        row
+    }
+
+    #[test]
+    fn render_delimited() {
+        let row = row();
+        let csv = DelimitedFormatter::csv();
+        assert_eq!(
+            "1,\"say \"\"hi\"\", ok\",,2021-01-03",
+            csv.format_row(&row).unwrap()
+        );
+        assert_eq!(
+            "id,name,amount,due\n1,\"say \"\"hi\"\", ok\",,2021-01-03",
+            csv.format_rows(std::slice::from_ref(&row)).unwrap()
+        );
+        assert_eq!(csv.format_row(&row).unwrap(), csv.display(&row).to_string());
+        assert_eq!(
+            "1\t\"say \"\"hi\"\", ok\"\t\t2021-01-03",
+            DelimitedFormatter::tsv().format_row(&row).unwrap()
+        );
+        assert!(
+            DelimitedFormatter::new(',', Some('"'), QuotingPolicy::Never)
+                .format_row(&row)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn render_json_and_table() {
+        let row = row();
+        assert_eq!(
+            r#"{"id":1,"name":"say \"hi\", ok","amount":null,"due":"2021-01-03"}"#,
+            JsonObjectFormatter::new().format_row(&row).unwrap()
+        );
+        assert_eq!(
+            "| id | name         | amount | due        |\n\
+             |----|--------------|--------|------------|\n\
+             | 1  | say \"hi\", ok |        | 2021-01-03 |",
+            PrettyTableFormatter::new().format_rows(&[row]).unwrap()
+        );
+        assert_eq!("", PrettyTableFormatter::new().format_rows(&[]).unwrap());
+    }
+}
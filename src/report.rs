@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, HashMap};
+
+use venum::value_type::ValueType;
+
+use crate::line_tokenizer::{StageTimings, TruncatedBuffers};
+
+#[cfg(feature = "io")]
+use crate::compression::CompressionKind;
+
+#[cfg(all(feature = "serde", feature = "serde_json"))]
+use crate::errors::{PattiCsvError, Result};
+
+/// Machine-readable summary of a (finished) parse run. See: `PattiCsvParserIterator::finish_report`.
+///
+/// With the `serde` feature, this is serializable (and with `serde_json` too, via `to_json()`), so it can be attached
+/// to data-lineage systems and the like.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ParseReport {
+    pub dialect: DialectReport,
+    pub schema: Vec<SchemaColumnReport>,
+    pub num_lines_read: usize,
+    pub num_lines_skipped: usize,
+    pub num_comment_lines: usize, // also counted in num_lines_skipped
+    pub num_duplicate_rows: usize,
+    pub num_rows_filtered: usize,
+    pub num_rows_dropped: usize,
+    pub num_rows_ok: usize, // data rows only, i.e. without the header row
+    pub num_rows_err: usize,
+    pub skipped_lines_by_rule: Vec<SkipRuleReport>,
+    pub skipped_lines_metadata: HashMap<String, String>, // see: `SkipTakeLines::metadata`
+    pub errors: Vec<ErrorSummary>,
+    pub bytes_read: usize,
+    pub duration_secs: f64,
+    pub rows_per_sec: f64,
+    pub bytes_per_sec: f64,
+    pub stage_timings: Option<StageTimings>, // only if enabled, see: `PattiCsvParserBuilder::stage_timings`
+    pub truncated_buffers: TruncatedBuffers, // see: `MemoryBudget`
+    #[cfg(feature = "io")]
+    pub compression: Option<CompressionKind>, // only if set, see: `with_compression`
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct DialectReport {
+    pub separator_char: char,
+    pub enclosure_char: Option<char>,
+    pub first_data_line_is_header: bool,
+    pub trim_trailing_whitespace: bool,
+    pub comment_char: Option<char>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SchemaColumnReport {
+    pub idx: usize,
+    pub name: String,
+    pub target_type: ValueType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SkipRuleReport {
+    pub rule: String,                  // for humans, see: `SkipTakeLines::get_self_info`
+    pub filter: Option<ComponentInfo>, // for tooling, see: `SkipTakeLines::describe`
+    pub num_lines: usize,
+}
+
+/// A stable, machine-readable description of a pluggable part, i.e. a sanitizer or a skip filter: the id of its kind
+/// (e.g. "trimAll" or "skipLinesByRegex") and its parameters. Unlike `get_self_info()`, which is `Debug` output and
+/// changes between releases, external tooling can rely on this. Custom implementations are of kind "custom", unless
+/// they say otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ComponentInfo {
+    pub kind: String,
+    pub params: BTreeMap<String, String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub children: Vec<ComponentInfo>, // e.g. the filters of a combined skip filter
+}
+
+impl ComponentInfo {
+    pub fn new<T>(kind: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            kind: kind.into(),
+            params: BTreeMap::new(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_param<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: ToString,
+    {
+        self.params.insert(key.into(), value.to_string());
+        self
+    }
+
+    pub fn with_children(mut self, children: Vec<ComponentInfo>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Helper method. The description in one line, e.g. `kind{key=value,...}[child;...]` (the params sorted by key), for
+    /// the fingerprint of the parser.
+    pub(crate) fn canonical(&self) -> String {
+        let params = self
+            .params
+            .iter()
+            .map(|(k, v)| format!("{}={:?}", k, v))
+            .collect::<Vec<_>>()
+            .join(",");
+        let children = self
+            .children
+            .iter()
+            .map(ComponentInfo::canonical)
+            .collect::<Vec<_>>()
+            .join(";");
+        format!("{}{{{}}}[{}]", self.kind, params, children)
+    }
+}
+
+/// Errors of the same kind are summarized, i.e. counted, and only the first one is kept (with its line number).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ErrorSummary {
+    pub kind: String,
+    pub count: usize,
+    pub first_line_num: usize,
+    pub first_msg: String,
+}
+
+impl ParseReport {
+    /// The parser only sees the decompressed data, so the compression of the input is set by whoever opened it, e.g.
+    /// from `DecompressedReader::kind`. `PattiCsvParser::parse_file` sets it itself.
+    #[cfg(feature = "io")]
+    pub fn with_compression(mut self, kind: CompressionKind) -> Self {
+        self.compression = Some(kind);
+        self
+    }
+
+    #[cfg(all(feature = "serde", feature = "serde_json"))]
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| PattiCsvError::Generic {
+            msg: format!("[ERROR_ON_REPORT] Could not serialize parse report: {}", e),
+        })
+    }
+}
+
+/// Helper method. Adds an error (of a given kind) to the summaries, or counts it, if we already have one of this kind.
+pub(crate) fn add_error_to_summaries(
+    summaries: &mut Vec<ErrorSummary>,
+    kind: String,
+    line_num: usize,
+    msg: String,
+) {
+    match summaries.iter_mut().find(|s| s.kind == kind) {
+        Some(s) => s.count += 1,
+        None => summaries.push(ErrorSummary {
+            kind,
+            count: 1,
+            first_line_num: line_num,
+            first_msg: msg,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_errors_to_summaries() {
+        let mut summaries = Vec::new();
+        add_error_to_summaries(&mut summaries, String::from("A"), 1, String::from("a1"));
+        add_error_to_summaries(&mut summaries, String::from("B"), 2, String::from("b1"));
+        add_error_to_summaries(&mut summaries, String::from("A"), 3, String::from("a2"));
+
+        assert_eq!(
+            vec![
+                ErrorSummary {
+                    kind: String::from("A"),
+                    count: 2,
+                    first_line_num: 1,
+                    first_msg: String::from("a1")
+                },
+                ErrorSummary {
+                    kind: String::from("B"),
+                    count: 1,
+                    first_line_num: 2,
+                    first_msg: String::from("b1")
+                }
+            ],
+            summaries
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::VecDeque;
+
+use venum::value::Value;
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{
+    derived_columns::{append_derived_template_cells, check_derived_columns},
+    errors::{PattiCsvError, Result},
+    iterating_parser::PattiCsvParser,
+    money::{check_money_columns_typings, set_combined_money_template_cells},
+    parser_common::{apply_global_map_to_none, build_layout_template, type_line_tokens},
+    parser_config::TypeColumnEntry,
+};
+
+/// A row, that can be typed (again) afterwards. See: `PattiCsvParser::retype`.
+pub trait RetypeRow {
+    /// The header of the columns, if the row knows it.
+    fn header(&self) -> Option<VecDeque<String>> {
+        None
+    }
+    fn into_tokens(self) -> Result<VecDeque<String>>;
+}
+
+impl RetypeRow for VecDeque<String> {
+    fn into_tokens(self) -> Result<VecDeque<String>> {
+        Ok(self)
+    }
+}
+
+impl RetypeRow for Vec<String> {
+    fn into_tokens(self) -> Result<VecDeque<String>> {
+        Ok(VecDeque::from(self))
+    }
+}
+
+/// A stringly typed row, i.e. every cell is a `Value::String` or `Value::None` (which becomes an empty token again, so
+/// that the null policies of the new typings decide about it). The names of the cells are the header.
+impl RetypeRow for DataCellRow {
+    fn header(&self) -> Option<VecDeque<String>> {
+        Some(self.0.iter().map(|c| String::from(c.get_name())).collect())
+    }
+
+    fn into_tokens(self) -> Result<VecDeque<String>> {
+        self.0
+            .into_iter()
+            .enumerate()
+            .map(|(i, cell)| match cell.data {
+                Value::String(s) => Ok(s),
+                Value::None => Ok(String::new()),
+                other => Err(PattiCsvError::Generic {
+                    msg: format!(
+                        "[ERROR_ON_RETYPE] Only stringly typed rows can be retyped, but the cell is: {:?}; column: {}; header: {}",
+                        other, i, cell.name
+                    ),
+                }),
+            })
+            .collect()
+    }
+}
+
+/// Iterator over the retyped rows. See: `PattiCsvParser::retype`.
+pub struct RetypeIter<'pars, I> {
+    parser: &'pars PattiCsvParser,
+    rows: I,
+    column_typings: Vec<TypeColumnEntry>,
+    column_layout_template: Option<DataCellRow>,
+    row_num: usize,
+}
+
+impl<'pars, I> RetypeIter<'pars, I>
+where
+    I: Iterator,
+    I::Item: RetypeRow,
+{
+    pub(crate) fn new(
+        parser: &'pars PattiCsvParser,
+        rows: I,
+        mut column_typings: Vec<TypeColumnEntry>,
+    ) -> Result<Self> {
+        column_typings
+            .iter_mut()
+            .for_each(TypeColumnEntry::apply_deprecated_map_to_none);
+        if !parser.global_map_to_none.is_empty() {
+            column_typings = apply_global_map_to_none(&column_typings, &parser.global_map_to_none);
+        }
+        check_derived_columns(
+            &column_typings,
+            &parser.derived_columns,
+            &parser.derived_splits,
+            false,
+        )?;
+        check_money_columns_typings(
+            &column_typings,
+            parser.money_columns.as_deref().unwrap_or_default(),
+            false,
+        )?;
+        Ok(Self {
+            parser,
+            rows,
+            column_typings,
+            column_layout_template: None,
+            row_num: 0,
+        })
+    }
+
+    /// Helper method. Types one row, building the column layout template from the first one.
+    fn retype_row(&mut self, row: I::Item) -> Result<DataCellRow> {
+        let header = row.header();
+        let tokens = row.into_tokens().map_err(|e| match e {
+            PattiCsvError::Generic { msg } => PattiCsvError::Generic {
+                msg: format!("{}; line: {}", msg, self.row_num),
+            },
+            e => e,
+        })?;
+        if tokens.len() != self.column_typings.len() {
+            return Err(PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_RETYPE] Row has {} columns, but there are {} column typings; line: {}",
+                    tokens.len(),
+                    self.column_typings.len(),
+                    self.row_num
+                ),
+            });
+        }
+        if self.column_layout_template.is_none() {
+            let mut template = build_layout_template(header.as_ref(), &self.column_typings)?;
+            set_combined_money_template_cells(&mut template, self.parser.money_columns.as_ref());
+            append_derived_template_cells(
+                &mut template,
+                &self.parser.derived_columns,
+                &self.parser.derived_splits,
+            );
+            self.column_layout_template = Some(template);
+        }
+        type_line_tokens(
+            self.row_num,
+            tokens,
+            self.column_layout_template.as_ref().unwrap(), // set above
+            &self.column_typings,
+            self.parser,
+        )
+    }
+}
+
+impl<I> Iterator for RetypeIter<'_, I>
+where
+    I: Iterator,
+    I::Item: RetypeRow,
+{
+    type Item = Result<DataCellRow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = self.rows.next()?;
+        self.row_num += 1;
+        Some(self.retype_row(row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use venum::value_type::ValueType;
+    use venum_tds::data_cell::DataCell;
+
+    use crate::{
+        derived_columns::{DatePart, DerivedColumn},
+        iterating_parser::PattiCsvParserBuilder,
+        parser_config::NullPolicy,
+    };
+
+    use super::*;
+
+    #[test]
+    fn retype_stringly_rows() {
+        let stringly = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(3)
+            .build()
+            .unwrap();
+        let mut data = std::io::Cursor::new("id,amount,date\n1,12.5,2021-01-03\n2,,2024-12-30");
+        let rows = stringly
+            .parse_iter(&mut data)
+            .skip(1) // the header row
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        let typings = vec![
+            TypeColumnEntry::new(None, ValueType::Int32),
+            TypeColumnEntry::new(Some(String::from("amt")), ValueType::Float64)
+                .with_null_policy(NullPolicy::empty_is_none()),
+            TypeColumnEntry::new(None, ValueType::NaiveDate),
+        ];
+        let typed = PattiCsvParserBuilder::csv()
+            .column_typings(typings.clone())
+            .derived_column(DerivedColumn::new(2, DatePart::Year, "year"))
+            .build()
+            .unwrap();
+        let retyped = typed
+            .retype(rows, typings)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(2, retyped.len());
+        assert_eq!(
+            &Value::Int32(1),
+            retyped[0].get_by_name("id").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Float64(12.5),
+            retyped[0].get_by_name("amt").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::None,
+            retyped[1].get_by_name("amt").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::NaiveDate(NaiveDate::from_ymd_opt(2024, 12, 30).unwrap()),
+            retyped[1].get_by_name("date").unwrap().get_data()
+        );
+        assert_eq!(
+            &Value::Int32(2024),
+            retyped[1].get_by_name("year").unwrap().get_data()
+        );
+    }
+
+    #[test]
+    fn retype_token_rows() {
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let typings = || {
+            vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::Bool),
+            ]
+        };
+        let rows = vec![
+            vec![String::from("1"), String::from("true")],
+            vec![String::from("x"), String::from("false")],
+            vec![String::from("3")],
+        ];
+        let res = parser.retype(rows, typings()).unwrap().collect::<Vec<_>>();
+
+        let row = res[0].as_ref().unwrap();
+        assert_eq!(&Value::Int32(1), row.get_by_name("0").unwrap().get_data());
+        assert_eq!(&Value::Bool(true), row.get_by_name("1").unwrap().get_data());
+        assert!(res[1].is_err());
+        assert!(res[2].is_err());
+
+        let mut not_stringly = DataCellRow::new();
+        not_stringly.push(DataCell::new(String::from("a"), 0, Value::Int32(1)).unwrap());
+        not_stringly.push(DataCell::new(String::from("b"), 1, Value::Bool(true)).unwrap());
+        let mut res = parser.retype(vec![not_stringly], typings()).unwrap();
+        assert!(res.next().unwrap().is_err());
+
+        let derives_year = PattiCsvParserBuilder::csv()
+            .column_typings(vec![TypeColumnEntry::new(None, ValueType::NaiveDate)])
+            .derived_column(DerivedColumn::new(0, DatePart::Year, "year"))
+            .build()
+            .unwrap();
+        assert!(derives_year
+            .retype(Vec::<Vec<String>>::new(), typings())
+            .is_err());
+    }
+
+    #[test]
+    fn retype_with_global_map_to_none() {
+        let parser = PattiCsvParserBuilder::csv()
+            .stringly_type_columns(2)
+            .global_map_to_none(vec![String::from("NULL")])
+            .build()
+            .unwrap();
+        let rows = vec![
+            vec![String::from("NULL"), String::from("true")],
+            vec![String::from("3"), String::from("NULL")],
+        ];
+        let typings = vec![
+            TypeColumnEntry::new(None, ValueType::Int32),
+            TypeColumnEntry::new_with_map_to_none(None, ValueType::String, vec![String::from("-")]),
+        ];
+        let res = parser
+            .retype(rows, typings)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(&Value::None, res[0].get_by_name("0").unwrap().get_data());
+        assert_eq!(
+            &Value::Int32(3),
+            res[1].get_by_name("0").unwrap().get_data()
+        );
+        // the map list of the column overrides the global one
+        assert_eq!(
+            &Value::String(String::from("NULL")),
+            res[1].get_by_name("1").unwrap().get_data()
+        );
+    }
+}
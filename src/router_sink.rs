@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    pipeline::RowSink,
+};
+
+pub type BoxedRoutePredicate = Box<dyn Fn(&DataCellRow) -> bool>;
+pub type BoxedRouteKey = Box<dyn Fn(&DataCellRow) -> Option<String>>;
+pub type BoxedSinkFactory<S> = Box<dyn FnMut(&str) -> Result<S>>;
+
+/// One sink of a `RouterSink`.
+struct Route<S> {
+    name: String,
+    sink: S,
+    num_rows: usize,
+}
+
+impl<S: RowSink> Route<S> {
+    fn new(name: String, sink: S) -> Self {
+        Self {
+            name,
+            sink,
+            num_rows: 0,
+        }
+    }
+}
+
+/// A `RowSink` that routes every row to one of several sinks, e.g. to split the rows by country into separate files.
+/// A row goes to:
+///
+/// 1. the first route, whose predicate matches (see: `route`), or else ...
+/// 2. the sink of its key, e.g. the value of a column (see: `by_key` and `by_column`). Every key gets its own sink,
+///    which is created on the first row with that key, or else ...
+/// 3. the fallback sink (see: `fallback`). Without one, the row is an error (tagged `[ERROR_ON_ROUTE]`).
+///
+/// Failed and dropped rows have no (typed) values to route by, so they go to the fallback sink. Without one, errors
+/// fail fast, and dropped rows are ignored. Every sink gets the header (if any) before its first row, and all of them
+/// are finished, when the router is.
+pub struct RouterSink<S: RowSink> {
+    predicate_routes: Vec<(BoxedRoutePredicate, Route<S>)>,
+    key_routing: Option<(BoxedRouteKey, BoxedSinkFactory<S>)>,
+    key_routes: Vec<Route<S>>,
+    key_route_idxs: HashMap<String, usize>,
+    fallback: Option<Route<S>>,
+    header: Option<DataCellRow>,
+}
+
+fn route_err(msg: String) -> PattiCsvError {
+    PattiCsvError::Generic {
+        msg: format!("[ERROR_ON_ROUTE] {}", msg),
+    }
+}
+
+impl<S: RowSink> RouterSink<S> {
+    pub fn new() -> Self {
+        Self {
+            predicate_routes: Vec::new(),
+            key_routing: None,
+            key_routes: Vec::new(),
+            key_route_idxs: HashMap::new(),
+            fallback: None,
+            header: None,
+        }
+    }
+
+    /// Routes are checked in the order they were added.
+    pub fn route<N, P>(mut self, name: N, predicate: P, sink: S) -> Self
+    where
+        N: Into<String>,
+        P: Fn(&DataCellRow) -> bool + 'static,
+    {
+        self.predicate_routes
+            .push((Box::new(predicate), Route::new(name.into(), sink)));
+        self
+    }
+
+    /// Routes by the key of a row, `new_sink` creates the sink for a new key. Rows without a key go to the fallback.
+    pub fn by_key<K, F>(mut self, key: K, new_sink: F) -> Self
+    where
+        K: Fn(&DataCellRow) -> Option<String> + 'static,
+        F: FnMut(&str) -> Result<S> + 'static,
+    {
+        self.key_routing = Some((Box::new(key), Box::new(new_sink)));
+        self
+    }
+
+    /// Routes by the (string) value of a column. Rows without the column, or with `Value::None` in it, go to the
+    /// fallback.
+    pub fn by_column<N, F>(self, column: N, new_sink: F) -> Self
+    where
+        N: Into<String>,
+        F: FnMut(&str) -> Result<S> + 'static,
+    {
+        let column = column.into();
+        self.by_key(
+            move |row| {
+                let data = row.get_by_name(&column)?.get_data();
+                match data {
+                    venum::value::Value::None => None,
+                    v => String::try_from(v).ok(),
+                }
+            },
+            new_sink,
+        )
+    }
+
+    pub fn fallback(mut self, sink: S) -> Self {
+        self.fallback = Some(Route::new(String::from("fallback"), sink));
+        self
+    }
+
+    /// The number of rows every route got so far (by name, or by key), in the order the routes were added (or
+    /// created). The fallback is last.
+    pub fn num_rows_by_route(&self) -> Vec<(&str, usize)> {
+        self.routes()
+            .map(|r| (r.name.as_str(), r.num_rows))
+            .collect()
+    }
+
+    /// The sinks, in the same order as `num_rows_by_route`.
+    pub fn into_sinks(self) -> Vec<(String, S)> {
+        self.predicate_routes
+            .into_iter()
+            .map(|(_, r)| r)
+            .chain(self.key_routes)
+            .chain(self.fallback)
+            .map(|r| (r.name, r.sink))
+            .collect()
+    }
+
+    fn routes(&self) -> impl Iterator<Item = &Route<S>> {
+        self.predicate_routes
+            .iter()
+            .map(|(_, r)| r)
+            .chain(self.key_routes.iter())
+            .chain(self.fallback.iter())
+    }
+
+    /// Helper method. The (predicate or key) route of a row, if there is one. Creates the sink of a new key.
+    fn route_of(&mut self, row: &DataCellRow) -> Result<Option<RouteIdx>> {
+        if let Some(idx) = self.predicate_routes.iter().position(|(p, _)| p(row)) {
+            return Ok(Some(RouteIdx::Predicate(idx)));
+        }
+        if let Some((key_of, new_sink)) = &mut self.key_routing {
+            if let Some(key) = key_of(row) {
+                let idx = match self.key_route_idxs.get(&key) {
+                    Some(idx) => *idx,
+                    None => {
+                        let sink = new_sink(&key)?;
+                        self.key_routes.push(Route::new(key.clone(), sink));
+                        self.key_route_idxs.insert(key, self.key_routes.len() - 1);
+                        self.key_routes.len() - 1
+                    }
+                };
+                return Ok(Some(RouteIdx::Key(idx)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RouteIdx {
+    Predicate(usize),
+    Key(usize),
+}
+
+impl<S: RowSink> Default for RouterSink<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: RowSink> RowSink for RouterSink<S> {
+    fn write_header(&mut self, header: &DataCellRow) -> Result<()> {
+        self.header = Some(header.clone());
+        Ok(())
+    }
+
+    fn write_row(&mut self, line_num: usize, row: DataCellRow) -> Result<()> {
+        let route = match self.route_of(&row)? {
+            Some(RouteIdx::Predicate(idx)) => &mut self.predicate_routes[idx].1,
+            Some(RouteIdx::Key(idx)) => &mut self.key_routes[idx],
+            None => match &mut self.fallback {
+                Some(route) => route,
+                None => return Err(route_err(format!("No route for row; line: {}", line_num))),
+            },
+        };
+        if let (0, Some(header)) = (route.num_rows, &self.header) {
+            route.sink.write_header(header)?;
+        }
+        route.num_rows += 1;
+        route.sink.write_row(line_num, row)
+    }
+
+    fn on_error(&mut self, line_num: usize, e: PattiCsvError) -> Result<()> {
+        match &mut self.fallback {
+            Some(route) => route.sink.on_error(line_num, e),
+            None => Err(e),
+        }
+    }
+
+    fn on_dropped(&mut self, line_num: usize, reason: &str) -> Result<()> {
+        match &mut self.fallback {
+            Some(route) => route.sink.on_dropped(line_num, reason),
+            None => Ok(()),
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        for (_, route) in self.predicate_routes.iter_mut() {
+            route.sink.finish()?;
+        }
+        for route in self.key_routes.iter_mut().chain(self.fallback.iter_mut()) {
+            route.sink.finish()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::{value::Value, value_type::ValueType};
+
+    use super::*;
+    use crate::{
+        iterating_parser::PattiCsvParserBuilder, parser_config::TypeColumnEntry, pipeline::Pipeline,
+    };
+
+    #[derive(Debug, Default)]
+    struct TestSink {
+        header: Option<DataCellRow>,
+        line_nums: Vec<usize>,
+        finished: bool,
+    }
+    impl RowSink for TestSink {
+        fn write_header(&mut self, header: &DataCellRow) -> Result<()> {
+            self.header = Some(header.clone());
+            Ok(())
+        }
+        fn write_row(&mut self, line_num: usize, _row: DataCellRow) -> Result<()> {
+            self.line_nums.push(line_num);
+            Ok(())
+        }
+        fn finish(&mut self) -> Result<()> {
+            self.finished = true;
+            Ok(())
+        }
+    }
+
+    fn pipeline() -> Pipeline {
+        Pipeline::builder(PattiCsvParserBuilder::csv().column_typings(vec![
+            TypeColumnEntry::new(None, ValueType::String),
+            TypeColumnEntry::new(None, ValueType::Int32),
+        ]))
+        .build()
+        .unwrap()
+    }
+    const DATA: &str = "country,amount\nCH,1\nDE,2\nCH,-3\n,4";
+
+    #[test]
+    fn route_by_predicate_and_column() {
+        let mut router = RouterSink::new()
+            .route(
+                "negative",
+                |row| matches!(row.get_by_name("amount").unwrap().get_data(), Value::Int32(a) if *a < 0),
+                TestSink::default(),
+            )
+            .by_column("country", |_| Ok(TestSink::default()))
+            .fallback(TestSink::default());
+        pipeline()
+            .run(&mut std::io::Cursor::new(DATA), &mut router)
+            .unwrap();
+
+        assert_eq!(
+            vec![("negative", 1), ("CH", 1), ("DE", 1), ("fallback", 1)],
+            router.num_rows_by_route()
+        );
+        let sinks = router.into_sinks();
+        assert_eq!(vec![4], sinks[0].1.line_nums);
+        assert_eq!(vec![2], sinks[1].1.line_nums);
+        assert_eq!(vec![5], sinks[3].1.line_nums);
+        assert!(sinks.iter().all(|(_, s)| s.finished && s.header.is_some()));
+    }
+
+    #[test]
+    fn row_without_route_is_an_error() {
+        let mut router = RouterSink::new().by_column("country", |_| Ok(TestSink::default()));
+        let res = pipeline().run(&mut std::io::Cursor::new(DATA), &mut router);
+        assert!(
+            matches!(&res, Err(PattiCsvError::Generic { msg }) if msg == "[ERROR_ON_ROUTE] No route for row; line: 5"),
+            "{:?}",
+            res
+        );
+    }
+}
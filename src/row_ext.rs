@@ -0,0 +1,74 @@
+use venum_tds::{data_cell::DataCell, data_cell_row::DataCellRow};
+
+/// Case-insensitive access to the cells of a (parsed) row by column name, since the casing of headers differs across
+/// files, e.g. "CustomerId" vs. "customerid". The names of the cells are kept as they are, only the lookup ignores the
+/// case. An exact match always wins, otherwise the first cell that matches case-insensitively.
+///
+/// ```
+/// use patti_csv::prelude::*;
+///
+/// let parser = PattiCsvParserBuilder::csv()
+///     .stringly_type_columns(2)
+///     .build()
+///     .unwrap();
+/// let mut data = std::io::Cursor::new("Id,Name\n1,foo");
+/// let rows = parser.parse_iter(&mut data).collect::<Result<Vec<_>>>().unwrap();
+/// assert_eq!("Name", rows[1].get_ci("NAME").unwrap().get_name());
+/// ```
+pub trait DataCellRowExt {
+    fn position_ci(&self, name: &str) -> Option<usize>;
+    fn get_ci(&self, name: &str) -> Option<&DataCell>;
+    fn get_ci_mut(&mut self, name: &str) -> Option<&mut DataCell>;
+}
+
+/// Helper method. Compares by the (full unicode) lowercase, without allocating.
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+impl DataCellRowExt for DataCellRow {
+    fn position_ci(&self, name: &str) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|cell| cell.get_name() == name)
+            .or_else(|| {
+                self.0
+                    .iter()
+                    .position(|cell| eq_ignore_case(cell.get_name(), name))
+            })
+    }
+
+    fn get_ci(&self, name: &str) -> Option<&DataCell> {
+        self.position_ci(name).map(|idx| &self.0[idx])
+    }
+
+    fn get_ci_mut(&mut self, name: &str) -> Option<&mut DataCell> {
+        self.position_ci(name).map(|idx| &mut self.0[idx])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::value::Value;
+
+    use super::*;
+
+    #[test]
+    fn get_case_insensitive() {
+        let mut row = DataCellRow::new();
+        for (idx, name) in ["Straße", "id", "ID"].iter().enumerate() {
+            row.push(DataCell::new(String::from(*name), idx, Value::Int32(idx as i32)).unwrap());
+        }
+
+        assert_eq!(Some(0), row.position_ci("STRAßE"));
+        assert_eq!(Some(1), row.position_ci("Id"));
+        assert_eq!(Some(2), row.position_ci("ID")); // exact match wins
+        assert_eq!(None, row.position_ci("name"));
+
+        row.get_ci_mut("iD").unwrap().data = Value::Int32(42);
+        assert_eq!(&Value::Int32(42), row.get_ci("id").unwrap().get_data());
+        assert_eq!("id", row.get_ci("Id").unwrap().get_name());
+    }
+}
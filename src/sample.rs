@@ -0,0 +1,122 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use venum_tds::data_cell_row::DataCellRow;
+
+/// A bounded, uniformly random sample of (typed) rows, collected while parsing (reservoir sampling, "algorithm R").
+/// I.e. every row has the same chance to end up in the sample, no matter how many rows there are, and we never hold
+/// more than `capacity` rows.
+#[derive(Debug, Clone)]
+pub struct RowReservoir {
+    capacity: usize,
+    num_seen: usize,
+    rows: Vec<(usize, DataCellRow)>, // (line number, row)
+    rng_state: u64,
+}
+
+impl RowReservoir {
+    /// With the same seed (and the same data) the sample is always the same. Without a seed, it's random.
+    pub fn new(capacity: usize, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        Self {
+            capacity,
+            num_seen: 0,
+            rows: Vec::with_capacity(capacity),
+            rng_state: seed,
+        }
+    }
+
+    /// splitmix64, which is plenty for sampling. (We don't want a dependency just for this.)
+    fn next_random(&mut self) -> u64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Offers a row to the sample. The row is only cloned, if it's actually taken.
+    pub fn offer(&mut self, line_num: usize, row: &DataCellRow) {
+        self.num_seen += 1;
+        if self.rows.len() < self.capacity {
+            self.rows.push((line_num, row.clone()));
+            return;
+        }
+        let slot = (self.next_random() % self.num_seen as u64) as usize;
+        if slot < self.capacity {
+            self.rows[slot] = (line_num, row.clone());
+        }
+    }
+
+    /// The number of rows, that were offered to the sample.
+    pub fn num_seen(&self) -> usize {
+        self.num_seen
+    }
+
+    /// The sampled rows with their line numbers, in the order of the data.
+    pub fn into_rows(mut self) -> Vec<(usize, DataCellRow)> {
+        self.rows.sort_by_key(|(line_num, _)| *line_num);
+        self.rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::value::Value;
+    use venum_tds::data_cell::DataCell;
+
+    use super::*;
+
+    fn row(i: usize) -> DataCellRow {
+        let mut row = DataCellRow::new();
+        row.push(DataCell::new(String::from("n"), 0, Value::UInt64(i as u64)).unwrap());
+        row
+    }
+
+    #[test]
+    fn reservoir_takes_everything_below_capacity() {
+        let mut res = RowReservoir::new(10, Some(42));
+        (1..=5).for_each(|i| res.offer(i, &row(i)));
+        assert_eq!(5, res.num_seen());
+        assert_eq!(
+            vec![1, 2, 3, 4, 5],
+            res.into_rows().iter().map(|(l, _)| *l).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn reservoir_is_bounded_sorted_and_reproducible() {
+        let sample = |seed| {
+            let mut res = RowReservoir::new(10, Some(seed));
+            (1..=1000).for_each(|i| res.offer(i, &row(i)));
+            res.into_rows()
+                .into_iter()
+                .map(|(l, _)| l)
+                .collect::<Vec<_>>()
+        };
+        let s = sample(42);
+        assert_eq!(10, s.len());
+        assert!(s.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(s, sample(42));
+        assert_ne!(s, sample(43));
+        // With 1000 rows, it's (very) unlikely that the sample is just the first rows.
+        assert!(s.iter().any(|l| *l > 10));
+    }
+
+    #[test]
+    fn reservoir_is_roughly_uniform() {
+        // Every row should be sampled with probability 10/100. Over many runs, the first and the second half of the
+        // data should be hit about equally often.
+        let mut first_half = 0;
+        for seed in 0..200 {
+            let mut res = RowReservoir::new(10, Some(seed));
+            (1..=100).for_each(|i| res.offer(i, &row(i)));
+            first_half += res.into_rows().iter().filter(|(l, _)| *l <= 50).count();
+        }
+        assert!((800..1200).contains(&first_half), "{}", first_half);
+    }
+}
@@ -0,0 +1,213 @@
+use venum::value_type::ValueType;
+
+use crate::parser_config::{HeaderMatchMode, TypeColumnEntry};
+
+/// Difference between some (stored) column typings and the header line of a (new) file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SchemaDiff {
+    /// Columns, that are matched exactly by the header of their typing.
+    pub unchanged: Vec<String>,
+    /// (header of the typing, header in the file) for columns, that are only matched through an alias, or through
+    /// the match mode (e.g. ignoring casing).
+    pub renamed: Vec<(String, String)>,
+    /// Columns of the file, without any typing.
+    pub added: Vec<String>,
+    /// Headers of the typings, without any column in the file. (Typings without a header are named by their index.)
+    pub removed: Vec<String>,
+}
+
+impl SchemaDiff {
+    pub fn is_unchanged(&self) -> bool {
+        self.renamed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// For every column of the header line, the index of the typing it is matched by (if any). Typings with a header
+/// are matched by name (and aliases), typings without one by their position.
+fn match_typings_to_columns(
+    column_typings: &[TypeColumnEntry],
+    header: &[String],
+    mode: &HeaderMatchMode,
+) -> Vec<Option<usize>> {
+    let mut matched: Vec<Option<usize>> = vec![None; header.len()];
+
+    // Exact matches of all typings first, so that a soft match never takes away the column of an exact one.
+    for soft in [false, true] {
+        for (typing_idx, tce) in column_typings.iter().enumerate() {
+            if tce.header.is_none() || matched.contains(&Some(typing_idx)) {
+                continue;
+            }
+            let col_idx = header.iter().enumerate().position(|(i, h)| {
+                matched[i].is_none()
+                    && match soft {
+                        false => tce.header.as_deref() == Some(h.as_str()),
+                        true => tce.matches_header(h, mode),
+                    }
+            });
+            if let Some(col_idx) = col_idx {
+                matched[col_idx] = Some(typing_idx);
+            }
+        }
+    }
+    for (typing_idx, tce) in column_typings.iter().enumerate() {
+        if tce.header.is_none() && typing_idx < header.len() && matched[typing_idx].is_none() {
+            matched[typing_idx] = Some(typing_idx);
+        }
+    }
+    matched
+}
+
+fn typing_name(tce: &TypeColumnEntry, typing_idx: usize) -> String {
+    tce.header
+        .clone()
+        .unwrap_or_else(|| format!("#{}", typing_idx))
+}
+
+/// Compares (stored) column typings against the header line of a (new) file, using the header aliases and the match
+/// mode to detect renamed columns.
+pub fn diff_schema(
+    column_typings: &[TypeColumnEntry],
+    header: &[String],
+    mode: &HeaderMatchMode,
+) -> SchemaDiff {
+    let matched = match_typings_to_columns(column_typings, header, mode);
+    let mut diff = SchemaDiff::default();
+
+    for (col_idx, typing_idx) in matched.iter().enumerate() {
+        let col_header = header[col_idx].clone();
+        match typing_idx.map(|ti| &column_typings[ti]) {
+            None => diff.added.push(col_header),
+            Some(tce) if tce.header.is_none() || tce.header.as_ref() == Some(&col_header) => {
+                diff.unchanged.push(col_header)
+            }
+            Some(tce) => diff
+                .renamed
+                .push((tce.header.clone().unwrap_or_default(), col_header)),
+        }
+    }
+    for (typing_idx, tce) in column_typings.iter().enumerate() {
+        if !matched.contains(&Some(typing_idx)) {
+            diff.removed.push(typing_name(tce, typing_idx));
+        }
+    }
+    diff
+}
+
+/// Adapts (stored) column typings to the header line of a (new) file, i.e. the result has one typing per column, in
+/// the order of the header line. Matched typings are kept as they are (i.e. with their header, so the resulting
+/// column names stay the same), new columns are typed as `String`, and typings of removed columns are dropped.
+pub fn adapt_typings(
+    column_typings: &[TypeColumnEntry],
+    header: &[String],
+    mode: &HeaderMatchMode,
+) -> Vec<TypeColumnEntry> {
+    match_typings_to_columns(column_typings, header, mode)
+        .into_iter()
+        .enumerate()
+        .map(|(col_idx, typing_idx)| match typing_idx {
+            Some(ti) => column_typings[ti].clone(),
+            None => TypeColumnEntry::new(Some(header[col_idx].clone()), ValueType::String),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typings() -> Vec<TypeColumnEntry> {
+        vec![
+            TypeColumnEntry::new(Some(String::from("id")), ValueType::UInt32),
+            TypeColumnEntry::new(Some(String::from("Qty")), ValueType::Int32)
+                .with_header_aliases(vec![String::from("Quantity")]),
+            TypeColumnEntry::new(Some(String::from("Price")), ValueType::Float64),
+            TypeColumnEntry::new(Some(String::from("Comment")), ValueType::String),
+        ]
+    }
+
+    fn header(h: &[&str]) -> Vec<String> {
+        h.iter().map(|s| String::from(*s)).collect()
+    }
+
+    #[test]
+    fn diff_schema_unchanged() {
+        let diff = diff_schema(
+            &typings(),
+            &header(&["id", "Qty", "Price", "Comment"]),
+            &HeaderMatchMode::Exact,
+        );
+        assert!(diff.is_unchanged());
+        assert_eq!(4, diff.unchanged.len());
+    }
+
+    #[test]
+    fn diff_schema_renamed_added_removed() {
+        let diff = diff_schema(
+            &typings(),
+            &header(&["ID", "Quantity", "Price", "Currency"]),
+            &HeaderMatchMode::IgnoreCaseAndWhitespace,
+        );
+        assert_eq!(
+            SchemaDiff {
+                unchanged: vec![String::from("Price")],
+                renamed: vec![
+                    (String::from("id"), String::from("ID")),
+                    (String::from("Qty"), String::from("Quantity"))
+                ],
+                added: vec![String::from("Currency")],
+                removed: vec![String::from("Comment")],
+            },
+            diff
+        );
+        assert!(!diff.is_unchanged());
+    }
+
+    #[test]
+    fn diff_schema_exact_mode_does_not_soft_match() {
+        let diff = diff_schema(&typings()[..1], &header(&["ID"]), &HeaderMatchMode::Exact);
+        assert_eq!(vec![String::from("ID")], diff.added);
+        assert_eq!(vec![String::from("id")], diff.removed);
+    }
+
+    #[test]
+    fn diff_schema_exact_match_wins_over_earlier_alias() {
+        let typings = vec![
+            TypeColumnEntry::new(Some(String::from("a")), ValueType::Int32)
+                .with_header_aliases(vec![String::from("b")]),
+            TypeColumnEntry::new(Some(String::from("b")), ValueType::String),
+        ];
+        let diff = diff_schema(&typings, &header(&["b"]), &HeaderMatchMode::Exact);
+        assert_eq!(vec![String::from("b")], diff.unchanged);
+        assert!(diff.renamed.is_empty());
+        assert_eq!(vec![String::from("a")], diff.removed);
+    }
+
+    #[test]
+    fn diff_schema_typings_without_header_are_positional() {
+        let typings = vec![
+            TypeColumnEntry::new(None, ValueType::Int32),
+            TypeColumnEntry::new(None, ValueType::Int32),
+        ];
+        let diff = diff_schema(&typings, &header(&["a"]), &HeaderMatchMode::Exact);
+        assert_eq!(vec![String::from("a")], diff.unchanged);
+        assert_eq!(vec![String::from("#1")], diff.removed);
+    }
+
+    #[test]
+    fn adapt_typings_to_new_header() {
+        let adapted = adapt_typings(
+            &typings(),
+            &header(&["Currency", "Quantity", "id", "Price"]),
+            &HeaderMatchMode::Exact,
+        );
+        assert_eq!(
+            vec![
+                TypeColumnEntry::new(Some(String::from("Currency")), ValueType::String),
+                typings()[1].clone(),
+                typings()[0].clone(),
+                typings()[2].clone(),
+            ],
+            adapted
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::convert::TryFrom;
+
+use venum::{value::Value, value_type::ValueType};
+use venum_tds::{data_cell::DataCell, data_cell_row::DataCellRow};
+
+use crate::errors::{PattiCsvError, Result};
+
+/// A plain row of (typed) values with their column names, for users who don't want to deal with `venum_tds` types in
+/// their own code. The parser still produces `DataCellRow`s (which stay the default), this is just a conversion at the
+/// end. See: `SimpleRowIter`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SimpleRow {
+    pub names: Vec<String>,
+    pub values: Vec<Value>,
+}
+
+impl SimpleRow {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Value> {
+        self.values.get(idx)
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&Value> {
+        self.names
+            .iter()
+            .position(|n| n == name)
+            .and_then(|idx| self.values.get(idx))
+    }
+
+    /// Converts back into a `DataCellRow` with the given column types. Unlike `TryFrom`, this also works for rows with
+    /// `Value::None`s.
+    pub fn into_data_cell_row(self, types: &[ValueType]) -> Result<DataCellRow> {
+        if types.len() != self.values.len() || self.names.len() != self.values.len() {
+            return Err(PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_SIMPLE_ROW] Got {} names and {} types for {} values",
+                    self.names.len(),
+                    types.len(),
+                    self.values.len()
+                ),
+            });
+        }
+        let mut row = DataCellRow::with_capacity(self.values.len());
+        for (idx, ((name, value), vt)) in self
+            .names
+            .into_iter()
+            .zip(self.values)
+            .zip(types)
+            .enumerate()
+        {
+            row.push(
+                DataCell::new_with_type_info(vt.clone(), name, idx, value).map_err(|e| {
+                    PattiCsvError::Generic {
+                        msg: format!("[ERROR_ON_SIMPLE_ROW] {:?}", e),
+                    }
+                })?,
+            );
+        }
+        Ok(row)
+    }
+}
+
+impl From<DataCellRow> for SimpleRow {
+    fn from(row: DataCellRow) -> Self {
+        let (names, values) = row
+            .into_iter()
+            .map(|cell| (String::from(cell.get_name()), cell.get_data().clone()))
+            .unzip();
+        Self { names, values }
+    }
+}
+
+/// The column types are taken from the values, so this fails for rows with `Value::None`s. Use
+/// `SimpleRow::into_data_cell_row` for these.
+impl TryFrom<SimpleRow> for DataCellRow {
+    type Error = PattiCsvError;
+
+    fn try_from(row: SimpleRow) -> Result<Self> {
+        if row.names.len() != row.values.len() {
+            return Err(PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_SIMPLE_ROW] Got {} names for {} values",
+                    row.names.len(),
+                    row.values.len()
+                ),
+            });
+        }
+        let mut dcr = DataCellRow::with_capacity(row.values.len());
+        for (idx, (name, value)) in row.names.into_iter().zip(row.values).enumerate() {
+            dcr.push(
+                DataCell::new(name, idx, value).map_err(|e| PattiCsvError::Generic {
+                    msg: format!("[ERROR_ON_SIMPLE_ROW] column: {}; {:?}", idx, e),
+                })?,
+            );
+        }
+        Ok(dcr)
+    }
+}
+
+/// Wraps a row iterator (e.g. a `PattiCsvParserIterator`), and converts every row into a `SimpleRow`.
+pub struct SimpleRowIter<I> {
+    inner: I,
+}
+
+impl<I: Iterator<Item = Result<DataCellRow>>> SimpleRowIter<I> {
+    pub fn new(inner: I) -> Self {
+        Self { inner }
+    }
+
+    /// Gives back the wrapped iterator, e.g. to get its stats or report.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I: Iterator<Item = Result<DataCellRow>>> Iterator for SimpleRowIter<I> {
+    type Item = Result<SimpleRow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|res| res.map(SimpleRow::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple_row() -> SimpleRow {
+        SimpleRow {
+            names: vec![String::from("id"), String::from("name")],
+            values: vec![Value::Int32(1), Value::String(String::from("foo"))],
+        }
+    }
+
+    #[test]
+    fn simple_row_roundtrip() {
+        let dcr = DataCellRow::try_from(simple_row()).unwrap();
+        assert_eq!(&Value::Int32(1), dcr.get_by_name("id").unwrap().get_data());
+        let sr = SimpleRow::from(dcr);
+        assert_eq!(simple_row(), sr);
+        assert_eq!(2, sr.len());
+        assert_eq!(Some(&Value::Int32(1)), sr.get(0));
+        assert_eq!(
+            Some(&Value::String(String::from("foo"))),
+            sr.get_by_name("name")
+        );
+        assert_eq!(None, sr.get_by_name("nope"));
+    }
+
+    #[test]
+    fn simple_row_with_none_needs_types() {
+        let mut sr = simple_row();
+        sr.values[1] = Value::None;
+        assert!(DataCellRow::try_from(sr.clone()).is_err());
+        assert!(sr.clone().into_data_cell_row(&[ValueType::Int32]).is_err());
+
+        let dcr = sr
+            .into_data_cell_row(&[ValueType::Int32, ValueType::String])
+            .unwrap();
+        assert_eq!(&Value::None, dcr.get_by_name("name").unwrap().get_data());
+    }
+
+    #[test]
+    fn simple_row_iter() {
+        let rows = vec![
+            Ok(DataCellRow::try_from(simple_row()).unwrap()),
+            Err(PattiCsvError::Generic {
+                msg: String::from("nope"),
+            }),
+        ];
+        let res = SimpleRowIter::new(rows.into_iter()).collect::<Vec<_>>();
+        assert_eq!(simple_row(), *res[0].as_ref().unwrap());
+        assert!(res[1].is_err());
+    }
+}
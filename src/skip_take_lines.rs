@@ -1,11 +1,27 @@
 use regex::Regex;
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::RangeInclusive};
 
-use crate::errors::{PattiCsvError, Result};
+use crate::{
+    errors::{PattiCsvError, Result},
+    report::ComponentInfo,
+};
 
 pub trait SkipTakeLines: Debug {
     fn skip(&self, line_num: usize, line_content: &str) -> bool;
     fn get_self_info(&self) -> String;
+    /// The stable id of this kind of filter. See: `ComponentInfo`
+    fn kind(&self) -> &'static str {
+        "custom"
+    }
+    /// The kind and the parameters (and the nested filters, if any), e.g. for reports.
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind())
+    }
+    /// Key-value metadata of a line this filter skipped (without the line terminator), e.g. the report date of a
+    /// banner line. It's collected in the stats, instead of being lost with the line.
+    fn metadata(&self, _line_content: &str) -> Vec<(String, String)> {
+        Vec::new()
+    }
 }
 
 #[derive(Debug)]
@@ -24,6 +40,12 @@ impl SkipTakeLines for SkipLinesFromStart {
     fn get_self_info(&self) -> String {
         format!("{self:?}")
     }
+    fn kind(&self) -> &'static str {
+        "skipLinesFromStart"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("numLines", self.skip_num_lines)
+    }
 }
 
 #[derive(Debug)]
@@ -47,6 +69,12 @@ impl SkipTakeLines for SkipLinesStartingWith {
     fn get_self_info(&self) -> String {
         format!("{self:?}")
     }
+    fn kind(&self) -> &'static str {
+        "skipLinesStartingWith"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("startsWith", &self.starts_with)
+    }
 }
 
 #[derive(Debug)]
@@ -72,6 +100,33 @@ impl SkipTakeLines for SkipLinesByRegex {
     fn get_self_info(&self) -> String {
         format!("{self:?}")
     }
+
+    fn kind(&self) -> &'static str {
+        "skipLinesByRegex"
+    }
+
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("regex", &self.regex)
+    }
+
+    /// The named capture groups (that participated in the match), e.g. `^# Report Date: (?P<report_date>.*)`.
+    fn metadata(&self, line_content: &str) -> Vec<(String, String)> {
+        if self.regex.capture_names().flatten().next().is_none() {
+            return Vec::new(); // don't bother capturing
+        }
+        let caps = match self.regex.captures(line_content) {
+            Some(caps) => caps,
+            None => return Vec::new(),
+        };
+        self.regex
+            .capture_names()
+            .flatten()
+            .filter_map(|name| {
+                caps.name(name)
+                    .map(|m| (String::from(name), String::from(m.as_str())))
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -88,11 +143,257 @@ impl SkipTakeLines for SkipEmptyLines {
     fn get_self_info(&self) -> String {
         format!("{self:?}")
     }
+    fn kind(&self) -> &'static str {
+        "skipEmptyLines"
+    }
+}
+
+/// Takes (only) the lines, that start with the given string, i.e. skips all others. Since the parser skips a line, if
+/// any of its filters wants to, several take filters only take the lines all of them take. To take the lines any of
+/// them takes, combine them with `and` (see: `SkipIfAll`).
+#[derive(Debug)]
+pub struct TakeLinesStartingWith {
+    starts_with: String,
+}
+impl TakeLinesStartingWith {
+    pub fn new<T>(starts_with: T) -> Self
+    where
+        T: Into<String> + Debug,
+    {
+        Self {
+            starts_with: starts_with.into(),
+        }
+    }
+}
+impl SkipTakeLines for TakeLinesStartingWith {
+    fn skip(&self, _line_num: usize, line_content: &str) -> bool {
+        !line_content.starts_with(&self.starts_with)
+    }
+    fn get_self_info(&self) -> String {
+        format!("{self:?}")
+    }
+    fn kind(&self) -> &'static str {
+        "takeLinesStartingWith"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("startsWith", &self.starts_with)
+    }
+}
+
+/// Takes (only) the lines, that match the regex, i.e. skips all others. See: `TakeLinesStartingWith`
+#[derive(Debug)]
+pub struct TakeLinesByRegex {
+    regex: Regex,
+}
+impl TakeLinesByRegex {
+    pub fn new<T>(regex_pattern: T) -> Result<Self>
+    where
+        T: AsRef<str> + Debug,
+    {
+        let re = Regex::new(regex_pattern.as_ref()).map_err(|e| {
+            PattiCsvError::ConfigError {msg: format!("[ERROR_ON_REGEX_COMPILE] Cannot create TakeLinesByRegex by given regex str={}. Error: {}", regex_pattern.as_ref(), e)}
+        })?;
+        Ok(Self { regex: re })
+    }
+}
+impl SkipTakeLines for TakeLinesByRegex {
+    fn skip(&self, _line_num: usize, line_content: &str) -> bool {
+        !self.regex.is_match(line_content)
+    }
+    fn get_self_info(&self) -> String {
+        format!("{self:?}")
+    }
+    fn kind(&self) -> &'static str {
+        "takeLinesByRegex"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("regex", &self.regex)
+    }
+}
+
+/// Takes (only) the lines within the range (1-indexed, inclusive), i.e. skips all others. See: `TakeLinesStartingWith`
+#[derive(Debug)]
+pub struct TakeLinesRange {
+    lines: RangeInclusive<usize>,
+}
+impl TakeLinesRange {
+    pub fn new(from: usize, to: usize) -> Self {
+        Self { lines: from..=to }
+    }
+}
+impl SkipTakeLines for TakeLinesRange {
+    fn skip(&self, line_num: usize, _line_content: &str) -> bool {
+        !self.lines.contains(&line_num)
+    }
+    fn get_self_info(&self) -> String {
+        format!("{self:?}")
+    }
+    fn kind(&self) -> &'static str {
+        "takeLinesRange"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind())
+            .with_param("from", self.lines.start())
+            .with_param("to", self.lines.end())
+    }
+}
+
+/// Restricts a filter to a range of lines (1-indexed, inclusive), e.g. only skip lines starting with "#" within the
+/// first 20 lines, so that legitimate data further down is never skipped by accident.
+#[derive(Debug)]
+pub struct OnlyWithinLines {
+    lines: RangeInclusive<usize>,
+    filter: Box<dyn SkipTakeLines + Send + Sync>,
+}
+impl OnlyWithinLines {
+    pub fn new(lines: RangeInclusive<usize>, filter: Box<dyn SkipTakeLines + Send + Sync>) -> Self {
+        Self { lines, filter }
+    }
+}
+impl SkipTakeLines for OnlyWithinLines {
+    fn skip(&self, line_num: usize, line_content: &str) -> bool {
+        self.lines.contains(&line_num) && self.filter.skip(line_num, line_content)
+    }
+    fn get_self_info(&self) -> String {
+        format!(
+            "OnlyWithinLines {{ lines: {:?}, filter: {} }}",
+            self.lines,
+            self.filter.get_self_info()
+        )
+    }
+    fn kind(&self) -> &'static str {
+        "onlyWithinLines"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind())
+            .with_param("from", self.lines.start())
+            .with_param("to", self.lines.end())
+            .with_children(vec![self.filter.describe()])
+    }
+    fn metadata(&self, line_content: &str) -> Vec<(String, String)> {
+        self.filter.metadata(line_content)
+    }
+}
+
+/// Skips a line, if all of the filters want to skip it.
+#[derive(Debug)]
+pub struct SkipIfAll {
+    filters: Vec<Box<dyn SkipTakeLines + Send + Sync>>,
+}
+impl SkipIfAll {
+    pub fn new(filters: Vec<Box<dyn SkipTakeLines + Send + Sync>>) -> Self {
+        Self { filters }
+    }
+}
+impl SkipTakeLines for SkipIfAll {
+    fn skip(&self, line_num: usize, line_content: &str) -> bool {
+        self.filters.iter().all(|f| f.skip(line_num, line_content))
+    }
+    fn get_self_info(&self) -> String {
+        combined_self_info("SkipIfAll", &self.filters)
+    }
+    fn kind(&self) -> &'static str {
+        "skipIfAll"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind())
+            .with_children(self.filters.iter().map(|f| f.describe()).collect())
+    }
+    fn metadata(&self, line_content: &str) -> Vec<(String, String)> {
+        self.filters
+            .iter()
+            .flat_map(|f| f.metadata(line_content))
+            .collect()
+    }
 }
 
+/// Skips a line, if any of the filters wants to skip it. (This is what the parser does with its filters anyway, it's
+/// for nesting, e.g. inside of `SkipIfAll`.)
+#[derive(Debug)]
+pub struct SkipIfAny {
+    filters: Vec<Box<dyn SkipTakeLines + Send + Sync>>,
+}
+impl SkipIfAny {
+    pub fn new(filters: Vec<Box<dyn SkipTakeLines + Send + Sync>>) -> Self {
+        Self { filters }
+    }
+}
+impl SkipTakeLines for SkipIfAny {
+    fn skip(&self, line_num: usize, line_content: &str) -> bool {
+        self.filters.iter().any(|f| f.skip(line_num, line_content))
+    }
+    fn get_self_info(&self) -> String {
+        combined_self_info("SkipIfAny", &self.filters)
+    }
+    fn kind(&self) -> &'static str {
+        "skipIfAny"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind())
+            .with_children(self.filters.iter().map(|f| f.describe()).collect())
+    }
+    fn metadata(&self, line_content: &str) -> Vec<(String, String)> {
+        self.filters
+            .iter()
+            .flat_map(|f| f.metadata(line_content))
+            .collect()
+    }
+}
+
+/// Skips a line, if the filter doesn't want to skip it.
+#[derive(Debug)]
+pub struct SkipIfNot {
+    filter: Box<dyn SkipTakeLines + Send + Sync>,
+}
+impl SkipIfNot {
+    pub fn new(filter: Box<dyn SkipTakeLines + Send + Sync>) -> Self {
+        Self { filter }
+    }
+}
+impl SkipTakeLines for SkipIfNot {
+    fn skip(&self, line_num: usize, line_content: &str) -> bool {
+        !self.filter.skip(line_num, line_content)
+    }
+    fn get_self_info(&self) -> String {
+        format!("SkipIfNot({})", self.filter.get_self_info())
+    }
+    fn kind(&self) -> &'static str {
+        "skipIfNot"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_children(vec![self.filter.describe()])
+    }
+}
+
+fn combined_self_info(name: &str, filters: &[Box<dyn SkipTakeLines + Send + Sync>]) -> String {
+    format!(
+        "{}({})",
+        name,
+        filters
+            .iter()
+            .map(|f| f.get_self_info())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Builder helpers to combine filters, e.g. `SkipLinesStartingWith::new("#").and(SkipLinesFromStart::new(1).not())`.
+pub trait SkipTakeLinesCombinators: SkipTakeLines + Send + Sync + Sized + 'static {
+    fn and<F: SkipTakeLines + Send + Sync + 'static>(self, other: F) -> SkipIfAll {
+        SkipIfAll::new(vec![Box::new(self), Box::new(other)])
+    }
+    fn or<F: SkipTakeLines + Send + Sync + 'static>(self, other: F) -> SkipIfAny {
+        SkipIfAny::new(vec![Box::new(self), Box::new(other)])
+    }
+    fn not(self) -> SkipIfNot {
+        SkipIfNot::new(Box::new(self))
+    }
+}
+impl<T: SkipTakeLines + Send + Sync + 'static> SkipTakeLinesCombinators for T {}
+
 #[cfg(test)]
 mod tests {
-    use crate::skip_take_lines::*;
+    use crate::{report::ComponentInfo, skip_take_lines::*, testing::skip_mask};
 
     fn test_data_01() -> Vec<&'static str> {
         vec![
@@ -156,6 +457,100 @@ mod tests {
         ];
     }
 
+    #[test]
+    fn skip_lines_by_regex_metadata() {
+        let check_line =
+            SkipLinesByRegex::new(r"^# Report (?P<report>\w+)(, Date: (?P<date>[\d-]+))?").unwrap();
+        assert_eq!(
+            vec![
+                (String::from("report"), String::from("Sales")),
+                (String::from("date"), String::from("2024-01-01"))
+            ],
+            check_line.metadata("# Report Sales, Date: 2024-01-01")
+        );
+        assert_eq!(
+            vec![(String::from("report"), String::from("Sales"))],
+            check_line.metadata("# Report Sales")
+        );
+        assert!(SkipLinesByRegex::new("^#")
+            .unwrap()
+            .metadata("# Report")
+            .is_empty());
+    }
+
+    #[test]
+    fn skip_only_within_lines() {
+        let check_line = OnlyWithinLines::new(1..=2, Box::new(SkipLinesStartingWith::new("#")));
+        let to_skip = skip_mask(&check_line, &["foo\n", "# bar\n", "# baz\n"]);
+
+        assert_eq![vec![false, true, false], to_skip];
+    }
+
+    #[test]
+    fn skip_by_combined_filters() {
+        let check_line = SkipLinesStartingWith::new("#").and(SkipLinesFromStart::new(1).not());
+        let to_skip = skip_mask(&check_line, &["# header\n", "foo\n", "# comment\n"]);
+        assert_eq![vec![false, false, true], to_skip];
+
+        let check_line = SkipLinesStartingWith::new("#").or(SkipEmptyLines::new());
+        let to_skip = skip_mask(&check_line, &["# header\n", "foo\n", "\n"]);
+        assert_eq![vec![true, false, true], to_skip];
+    }
+
+    #[test]
+    fn describe_filters() {
+        let check_line = OnlyWithinLines::new(
+            1..=20,
+            Box::new(SkipLinesStartingWith::new("#").and(SkipLinesFromStart::new(1).not())),
+        );
+        assert_eq!(
+            ComponentInfo::new("onlyWithinLines")
+                .with_param("from", 1)
+                .with_param("to", 20)
+                .with_children(vec![ComponentInfo::new("skipIfAll").with_children(vec![
+                    ComponentInfo::new("skipLinesStartingWith").with_param("startsWith", "#"),
+                    ComponentInfo::new("skipIfNot")
+                        .with_children(vec![
+                            ComponentInfo::new("skipLinesFromStart").with_param("numLines", 1)
+                        ])
+                ])]),
+            check_line.describe()
+        );
+        assert_eq!(
+            ComponentInfo::new("skipLinesByRegex").with_param("regex", "^#"),
+            SkipLinesByRegex::new("^#").unwrap().describe()
+        );
+    }
+
+    #[test]
+    fn take_lines() {
+        let to_skip = skip_mask(&TakeLinesStartingWith::new("\"SOME"), &test_data_01());
+        assert_eq![
+            vec![true, true, true, true, false, false, false, true],
+            to_skip
+        ];
+
+        let check_line = TakeLinesByRegex::new(r",[13],").unwrap();
+        assert_eq![
+            vec![true, true, true, true, false, true, false, true],
+            skip_mask(&check_line, &test_data_01())
+        ];
+        assert!(TakeLinesByRegex::new("(").is_err());
+
+        // Takes the header line as well as the data lines
+        let check_line = TakeLinesRange::new(4, 4).and(TakeLinesStartingWith::new("\"SOME"));
+        assert_eq![
+            vec![true, true, true, false, false, false, false, true],
+            skip_mask(&check_line, &test_data_01())
+        ];
+        assert_eq!(
+            ComponentInfo::new("takeLinesRange")
+                .with_param("from", 4)
+                .with_param("to", 4),
+            TakeLinesRange::new(4, 4).describe()
+        );
+    }
+
     #[test]
     fn skip_empty_rows() {
         let check_line = SkipEmptyLines {};
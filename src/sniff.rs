@@ -0,0 +1,152 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::{
+    errors::Result,
+    iterating_parser::PattiCsvParserBuilder,
+    line_tokenizer::DelimitedLineTokenizer,
+    probe::{decode, probe, sniff, sniff_encoding, LineTerminator, SniffReport, SniffedEncoding},
+};
+
+/// The max. number of (sampled) lines, that are tokenized to check for a header line.
+const SNIFF_HEADER_LINES: usize = 100;
+
+/// The dialect of some data, as sniffed by `Sniffer::sniff`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dialect {
+    /// `,` if no separator was found, e.g. for a single column.
+    pub separator_char: char,
+    pub enclosure_char: Option<char>,
+    /// Does the first line look like a header? (I.e. only non-empty, unique tokens, none of which is a number.)
+    pub has_header: bool,
+    pub line_terminator: LineTerminator,
+    pub encoding: SniffedEncoding,
+    /// What the dialect is based on, with the confidence scores and notes.
+    pub report: SniffReport,
+}
+
+impl Dialect {
+    /// Sets the dialect on the builder: the separator and enclosure char, whether the first line is a header, `\r` as
+    /// record separator for old Mac files, and the encoding for UTF-16 (which needs the feature `encoding`). Anything
+    /// else of the builder stays as it is.
+    pub fn configure(&self, builder: PattiCsvParserBuilder) -> PattiCsvParserBuilder {
+        let mut builder = builder
+            .separator_char(self.separator_char)
+            .enclosure_char(self.enclosure_char)
+            .first_data_line_is_header(self.has_header);
+        if self.line_terminator == LineTerminator::Cr {
+            builder = builder.record_separator('\r');
+        }
+        match self.encoding {
+            SniffedEncoding::Utf16Le => builder.encoding("utf-16le"),
+            SniffedEncoding::Utf16Be => builder.encoding("utf-16be"),
+            _ => builder,
+        }
+    }
+
+    /// A new builder with the dialect (see: `configure`), i.e. only the column typings are missing.
+    pub fn to_builder(&self) -> PattiCsvParserBuilder {
+        self.configure(PattiCsvParserBuilder::new())
+    }
+}
+
+/// Sniffs the dialect of third-party data, so nobody has to guess it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sniffer;
+
+impl Sniffer {
+    /// Reads (at most) `sample_bytes` bytes from the current position of the reader, sniffs the encoding, line
+    /// terminator, separator and enclosure char (see: `probe::sniff`), and checks whether the first line is a header.
+    /// Afterwards, the reader is back at the position it was at, i.e. ready to be parsed with the dialect.
+    pub fn sniff<R: Read + Seek>(reader: &mut R, sample_bytes: usize) -> Result<Dialect> {
+        let start = reader.stream_position()?;
+        let mut bytes = Vec::with_capacity(sample_bytes.min(64 * 1024));
+        let res = reader
+            .by_ref()
+            .take(sample_bytes as u64)
+            .read_to_end(&mut bytes);
+        reader.seek(SeekFrom::Start(start))?;
+        res?;
+
+        let mut report = sniff(&mut bytes.as_slice(), sample_bytes)?;
+        let separator_char = report.separator_char.unwrap_or(',');
+        let has_header =
+            match sniff_has_header(&bytes, sample_bytes, separator_char, report.enclosure_char) {
+                Some(has_header) => has_header,
+                None => {
+                    report.notes.push(String::from(
+                        "The sample couldn't be tokenized, so there's probably no header line",
+                    ));
+                    false
+                }
+            };
+        Ok(Dialect {
+            separator_char,
+            enclosure_char: report.enclosure_char,
+            has_header,
+            line_terminator: report.line_terminator,
+            encoding: report.encoding,
+            report,
+        })
+    }
+}
+
+/// Helper method. Whether the first line looks like a header, `None` if the sample can't be tokenized (or decoded).
+fn sniff_has_header(
+    bytes: &[u8],
+    sample_bytes: usize,
+    separator_char: char,
+    enclosure_char: Option<char>,
+) -> Option<bool> {
+    let (encoding, bom_len) = sniff_encoding(bytes);
+    let mut text = decode(&bytes[bom_len..], encoding)?;
+    // The last line is probably cut off (unless we've read everything), so we don't use it.
+    if bytes.len() == sample_bytes {
+        if let Some(pos) = text.rfind(['\n', '\r']) {
+            text.truncate(pos + 1);
+        }
+    }
+    let dlt = DelimitedLineTokenizer::new(separator_char, enclosure_char, None, false);
+    let probed = probe(&dlt, &mut text.as_bytes(), SNIFF_HEADER_LINES).ok()?;
+    Some(probed.candidate_header.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use venum::value::Value;
+
+    use super::*;
+
+    #[test]
+    fn sniff_and_parse() {
+        let mut data = Cursor::new("id;name\n1;'Doe; John'\n2;Jane\n");
+        let dialect = Sniffer::sniff(&mut data, 1024).unwrap();
+        assert_eq!(0, data.position());
+        assert_eq!(';', dialect.separator_char);
+        assert_eq!(Some('\''), dialect.enclosure_char);
+        assert!(dialect.has_header);
+        assert_eq!(LineTerminator::Lf, dialect.line_terminator);
+
+        let parser = dialect
+            .to_builder()
+            .stringly_type_columns(2)
+            .build()
+            .unwrap();
+        let rows = parser
+            .parse_iter(&mut data)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(3, rows.len());
+        assert_eq!(
+            &Value::from(String::from("Doe; John")),
+            rows[1].get_by_name("name").unwrap().get_data()
+        );
+
+        let mut data = Cursor::new("1,2\n3,4\n5,6");
+        let dialect = Sniffer::sniff(&mut data, 5).unwrap();
+        assert_eq!(',', dialect.separator_char);
+        assert!(!dialect.has_header);
+        assert_eq!(0, data.position());
+    }
+}
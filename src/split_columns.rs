@@ -0,0 +1,448 @@
+use regex::Regex;
+use std::{collections::VecDeque, fmt::Debug};
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    line_tokenizer::TokenMeta,
+    parser_config::TypeColumnEntry,
+    report::ComponentInfo,
+};
+
+/// Splits one (raw) token into parts, one per destination column. See: `SplitColumn`.
+pub trait SplitToken: Debug {
+    fn split(&self, token: &str) -> Result<Vec<String>>;
+    /// The number of parts, if it's always the same (e.g. the capture groups of a regex). It's checked against the
+    /// number of destination columns when building the parser, otherwise only per line.
+    fn num_parts(&self) -> Option<usize> {
+        None
+    }
+    fn get_self_info(&self) -> String {
+        String::from("n/a")
+    }
+    /// The stable id of this kind of splitter. See: `ComponentInfo`
+    fn kind(&self) -> &'static str {
+        "custom"
+    }
+    /// The kind and the parameters, e.g. for reports.
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind())
+    }
+}
+
+pub type BoxedSplitToken = Box<dyn SplitToken + Send + Sync>;
+
+/// Splits on every occurrence of the separator, e.g. "a;b;c" into "a", "b" and "c".
+#[derive(Debug)]
+pub struct SplitBySeparator {
+    separator: String,
+}
+impl SplitBySeparator {
+    pub fn new<T>(separator: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            separator: separator.into(),
+        }
+    }
+}
+impl SplitToken for SplitBySeparator {
+    fn split(&self, token: &str) -> Result<Vec<String>> {
+        Ok(token
+            .split(self.separator.as_str())
+            .map(String::from)
+            .collect())
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "separator"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("separator", &self.separator)
+    }
+}
+
+/// Splits into the capture groups of the regex, e.g. `^(\w+)-(\d+)$`. Groups that don't participate in the match give
+/// empty parts, a token that doesn't match at all is an error.
+#[derive(Debug)]
+pub struct SplitByRegex {
+    regex: Regex,
+}
+impl SplitByRegex {
+    pub fn new<T>(regex_pattern: T) -> Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        let regex = Regex::new(regex_pattern.as_ref()).map_err(|e| {
+            PattiCsvError::ConfigError {msg: format!("[ERROR_ON_REGEX_COMPILE] Cannot create SplitByRegex by given regex str={}. Error: {}", regex_pattern.as_ref(), e)}
+        })?;
+        Ok(Self { regex })
+    }
+}
+impl SplitToken for SplitByRegex {
+    fn split(&self, token: &str) -> Result<Vec<String>> {
+        let caps = self
+            .regex
+            .captures(token)
+            .ok_or_else(|| PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_SPLIT] Token does not match regex {}; token: {}",
+                    self.regex, token
+                ),
+            })?;
+        Ok(caps
+            .iter()
+            .skip(1) // the whole match
+            .map(|m| m.map_or_else(String::new, |m| String::from(m.as_str())))
+            .collect())
+    }
+    fn num_parts(&self) -> Option<usize> {
+        Some(self.regex.captures_len() - 1)
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "regex"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("regex", self.regex.as_str())
+    }
+}
+
+/// The default regex of `SplitValueAndUnit`: a number (with an optional sign, and with any `.`, `,` and `'` in between,
+/// so that e.g. `1'000.50` and `10,5` match as well), then the (optional) unit, e.g. `10.00 CHF`, `42kg` or `-3 %`.
+pub const DEFAULT_VALUE_AND_UNIT_REGEX: &str = r"^\s*([-+]?\d(?:[\d.,']*\d)?)\s*(.*?)\s*$";
+
+/// Splits a value with a unit suffix, e.g. `10.00 CHF` or `42 kg`, into the value and the unit (empty if there is none),
+/// i.e. into exactly two parts. The value part is meant for a numeric destination (see also: `NumberFormat` for
+/// grouped or decimal comma values), the unit part for a `String` one. See: `SplitColumn::value_and_unit`
+#[derive(Debug)]
+pub struct SplitValueAndUnit {
+    inner: SplitByRegex,
+}
+impl SplitValueAndUnit {
+    pub fn new() -> Self {
+        Self {
+            inner: SplitByRegex::new(DEFAULT_VALUE_AND_UNIT_REGEX)
+                .expect("the default value and unit regex is valid"),
+        }
+    }
+
+    /// A custom regex, e.g. to only allow certain units. It must have exactly two capture groups: the value and the unit
+    /// (in this order).
+    pub fn with_regex<T>(regex_pattern: T) -> Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        let inner = SplitByRegex::new(regex_pattern.as_ref())?;
+        if inner.num_parts() != Some(2) {
+            return Err(split_err(format!(
+                "The regex of SplitValueAndUnit needs exactly two capture groups (value and unit), but has {:?}: {}",
+                inner.num_parts(),
+                regex_pattern.as_ref()
+            )));
+        }
+        Ok(Self { inner })
+    }
+}
+impl Default for SplitValueAndUnit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl SplitToken for SplitValueAndUnit {
+    fn split(&self, token: &str) -> Result<Vec<String>> {
+        self.inner.split(token)
+    }
+    fn num_parts(&self) -> Option<usize> {
+        Some(2)
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "valueAndUnit"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("regex", self.inner.regex.as_str())
+    }
+}
+
+/// Splits a column into (two or more) new columns, each with its own typing (incl. the header). The column typings of
+/// the parser describe the columns before splitting, i.e. the typing of the split column is replaced by the typings of
+/// the destinations. Sanitizers, converters etc. on the other hand work on the columns after splitting.
+///
+/// An empty token gives an empty token for every destination. In the header line, the header of a destination is
+/// used, or `<header>_<n>` (with n starting at 1) if it has none.
+#[derive(Debug)]
+pub struct SplitColumn {
+    pub column: usize, // before splitting
+    pub splitter: BoxedSplitToken,
+    pub destinations: Vec<TypeColumnEntry>,
+}
+
+impl SplitColumn {
+    pub fn new(
+        column: usize,
+        splitter: BoxedSplitToken,
+        destinations: Vec<TypeColumnEntry>,
+    ) -> Self {
+        Self {
+            column,
+            splitter,
+            destinations,
+        }
+    }
+
+    /// Splits a column with values like `10.00 CHF` or `42 kg` into a value and a unit column, with the default regex
+    /// of `SplitValueAndUnit`.
+    pub fn value_and_unit(column: usize, value: TypeColumnEntry, unit: TypeColumnEntry) -> Self {
+        Self::new(
+            column,
+            Box::new(SplitValueAndUnit::new()),
+            vec![value, unit],
+        )
+    }
+}
+
+fn split_err(msg: String) -> PattiCsvError {
+    PattiCsvError::ConfigError {
+        msg: format!("[ERROR_ON_SPLIT] {}", msg),
+    }
+}
+
+/// Helper method. Checks the splits, and replaces the typings of the split columns by the typings of their destinations.
+/// Returns the splits sorted by column (descending), so that splitting one column never shifts another one. If the
+/// typings are resolved by header, the typings of the destinations are appended instead (the split columns are gone,
+/// so they can't have typings).
+pub(crate) fn splice_split_columns(
+    column_typings: &mut Vec<TypeColumnEntry>,
+    mut split_columns: Vec<SplitColumn>,
+    by_header: bool,
+) -> Result<Vec<SplitColumn>> {
+    split_columns.sort_by_key(|sc| std::cmp::Reverse(sc.column));
+    for (i, sc) in split_columns.iter().enumerate() {
+        if sc.destinations.len() < 2 {
+            return Err(split_err(format!(
+                "Column {} must be split into at least two destinations, but there are: {}",
+                sc.column,
+                sc.destinations.len()
+            )));
+        }
+        if let Some(n) = sc.splitter.num_parts() {
+            if n != sc.destinations.len() {
+                return Err(split_err(format!(
+                    "Splitter of column {} gives {} parts, but there are {} destinations",
+                    sc.column,
+                    n,
+                    sc.destinations.len()
+                )));
+            }
+        }
+        if split_columns[..i].iter().any(|o| o.column == sc.column) {
+            return Err(split_err(format!(
+                "Column {} is split more than once",
+                sc.column
+            )));
+        }
+        if by_header {
+            if let Some(d) = sc.destinations.iter().find(|d| d.header.is_none()) {
+                return Err(split_err(format!(
+                    "Typings are resolved by header, so every destination of column {} needs one, but this has none: {:?}",
+                    sc.column, d
+                )));
+            }
+        } else if sc.column >= column_typings.len() {
+            return Err(split_err(format!(
+                "Column {} can't be split, there are only {} column typings",
+                sc.column,
+                column_typings.len()
+            )));
+        }
+    }
+    for sc in split_columns.iter() {
+        if by_header {
+            column_typings.extend(sc.destinations.iter().cloned());
+        } else {
+            column_typings.splice(sc.column..=sc.column, sc.destinations.iter().cloned());
+        }
+    }
+    Ok(split_columns)
+}
+
+/// Helper method. Splits the tokens of a line (see: `splice_split_columns` for the order of the splits). For the header
+/// line, the destination headers are used instead.
+pub(crate) fn split_tokens(
+    line_num: usize,
+    tokens: VecDeque<String>,
+    split_columns: &[SplitColumn],
+    is_header: bool,
+) -> Result<VecDeque<String>> {
+    if split_columns.is_empty() {
+        return Ok(tokens);
+    }
+    let mut tokens = Vec::from(tokens);
+    for sc in split_columns {
+        let token = match tokens.get(sc.column) {
+            Some(t) => t,
+            None => {
+                return Err(PattiCsvError::Generic {
+                    msg: format!(
+                        "[ERROR_ON_SPLIT] No column {} to split; line: {}",
+                        sc.column, line_num
+                    ),
+                })
+            }
+        };
+        let parts = if is_header {
+            sc.destinations
+                .iter()
+                .enumerate()
+                .map(|(i, d)| {
+                    d.header
+                        .clone()
+                        .unwrap_or_else(|| format!("{}_{}", token, i + 1))
+                })
+                .collect()
+        } else if token.is_empty() {
+            vec![String::new(); sc.destinations.len()]
+        } else {
+            let parts = sc.splitter.split(token).map_err(|e| match e {
+                PattiCsvError::Generic { msg } => PattiCsvError::Generic {
+                    msg: format!("{}; line: {}; column: {}", msg, line_num, sc.column),
+                },
+                e => e,
+            })?;
+            if parts.len() != sc.destinations.len() {
+                return Err(PattiCsvError::Generic {
+                    msg: format!(
+                        "[ERROR_ON_SPLIT] Split gave {} parts, but there are {} destinations; line: {}; column: {}; token: {}",
+                        parts.len(),
+                        sc.destinations.len(),
+                        line_num,
+                        sc.column,
+                        token
+                    ),
+                });
+            }
+            parts
+        };
+        tokens.splice(sc.column..=sc.column, parts);
+    }
+    Ok(VecDeque::from(tokens))
+}
+
+/// Helper method. Splits the metadata of the tokens like `split_tokens` splits the tokens, i.e. every destination gets
+/// the metadata of the split token.
+pub(crate) fn split_token_meta(token_meta: &mut Vec<TokenMeta>, split_columns: &[SplitColumn]) {
+    for sc in split_columns {
+        if let Some(meta) = token_meta.get(sc.column).copied() {
+            token_meta.splice(
+                sc.column..=sc.column,
+                std::iter::repeat_n(meta, sc.destinations.len()),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::value_type::ValueType;
+
+    use super::*;
+
+    #[test]
+    fn split_tokens_and_splice_typings() {
+        let mut typings = vec![
+            TypeColumnEntry::new(None, ValueType::Int32),
+            TypeColumnEntry::new(None, ValueType::String),
+        ];
+        let splits = splice_split_columns(
+            &mut typings,
+            vec![SplitColumn::new(
+                1,
+                Box::new(SplitBySeparator::new(";")),
+                vec![
+                    TypeColumnEntry::new(Some(String::from("a")), ValueType::String),
+                    TypeColumnEntry::new(None, ValueType::Int32),
+                    TypeColumnEntry::new(None, ValueType::Bool),
+                ],
+            )],
+            false,
+        )
+        .unwrap();
+        assert_eq!(4, typings.len());
+        assert_eq!(ValueType::Bool, typings[3].target_type);
+
+        let tokens = |v: &[&str]| v.iter().map(|s| String::from(*s)).collect::<VecDeque<_>>();
+        assert_eq!(
+            tokens(&["id", "a", "x_2", "x_3"]),
+            split_tokens(1, tokens(&["id", "x"]), &splits, true).unwrap()
+        );
+        assert_eq!(
+            tokens(&["1", "foo", "2", "true"]),
+            split_tokens(2, tokens(&["1", "foo;2;true"]), &splits, false).unwrap()
+        );
+        assert_eq!(
+            tokens(&["1", "", "", ""]),
+            split_tokens(3, tokens(&["1", ""]), &splits, false).unwrap()
+        );
+        assert!(split_tokens(4, tokens(&["1", "foo;2"]), &splits, false).is_err());
+    }
+
+    #[test]
+    fn splice_split_columns_checks_destinations() {
+        let typings = || vec![TypeColumnEntry::new(None, ValueType::String)];
+        let dests = |n: usize| vec![TypeColumnEntry::new(None, ValueType::String); n];
+        let by_regex = || Box::new(SplitByRegex::new(r"^(\w+)-(\d+)$").unwrap());
+
+        assert!(splice_split_columns(
+            &mut typings(),
+            vec![SplitColumn::new(0, by_regex(), dests(3))],
+            false
+        )
+        .is_err());
+        assert!(splice_split_columns(
+            &mut typings(),
+            vec![SplitColumn::new(1, by_regex(), dests(2))],
+            false
+        )
+        .is_err());
+        assert!(splice_split_columns(
+            &mut typings(),
+            vec![SplitColumn::new(0, by_regex(), dests(2))],
+            true
+        )
+        .is_err());
+        assert!(splice_split_columns(
+            &mut typings(),
+            vec![SplitColumn::new(0, by_regex(), dests(2))],
+            false
+        )
+        .is_ok());
+        assert_eq!(
+            vec![String::from("ab"), String::from("12")],
+            by_regex().split("ab-12").unwrap()
+        );
+        assert!(by_regex().split("ab").is_err());
+    }
+
+    #[test]
+    fn split_value_and_unit() {
+        let parts = |s: &str, u: &str| vec![String::from(s), String::from(u)];
+        let vu = SplitValueAndUnit::new();
+        assert_eq!(parts("10.00", "CHF"), vu.split(" 10.00 CHF ").unwrap());
+        assert_eq!(parts("42", "kg"), vu.split("42kg").unwrap());
+        assert_eq!(parts("-1'000,5", "m²"), vu.split("-1'000,5 m²").unwrap());
+        assert_eq!(parts("7", ""), vu.split("7").unwrap());
+        assert!(vu.split("kg").is_err());
+
+        let weights = SplitValueAndUnit::with_regex(r"^(\d+)\s*(kg|g)$").unwrap();
+        assert_eq!(parts("500", "g"), weights.split("500 g").unwrap());
+        assert!(weights.split("500 m").is_err());
+        assert!(SplitValueAndUnit::with_regex(r"^(\d+)$").is_err());
+    }
+}
@@ -0,0 +1,427 @@
+use std::{cmp::Ordering, io::Read};
+
+use venum::value::Value;
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    iterating_parser::{PattiCsvParser, PattiCsvParserIterator},
+};
+
+/// How the rows of the two inputs are matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffMode {
+    /// The n-th data row of the left input is compared to the n-th data row of the right input.
+    Positional,
+    /// Rows are matched by the values of these key columns (by index). Both inputs must be sorted by the key
+    /// (ascending), so we only ever hold one row per input in memory. Unsorted input is an error.
+    SortedByKey(Vec<usize>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellDiff {
+    pub column: usize,
+    pub header: String,
+    pub left: Value,
+    pub right: Value,
+}
+
+/// A difference between the two inputs. The line numbers are those of the respective input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowDiff {
+    OnlyLeft {
+        line: usize,
+        row: DataCellRow,
+    },
+    OnlyRight {
+        line: usize,
+        row: DataCellRow,
+    },
+    Changed {
+        left_line: usize,
+        right_line: usize,
+        cells: Vec<CellDiff>,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffStats {
+    pub num_rows_left: usize,
+    pub num_rows_right: usize,
+    pub num_rows_equal: usize,
+    pub num_rows_changed: usize,
+    pub num_rows_only_left: usize,
+    pub num_rows_only_right: usize,
+}
+
+struct DiffSide<'pars, 'rd, R: Read> {
+    name: &'static str, // for error context
+    iter: PattiCsvParserIterator<'pars, 'rd, R>,
+    done: bool,
+    peeked: Option<(usize, DataCellRow)>,
+    prev_key: Option<Vec<Value>>,
+}
+
+impl<'pars, 'rd, R: Read> DiffSide<'pars, 'rd, R> {
+    fn new(parser: &'pars PattiCsvParser, data: &'rd mut R, name: &'static str) -> Self {
+        let mut iter = parser.parse_iter(data);
+        iter.emit_header_row = false;
+        Self {
+            name,
+            iter,
+            done: false,
+            peeked: None,
+            prev_key: None,
+        }
+    }
+
+    /// The next data row (i.e. the header row is skipped) with its line number.
+    fn next_row(&mut self) -> Option<Result<(usize, DataCellRow)>> {
+        if self.done {
+            return None;
+        }
+        match self.iter.next() {
+            None => {
+                self.done = true;
+                None
+            }
+            Some(res) => Some(res.map(|row| (self.iter.curr_line_num(), row))),
+        }
+    }
+
+    /// Makes sure we have a peeked row (unless we're at the end), and that the input is still sorted by key.
+    fn fill(&mut self, key_cols: &[usize]) -> Result<()> {
+        if self.peeked.is_some() {
+            return Ok(());
+        }
+        let (line, row) = match self.next_row() {
+            None => return Ok(()),
+            Some(res) => res?,
+        };
+        let key = key_of(&row, key_cols);
+        if let Some(prev_key) = &self.prev_key {
+            if compare_keys(prev_key, &key, line)? == Ordering::Greater {
+                return Err(PattiCsvError::Generic {
+                    msg: format!(
+                        "[ERROR_ON_DIFF] The {} input is not sorted by key; line: {}",
+                        self.name, line
+                    ),
+                });
+            }
+        }
+        self.prev_key = Some(key);
+        self.peeked = Some((line, row));
+        Ok(())
+    }
+}
+
+fn key_of(row: &DataCellRow, key_cols: &[usize]) -> Vec<Value> {
+    key_cols
+        .iter()
+        .map(|&c| {
+            row.0
+                .get(c)
+                .map_or(Value::None, |cell| cell.get_data().clone())
+        })
+        .collect()
+}
+
+fn compare_keys(a: &[Value], b: &[Value], line: usize) -> Result<Ordering> {
+    a.partial_cmp(b).ok_or_else(|| PattiCsvError::Generic {
+        msg: format!(
+            "[ERROR_ON_DIFF] Keys are not comparable: {:?} vs {:?}; line: {}",
+            a, b, line
+        ),
+    })
+}
+
+fn diff_cells(left: &DataCellRow, right: &DataCellRow) -> Vec<CellDiff> {
+    let num_cols = left.0.len().max(right.0.len());
+    (0..num_cols)
+        .filter_map(|i| {
+            let l = left.0.get(i);
+            let r = right.0.get(i);
+            let l_data = l.map_or(&Value::None, |c| c.get_data());
+            let r_data = r.map_or(&Value::None, |c| c.get_data());
+            (l_data != r_data).then(|| CellDiff {
+                column: i,
+                header: String::from(l.or(r).map_or("", |c| c.get_name())),
+                left: l_data.clone(),
+                right: r_data.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Parses two inputs with the same parser (config) and yields their differences, row by row. Parse errors of either
+/// input are yielded as errors (and the erroneous row is not compared). In positional mode, the row it would have
+/// been compared to is then skipped as well.
+///
+/// See: `PattiCsvParser::diff_iter`
+pub struct DiffIter<'pars, 'rd, L: Read, R: Read> {
+    mode: DiffMode,
+    left: DiffSide<'pars, 'rd, L>,
+    right: DiffSide<'pars, 'rd, R>,
+    stats: DiffStats,
+}
+
+impl<'pars, 'rd, L: Read, R: Read> DiffIter<'pars, 'rd, L, R> {
+    pub fn new(
+        parser: &'pars PattiCsvParser,
+        left: &'rd mut L,
+        right: &'rd mut R,
+        mode: DiffMode,
+    ) -> Self {
+        Self {
+            mode,
+            left: DiffSide::new(parser, left, "left"),
+            right: DiffSide::new(parser, right, "right"),
+            stats: DiffStats::default(),
+        }
+    }
+
+    pub fn get_stats(&self) -> &DiffStats {
+        &self.stats
+    }
+
+    fn only_left(&mut self, (line, row): (usize, DataCellRow)) -> RowDiff {
+        self.stats.num_rows_left += 1;
+        self.stats.num_rows_only_left += 1;
+        RowDiff::OnlyLeft { line, row }
+    }
+
+    fn only_right(&mut self, (line, row): (usize, DataCellRow)) -> RowDiff {
+        self.stats.num_rows_right += 1;
+        self.stats.num_rows_only_right += 1;
+        RowDiff::OnlyRight { line, row }
+    }
+
+    /// Compares two matched rows. `None` if they are equal.
+    fn compare(
+        &mut self,
+        (left_line, left_row): (usize, DataCellRow),
+        (right_line, right_row): (usize, DataCellRow),
+    ) -> Option<RowDiff> {
+        self.stats.num_rows_left += 1;
+        self.stats.num_rows_right += 1;
+        let cells = diff_cells(&left_row, &right_row);
+        if cells.is_empty() {
+            self.stats.num_rows_equal += 1;
+            return None;
+        }
+        self.stats.num_rows_changed += 1;
+        Some(RowDiff::Changed {
+            left_line,
+            right_line,
+            cells,
+        })
+    }
+
+    fn next_positional(&mut self) -> Option<Result<RowDiff>> {
+        loop {
+            let diff = match (self.left.next_row(), self.right.next_row()) {
+                (None, None) => return None,
+                (Some(Err(e)), _) | (_, Some(Err(e))) => return Some(Err(e)),
+                (Some(Ok(l)), None) => Some(self.only_left(l)),
+                (None, Some(Ok(r))) => Some(self.only_right(r)),
+                (Some(Ok(l)), Some(Ok(r))) => self.compare(l, r),
+            };
+            if diff.is_some() {
+                return diff.map(Ok);
+            }
+        }
+    }
+
+    fn next_sorted_by_key(&mut self, key_cols: &[usize]) -> Option<Result<RowDiff>> {
+        loop {
+            if let Err(e) = self.left.fill(key_cols) {
+                return Some(Err(e));
+            }
+            if let Err(e) = self.right.fill(key_cols) {
+                return Some(Err(e));
+            }
+            let ord = match (&self.left.peeked, &self.right.peeked) {
+                (None, None) => return None,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((line, l)), Some((_, r))) => {
+                    match compare_keys(&key_of(l, key_cols), &key_of(r, key_cols), *line) {
+                        Ok(ord) => ord,
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+            };
+            let (left_peeked, right_peeked) = match ord {
+                Ordering::Less => (self.left.peeked.take(), None),
+                Ordering::Greater => (None, self.right.peeked.take()),
+                Ordering::Equal => (self.left.peeked.take(), self.right.peeked.take()),
+            };
+            let diff = match (left_peeked, right_peeked) {
+                (Some(l), Some(r)) => self.compare(l, r),
+                (Some(l), None) => Some(self.only_left(l)),
+                (None, Some(r)) => Some(self.only_right(r)),
+                (None, None) => None, // can't happen
+            };
+            if diff.is_some() {
+                return diff.map(Ok);
+            }
+        }
+    }
+}
+
+impl<'pars, 'rd, L: Read, R: Read> Iterator for DiffIter<'pars, 'rd, L, R> {
+    type Item = Result<RowDiff>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.mode.clone() {
+            DiffMode::Positional => self.next_positional(),
+            DiffMode::SortedByKey(key_cols) => self.next_sorted_by_key(&key_cols),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::value_type::ValueType;
+
+    use super::*;
+    use crate::{iterating_parser::PattiCsvParserBuilder, parser_config::TypeColumnEntry};
+
+    fn parser() -> PattiCsvParser {
+        PattiCsvParserBuilder::csv()
+            .first_data_line_is_header(true)
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn diff_positional() {
+        let parser = parser();
+        let mut left = std::io::Cursor::new("id,name\n1,foo\n2,bar\n3,baz");
+        let mut right = std::io::Cursor::new("id,name\n1,foo\n2,BAR\n3,baz\n4,new");
+
+        let mut iter = parser.diff_iter(&mut left, &mut right, DiffMode::Positional);
+        let diffs = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(
+            vec![
+                RowDiff::Changed {
+                    left_line: 3,
+                    right_line: 3,
+                    cells: vec![CellDiff {
+                        column: 1,
+                        header: String::from("name"),
+                        left: Value::String(String::from("bar")),
+                        right: Value::String(String::from("BAR")),
+                    }]
+                },
+                RowDiff::OnlyRight {
+                    line: 5,
+                    row: parser
+                        .parse_iter(&mut std::io::Cursor::new("id,name\n4,new"))
+                        .nth(1)
+                        .unwrap()
+                        .unwrap()
+                },
+            ],
+            diffs
+        );
+        assert_eq!(
+            &DiffStats {
+                num_rows_left: 3,
+                num_rows_right: 4,
+                num_rows_equal: 2,
+                num_rows_changed: 1,
+                num_rows_only_left: 0,
+                num_rows_only_right: 1,
+            },
+            iter.get_stats()
+        );
+    }
+
+    #[test]
+    fn diff_positional_without_emitted_header_row() {
+        let parser = PattiCsvParserBuilder::csv()
+            .emit_header_row(false)
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+            ])
+            .build()
+            .unwrap();
+        let mut left = std::io::Cursor::new("id,name\n1,foo\n2,bar");
+        let mut right = std::io::Cursor::new("id,name\n1,FOO\n2,bar");
+
+        let mut iter = parser.diff_iter(&mut left, &mut right, DiffMode::Positional);
+        let diffs = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(
+            vec![RowDiff::Changed {
+                left_line: 2,
+                right_line: 2,
+                cells: vec![CellDiff {
+                    column: 1,
+                    header: String::from("name"),
+                    left: Value::String(String::from("foo")),
+                    right: Value::String(String::from("FOO")),
+                }]
+            }],
+            diffs
+        );
+        assert_eq!(2, iter.get_stats().num_rows_left);
+    }
+
+    #[test]
+    fn diff_sorted_by_key() {
+        let parser = parser();
+        let mut left = std::io::Cursor::new("id,name\n1,foo\n2,bar\n4,qux\n5,x");
+        let mut right = std::io::Cursor::new("id,name\n1,foo\n3,baz\n4,QUX\n5,x\n6,y");
+
+        let mut iter = parser.diff_iter(&mut left, &mut right, DiffMode::SortedByKey(vec![0]));
+        let diffs = iter.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        let summary = diffs
+            .iter()
+            .map(|d| match d {
+                RowDiff::OnlyLeft { line, .. } => format!("-{}", line),
+                RowDiff::OnlyRight { line, .. } => format!("+{}", line),
+                RowDiff::Changed {
+                    left_line, cells, ..
+                } => format!("~{}:{}", left_line, cells[0].header),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["-3", "+3", "~4:name", "+6"], summary);
+        assert_eq!(2, iter.get_stats().num_rows_equal);
+        assert_eq!(4, iter.get_stats().num_rows_left);
+        assert_eq!(5, iter.get_stats().num_rows_right);
+    }
+
+    #[test]
+    fn diff_sorted_by_key_unsorted_input() {
+        let parser = parser();
+        let mut left = std::io::Cursor::new("id,name\n2,foo\n1,bar");
+        let mut right = std::io::Cursor::new("id,name\n1,bar\n2,foo");
+
+        let res = parser
+            .diff_iter(&mut left, &mut right, DiffMode::SortedByKey(vec![0]))
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            res.last(),
+            Some(Err(PattiCsvError::Generic { msg })) if msg.starts_with("[ERROR_ON_DIFF] The left input is not sorted")
+        ));
+    }
+
+    #[test]
+    fn diff_with_parse_errors() {
+        let parser = parser();
+        let mut left = std::io::Cursor::new("id,name\nx,foo\n2,bar");
+        let mut right = std::io::Cursor::new("id,name\n1,foo\n2,bar");
+
+        let res = parser
+            .diff_iter(&mut left, &mut right, DiffMode::Positional)
+            .collect::<Vec<_>>();
+        assert_eq!(1, res.len());
+        assert!(res[0].is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Helpers to unit test transitizer pipelines and skip filters, e.g. of custom configs.
+//!
+//! ```
+//! use patti_csv::assert_transitizes;
+//! use patti_csv::parser_config::VecOfTokenTransitizers;
+//! use patti_csv::transform_sanitize_token::{ToUppercase, TrimAll};
+//!
+//! let pipeline: VecOfTokenTransitizers = vec![Box::new(TrimAll), Box::new(ToUppercase)];
+//! assert_transitizes!(pipeline, " foo " => "FOO", "bar" => "BAR");
+//! ```
+
+use crate::{
+    errors::Result, parser_config::VecOfTokenTransitizers, skip_take_lines::SkipTakeLines,
+};
+
+/// Applies all transitizers of the pipeline to the token, in order.
+pub fn transitize_all(pipeline: &VecOfTokenTransitizers, token: &str) -> Result<String> {
+    pipeline
+        .iter()
+        .try_fold(String::from(token), |acc, t| t.transitize(&acc))
+}
+
+/// For every line, whether the filter wants to skip it. Line numbers start at 1, like in the tokenizer.
+pub fn skip_mask<S: SkipTakeLines + ?Sized>(filter: &S, lines: &[&str]) -> Vec<bool> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| filter.skip(i + 1, line))
+        .collect()
+}
+
+/// The line numbers (starting at 1) of all lines, that are skipped by any of the filters.
+pub fn skipped_line_nums(
+    filters: &[Box<dyn SkipTakeLines + Send + Sync>],
+    lines: &[&str],
+) -> Vec<usize> {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(i, line)| filters.iter().any(|f| f.skip(i + 1, line)))
+        .map(|(i, _)| i + 1)
+        .collect()
+}
+
+/// Asserts, that a transitizer pipeline (`VecOfTokenTransitizers`) turns every input into the expected output.
+#[macro_export]
+macro_rules! assert_transitizes {
+    ($pipeline:expr, $($input:expr => $expected:expr),+ $(,)?) => {
+        $(
+            match $crate::testing::transitize_all(&$pipeline, $input) {
+                Ok(res) => assert_eq!(
+                    $expected, res,
+                    "transitizing {:?} gave {:?}, expected {:?}", $input, res, $expected
+                ),
+                Err(e) => panic!("transitizing {:?} failed: {}", $input, e),
+            }
+        )+
+    };
+}
+
+/// Asserts, that a transitizer pipeline (`VecOfTokenTransitizers`) fails on every input.
+#[macro_export]
+macro_rules! assert_transitize_fails {
+    ($pipeline:expr, $($input:expr),+ $(,)?) => {
+        $(
+            if let Ok(res) = $crate::testing::transitize_all(&$pipeline, $input) {
+                panic!("transitizing {:?} should fail, but gave {:?}", $input, res);
+            }
+        )+
+    };
+}
+
+/// Asserts, that exactly the given line numbers (starting at 1) of the lines are skipped by the filters.
+#[macro_export]
+macro_rules! assert_skips {
+    ($filters:expr, $lines:expr => [$($line_num:expr),* $(,)?]) => {
+        assert_eq!(
+            { let expected: Vec<usize> = vec![$($line_num),*]; expected },
+            $crate::testing::skipped_line_nums(&$filters, &$lines),
+            "skipped line numbers differ"
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        skip_take_lines::{SkipEmptyLines, SkipLinesFromStart, SkipLinesStartingWith},
+        transform_sanitize_token::{RegexTake, ReplaceWith, ToUppercase, TrimAll},
+    };
+
+    use super::*;
+
+    #[test]
+    fn transitize_pipeline_in_order() {
+        let pipeline: VecOfTokenTransitizers = vec![
+            Box::new(TrimAll),
+            Box::new(ReplaceWith::new("o", "u")),
+            Box::new(ToUppercase),
+        ];
+        assert_transitizes!(pipeline, " foo " => "FUU", "bar" => "BAR");
+        assert_transitizes!(Vec::new(), " foo " => " foo ");
+    }
+
+    #[test]
+    fn transitize_pipeline_fails() {
+        let pipeline: VecOfTokenTransitizers =
+            vec![Box::new(RegexTake::new("(\\d+\\.\\d+).*").unwrap())];
+        assert_transitize_fails!(pipeline, "10 (CHF)", "");
+        assert_transitizes!(pipeline, "10.00 (CHF)" => "10.00");
+    }
+
+    #[test]
+    #[should_panic(expected = "transitizing \"x\" gave \"X\", expected \"x\"")]
+    fn transitize_pipeline_mismatch() {
+        let pipeline: VecOfTokenTransitizers = vec![Box::new(ToUppercase)];
+        assert_transitizes!(pipeline, "x" => "x");
+    }
+
+    #[test]
+    fn skip_filters_over_lines() {
+        let lines = ["header\n", "# comment\n", "\n", "data\n"];
+        let filters: Vec<Box<dyn SkipTakeLines + Send + Sync>> = vec![
+            Box::new(SkipLinesStartingWith::new("#")),
+            Box::new(SkipEmptyLines::new()),
+        ];
+        assert_skips!(filters, lines => [2, 3]);
+        assert_skips!(Vec::new(), lines => []);
+    }
+
+    #[test]
+    fn skip_mask_over_lines() {
+        let lines = ["header\n", "# comment\n", "\n", "data\n"];
+        assert_eq!(
+            vec![true, false, false, false],
+            skip_mask(&SkipLinesFromStart::new(1), &lines)
+        );
+        assert_eq!(
+            vec![false, true, false, false],
+            skip_mask(&SkipLinesStartingWith::new("#"), &lines)
+        );
+        // line numbers start at 1
+        assert_eq!(
+            vec![true, true, true, false],
+            skip_mask(&SkipLinesFromStart::new(3), &lines)
+        );
+        // also for trait objects
+        let filter: Box<dyn SkipTakeLines + Send + Sync> = Box::new(SkipEmptyLines::new());
+        assert_eq!(
+            vec![false, false, true, false],
+            skip_mask(filter.as_ref(), &lines)
+        );
+        assert!(skip_mask(&SkipEmptyLines::new(), &[]).is_empty());
+    }
+}
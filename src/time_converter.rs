@@ -0,0 +1,350 @@
+use chrono::{NaiveTime, Timelike};
+use venum::value::Value;
+
+use crate::{
+    cell_converter::CellConverter,
+    errors::{PattiCsvError, Result},
+    parser_config::TypeColumnEntry,
+    report::ComponentInfo,
+};
+
+/// The unit of the integer values, time-of-day and duration columns are converted into. (venum has no dedicated
+/// value types for these, but plain integers work with everything downstream, e.g. sorting, aggregating, arrow.)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeUnit {
+    #[default]
+    Seconds,
+    Millis,
+}
+
+impl TimeUnit {
+    /// Helper method. The stable name, see: `ComponentInfo`
+    fn name(&self) -> &'static str {
+        match self {
+            TimeUnit::Seconds => "seconds",
+            TimeUnit::Millis => "millis",
+        }
+    }
+}
+
+/// Converts time-of-day tokens (e.g. `13:45:00`) into the seconds (or millis) since midnight, as `Value::UInt32`.
+///
+/// Uses the `chrono_pattern` of the column typing, if there is one. Otherwise `%H:%M:%S%.f` and `%H:%M` are tried.
+#[derive(Debug, Default)]
+pub struct TimeOfDayCellConverter {
+    unit: TimeUnit,
+}
+impl TimeOfDayCellConverter {
+    pub fn new(unit: TimeUnit) -> Self {
+        Self { unit }
+    }
+}
+impl CellConverter for TimeOfDayCellConverter {
+    fn convert(&self, token: String, column_typing: &TypeColumnEntry) -> Result<Value> {
+        let time = match column_typing.chrono_pattern.as_deref() {
+            Some(pattern) => NaiveTime::parse_from_str(&token, pattern).ok(),
+            None => NaiveTime::parse_from_str(&token, "%H:%M:%S%.f")
+                .or_else(|_| NaiveTime::parse_from_str(&token, "%H:%M"))
+                .ok(),
+        }
+        .ok_or_else(|| PattiCsvError::Generic {
+            msg: format!(
+                "[ERROR_ON_TIME_OF_DAY] Cannot parse time of day: '{}'",
+                token
+            ),
+        })?;
+
+        let secs = time.num_seconds_from_midnight();
+        Ok(Value::UInt32(match self.unit {
+            TimeUnit::Seconds => secs,
+            // Leap seconds are represented with nanos >= 1s, so we cap them.
+            TimeUnit::Millis => secs * 1000 + (time.nanosecond() / 1_000_000).min(999),
+        }))
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "timeOfDay"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("unit", self.unit.name())
+    }
+}
+
+/// Converts duration tokens into the (signed) number of seconds (or millis), as `Value::Int64`. Fractional seconds
+/// are truncated, if the unit is `Seconds`. Supported formats are:
+/// - ISO 8601 durations with weeks, days, hours, minutes and seconds, e.g. `PT1H30M`, `P1DT2H`, `PT0.5S`. Years and
+///   months are rejected, because their length depends on the calendar.
+/// - Clock-style durations, e.g. `01:30:00`, `90:15` or `1:30:00.250`. (Hours may exceed 24.)
+///
+/// Both may have a leading `-`.
+#[derive(Debug, Default)]
+pub struct DurationCellConverter {
+    unit: TimeUnit,
+}
+impl DurationCellConverter {
+    pub fn new(unit: TimeUnit) -> Self {
+        Self { unit }
+    }
+}
+impl CellConverter for DurationCellConverter {
+    fn convert(&self, token: String, _column_typing: &TypeColumnEntry) -> Result<Value> {
+        let millis = parse_duration_millis(&token).ok_or_else(|| PattiCsvError::Generic {
+            msg: format!("[ERROR_ON_DURATION] Cannot parse duration: '{}'", token),
+        })?;
+        Ok(Value::Int64(match self.unit {
+            TimeUnit::Seconds => millis / 1000,
+            TimeUnit::Millis => millis,
+        }))
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "duration"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("unit", self.unit.name())
+    }
+}
+
+/// Parses a duration (see: `DurationCellConverter`) into millis. `None` if it's malformed or overflows.
+pub fn parse_duration_millis(s: &str) -> Option<i64> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let millis = match s.strip_prefix('P') {
+        Some(iso) => parse_iso8601_duration_millis(iso)?,
+        None => parse_clock_duration_millis(s)?,
+    };
+    Some(if negative { -millis } else { millis })
+}
+
+/// Parses whole seconds with an optional fraction (`.` or `,`, as allowed by ISO 8601) into millis.
+fn parse_seconds_millis(s: &str) -> Option<i64> {
+    let (whole, frac) = match s.find(['.', ',']) {
+        Some(pos) => (&s[..pos], &s[pos + 1..]),
+        None => (s, ""),
+    };
+    if whole.is_empty() || !whole.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if !frac.bytes().all(|b| b.is_ascii_digit()) || (s.len() > whole.len() && frac.is_empty()) {
+        return None;
+    }
+    // Only millisecond precision, further digits are truncated.
+    let frac_millis = frac
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(3)
+        .fold(0, |acc, b| acc * 10 + i64::from(b - b'0'));
+    whole
+        .parse::<i64>()
+        .ok()?
+        .checked_mul(1000)?
+        .checked_add(frac_millis)
+}
+
+/// `[n]W[n]D[T[n]H[n]M[n]S]`, i.e. everything after the `P`.
+fn parse_iso8601_duration_millis(s: &str) -> Option<i64> {
+    const UNITS: [(char, i64); 5] = [
+        ('W', 7 * 24 * 3_600_000),
+        ('D', 24 * 3_600_000),
+        ('H', 3_600_000),
+        ('M', 60_000),
+        ('S', 1000),
+    ];
+    let (date_part, time_part) = match s.split_once('T') {
+        Some((_, "")) => return None,
+        Some((d, t)) => (d, Some(t)),
+        None => (s, None),
+    };
+    if date_part.is_empty() && time_part.is_none() {
+        return None;
+    }
+
+    let mut total: i64 = 0;
+    // The units have to be in order, and every one at most once. Date units are W and D, time units H, M and S.
+    for (part, allowed) in [
+        (date_part, &UNITS[..2]),
+        (time_part.unwrap_or(""), &UNITS[2..]),
+    ] {
+        let mut next_unit_idx = 0;
+        let mut num_start = 0;
+        for (pos, c) in part.char_indices() {
+            if c.is_ascii_digit() || c == '.' || c == ',' {
+                continue;
+            }
+            let unit_idx = allowed.iter().position(|(u, _)| *u == c)?;
+            if unit_idx < next_unit_idx {
+                return None;
+            }
+            let num = &part[num_start..pos];
+            let millis = if c == 'S' {
+                parse_seconds_millis(num)?
+            } else {
+                if num.is_empty() || !num.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                num.parse::<i64>().ok()?.checked_mul(allowed[unit_idx].1)?
+            };
+            total = total.checked_add(millis)?;
+            next_unit_idx = unit_idx + 1;
+            num_start = pos + 1;
+        }
+        if num_start != part.len() {
+            return None; // a trailing number without unit
+        }
+    }
+    Some(total)
+}
+
+/// `H:MM` or `H:MM:SS[.fff]`, where the hours may have any number of digits.
+fn parse_clock_duration_millis(s: &str) -> Option<i64> {
+    let parts: Vec<&str> = s.split(':').collect();
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m] => (*h, *m, None),
+        [h, m, s] => (*h, *m, Some(*s)),
+        _ => return None,
+    };
+    if hours.is_empty() || !hours.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let minutes = match minutes.parse::<i64>() {
+        Ok(m) if minutes.len() == 2 && minutes.bytes().all(|b| b.is_ascii_digit()) && m < 60 => m,
+        _ => return None,
+    };
+    let seconds_millis = match seconds {
+        None => 0,
+        Some(sec) => match parse_seconds_millis(sec)? {
+            millis if sec.find(['.', ',']).unwrap_or(sec.len()) == 2 && millis < 60_000 => millis,
+            _ => return None,
+        },
+    };
+    hours
+        .parse::<i64>()
+        .ok()?
+        .checked_mul(3_600_000)?
+        .checked_add(minutes * 60_000 + seconds_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use venum::value_type::ValueType;
+
+    use super::*;
+
+    #[test]
+    fn parse_iso8601_durations() {
+        assert_eq!(Some(5_400_000), parse_duration_millis("PT1H30M"));
+        assert_eq!(Some(93_600_000), parse_duration_millis("P1DT2H"));
+        assert_eq!(Some(604_800_000), parse_duration_millis("P1W"));
+        assert_eq!(Some(500), parse_duration_millis("PT0.5S"));
+        assert_eq!(Some(1_250), parse_duration_millis("PT1,25S"));
+        assert_eq!(Some(-90_000), parse_duration_millis("-PT1M30S"));
+        assert_eq!(Some(0), parse_duration_millis("PT0S"));
+    }
+
+    #[test]
+    fn parse_iso8601_durations_err() {
+        for s in [
+            "P",
+            "PT",
+            "P1Y",
+            "P1M",
+            "PT1H1H",
+            "PT1M1H",
+            "P1H",
+            "PT1",
+            "PTH",
+            "PT1.5H",
+            "PT.5S",
+            "PT1.S",
+            "P1DT",
+            "pt1h",
+            "P9999999999999999W",
+        ] {
+            assert_eq!(None, parse_duration_millis(s), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_clock_durations() {
+        assert_eq!(Some(5_400_000), parse_duration_millis("01:30:00"));
+        assert_eq!(Some(324_900_000), parse_duration_millis("90:15"));
+        assert_eq!(Some(5_400_250), parse_duration_millis("1:30:00.250"));
+        assert_eq!(Some(-60_000), parse_duration_millis("-0:01"));
+    }
+
+    #[test]
+    fn parse_clock_durations_err() {
+        for s in [
+            "",
+            "1",
+            "1:3",
+            "1:60",
+            "1:30:60",
+            "1:30:5",
+            "1:30:00:00",
+            ":30",
+            "a:30",
+            "1:30:00.",
+        ] {
+            assert_eq!(None, parse_duration_millis(s), "{}", s);
+        }
+    }
+
+    #[test]
+    fn duration_cell_converter() {
+        let tce = TypeColumnEntry::new(None, ValueType::Int64);
+        assert_eq!(
+            Value::Int64(5400),
+            DurationCellConverter::default()
+                .convert(String::from("PT1H30M"), &tce)
+                .unwrap()
+        );
+        assert_eq!(
+            Value::Int64(1500),
+            DurationCellConverter::new(TimeUnit::Millis)
+                .convert(String::from("PT1.5S"), &tce)
+                .unwrap()
+        );
+        assert!(DurationCellConverter::default()
+            .convert(String::from("1h30"), &tce)
+            .is_err());
+    }
+
+    #[test]
+    fn time_of_day_cell_converter() {
+        let tce = TypeColumnEntry::new(None, ValueType::UInt32);
+        let conv = TimeOfDayCellConverter::default();
+        assert_eq!(
+            Value::UInt32(49_500),
+            conv.convert(String::from("13:45:00"), &tce).unwrap()
+        );
+        assert_eq!(
+            Value::UInt32(49_500),
+            conv.convert(String::from("13:45"), &tce).unwrap()
+        );
+        assert_eq!(
+            Value::UInt32(49_500_500),
+            TimeOfDayCellConverter::new(TimeUnit::Millis)
+                .convert(String::from("13:45:00.5"), &tce)
+                .unwrap()
+        );
+        assert!(conv.convert(String::from("25:00"), &tce).is_err());
+    }
+
+    #[test]
+    fn time_of_day_cell_converter_with_pattern() {
+        let tce = TypeColumnEntry::new_with_chrono_pattern(None, ValueType::UInt32, "%I:%M %p");
+        let conv = TimeOfDayCellConverter::default();
+        assert_eq!(
+            Value::UInt32(49_500),
+            conv.convert(String::from("01:45 PM"), &tce).unwrap()
+        );
+        assert!(conv.convert(String::from("13:45:00"), &tce).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use compact_str::CompactString;
+
+/// The storage used for the tokens of a line, while tokenizing it. Every token is converted into a `String` in the
+/// end, so this is only about the memory layout (and thusly speed) while tokenizing.
+///
+/// Which type is used, is decided through cargo features (see: `TokenBuf`).
+pub trait TokenStorage {
+    /// A new, empty token. `inline_capacity` is the number of bytes that can be stored without a heap allocation, for
+    /// implementations that support this.
+    fn new_token(inline_capacity: usize) -> Self;
+    fn push_char(&mut self, c: char);
+    fn as_token_str(&self) -> &str;
+    fn truncate_token(&mut self, new_len: usize);
+}
+
+impl TokenStorage for CompactString {
+    fn new_token(inline_capacity: usize) -> Self {
+        CompactString::with_capacity(inline_capacity)
+    }
+    fn push_char(&mut self, c: char) {
+        self.push(c)
+    }
+    fn as_token_str(&self) -> &str {
+        self.as_str()
+    }
+    fn truncate_token(&mut self, new_len: usize) {
+        self.truncate(new_len)
+    }
+}
+
+impl TokenStorage for String {
+    fn new_token(_inline_capacity: usize) -> Self {
+        String::new() // Doesn't allocate (yet)
+    }
+    fn push_char(&mut self, c: char) {
+        self.push(c)
+    }
+    fn as_token_str(&self) -> &str {
+        self.as_str()
+    }
+    fn truncate_token(&mut self, new_len: usize) {
+        self.truncate(new_len)
+    }
+}
+
+#[cfg(feature = "token_smartstring")]
+impl TokenStorage for smartstring::alias::String {
+    fn new_token(_inline_capacity: usize) -> Self {
+        smartstring::alias::String::new()
+    }
+    fn push_char(&mut self, c: char) {
+        self.push(c)
+    }
+    fn as_token_str(&self) -> &str {
+        self.as_str()
+    }
+    fn truncate_token(&mut self, new_len: usize) {
+        self.truncate(new_len)
+    }
+}
+
+/// The token storage that is actually used. Benchmarks showed `CompactString` to be the fastest for typical files,
+/// so that's the default. With the feature `token_string` it's a plain `String`, with `token_smartstring` it's a
+/// `SmartString`. (If both are enabled, `token_string` wins.)
+#[cfg(feature = "token_string")]
+pub type TokenBuf = String;
+#[cfg(all(feature = "token_smartstring", not(feature = "token_string")))]
+pub type TokenBuf = smartstring::alias::String;
+#[cfg(not(any(feature = "token_string", feature = "token_smartstring")))]
+pub type TokenBuf = CompactString;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_and_truncate<T: TokenStorage>() {
+        let mut t = T::new_token(24);
+        "foo bar ".chars().for_each(|c| t.push_char(c));
+        assert_eq!("foo bar ", t.as_token_str());
+        t.truncate_token(3);
+        assert_eq!("foo", t.as_token_str());
+    }
+
+    #[test]
+    fn token_storage_compact_string() {
+        push_and_truncate::<CompactString>();
+    }
+
+    #[test]
+    fn token_storage_string() {
+        push_and_truncate::<String>();
+    }
+
+    #[cfg(feature = "token_smartstring")]
+    #[test]
+    fn token_storage_smartstring() {
+        push_and_truncate::<smartstring::alias::String>();
+    }
+}
@@ -1,61 +1,201 @@
-use regex::Regex;
+use regex::{NoExpand, Regex, RegexBuilder};
 use std::fmt::Debug;
+#[cfg(feature = "unicode")]
+use unicode_normalization::UnicodeNormalization;
+#[cfg(feature = "unicode")]
+use unicode_segmentation::UnicodeSegmentation;
 
-use crate::errors::{PattiCsvError, Result, SanitizeError};
+use venum::value_type::ValueType;
+
+use crate::{
+    errors::{PattiCsvError, Result, SanitizeError},
+    parser_config::VecOfTokenTransitizers,
+    report::ComponentInfo,
+};
 
 pub trait TransformSanitizeToken: Debug {
     fn transitize(&self, input_token: &str) -> Result<String>;
     fn get_self_info(&self) -> String {
         String::from("n/a")
     }
+    /// The stable id of this kind of transitizer. See: `ComponentInfo`
+    fn kind(&self) -> &'static str {
+        "custom"
+    }
+    /// The kind and the parameters, e.g. for reports.
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind())
+    }
+}
+
+/// How a literal (of `ReplaceWith` or `Eradicate`) is matched. The default is: case-sensitive, anywhere in the token, and
+/// all occurrences.
+#[derive(Debug, Clone, Default)]
+pub struct LiteralMatch {
+    pub ignore_case: bool,
+    /// Only replace, if the whole token is the literal (e.g. "N/A", but not "N/A or so").
+    pub whole_token: bool,
+    /// Only the first n occurrences. None means all of them.
+    pub max_count: Option<usize>,
+}
+
+impl LiteralMatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    pub fn whole_token(mut self) -> Self {
+        self.whole_token = true;
+        self
+    }
+
+    pub fn max_count(mut self, max_count: usize) -> Self {
+        self.max_count = Some(max_count);
+        self
+    }
+
+    /// Only needed for case-insensitive or whole-token matching. Plain matching is done on the strings directly.
+    fn to_regex(&self, literal: &str) -> Option<Regex> {
+        if !self.ignore_case && !self.whole_token {
+            return None;
+        }
+        let mut pattern = regex::escape(literal);
+        if self.whole_token {
+            pattern = format!("^{}$", pattern);
+        }
+        RegexBuilder::new(&pattern)
+            .case_insensitive(self.ignore_case)
+            .build()
+            .ok() // can't fail, it's an escaped literal
+    }
+}
+
+/// Helper. A literal with the (compiled) way to match it.
+#[derive(Debug)]
+struct Literal {
+    literal: String,
+    match_opts: LiteralMatch,
+    regex: Option<Regex>,
+}
+
+impl Literal {
+    fn new(literal: String, match_opts: LiteralMatch) -> Self {
+        let regex = match_opts.to_regex(&literal);
+        Self {
+            literal,
+            match_opts,
+            regex,
+        }
+    }
+
+    /// The literal (under `key`) and the match options, that aren't the default.
+    fn describe(&self, info: ComponentInfo, key: &str) -> ComponentInfo {
+        let mut info = info.with_param(key, &self.literal);
+        if self.match_opts.ignore_case {
+            info = info.with_param("ignoreCase", true);
+        }
+        if self.match_opts.whole_token {
+            info = info.with_param("wholeToken", true);
+        }
+        match self.match_opts.max_count {
+            Some(n) => info.with_param("maxCount", n),
+            None => info,
+        }
+    }
+
+    fn replace(&self, token: &str, to: &str) -> String {
+        // For the regex, 0 means all occurrences
+        match (&self.regex, self.match_opts.max_count) {
+            (_, Some(0)) => String::from(token),
+            (Some(re), n) => re
+                .replacen(token, n.unwrap_or(0), NoExpand(to))
+                .into_owned(),
+            (None, Some(n)) => token.replacen(self.literal.as_str(), to, n),
+            (None, None) => token.replace(self.literal.as_str(), to),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct ReplaceWith {
-    from: String,
+    from: Literal,
     to: String,
 }
 impl ReplaceWith {
     pub fn new<T>(from: T, to: T) -> Self
+    where
+        T: Into<String> + Debug,
+    {
+        Self::new_with_match(from, to, LiteralMatch::default())
+    }
+
+    pub fn new_with_match<T>(from: T, to: T, match_opts: LiteralMatch) -> Self
     where
         T: Into<String> + Debug,
     {
         Self {
-            from: from.into(),
+            from: Literal::new(from.into(), match_opts),
             to: to.into(),
         }
     }
 }
 impl TransformSanitizeToken for ReplaceWith {
     fn transitize(&self, input_token: &str) -> Result<String> {
-        Ok(input_token.replace(self.from.as_str(), self.to.as_str()))
+        Ok(self.from.replace(input_token, self.to.as_str()))
     }
     fn get_self_info(&self) -> String {
         format!("{:?}", self)
     }
+    fn kind(&self) -> &'static str {
+        "replace"
+    }
+    fn describe(&self) -> ComponentInfo {
+        self.from
+            .describe(ComponentInfo::new(self.kind()), "from")
+            .with_param("to", &self.to)
+    }
 }
 
 #[derive(Debug)]
 pub struct Eradicate {
-    eradicate: String,
+    eradicate: Literal,
 }
 impl Eradicate {
     pub fn new<T>(eradicate: T) -> Self
+    where
+        T: Into<String> + Debug,
+    {
+        Self::new_with_match(eradicate, LiteralMatch::default())
+    }
+
+    pub fn new_with_match<T>(eradicate: T, match_opts: LiteralMatch) -> Self
     where
         T: Into<String> + Debug,
     {
         Self {
-            eradicate: eradicate.into(),
+            eradicate: Literal::new(eradicate.into(), match_opts),
         }
     }
 }
 impl TransformSanitizeToken for Eradicate {
     fn transitize(&self, input_token: &str) -> Result<String> {
-        Ok(input_token.replace(self.eradicate.as_str(), ""))
+        Ok(self.eradicate.replace(input_token, ""))
     }
     fn get_self_info(&self) -> String {
         format!("{:?}", self)
     }
+    fn kind(&self) -> &'static str {
+        "eradicate"
+    }
+    fn describe(&self) -> ComponentInfo {
+        self.eradicate
+            .describe(ComponentInfo::new(self.kind()), "literal")
+    }
 }
 
 #[derive(Debug)]
@@ -72,6 +212,9 @@ impl TransformSanitizeToken for ToLowercase {
     fn get_self_info(&self) -> String {
         format!("{:?}", self)
     }
+    fn kind(&self) -> &'static str {
+        "toLowercase"
+    }
 }
 
 #[derive(Debug)]
@@ -88,6 +231,79 @@ impl TransformSanitizeToken for ToUppercase {
     fn get_self_info(&self) -> String {
         format!("{:?}", self)
     }
+    fn kind(&self) -> &'static str {
+        "toUppercase"
+    }
+}
+
+/// Normalizes the token to a Unicode normal form, e.g. so that "é" is the same, whether it's one char or an "e" with
+/// a combining accent. NFKC also replaces compatibility chars, e.g. a no-break space by a space, or "ﬁ" by "fi".
+/// Needs the feature `unicode`.
+#[cfg(feature = "unicode")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizeUnicode {
+    Nfc,
+    Nfkc,
+}
+#[cfg(feature = "unicode")]
+impl TransformSanitizeToken for NormalizeUnicode {
+    fn transitize(&self, input_token: &str) -> Result<String> {
+        match self {
+            NormalizeUnicode::Nfc => Ok(input_token.nfc().collect()),
+            NormalizeUnicode::Nfkc => Ok(input_token.nfkc().collect()),
+        }
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "normalizeUnicode"
+    }
+    fn describe(&self) -> ComponentInfo {
+        let form = match self {
+            NormalizeUnicode::Nfc => "nfc",
+            NormalizeUnicode::Nfkc => "nfkc",
+        };
+        ComponentInfo::new(self.kind()).with_param("form", form)
+    }
+}
+
+/// Removes the chars, that are invisible, but break typing, e.g. "12\u{200B}3" (with a zero-width space) is no
+/// number: control chars (except for tabs and line breaks), zero-width chars (incl. the zero-width joiner of emoji
+/// sequences), BOMs, soft hyphens and bidi marks.
+#[derive(Debug, Default)]
+pub struct StripControlChars;
+impl StripControlChars {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn is_stripped(c: char) -> bool {
+        (c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+            || matches!(
+                c,
+                '\u{00AD}'
+                    | '\u{200B}'..='\u{200F}'
+                    | '\u{202A}'..='\u{202E}'
+                    | '\u{2060}'..='\u{2064}'
+                    | '\u{2066}'..='\u{206F}'
+                    | '\u{FEFF}'
+            )
+    }
+}
+impl TransformSanitizeToken for StripControlChars {
+    fn transitize(&self, input_token: &str) -> Result<String> {
+        Ok(input_token
+            .chars()
+            .filter(|c| !Self::is_stripped(*c))
+            .collect())
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "stripControlChars"
+    }
 }
 
 #[derive(Debug)]
@@ -104,6 +320,9 @@ impl TransformSanitizeToken for TrimLeading {
     fn get_self_info(&self) -> String {
         format!("{:?}", self)
     }
+    fn kind(&self) -> &'static str {
+        "trimLeading"
+    }
 }
 
 #[derive(Debug)]
@@ -120,6 +339,9 @@ impl TransformSanitizeToken for TrimTrailing {
     fn get_self_info(&self) -> String {
         format!("{:?}", self)
     }
+    fn kind(&self) -> &'static str {
+        "trimTrailing"
+    }
 }
 
 #[derive(Debug)]
@@ -136,6 +358,9 @@ impl TransformSanitizeToken for TrimAll {
     fn get_self_info(&self) -> String {
         format!("{:?}", self)
     }
+    fn kind(&self) -> &'static str {
+        "trimAll"
+    }
 }
 
 #[derive(Debug)]
@@ -177,12 +402,471 @@ impl TransformSanitizeToken for RegexTake {
     fn get_self_info(&self) -> String {
         format!("{:?}", self)
     }
+    fn kind(&self) -> &'static str {
+        "regexTake"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("regex", &self.regex)
+    }
+}
+
+/// What `Substring` counts in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextUnit {
+    /// Unicode scalar values, i.e. `char`s. A combining mark (e.g. the breve of "y̆") is a char of its own.
+    #[default]
+    Chars,
+    /// Extended grapheme clusters, i.e. what a user perceives as one character (e.g. "y̆", or a family emoji). Needs
+    /// the feature `unicode`.
+    #[cfg(feature = "unicode")]
+    Graphemes,
+}
+
+/// Takes (at most) `len` chars (or graphemes, see: `TextUnit`) of the token, starting at `start` (0-based). Without a
+/// `len`, everything from `start` on is taken. A token that is too short gives what there is, i.e. possibly an empty
+/// token. Never cuts a char (or grapheme) in half.
+#[derive(Debug)]
+pub struct Substring {
+    start: usize,
+    len: Option<usize>,
+    unit: TextUnit,
+}
+impl Substring {
+    pub fn new(start: usize, len: Option<usize>) -> Self {
+        Self {
+            start,
+            len,
+            unit: TextUnit::Chars,
+        }
+    }
+
+    #[cfg(feature = "unicode")]
+    pub fn graphemes(mut self) -> Self {
+        self.unit = TextUnit::Graphemes;
+        self
+    }
+
+    /// The byte range of the substring. `boundaries` are the byte offsets of the chars (or graphemes).
+    fn byte_range<I>(&self, token: &str, mut boundaries: I) -> (usize, usize)
+    where
+        I: Iterator<Item = usize>,
+    {
+        let from = match boundaries.nth(self.start) {
+            Some(from) => from,
+            None => return (token.len(), token.len()),
+        };
+        let to = match self.len {
+            Some(0) => from,
+            // The boundary after the last unit we take, i.e. the start of the next one
+            Some(len) => boundaries.nth(len - 1).unwrap_or(token.len()),
+            None => token.len(),
+        };
+        (from, to)
+    }
+}
+impl TransformSanitizeToken for Substring {
+    fn transitize(&self, input_token: &str) -> Result<String> {
+        let (from, to) = match self.unit {
+            TextUnit::Chars => {
+                self.byte_range(input_token, input_token.char_indices().map(|(i, _)| i))
+            }
+            #[cfg(feature = "unicode")]
+            TextUnit::Graphemes => self.byte_range(
+                input_token,
+                input_token.grapheme_indices(true).map(|(i, _)| i),
+            ),
+        };
+        Ok(String::from(&input_token[from..to]))
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "substring"
+    }
+    fn describe(&self) -> ComponentInfo {
+        let info = ComponentInfo::new(self.kind())
+            .with_param("start", self.start)
+            .with_param("graphemes", self.unit != TextUnit::Chars);
+        match self.len {
+            Some(len) => info.with_param("len", len),
+            None => info,
+        }
+    }
+}
+
+/// Drops the whole row, if the token is one of the given tokens (e.g. "VOID"). The token is compared as is, i.e. after
+/// the transitizers that come before this one.
+#[derive(Debug)]
+pub struct DropRowOn {
+    tokens: Vec<String>,
+}
+impl DropRowOn {
+    pub fn new<T>(tokens: Vec<T>) -> Self
+    where
+        T: Into<String> + Debug,
+    {
+        Self {
+            tokens: tokens.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+impl TransformSanitizeToken for DropRowOn {
+    fn transitize(&self, input_token: &str) -> Result<String> {
+        if self.tokens.iter().any(|t| t == input_token) {
+            return Err(PattiCsvError::DropRow {
+                reason: format!("token: {:?}", input_token),
+            });
+        }
+        Ok(String::from(input_token))
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "dropRow"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("tokens", format!("{:?}", self.tokens))
+    }
+}
+
+/// When a `ConditionalTransitizer` applies its transitizers.
+pub enum TokenCondition {
+    /// The regex matches (anywhere in) the token, i.e. anchor it (`^...$`) to match the whole token.
+    Regex(Regex),
+    Predicate(Box<dyn Fn(&str) -> bool + Send + Sync>),
+}
+impl Debug for TokenCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenCondition::Regex(regex) => f.debug_tuple("Regex").field(regex).finish(),
+            TokenCondition::Predicate(_) => f.write_str("Predicate"),
+        }
+    }
+}
+
+/// Applies the transitizers (in order) only to tokens, that match the condition, e.g. only uppercase tokens that look
+/// like ISO country codes. All other tokens are left as they are.
+#[derive(Debug)]
+pub struct ConditionalTransitizer {
+    condition: TokenCondition,
+    then: VecOfTokenTransitizers,
+}
+impl ConditionalTransitizer {
+    pub fn new(condition: TokenCondition, then: VecOfTokenTransitizers) -> Self {
+        Self { condition, then }
+    }
+
+    pub fn when_regex<T>(regex_pattern: T, then: VecOfTokenTransitizers) -> Result<Self>
+    where
+        T: AsRef<str> + Debug,
+    {
+        let re = Regex::new(regex_pattern.as_ref()).map_err(|e| {
+            PattiCsvError::Sanitize(SanitizeError::minim(
+                format!("{}", e),
+                "ERROR_ON_REGEX_COMPILE".into(),
+            ))
+        })?;
+        Ok(Self::new(TokenCondition::Regex(re), then))
+    }
+
+    pub fn when<F>(predicate: F, then: VecOfTokenTransitizers) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        Self::new(TokenCondition::Predicate(Box::new(predicate)), then)
+    }
+
+    fn matches(&self, token: &str) -> bool {
+        match &self.condition {
+            TokenCondition::Regex(regex) => regex.is_match(token),
+            TokenCondition::Predicate(predicate) => predicate(token),
+        }
+    }
+}
+impl TransformSanitizeToken for ConditionalTransitizer {
+    fn transitize(&self, input_token: &str) -> Result<String> {
+        if !self.matches(input_token) {
+            return Ok(String::from(input_token));
+        }
+        self.then
+            .iter()
+            .try_fold(String::from(input_token), |token, t| t.transitize(&token))
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "when"
+    }
+    fn describe(&self) -> ComponentInfo {
+        let info = match &self.condition {
+            TokenCondition::Regex(regex) => {
+                ComponentInfo::new(self.kind()).with_param("match", regex)
+            }
+            TokenCondition::Predicate(_) => ComponentInfo::new(self.kind()),
+        };
+        info.with_children(self.then.iter().map(|t| t.describe()).collect())
+    }
+}
+
+/// The "smart defaults" for columns without explicitly configured sanitizers, depending on the target type.
+/// Numeric and date types get trimmed, bools get trimmed and lowercased. Everything else is left as is.
+pub fn apply_smart_sanitizer_defaults(token: &str, target_type: &ValueType) -> Result<String> {
+    match target_type {
+        ValueType::Int8
+        | ValueType::Int16
+        | ValueType::Int32
+        | ValueType::Int64
+        | ValueType::Int128
+        | ValueType::UInt8
+        | ValueType::UInt16
+        | ValueType::UInt32
+        | ValueType::UInt64
+        | ValueType::UInt128
+        | ValueType::Float32
+        | ValueType::Float64
+        | ValueType::Decimal
+        | ValueType::NaiveDate
+        | ValueType::NaiveDateTime
+        | ValueType::DateTime => TrimAll.transitize(token),
+        ValueType::Bool => ToLowercase.transitize(&TrimAll.transitize(token)?),
+        _ => Ok(String::from(token)),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::transform_sanitize_token::*;
 
+    #[test]
+    fn test_smart_sanitizer_defaults() {
+        assert_eq!(
+            Ok(String::from("42")),
+            apply_smart_sanitizer_defaults(" 42 ", &ValueType::Int32)
+        );
+        assert_eq!(
+            Ok(String::from("2022-12-31")),
+            apply_smart_sanitizer_defaults("2022-12-31 ", &ValueType::NaiveDate)
+        );
+        assert_eq!(
+            Ok(String::from("true")),
+            apply_smart_sanitizer_defaults(" TRUE", &ValueType::Bool)
+        );
+        assert_eq!(
+            Ok(String::from(" Foo ")),
+            apply_smart_sanitizer_defaults(" Foo ", &ValueType::String)
+        );
+    }
+
+    #[test]
+    fn test_replace_and_eradicate_with_match_opts() {
+        let replace = ReplaceWith::new_with_match("usd", "$", LiteralMatch::new().ignore_case());
+        assert_eq!(
+            Ok(String::from("$ 10, $ 5")),
+            replace.transitize("USD 10, Usd 5")
+        );
+
+        let replace = ReplaceWith::new_with_match("N/A", "", LiteralMatch::new().whole_token());
+        assert_eq!(Ok(String::from("")), replace.transitize("N/A"));
+        assert_eq!(
+            Ok(String::from("N/A or so")),
+            replace.transitize("N/A or so")
+        );
+
+        let eradicate = Eradicate::new_with_match(",", LiteralMatch::new().max_count(1));
+        assert_eq!(
+            Ok(String::from("1000,50")),
+            eradicate.transitize("1,000,50")
+        );
+
+        let eradicate =
+            Eradicate::new_with_match("x", LiteralMatch::new().ignore_case().max_count(2));
+        assert_eq!(Ok(String::from("ax")), eradicate.transitize("XaXx"));
+        assert_eq!(
+            Ok(String::from("aa")),
+            Eradicate::new("X").transitize("XaXa")
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            ComponentInfo::new("replace")
+                .with_param("from", "N/A")
+                .with_param("to", "")
+                .with_param("wholeToken", true),
+            ReplaceWith::new_with_match("N/A", "", LiteralMatch::new().whole_token()).describe()
+        );
+        assert_eq!(
+            ComponentInfo::new("eradicate")
+                .with_param("literal", ",")
+                .with_param("maxCount", 1),
+            Eradicate::new_with_match(",", LiteralMatch::new().max_count(1)).describe()
+        );
+        assert_eq!(
+            ComponentInfo::new("substring")
+                .with_param("start", 0)
+                .with_param("len", 3)
+                .with_param("graphemes", false),
+            Substring::new(0, Some(3)).describe()
+        );
+        assert_eq!(
+            ComponentInfo::new("dropRow").with_param("tokens", r#"["VOID"]"#),
+            DropRowOn::new(vec!["VOID"]).describe()
+        );
+        assert_eq!("trimAll", TrimAll.kind());
+    }
+
+    #[test]
+    fn test_drop_row_on() {
+        let drop_row_on = DropRowOn::new(vec!["VOID", "n/a"]);
+        assert_eq!(Ok(String::from("VOIDED")), drop_row_on.transitize("VOIDED"));
+        assert_eq!(
+            Err(PattiCsvError::DropRow {
+                reason: String::from("token: \"VOID\"")
+            }),
+            drop_row_on.transitize("VOID")
+        );
+    }
+
+    #[test]
+    fn test_conditional_transitizer() {
+        let when_code = ConditionalTransitizer::when_regex(
+            "^\\s*[a-zA-Z]{2}\\s*$",
+            vec![Box::new(TrimAll), Box::new(ToUppercase)],
+        )
+        .unwrap();
+        assert_eq!(Ok(String::from("CH")), when_code.transitize(" ch "));
+        assert_eq!(
+            Ok(String::from(" Zurich ")),
+            when_code.transitize(" Zurich ")
+        );
+        assert_eq!(
+            ComponentInfo::new("when")
+                .with_param("match", "^\\s*[a-zA-Z]{2}\\s*$")
+                .with_children(vec![
+                    ComponentInfo::new("trimAll"),
+                    ComponentInfo::new("toUppercase")
+                ]),
+            when_code.describe()
+        );
+
+        let when_negative = ConditionalTransitizer::when(
+            |t| t.starts_with('-'),
+            vec![Box::new(DropRowOn::new(vec!["-"]))],
+        );
+        assert_eq!(Ok(String::from("42")), when_negative.transitize("42"));
+        assert!(matches!(
+            when_negative.transitize("-"),
+            Err(PattiCsvError::DropRow { .. })
+        ));
+        assert!(ConditionalTransitizer::when_regex("(", vec![]).is_err());
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_normalize_unicode() {
+        let decomposed = "Cafe\u{0301}";
+        assert_eq!(
+            Ok(String::from("Caf\u{00E9}")),
+            NormalizeUnicode::Nfc.transitize(decomposed)
+        );
+        assert_eq!(
+            Ok(String::from("\u{FB01}le\u{00A0}1")),
+            NormalizeUnicode::Nfc.transitize("\u{FB01}le\u{00A0}1")
+        );
+        assert_eq!(
+            Ok(String::from("file 1")),
+            NormalizeUnicode::Nfkc.transitize("\u{FB01}le\u{00A0}1")
+        );
+        assert_eq!(
+            ComponentInfo::new("normalizeUnicode").with_param("form", "nfkc"),
+            NormalizeUnicode::Nfkc.describe()
+        );
+    }
+
+    #[test]
+    fn test_strip_control_chars() {
+        assert_eq!(
+            Ok(String::from("1234")),
+            StripControlChars.transitize("\u{FEFF}12\u{200B}3\u{0007}4\u{00AD}")
+        );
+        assert_eq!(
+            Ok(String::from("a\tb\nc")),
+            StripControlChars.transitize("a\tb\nc")
+        );
+    }
+
+    #[test]
+    fn test_substring() {
+        let sub = |s: Substring, token: &str| s.transitize(token).unwrap();
+        assert_eq!("bar", sub(Substring::new(3, Some(3)), "foobarbaz"));
+        assert_eq!("baz", sub(Substring::new(6, None), "foobarbaz"));
+        assert_eq!("az", sub(Substring::new(7, Some(5)), "foobarbaz"));
+        assert_eq!("", sub(Substring::new(9, Some(1)), "foobarbaz"));
+        assert_eq!("", sub(Substring::new(0, Some(0)), "foobarbaz"));
+
+        // "y̆" is a "y" followed by a combining breve
+        assert_eq!("y", sub(Substring::new(0, Some(1)), "y̆es"));
+        assert_eq!("\u{306}e", sub(Substring::new(1, Some(2)), "y̆es"));
+        assert_eq!("€", sub(Substring::new(2, Some(1)), "10€"));
+        #[cfg(feature = "unicode")]
+        {
+            assert_eq!("y̆", sub(Substring::new(0, Some(1)).graphemes(), "y̆es"));
+            assert_eq!("es", sub(Substring::new(1, None).graphemes(), "y̆es"));
+            assert_eq!("👩‍👩‍👧", sub(Substring::new(1, Some(1)).graphemes(), "a👩‍👩‍👧b"));
+        }
+    }
+
+    #[test]
+    fn test_unicode_transitizers() {
+        // Trims remove Unicode whitespace (e.g. no-break and ideographic space), but not zero-width chars
+        assert_eq!(
+            Ok(String::from("foo")),
+            TrimAll.transitize("\u{a0}\u{3000}foo\u{2003}")
+        );
+        assert_eq!(
+            Ok(String::from("\u{200b}foo")),
+            TrimAll.transitize(" \u{200b}foo ")
+        );
+        // ... and never cut off a combining mark
+        assert_eq!(Ok(String::from("y̆")), TrimTrailing.transitize("y̆ "));
+        assert_eq!(
+            Ok(String::from("\u{306}x")),
+            TrimLeading.transitize(" \u{306}x")
+        );
+
+        // Casing may change the length
+        assert_eq!(
+            Ok(String::from("STRASSE")),
+            ToUppercase.transitize("straße")
+        );
+        assert_eq!(Ok(String::from("ÿ̆es")), ToLowercase.transitize("Ÿ̆ES"));
+
+        // Literals are matched by chars, so a combining mark is left, if only its base is matched
+        assert_eq!(
+            Ok(String::from("\u{306}es")),
+            Eradicate::new("y").transitize("y̆es")
+        );
+        assert_eq!(
+            Ok(String::from("es")),
+            Eradicate::new("y̆").transitize("y̆es")
+        );
+        assert_eq!(
+            Ok(String::from("aeRGER aerger")),
+            ReplaceWith::new_with_match("ä", "ae", LiteralMatch::new().ignore_case())
+                .transitize("ÄRGER ärger")
+        );
+        assert_eq!(
+            Ok(String::from("12,50")),
+            RegexTake::new(r"^(\d+,\d+)\s*€$")
+                .unwrap()
+                .transitize("12,50\u{a0}€")
+        );
+    }
+
     #[test]
     fn test_regex_take() {
         assert_eq!(
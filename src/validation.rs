@@ -0,0 +1,184 @@
+use venum::value_type::ValueType;
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{
+    errors::RowError,
+    report::{add_error_to_summaries, ErrorSummary},
+};
+
+#[cfg(feature = "jsonconf")]
+use crate::errors::{PattiCsvError, Result};
+
+/// The number of samples kept, i.e. of offending values per column, and of column count mismatches. The first ones
+/// are kept, the others are only counted.
+pub const MAX_SAMPLES: usize = 5;
+
+/// Result of a dry run over the data, i.e. what would go wrong when parsing it with this parser. See:
+/// `PattiCsvParser::validate`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "jsonconf", derive(serde::Serialize))]
+#[cfg_attr(feature = "jsonconf", serde(rename_all = "camelCase"))]
+pub struct ValidationReport {
+    /// Data rows only, i.e. without the header row.
+    pub num_rows_validated: usize,
+    /// Rows with bad cells or other errors.
+    pub num_rows_err: usize,
+    /// The number of columns of the first line (header or data), `None` if there was none.
+    pub expected_num_columns: Option<usize>,
+    pub num_column_count_mismatches: usize,
+    pub column_count_mismatches: Vec<ColumnCountMismatch>, // the first ones, see: MAX_SAMPLES
+    pub columns: Vec<ColumnValidation>,
+    /// All errors, that are not about a cell, e.g. of the tokenizer or of a sanitizer.
+    pub errors: Vec<ErrorSummary>,
+}
+
+/// A line with more or less columns (i.e. tokens, after splitting columns) than the first line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "jsonconf", derive(serde::Serialize))]
+#[cfg_attr(feature = "jsonconf", serde(rename_all = "camelCase"))]
+pub struct ColumnCountMismatch {
+    pub line_num: usize,
+    pub num_columns: usize,
+}
+
+/// The cells of a column, that can't be typed (incl. missing ones).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "jsonconf", derive(serde::Serialize))]
+#[cfg_attr(feature = "jsonconf", serde(rename_all = "camelCase"))]
+pub struct ColumnValidation {
+    pub idx: usize,
+    pub name: String,
+    pub target_type: ValueType,
+    pub num_errors: usize,
+    pub samples: Vec<OffendingValue>, // the first ones, see: MAX_SAMPLES
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "jsonconf", derive(serde::Serialize))]
+#[cfg_attr(feature = "jsonconf", serde(rename_all = "camelCase"))]
+pub struct OffendingValue {
+    pub line_num: usize,
+    pub token: Option<String>, // the sanitized token, None if it's missing
+    pub msg: String,
+}
+
+impl ValidationReport {
+    /// No bad rows and no column count mismatches, i.e. the data parses cleanly (as far as it was validated).
+    pub fn is_valid(&self) -> bool {
+        self.num_rows_err == 0 && self.num_column_count_mismatches == 0
+    }
+
+    /// The columns with errors.
+    pub fn failing_columns(&self) -> impl Iterator<Item = &ColumnValidation> {
+        self.columns.iter().filter(|c| c.num_errors > 0)
+    }
+
+    #[cfg(feature = "jsonconf")]
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| PattiCsvError::Generic {
+            msg: format!(
+                "[ERROR_ON_REPORT] Could not serialize validation report: {}",
+                e
+            ),
+        })
+    }
+
+    /// Helper method. Sets up the columns from the (complete) column layout template, once it's known.
+    pub(crate) fn set_columns(&mut self, template: &DataCellRow) {
+        self.columns = template
+            .0
+            .iter()
+            .map(|cell| ColumnValidation {
+                idx: cell.idx,
+                name: cell.name.clone(),
+                target_type: cell.dtype.clone(),
+                num_errors: 0,
+                samples: Vec::new(),
+            })
+            .collect();
+    }
+
+    /// Helper method. Checks the number of columns of a line against the one of the first line.
+    pub(crate) fn check_num_columns(&mut self, line_num: usize, num_columns: usize) {
+        match self.expected_num_columns {
+            None => self.expected_num_columns = Some(num_columns),
+            Some(expected) if expected != num_columns => {
+                self.num_column_count_mismatches += 1;
+                if self.column_count_mismatches.len() < MAX_SAMPLES {
+                    self.column_count_mismatches.push(ColumnCountMismatch {
+                        line_num,
+                        num_columns,
+                    });
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// Helper method. Counts the bad cells of a row per column.
+    pub(crate) fn add_row_error(&mut self, row_error: RowError) {
+        for cell in row_error.cells {
+            if let Some(column) = self.columns.get_mut(cell.column) {
+                column.num_errors += 1;
+                if column.samples.len() < MAX_SAMPLES {
+                    column.samples.push(OffendingValue {
+                        line_num: row_error.line,
+                        token: cell.token,
+                        msg: cell.msg,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Helper method. Any other error (of a given kind).
+    pub(crate) fn add_error(&mut self, kind: String, line_num: usize, msg: String) {
+        add_error_to_summaries(&mut self.errors, kind, line_num, msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use venum_tds::data_cell::DataCell;
+
+    use crate::errors::CellError;
+
+    use super::*;
+
+    #[test]
+    fn collect_validation_samples() {
+        let mut template = DataCellRow::new();
+        template.push(DataCell::new_without_data(
+            ValueType::Int32,
+            String::from("id"),
+            0,
+        ));
+        let mut report = ValidationReport::default();
+        report.set_columns(&template);
+
+        for line_num in 1..=(MAX_SAMPLES + 2) {
+            report.check_num_columns(line_num, if line_num == 2 { 2 } else { 1 });
+            report.add_row_error(RowError {
+                line: line_num,
+                cells: vec![CellError {
+                    column: 0,
+                    header: String::from("id"),
+                    msg: String::from("not a number"),
+                    token: Some(String::from("x")),
+                }],
+            });
+        }
+        assert_eq!(Some(1), report.expected_num_columns);
+        assert_eq!(
+            vec![ColumnCountMismatch {
+                line_num: 2,
+                num_columns: 2
+            }],
+            report.column_count_mismatches
+        );
+        assert_eq!(MAX_SAMPLES + 2, report.columns[0].num_errors);
+        assert_eq!(MAX_SAMPLES, report.columns[0].samples.len());
+        assert_eq!(1, report.failing_columns().count());
+        assert!(!report.is_valid()); // because of the mismatch, the rows are counted by the parser
+    }
+}
@@ -0,0 +1,331 @@
+use std::{collections::HashMap, fmt::Debug};
+
+use chrono::Duration;
+use rust_decimal::{prelude::ToPrimitive, Decimal, RoundingStrategy};
+use venum::value::Value;
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    report::ComponentInfo,
+};
+
+/// Transforms the typed `Value` of a cell, i.e. after typing (whereas the transitizers work on the string tokens before
+/// typing). E.g. to scale or round numbers, shift dates or map codes to labels. A transformer should keep the type of
+/// the value, since the type of the column is already set.
+///
+/// NOTE: `Value::None`s never reach the transformers.
+pub trait ValueTransformer: Debug {
+    fn transform(&self, value: Value) -> Result<Value>;
+    fn get_self_info(&self) -> String {
+        String::from("n/a")
+    }
+    /// The stable id of this kind of transformer. See: `ComponentInfo`
+    fn kind(&self) -> &'static str {
+        "custom"
+    }
+    /// The kind and the parameters, e.g. for reports.
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind())
+    }
+}
+
+pub type BoxedValueTransformer = Box<dyn ValueTransformer + Send + Sync>;
+
+/// Helper method. Applies the transformers in order.
+pub(crate) fn transform_value(
+    value: Value,
+    transformers: &[BoxedValueTransformer],
+) -> Result<Value> {
+    if value == Value::None {
+        return Ok(value);
+    }
+    transformers
+        .iter()
+        .try_fold(value, |acc, transformer| transformer.transform(acc))
+}
+
+fn unsupported(kind: &str, value: &Value) -> PattiCsvError {
+    PattiCsvError::Generic {
+        msg: format!(
+            "[ERROR_ON_VALUE_TRANSFORM] {} doesn't support the value: {:?}",
+            kind, value
+        ),
+    }
+}
+
+/// Multiplies `Float32`, `Float64` and `Decimal` values by the factor, e.g. `0.01` for cents to units. (For integer
+/// columns, type them as `Decimal`.)
+#[derive(Debug)]
+pub struct Scale {
+    factor: Decimal,
+}
+impl Scale {
+    pub fn new(factor: Decimal) -> Self {
+        Self { factor }
+    }
+}
+impl ValueTransformer for Scale {
+    fn transform(&self, value: Value) -> Result<Value> {
+        let factor = self.factor.to_f64().unwrap_or(f64::NAN);
+        match value {
+            Value::Float32(v) => Ok(Value::Float32((v as f64 * factor) as f32)),
+            Value::Float64(v) => Ok(Value::Float64(v * factor)),
+            Value::Decimal(d) => d
+                .checked_mul(self.factor)
+                .map(Value::Decimal)
+                .ok_or_else(|| PattiCsvError::Generic {
+                    msg: format!(
+                        "[ERROR_ON_VALUE_TRANSFORM] Overflow scaling {} by {}",
+                        d, self.factor
+                    ),
+                }),
+            other => Err(unsupported(self.kind(), &other)),
+        }
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "scale"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("factor", self.factor)
+    }
+}
+
+/// Rounds `Float32`, `Float64` and `Decimal` values to the number of decimal places (half away from zero).
+#[derive(Debug)]
+pub struct Round {
+    decimal_places: u32,
+}
+impl Round {
+    pub fn new(decimal_places: u32) -> Self {
+        Self { decimal_places }
+    }
+}
+impl ValueTransformer for Round {
+    fn transform(&self, value: Value) -> Result<Value> {
+        let pow = 10_f64.powi(self.decimal_places as i32);
+        match value {
+            Value::Float32(v) => Ok(Value::Float32(((v as f64 * pow).round() / pow) as f32)),
+            Value::Float64(v) => Ok(Value::Float64((v * pow).round() / pow)),
+            Value::Decimal(d) => Ok(Value::Decimal(d.round_dp_with_strategy(
+                self.decimal_places,
+                RoundingStrategy::MidpointAwayFromZero,
+            ))),
+            other => Err(unsupported(self.kind(), &other)),
+        }
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "round"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("decimalPlaces", self.decimal_places)
+    }
+}
+
+/// Shifts `NaiveDate`, `NaiveDateTime` and `DateTime` values by the number of days (negative for the past).
+#[derive(Debug)]
+pub struct ShiftDate {
+    days: i64,
+}
+impl ShiftDate {
+    pub fn new(days: i64) -> Self {
+        Self { days }
+    }
+}
+impl ValueTransformer for ShiftDate {
+    fn transform(&self, value: Value) -> Result<Value> {
+        let delta = Duration::try_days(self.days);
+        let shifted = match &value {
+            Value::NaiveDate(d) => delta
+                .and_then(|delta| d.checked_add_signed(delta))
+                .map(Value::NaiveDate),
+            Value::NaiveDateTime(d) => delta
+                .and_then(|delta| d.checked_add_signed(delta))
+                .map(Value::NaiveDateTime),
+            Value::DateTime(d) => delta
+                .and_then(|delta| d.checked_add_signed(delta))
+                .map(Value::DateTime),
+            other => return Err(unsupported(self.kind(), other)),
+        };
+        shifted.ok_or_else(|| PattiCsvError::Generic {
+            msg: format!(
+                "[ERROR_ON_VALUE_TRANSFORM] Out of range shifting {:?} by {} days",
+                value, self.days
+            ),
+        })
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn kind(&self) -> &'static str {
+        "shiftDate"
+    }
+    fn describe(&self) -> ComponentInfo {
+        ComponentInfo::new(self.kind()).with_param("days", self.days)
+    }
+}
+
+/// Maps `String` values, e.g. codes to labels (`"O"` -> `"open"`). Unmapped values are kept, unless it's strict.
+#[derive(Debug)]
+pub struct MapValues {
+    mapping: HashMap<String, String>,
+    strict: bool,
+}
+impl MapValues {
+    pub fn new(mapping: HashMap<String, String>) -> Self {
+        Self {
+            mapping,
+            strict: false,
+        }
+    }
+
+    /// Unmapped values are an error.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+}
+impl ValueTransformer for MapValues {
+    fn transform(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::String(s) => match self.mapping.get(&s) {
+                Some(mapped) => Ok(Value::String(mapped.clone())),
+                None if self.strict => Err(PattiCsvError::Generic {
+                    msg: format!("[ERROR_ON_VALUE_TRANSFORM] No mapping for: '{}'", s),
+                }),
+                None => Ok(Value::String(s)),
+            },
+            other => Err(unsupported(self.kind(), &other)),
+        }
+    }
+    fn get_self_info(&self) -> String {
+        let mut mapping = self.mapping.iter().collect::<Vec<_>>();
+        mapping.sort();
+        format!(
+            "MapValues {{ mapping: {:?}, strict: {} }}",
+            mapping, self.strict
+        )
+    }
+    fn kind(&self) -> &'static str {
+        "mapValues"
+    }
+    fn describe(&self) -> ComponentInfo {
+        let info = self
+            .mapping
+            .iter()
+            .fold(ComponentInfo::new(self.kind()), |info, (k, v)| {
+                info.with_param(format!("map.{}", k), v)
+            });
+        if self.strict {
+            info.with_param("strict", true)
+        } else {
+            info
+        }
+    }
+}
+
+/// A `ValueTransformer` from a closure, for the quick cases.
+pub struct FnValueTransformer<F> {
+    name: String,
+    f: F,
+}
+impl<F> FnValueTransformer<F>
+where
+    F: Fn(Value) -> Result<Value>,
+{
+    pub fn new<S: Into<String>>(name: S, f: F) -> Self {
+        Self {
+            name: name.into(),
+            f,
+        }
+    }
+}
+impl<F> Debug for FnValueTransformer<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnValueTransformer")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+impl<F> ValueTransformer for FnValueTransformer<F>
+where
+    F: Fn(Value) -> Result<Value>,
+{
+    fn transform(&self, value: Value) -> Result<Value> {
+        (self.f)(value)
+    }
+    fn get_self_info(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn built_in_value_transformers() {
+        let scale = Scale::new(Decimal::from_str("0.01").unwrap());
+        assert_eq!(
+            Value::Decimal(Decimal::from_str("12.34").unwrap()),
+            scale
+                .transform(Value::Decimal(Decimal::from(1234)))
+                .unwrap()
+        );
+        assert_eq!(
+            Value::Float64(0.5),
+            scale.transform(Value::Float64(50.0)).unwrap()
+        );
+        assert!(scale.transform(Value::Int32(1)).is_err());
+
+        let round = Round::new(1);
+        assert_eq!(
+            Value::Decimal(Decimal::from_str("1.3").unwrap()),
+            round
+                .transform(Value::Decimal(Decimal::from_str("1.25").unwrap()))
+                .unwrap()
+        );
+        assert_eq!(
+            Value::Float64(-1.3),
+            round.transform(Value::Float64(-1.26)).unwrap()
+        );
+
+        assert_eq!(
+            Value::NaiveDate(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+            ShiftDate::new(-1)
+                .transform(Value::NaiveDate(
+                    NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()
+                ))
+                .unwrap()
+        );
+
+        let map = MapValues::new(HashMap::from([(String::from("O"), String::from("open"))]));
+        assert_eq!(
+            Value::from(String::from("open")),
+            map.transform(Value::from(String::from("O"))).unwrap()
+        );
+        assert_eq!(
+            Value::from(String::from("X")),
+            map.transform(Value::from(String::from("X"))).unwrap()
+        );
+        assert!(map
+            .strict()
+            .transform(Value::from(String::from("X")))
+            .is_err());
+
+        // None is never transformed
+        assert_eq!(
+            Value::None,
+            transform_value(Value::None, &[Box::new(ShiftDate::new(1))]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use std::io::Write;
+
+use venum::{value::Value, value_type::ValueType};
+use venum_tds::data_cell_row::DataCellRow;
+
+use crate::{
+    errors::{PattiCsvError, Result},
+    output::OutputDialect,
+};
+
+/// When fields are enclosed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuotingPolicy {
+    /// Every field, except for `Value::None`s (which are written as nothing at all, so they can be told apart from
+    /// empty strings).
+    Always,
+    /// Only fields, that contain the separator, the enclosure character or a line break.
+    #[default]
+    Minimal,
+    /// No field. A field, that would need an enclosure, is an error.
+    Never,
+}
+
+/// Helper method. Pushes the field to the buffer, enclosed according to the policy (enclosure characters within are
+/// escaped by doubling them). Returns `false` (and pushes nothing), if the field needs an enclosure, but can't have one.
+pub(crate) fn push_field(
+    buf: &mut String,
+    field: &str,
+    separator_char: char,
+    enclosure_char: Option<char>,
+    quoting: QuotingPolicy,
+) -> bool {
+    let needs_enclosure = field
+        .chars()
+        .any(|c| c == separator_char || Some(c) == enclosure_char || c == '\n' || c == '\r');
+    match (
+        needs_enclosure || quoting == QuotingPolicy::Always,
+        enclosure_char,
+    ) {
+        (false, _) => buf.push_str(field),
+        (true, Some(encl)) if quoting != QuotingPolicy::Never => {
+            buf.push(encl);
+            for c in field.chars() {
+                if c == encl {
+                    buf.push(encl);
+                }
+                buf.push(c);
+            }
+            buf.push(encl);
+        }
+        (true, _) => return false,
+    }
+    true
+}
+
+/// Writes typed rows (e.g. from the parser) as RFC 4180 csv, i.e. records are terminated by CRLF (by default), and
+/// enclosure characters within fields are escaped by doubling them. Values are written in their string form (see:
+/// `Value::try_convert_to`), `Value::None` as an empty field.
+///
+/// The header is taken from the names of the cells of the first row, i.e. from the column layout template of the
+/// parser. So if the rows come from a parser, that yields the header row as well, either skip it, or don't write a
+/// header.
+#[derive(Debug)]
+pub struct PattiCsvWriter<W: Write> {
+    writer: W,
+    separator_char: char,
+    enclosure_char: Option<char>,
+    quoting: QuotingPolicy,
+    write_header: bool,
+    record_terminator: &'static str,
+    num_records: usize,
+    buf: String,
+}
+
+impl<W: Write> PattiCsvWriter<W> {
+    pub fn builder() -> PattiCsvWriterBuilder {
+        PattiCsvWriterBuilder::new()
+    }
+
+    /// Writes one row, and before the first one the header (unless configured not to).
+    pub fn write_row(&mut self, row: &DataCellRow) -> Result<()> {
+        if self.write_header {
+            self.write_header = false;
+            self.write_fields(row.0.iter().map(|c| Some(c.get_name().to_string())))?;
+        }
+        let fields = row
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, c)| value_to_field(c.get_data(), self.num_records + 1, i))
+            .collect::<Result<Vec<_>>>()?;
+        self.write_fields(fields)
+    }
+
+    /// Writes all the rows, and returns the number of rows written (without the header).
+    pub fn write_rows<'a, I>(&mut self, rows: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = &'a DataCellRow>,
+    {
+        let mut num_rows = 0;
+        for row in rows {
+            self.write_row(row)?;
+            num_rows += 1;
+        }
+        Ok(num_rows)
+    }
+
+    /// Helper method. `None` is a field, that is never enclosed.
+    fn write_fields<I>(&mut self, fields: I) -> Result<()>
+    where
+        I: IntoIterator<Item = Option<String>>,
+    {
+        self.buf.clear();
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                self.buf.push(self.separator_char);
+            }
+            let field = match field {
+                Some(f) => f,
+                None => continue,
+            };
+            if !push_field(
+                &mut self.buf,
+                &field,
+                self.separator_char,
+                self.enclosure_char,
+                self.quoting,
+            ) {
+                return Err(PattiCsvError::Generic {
+                    msg: format!(
+                        "[ERROR_ON_WRITE] Field needs an enclosure, but the quoting policy is {:?} and the enclosure character is {:?}; record: {}; column: {}; field: {:?}",
+                        self.quoting,
+                        self.enclosure_char,
+                        self.num_records + 1,
+                        i,
+                        field
+                    ),
+                });
+            }
+        }
+        self.buf.push_str(self.record_terminator);
+        self.writer.write_all(self.buf.as_bytes())?;
+        self.num_records += 1;
+        Ok(())
+    }
+
+    /// The number of records written so far, incl. the header.
+    pub fn num_records(&self) -> usize {
+        self.num_records
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Helper method. The string form of a value, `None` for `Value::None`.
+fn value_to_field(value: &Value, record: usize, column: usize) -> Result<Option<String>> {
+    match value {
+        Value::None => Ok(None),
+        Value::String(s) => Ok(Some(s.clone())),
+        v => v
+            .try_convert_to(&ValueType::String)
+            .and_then(String::try_from)
+            .map(Some)
+            .map_err(|e| PattiCsvError::Generic {
+                msg: format!(
+                    "[ERROR_ON_WRITE] Value has no string form: {:?}; record: {}; column: {}",
+                    e, record, column
+                ),
+            }),
+    }
+}
+
+#[derive(Debug)]
+pub struct PattiCsvWriterBuilder {
+    separator_char: char,
+    enclosure_char: Option<char>,
+    quoting: QuotingPolicy,
+    write_header: bool,
+    crlf: bool,
+}
+
+impl Default for PattiCsvWriterBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PattiCsvWriterBuilder {
+    /// `,` as separator, `"` as enclosure (if needed), a header line and CRLF as record terminator.
+    pub fn new() -> Self {
+        Self {
+            separator_char: ',',
+            enclosure_char: Some('"'),
+            quoting: QuotingPolicy::default(),
+            write_header: true,
+            crlf: true,
+        }
+    }
+
+    /// Like `new`, but with the separator, enclosure and header of the dialect (e.g. of an output target).
+    pub fn from_dialect(dialect: &OutputDialect) -> Self {
+        Self::new()
+            .separator_char(dialect.separator_char)
+            .enclosure_char(dialect.enclosure_char)
+            .write_header(dialect.write_header)
+    }
+
+    pub fn separator_char(mut self, c: char) -> PattiCsvWriterBuilder {
+        self.separator_char = c;
+        self
+    }
+
+    pub fn enclosure_char(mut self, c: Option<char>) -> PattiCsvWriterBuilder {
+        self.enclosure_char = c;
+        self
+    }
+
+    pub fn quoting(mut self, quoting: QuotingPolicy) -> PattiCsvWriterBuilder {
+        self.quoting = quoting;
+        self
+    }
+
+    pub fn write_header(mut self, b: bool) -> PattiCsvWriterBuilder {
+        self.write_header = b;
+        self
+    }
+
+    /// Terminate records by CRLF (the default, as of RFC 4180), or by LF only.
+    pub fn crlf(mut self, b: bool) -> PattiCsvWriterBuilder {
+        self.crlf = b;
+        self
+    }
+
+    pub fn build<W: Write>(self, writer: W) -> Result<PattiCsvWriter<W>> {
+        let is_line_break = |c: char| c == '\n' || c == '\r';
+        if is_line_break(self.separator_char) || self.enclosure_char.is_some_and(is_line_break) {
+            return Err(PattiCsvError::ConfigError {
+                msg: String::from(
+                    "[ERROR_ON_WRITE] Separator and enclosure character can't be line breaks",
+                ),
+            });
+        }
+        if Some(self.separator_char) == self.enclosure_char {
+            return Err(PattiCsvError::ConfigError {
+                msg: format!(
+                    "[ERROR_ON_WRITE] Separator and enclosure character must differ, but both are: {:?}",
+                    self.separator_char
+                ),
+            });
+        }
+        if self.quoting == QuotingPolicy::Always && self.enclosure_char.is_none() {
+            return Err(PattiCsvError::ConfigError {
+                msg: String::from(
+                    "[ERROR_ON_WRITE] Quoting policy is Always, but there is no enclosure character",
+                ),
+            });
+        }
+        Ok(PattiCsvWriter {
+            writer,
+            separator_char: self.separator_char,
+            enclosure_char: self.enclosure_char,
+            quoting: self.quoting,
+            write_header: self.write_header,
+            record_terminator: if self.crlf { "\r\n" } else { "\n" },
+            num_records: 0,
+            buf: String::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use venum_tds::data_cell::DataCell;
+
+    use crate::iterating_parser::PattiCsvParserBuilder;
+    use crate::parser_config::TypeColumnEntry;
+
+    use super::*;
+
+    fn rows() -> Vec<DataCellRow> {
+        let row = |id: i32, name: Value, date: Value| {
+            let mut row = DataCellRow::new();
+            row.push(DataCell::new(String::from("id"), 0, Value::Int32(id)).unwrap());
+            row.push(
+                DataCell::new_with_type_info(ValueType::String, String::from("name"), 1, name)
+                    .unwrap(),
+            );
+            row.push(
+                DataCell::new_with_type_info(ValueType::NaiveDate, String::from("day"), 2, date)
+                    .unwrap(),
+            );
+            row
+        };
+        vec![
+            row(
+                1,
+                Value::from("foo, \"bar\""),
+                Value::NaiveDate(NaiveDate::from_ymd_opt(2021, 1, 3).unwrap()),
+            ),
+            row(2, Value::from(""), Value::None),
+            row(3, Value::from("multi\nline"), Value::None),
+        ]
+    }
+
+    fn write(builder: PattiCsvWriterBuilder, rows: &[DataCellRow]) -> Result<String> {
+        let mut w = builder.build(Vec::new())?;
+        w.write_rows(rows)?;
+        Ok(String::from_utf8(w.into_inner()).unwrap())
+    }
+
+    #[test]
+    fn write_rows_with_quoting_policies() {
+        assert_eq!(
+            "id,name,day\r\n1,\"foo, \"\"bar\"\"\",2021-01-03\r\n2,,\r\n3,\"multi\nline\",\r\n",
+            write(PattiCsvWriterBuilder::new(), &rows()).unwrap()
+        );
+        assert_eq!(
+            "\"id\";\"name\";\"day\"\n\"1\";\"foo, \"\"bar\"\"\";\"2021-01-03\"\n\"2\";\"\";\n",
+            write(
+                PattiCsvWriterBuilder::new()
+                    .separator_char(';')
+                    .quoting(QuotingPolicy::Always)
+                    .crlf(false),
+                &rows()[..2]
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            "1\tfoo, \"bar\"\t2021-01-03\r\n",
+            write(
+                PattiCsvWriterBuilder::new()
+                    .separator_char('\t')
+                    .enclosure_char(None)
+                    .quoting(QuotingPolicy::Never)
+                    .write_header(false),
+                &rows()[..1]
+            )
+            .unwrap()
+        );
+        assert!(write(
+            PattiCsvWriterBuilder::new().quoting(QuotingPolicy::Never),
+            &rows()
+        )
+        .is_err());
+        assert!(PattiCsvWriterBuilder::new()
+            .separator_char('"')
+            .build(Vec::new())
+            .is_err());
+        assert!(PattiCsvWriterBuilder::new()
+            .enclosure_char(None)
+            .quoting(QuotingPolicy::Always)
+            .build(Vec::new())
+            .is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let parser = PattiCsvParserBuilder::csv()
+            .column_typings(vec![
+                TypeColumnEntry::new(None, ValueType::Int32),
+                TypeColumnEntry::new(None, ValueType::String),
+                TypeColumnEntry::new(None, ValueType::NaiveDate),
+            ])
+            .record_separator('\n')
+            .build()
+            .unwrap();
+        let written = write(PattiCsvWriterBuilder::new().crlf(false), &rows()).unwrap();
+        let parsed = parser
+            .parse_iter(&mut std::io::Cursor::new(written))
+            .skip(1) // the header row
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(rows()[0], parsed[0]);
+        assert_eq!(rows()[2], parsed[2]);
+    }
+}
@@ -0,0 +1,32 @@
+//! Parses every case of the corpus (see: examples/corpus/data) and compares the result to the `<case>.expected` file.
+//! After an intended change of behavior, `PATTI_CSV_BLESS=1 cargo test --test corpus` rewrites the expected files.
+
+#[path = "../examples/corpus/render.rs"]
+mod render;
+
+use std::fs;
+
+#[test]
+fn corpus_cases_match_expected() {
+    let bless = std::env::var_os("PATTI_CSV_BLESS").is_some();
+    let cases = render::case_names();
+    assert!(!cases.is_empty());
+
+    let mut mismatches = Vec::new();
+    for case in cases {
+        let actual = render::render_case(&case);
+        let path = render::corpus_dir().join(format!("{}.expected", case));
+        if bless {
+            fs::write(&path, &actual).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&path).unwrap_or_default();
+        if actual != expected {
+            mismatches.push(format!(
+                "== {} ==\n-- expected:\n{}-- actual:\n{}",
+                case, expected, actual
+            ));
+        }
+    }
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+}